@@ -0,0 +1,776 @@
+//! `ahc`: local scoring for heuristic contests with the official tools.
+
+use crate::{
+    atcoder::{AtCoder, Rejection, Verdict, DEFAULT_LANGUAGE},
+    build, execute,
+    i18n::tr,
+    logging,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    parse_duration,
+    project::{self, package_dir},
+    session_file,
+};
+use anyhow::{bail, ensure, Context as _, Result};
+use cargo_metadata::{Package, Target};
+use chrono::Utc;
+use console::Style;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+// Heuristic contests usually have only one problem.
+const DEFAULT_PROBLEM_ID: &str = "a";
+
+// The shortest submission interval in heuristic contests
+const MIN_SUBMISSION_INTERVAL_MINUTES: i64 = 5;
+
+// URL of the web visualizer, saved by `setup`
+const WEB_VISUALIZER_FILE: &str = "visualizer.txt";
+
+// Files written to the current directory by the local visualizers of the past contests
+const VIS_FILES: &[&str] = &["vis.html", "out.svg", "vis.svg"];
+
+const AUTOSUBMIT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(StructOpt)]
+pub enum AhcOpt {
+    /// Download and build the official local tools
+    Setup(SetupOpt),
+    /// Score the solution over seeds with the official tools
+    Run(RunOpt),
+    /// Visualize the output for the seed saved by `run`
+    Vis(VisOpt),
+    /// Submit the best solution scored with `run` whenever the submission interval allows
+    Autosubmit(AutosubmitOpt),
+}
+
+#[derive(StructOpt)]
+pub struct SetupOpt {
+    /// Problem ID
+    #[structopt(default_value(DEFAULT_PROBLEM_ID))]
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+#[derive(StructOpt)]
+pub struct RunOpt {
+    /// Problem ID (must be same as binary name)
+    #[structopt(default_value(DEFAULT_PROBLEM_ID))]
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Seeds to run, e.g. `100` (0 to 99) or `100..200`
+    #[structopt(long, default_value("100"), parse(try_from_str = parse_seeds))]
+    seeds: Range<u64>,
+    /// Number of the seeds to run in parallel (default: the number of the CPUs)
+    #[structopt(short, long, value_name("N"))]
+    jobs: Option<usize>,
+    /// Time limit for each seed in seconds
+    #[structopt(long, value_name("SECS"), default_value("10"))]
+    time_limit: f64,
+    /// Lower scores are better
+    #[structopt(long)]
+    minimize: bool,
+}
+
+#[derive(StructOpt)]
+pub struct VisOpt {
+    /// Seed to visualize
+    seed: u64,
+    /// Problem ID
+    #[structopt(default_value(DEFAULT_PROBLEM_ID))]
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Lower scores are better
+    #[structopt(long)]
+    minimize: bool,
+}
+
+#[derive(StructOpt)]
+pub struct AutosubmitOpt {
+    /// Problem ID
+    #[structopt(default_value(DEFAULT_PROBLEM_ID))]
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Submission interval of the contest (e.g. 5m, 30m, 1h)
+    #[structopt(long, default_value("30m"), parse(try_from_str = parse_duration))]
+    every: chrono::Duration,
+}
+
+fn parse_seeds(s: &str) -> Result<Range<u64>> {
+    let range = match s.split_once("..") {
+        Some((start, end)) => start.parse()?..end.parse()?,
+        None => 0..s.parse()?,
+    };
+    ensure!(!range.is_empty(), "No seeds in `{}`", s);
+    Ok(range)
+}
+
+pub async fn ahc(opt: AhcOpt) -> Result<()> {
+    match opt {
+        AhcOpt::Setup(opt) => setup(opt).await,
+        AhcOpt::Run(opt) => run(opt),
+        AhcOpt::Vis(opt) => vis(opt),
+        AhcOpt::Autosubmit(opt) => autosubmit(opt).await,
+    }
+}
+
+/// Directory of the official tools, extracted from the zip file
+fn tools_dir(package: &Package) -> PathBuf {
+    package_dir(package).join("tools")
+}
+
+// Where the inputs generated by us, the outputs and the scores are saved
+fn work_dir(package: &Package, problem_id: &str) -> PathBuf {
+    package_dir(package).join("ahc").join(problem_id)
+}
+
+fn tool_exe(package: &Package, name: &str) -> PathBuf {
+    tools_dir(package)
+        .join("target")
+        .join("release")
+        .join(name)
+        .with_extension(env::consts::EXE_EXTENSION)
+}
+
+async fn setup(opt: SetupOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    let contest_info = crate::contest_info_or_register(&atc, project::contest_id(package)?).await?;
+    let problem = contest_info
+        .problem(&opt.problem_id)
+        .with_context(|| tr!(ProblemNotInContest, opt.problem_id))?;
+
+    // The tools are linked as a zip file from the statement.
+    let html = atc.statement_html(&problem.url, false).await?;
+    let links = Html::parse_fragment(&html)
+        .select(&Selector::parse("a[href]").unwrap())
+        .filter_map(|a| a.value().attr("href"))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let src = links
+        .iter()
+        .find(|href| href.ends_with(".zip"))
+        .with_context(|| "Could not find the link to the local tools in the statement")?;
+
+    // The web visualizer is hosted next to the tools.
+    if let Some(url) = links
+        .iter()
+        .find(|href| href.contains("img.atcoder.jp") && href.contains(".html"))
+    {
+        let work_dir = work_dir(package, &opt.problem_id);
+        fs::create_dir_all(&work_dir)?;
+        fs::write(work_dir.join(WEB_VISUALIZER_FILE), url)?;
+    }
+
+    info!("Downloading {}...", src);
+    let zip = atc.download(src).await?;
+    let zip_path = package_dir(package).join("tools.zip");
+    fs::write(&zip_path, zip).with_context(|| format!("Failed to write {}", zip_path.display()))?;
+    extract_zip(&zip_path, package_dir(package))?;
+    ensure!(
+        tools_dir(package).join("Cargo.toml").is_file(),
+        "`tools/Cargo.toml` is not found in {}",
+        zip_path.display(),
+    );
+
+    info!("Building the tools...");
+    let status = Command::new("cargo")
+        .args(["build", "--release", "--quiet", "--manifest-path"])
+        .arg(tools_dir(package).join("Cargo.toml"))
+        .status()?;
+    ensure!(status.success(), "Failed to build the tools");
+
+    info!("Setup done.");
+    Ok(())
+}
+
+// `tar` on Windows 10 (bsdtar) can also extract ZIP files.
+fn extract_zip(zip: &Path, dir: &Path) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("tar");
+        cmd.arg("-xf").arg(zip).arg("-C").arg(dir);
+        cmd
+    } else {
+        let mut cmd = Command::new("unzip");
+        cmd.args(["-q", "-o"]).arg(zip).arg("-d").arg(dir);
+        cmd
+    };
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run {:?}", cmd))?;
+    ensure!(status.success(), "Failed to extract {}", zip.display());
+    Ok(())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SeedResult {
+    seed: u64,
+    /// `None` if the solution or the scorer failed. Some problems have negative or fractional
+    /// scores.
+    score: Option<f64>,
+    time_ms: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Scores {
+    results: Vec<SeedResult>,
+}
+
+impl Scores {
+    fn load(path: &Path) -> Option<Self> {
+        toml::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+
+    fn total(&self) -> f64 {
+        self.results.iter().filter_map(|r| r.score).sum()
+    }
+
+    fn by_seed(&self) -> BTreeMap<u64, &SeedResult> {
+        self.results.iter().map(|r| (r.seed, r)).collect()
+    }
+
+    /// The best and the worst scores over the seeds
+    fn best_and_worst(&self, minimize: bool) -> Option<(f64, f64)> {
+        let scores = self.results.iter().filter_map(|r| r.score);
+        let best = scores
+            .clone()
+            .reduce(|a, b| if is_better(b, a, minimize) { b } else { a })?;
+        let worst = scores.reduce(|a, b| if is_better(a, b, minimize) { b } else { a })?;
+        Some((best, worst))
+    }
+}
+
+fn is_better(score: f64, than: f64, minimize: bool) -> bool {
+    if minimize {
+        score < than
+    } else {
+        score > than
+    }
+}
+
+/// The best solution scored with `run` so far, whose source is saved as `best.rs`
+#[derive(Serialize, Deserialize)]
+struct Best {
+    /// Average score over the seeds, which may differ between the runs
+    average: f64,
+    sha256: String,
+    /// SHA-256 of the source submitted last by `autosubmit`
+    submitted_sha256: Option<String>,
+    /// UNIX time of the last submission by `autosubmit`
+    submitted_at: Option<i64>,
+}
+
+impl Best {
+    fn load(work_dir: &Path) -> Option<Self> {
+        toml::from_str(&fs::read_to_string(work_dir.join("best.toml")).ok()?).ok()
+    }
+
+    fn save(&self, work_dir: &Path) -> Result<()> {
+        let path = work_dir.join("best.toml");
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn sha256(source: &str) -> String {
+    data_encoding::HEXLOWER.encode(&Sha256::digest(source.as_bytes()))
+}
+
+fn run(opt: RunOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let problem_id = &opt.problem_id;
+
+    // Interactive problems come with `tester`, which runs the solution and prints the score.
+    let tester = tool_exe(package, "tester");
+    let vis = tool_exe(package, "vis");
+    if !tester.is_file() && !vis.is_file() {
+        bail!("The tools are not built. Run `cargo atcoder ahc setup` first.");
+    }
+
+    let work_dir = work_dir(package, problem_id);
+    fs::create_dir_all(work_dir.join("out"))?;
+    let seeds = opt.seeds.clone().collect::<Vec<_>>();
+    let inputs = prepare_inputs(package, &work_dir, &seeds)?;
+
+    let solution = match build::build_bin(package, problem_id, true)? {
+        Some(solution) => solution,
+        None => bail!("Failed to build `{}`", problem_id),
+    };
+
+    let jobs = opt
+        .jobs
+        .or_else(|| thread::available_parallelism().ok().map(Into::into))
+        .unwrap_or(1)
+        .max(1);
+    let time_limit = Duration::from_secs_f64(opt.time_limit);
+
+    let pb = logging::progress_bar(seeds.len() as u64, "Running");
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![]);
+    let errors = Mutex::new(vec![]);
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let (seed, input) = match (seeds.get(i), inputs.get(i)) {
+                    (Some(&seed), Some(input)) => (seed, input),
+                    _ => break,
+                };
+                let output = work_dir.join("out").join(format!("{:04}.txt", seed));
+                let start = Instant::now();
+                let score = score_seed(
+                    solution.as_std_path(),
+                    &tester,
+                    &vis,
+                    input,
+                    &output,
+                    time_limit,
+                );
+                let time_ms = start.elapsed().as_millis() as u64;
+                let score = score.unwrap_or_else(|err| {
+                    errors.lock().unwrap().push((seed, err));
+                    None
+                });
+                results.lock().unwrap().push(SeedResult {
+                    seed,
+                    score,
+                    time_ms,
+                });
+                pb.inc(1);
+            });
+        }
+    });
+    pb.finish_and_clear();
+
+    for (seed, err) in errors.into_inner().unwrap() {
+        warn!("seed {}: {:#}", seed, err);
+    }
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|r| r.seed);
+    let scores = Scores { results };
+
+    // The last run is kept for comparison.
+    let scores_path = work_dir.join("scores.toml");
+    let previous = Scores::load(&scores_path);
+    print_scores(&scores, previous.as_ref(), opt.minimize);
+    if scores_path.is_file() {
+        fs::rename(&scores_path, work_dir.join("previous.toml"))?;
+    }
+    fs::write(&scores_path, toml::to_string(&scores)?)
+        .with_context(|| format!("Failed to write {}", scores_path.display()))?;
+
+    // Only the solutions that succeeded on all the seeds are candidates for `autosubmit`.
+    if scores.results.iter().all(|r| r.score.is_some()) {
+        let Target { src_path, .. } = package.find_bin(problem_id)?;
+        let source =
+            fs::read_to_string(src_path).with_context(|| format!("Failed to read {}", src_path))?;
+        let average = scores.total() / scores.results.len() as f64;
+        let best = Best::load(&work_dir);
+        let is_best = best
+            .as_ref()
+            .is_none_or(|best| is_better(average, best.average, opt.minimize));
+        if is_best {
+            fs::write(work_dir.join("best.rs"), &source)?;
+            Best {
+                average,
+                sha256: sha256(&source),
+                submitted_sha256: best.as_ref().and_then(|b| b.submitted_sha256.clone()),
+                submitted_at: best.as_ref().and_then(|b| b.submitted_at),
+            }
+            .save(&work_dir)?;
+            info!("New best average score: {:.1}", average);
+        }
+    }
+    Ok(())
+}
+
+fn vis(opt: VisOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let work_dir = work_dir(package, &opt.problem_id);
+
+    let input = input_path(package, &work_dir, opt.seed);
+    let output = work_dir.join("out").join(format!("{:04}.txt", opt.seed));
+    ensure!(
+        input.is_file() && output.is_file(),
+        "The output for seed {} is not found. Run `cargo atcoder ahc run --seeds {}..{}` first.",
+        opt.seed,
+        opt.seed,
+        opt.seed + 1,
+    );
+
+    let vis = tool_exe(package, "vis");
+    if vis.is_file() {
+        let vis_dir = work_dir.join("vis");
+        fs::create_dir_all(&vis_dir)?;
+        for file in VIS_FILES {
+            let _ = fs::remove_file(vis_dir.join(file));
+        }
+        let out = Command::new(&vis)
+            .arg(&input)
+            .arg(&output)
+            .current_dir(&vis_dir)
+            .output()
+            .with_context(|| format!("Failed to run {}", vis.display()))?;
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        if let Some(score) = parse_score(&stdout) {
+            let rank = Scores::load(&work_dir.join("scores.toml"))
+                .and_then(|scores| scores.best_and_worst(opt.minimize))
+                .and_then(|(best, worst)| match score {
+                    s if s == best => Some(Style::new().green().apply_to(" (best of the run)")),
+                    s if s == worst => Some(Style::new().red().apply_to(" (worst of the run)")),
+                    _ => None,
+                });
+            info!(
+                "Score = {}{}",
+                score,
+                rank.map(|r| r.to_string()).unwrap_or_default()
+            );
+        }
+        if let Some(file) = VIS_FILES
+            .iter()
+            .map(|file| vis_dir.join(file))
+            .find(|path| path.is_file())
+        {
+            info!("Opening {}", file.display());
+            return crate::open(file.as_os_str());
+        }
+    }
+
+    // The web visualizers cannot be fed with files from the URL.
+    let url = fs::read_to_string(work_dir.join(WEB_VISUALIZER_FILE))
+        .ok()
+        .with_context(|| "No visualizer is found. Run `cargo atcoder ahc setup` first.")?;
+    info!("Input: {}", input.display());
+    info!("Output: {}", output.display());
+    info!("Opening {}. Load the files above in it.", url.trim());
+    crate::open(url.trim().as_ref())
+}
+
+async fn autosubmit(opt: AutosubmitOpt) -> Result<()> {
+    ensure!(
+        opt.every >= chrono::Duration::minutes(MIN_SUBMISSION_INTERVAL_MINUTES),
+        "The submission interval must be at least {} minutes",
+        MIN_SUBMISSION_INTERVAL_MINUTES,
+    );
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    let contest_id = project::contest_id(package)?;
+    let problem_id = &opt.problem_id;
+    let work_dir = work_dir(package, problem_id);
+
+    info!(
+        "Submitting the best solution of `{}` at most every {} minutes. Run `cargo atcoder ahc \
+         run` in another terminal to update it.",
+        problem_id,
+        opt.every.num_minutes(),
+    );
+    // The submission waiting for the judge, and the time AtCoder told to wait until
+    let mut judging = None;
+    let mut cooldown_until = None;
+    // Reloaded every time since `run` updates it.
+    loop {
+        if let Some(id) = judging {
+            match atc.submissions(contest_id, 1).await {
+                Ok((submissions, _)) => {
+                    if let Some(s) = submissions.iter().find(|s| s.id == id) {
+                        if s.verdict == Verdict::Ac {
+                            info!("Submission {}: AC with the score {}", id, s.score);
+                            judging = None;
+                        } else if s.verdict.is_judged() {
+                            warn!("Submission {} of the best solution got {}", id, s.verdict);
+                            judging = None;
+                        }
+                    }
+                }
+                Err(err) => warn!("failed to fetch the submissions: {:#}", err),
+            }
+        }
+
+        if let Some(mut best) = Best::load(&work_dir) {
+            let now = Utc::now().timestamp();
+            let allowed = best
+                .submitted_at
+                .is_none_or(|at| now >= at + opt.every.num_seconds())
+                && cooldown_until.is_none_or(|until| Instant::now() >= until);
+            if best.submitted_sha256.as_ref() != Some(&best.sha256) && allowed {
+                let source = fs::read_to_string(work_dir.join("best.rs"))?;
+                if sha256(&source) != best.sha256 {
+                    warn!("`best.rs` was modified. Run `cargo atcoder ahc run` again.");
+                } else {
+                    let submitted = atc
+                        .submit(contest_id, problem_id, &source, DEFAULT_LANGUAGE)
+                        .await;
+                    match submitted {
+                        Ok(id) => {
+                            best.submitted_sha256 = Some(best.sha256.clone());
+                            best.submitted_at = Some(Utc::now().timestamp());
+                            best.save(&work_dir)?;
+                            info!(
+                                "Submitted the solution with the average score {:.1} (submission \
+                                 {})",
+                                best.average, id,
+                            );
+                            judging = Some(id);
+                        }
+                        Err(err) => {
+                            match err.downcast_ref::<Rejection>() {
+                                // Not to submit the same code again and again
+                                Some(Rejection::Duplicate(_)) => {
+                                    best.submitted_sha256 = Some(best.sha256.clone());
+                                    best.save(&work_dir)?;
+                                }
+                                Some(Rejection::Cooldown(wait, _)) => {
+                                    cooldown_until = Some(Instant::now() + *wait);
+                                }
+                                _ => {}
+                            }
+                            warn!("failed to submit: {:#}", err);
+                        }
+                    }
+                }
+            }
+        }
+        tokio::time::sleep(AUTOSUBMIT_POLL_INTERVAL).await;
+    }
+}
+
+// The bundled input if any, or the one generated by `prepare_inputs`
+fn input_path(package: &Package, work_dir: &Path, seed: u64) -> PathBuf {
+    let file_name = format!("{:04}.txt", seed);
+    let bundled = tools_dir(package).join("in").join(&file_name);
+    if bundled.is_file() {
+        bundled
+    } else {
+        work_dir.join("in").join(file_name)
+    }
+}
+
+/// Paths to the inputs of the seeds, generating the ones that are not in `tools/in`.
+fn prepare_inputs(package: &Package, work_dir: &Path, seeds: &[u64]) -> Result<Vec<PathBuf>> {
+    let file_name = |seed: u64| format!("{:04}.txt", seed);
+    let generated = work_dir.join("in");
+
+    let inputs = seeds
+        .iter()
+        .map(|&seed| input_path(package, work_dir, seed))
+        .collect::<Vec<_>>();
+
+    let missing = seeds
+        .iter()
+        .zip(&inputs)
+        .filter(|(_, path)| !path.is_file())
+        .map(|(&seed, _)| seed)
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(inputs);
+    }
+
+    // `gen` writes `in/{i:04}.txt` for the `i`-th line of the seeds file.
+    let gen = tool_exe(package, "gen");
+    ensure!(
+        gen.is_file(),
+        "The inputs of {} seed(s) are not found, and `gen` is not built",
+        missing.len(),
+    );
+    let gen_dir = work_dir.join("gen");
+    if gen_dir.exists() {
+        fs::remove_dir_all(&gen_dir)?;
+    }
+    fs::create_dir_all(&gen_dir)?;
+    let seeds_file = gen_dir.join("seeds.txt");
+    fs::write(
+        &seeds_file,
+        missing
+            .iter()
+            .map(|s| format!("{}\n", s))
+            .collect::<String>(),
+    )?;
+    let status = Command::new(&gen)
+        .arg(&seeds_file)
+        .current_dir(&gen_dir)
+        .status()
+        .with_context(|| format!("Failed to run {}", gen.display()))?;
+    ensure!(status.success(), "Failed to generate the inputs");
+
+    fs::create_dir_all(&generated)?;
+    for (i, &seed) in missing.iter().enumerate() {
+        fs::rename(
+            gen_dir.join("in").join(file_name(i as u64)),
+            generated.join(file_name(seed)),
+        )
+        .with_context(|| format!("`gen` did not generate the input of seed {}", seed))?;
+    }
+    fs::remove_dir_all(&gen_dir)?;
+    Ok(inputs)
+}
+
+fn score_seed(
+    solution: &Path,
+    tester: &Path,
+    vis: &Path,
+    input: &Path,
+    output: &Path,
+    time_limit: Duration,
+) -> Result<Option<f64>> {
+    let input_data =
+        fs::read(input).with_context(|| format!("Failed to read {}", input.display()))?;
+
+    let run = if tester.is_file() {
+        let mut cmd = Command::new(tester);
+        cmd.arg(solution);
+        cmd
+    } else {
+        Command::new(solution)
+    };
+    let (out, timed_out) = execute(run, &input_data, time_limit)?;
+    fs::write(output, &out.stdout)?;
+    ensure!(!timed_out, "time limit exceeded");
+    ensure!(out.status.success(), "exited with {}", out.status);
+
+    if tester.is_file() {
+        return Ok(parse_score(&String::from_utf8_lossy(&out.stderr)));
+    }
+    let scored = Command::new(vis).arg(input).arg(output).output()?;
+    Ok(parse_score(&String::from_utf8_lossy(&scored.stdout))
+        .or_else(|| parse_score(&String::from_utf8_lossy(&scored.stderr))))
+}
+
+// The tools print e.g. `Score = 12345` at the end.
+fn parse_score(s: &str) -> Option<f64> {
+    static SCORE: Lazy<Regex> = Lazy::new(|| Regex::new(r"Score\s*=\s*(-?\d+(?:\.\d+)?)").unwrap());
+    SCORE
+        .captures_iter(s)
+        .last()
+        .and_then(|caps| caps[1].parse().ok())
+}
+
+fn print_scores(scores: &Scores, previous: Option<&Scores>, minimize: bool) {
+    let previous_by_seed = previous.map(Scores::by_seed).unwrap_or_default();
+    let diff = |current: f64, previous: f64| {
+        let style = if is_better(current, previous, minimize) {
+            Style::new().green()
+        } else if is_better(previous, current, minimize) {
+            Style::new().red()
+        } else {
+            Style::new().dim()
+        };
+        style
+            .apply_to(format!("{:+}", current - previous))
+            .to_string()
+    };
+    let best_and_worst = scores.best_and_worst(minimize);
+
+    for result in &scores.results {
+        // Padded before styled not to count the escape sequences
+        let score = match (result.score, best_and_worst) {
+            (Some(score), Some((best, _))) if score == best => {
+                Style::new().green().apply_to(format!("{:>12}", score))
+            }
+            (Some(score), Some((_, worst))) if score == worst => {
+                Style::new().red().apply_to(format!("{:>12}", score))
+            }
+            (Some(score), _) => Style::new().apply_to(format!("{:>12}", score)),
+            (None, _) => Style::new().red().apply_to(format!("{:>12}", "failed")),
+        };
+        let compared = match (
+            result.score,
+            previous_by_seed.get(&result.seed).and_then(|r| r.score),
+        ) {
+            (Some(current), Some(previous)) => format!(" ({})", diff(current, previous)),
+            _ => "".to_owned(),
+        };
+        println!(
+            "seed {:04}: {}{} {:>6} ms",
+            result.seed, score, compared, result.time_ms
+        );
+    }
+    println!();
+
+    let total = scores.total();
+    let failed = scores.results.iter().filter(|r| r.score.is_none()).count();
+    println!(
+        "Total: {}, Average: {:.1}{}",
+        total,
+        total / scores.results.len() as f64,
+        if failed > 0 {
+            format!(", Failed: {}", failed)
+        } else {
+            "".to_owned()
+        },
+    );
+
+    // Compared on the common seeds only
+    if previous.is_some() {
+        let common = scores
+            .results
+            .iter()
+            .filter(|r| previous_by_seed.contains_key(&r.seed))
+            .collect::<Vec<_>>();
+        if !common.is_empty() {
+            let current_total = common.iter().filter_map(|r| r.score).sum::<f64>();
+            let previous_total = common
+                .iter()
+                .filter_map(|r| previous_by_seed[&r.seed].score)
+                .sum::<f64>();
+            let wins = common
+                .iter()
+                .filter(|r| match (r.score, previous_by_seed[&r.seed].score) {
+                    (Some(current), Some(previous)) => is_better(current, previous, minimize),
+                    (current, previous) => current.is_some() && previous.is_none(),
+                })
+                .count();
+            println!(
+                "Previous run: {} ({}) on {} common seed(s), improved on {}",
+                previous_total,
+                diff(current_total, previous_total),
+                common.len(),
+                wins,
+            );
+        }
+    }
+}
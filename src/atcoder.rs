@@ -1,13 +1,220 @@
+use crate::cache;
+use crate::ci::Failure;
 use crate::http::{is_http_error, Client};
-use anyhow::{anyhow, bail, Context as _, Result};
+use crate::i18n::tr;
+use crate::selectors;
+use anyhow::{anyhow, bail, ensure, Context as _, Result};
+use chrono::{DateTime, FixedOffset, Utc};
+use console::Style;
 use itertools::Itertools as _;
+use log::warn;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use scraper::{element_ref::ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::env;
 use std::fmt;
 use std::path::Path;
+use std::time::Duration;
 use url::Url;
 
 const ATCODER_ENDPOINT: &str = "https://atcoder.jp";
 
+/// AtCoder, or the mock server of the tests in `CARGO_ATCODER_TEST_ENDPOINT`
+static ENDPOINT: Lazy<String> = Lazy::new(|| {
+    env::var("CARGO_ATCODER_TEST_ENDPOINT").unwrap_or_else(|_| ATCODER_ENDPOINT.to_owned())
+});
+
+/// Language selected in the submission form unless overridden
+pub const DEFAULT_LANGUAGE: &str = "Rust";
+
+/// Absolute URL of a page on AtCoder (e.g. `/contests/abc999/standings`)
+pub fn page_url(path: &str) -> String {
+    format!("{}{}", *ENDPOINT, path)
+}
+
+/// Pairs the sample inputs and outputs in the page of a problem, in Japanese or in English.
+pub fn parse_test_cases(html: &str) -> Result<Vec<TestCase>> {
+    let doc = Html::parse_document(html);
+
+    let mut inputs_ja = vec![];
+    let mut outputs_ja = vec![];
+    let mut inputs_en = vec![];
+    let mut outputs_en = vec![];
+
+    for section in sections(doc.root_element()) {
+        let label = section.heading.as_str();
+        if label.starts_with("入力例") {
+            inputs_ja.push(section.sample());
+        }
+        if label.starts_with("出力例") {
+            outputs_ja.push(section.sample());
+        }
+
+        if label.starts_with("Sample Input") {
+            inputs_en.push(section.sample());
+        }
+        if label.starts_with("Sample Output") {
+            outputs_en.push(section.sample());
+        }
+    }
+
+    let (inputs, outputs) = if !inputs_ja.is_empty() && inputs_ja.len() == outputs_ja.len() {
+        (inputs_ja, outputs_ja)
+    } else if !inputs_en.is_empty() && inputs_en.len() == outputs_en.len() {
+        (inputs_en, outputs_en)
+    } else {
+        bail!(
+            "Could not scrape sample test cases (JA inputs: {}, JA outputs: {}, EN inputs: \
+             {}, EN outputs: {})",
+            inputs_ja.len(),
+            outputs_ja.len(),
+            inputs_en.len(),
+            outputs_en.len(),
+        );
+    };
+
+    Ok(inputs
+        .into_iter()
+        .zip(outputs)
+        .map(|(input, output)| TestCase { input, output })
+        .collect())
+}
+
+/// The statement of a problem split into its sections, each the inner HTML without the heading
+#[derive(Debug, Default, Clone)]
+pub struct Statement {
+    pub problem: Option<String>,
+    pub constraints: Option<String>,
+    pub input_format: Option<String>,
+    pub output_format: Option<String>,
+    pub samples: Vec<TestCase>,
+    /// The other sections (e.g. `注意`, `Notes`) with their headings
+    pub others: Vec<(String, String)>,
+}
+
+/// Parses the statement in the page of a problem, or the HTML from
+/// [`AtCoder::statement_html`], in Japanese or in English if `english`.
+///
+/// The headings are recognized in both languages, for the statements not separated by
+/// languages.
+pub fn parse_statement(html: &str, english: bool) -> Result<Statement> {
+    let doc = Html::parse_document(html);
+    let root = doc
+        .select(&selectors::get("statement"))
+        .next()
+        .unwrap_or_else(|| doc.root_element());
+    let lang = Selector::parse(if english { ".lang-en" } else { ".lang-ja" }).unwrap();
+    let root = root.select(&lang).next().unwrap_or(root);
+
+    let sections = sections(root);
+    ensure!(
+        !sections.is_empty(),
+        "Could not find the sections of the statement"
+    );
+
+    let mut statement = Statement::default();
+    let (mut inputs, mut outputs) = (vec![], vec![]);
+    for section in sections {
+        let heading = section.heading.as_str();
+        let field = if heading.starts_with("入力例") || heading.starts_with("Sample Input") {
+            inputs.push(section.sample());
+            continue;
+        } else if heading.starts_with("出力例") || heading.starts_with("Sample Output") {
+            outputs.push(section.sample());
+            continue;
+        } else if heading.starts_with("問題文") || heading.starts_with("Problem Statement") {
+            &mut statement.problem
+        } else if heading.starts_with("制約") || heading.starts_with("Constraints") {
+            &mut statement.constraints
+        } else if heading.starts_with("入力") || heading.starts_with("Input") {
+            &mut statement.input_format
+        } else if heading.starts_with("出力") || heading.starts_with("Output") {
+            &mut statement.output_format
+        } else {
+            statement
+                .others
+                .push((section.heading.clone(), section.body()));
+            continue;
+        };
+        if field.is_none() {
+            *field = Some(section.body());
+        }
+    }
+    ensure!(
+        inputs.len() == outputs.len(),
+        "Could not pair the samples of the statement (inputs: {}, outputs: {})",
+        inputs.len(),
+        outputs.len(),
+    );
+    statement.samples = inputs
+        .into_iter()
+        .zip(outputs)
+        .map(|(input, output)| TestCase { input, output })
+        .collect();
+    Ok(statement)
+}
+
+/// A section of a statement with the text of its heading
+struct Section<'a> {
+    heading: String,
+    h3: ElementRef<'a>,
+    element: ElementRef<'a>,
+}
+
+impl Section<'_> {
+    /// The inner HTML without the heading
+    fn body(&self) -> String {
+        self.element
+            .inner_html()
+            .replacen(&self.h3.html(), "", 1)
+            .trim()
+            .to_owned()
+    }
+
+    /// The text of the first `<pre>`, which is a sample in the sections of the samples
+    fn sample(&self) -> String {
+        self.element
+            .select(&selectors::get("sample"))
+            .next()
+            .map(|pre| pre.text().collect::<String>().trim().to_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// The sections under `root` by the headings, in the order of the page.
+///
+/// A heading belongs to the `<section>` in the `<div class="part">` containing it, even if it is
+/// wrapped in other elements, or else (in the older statements) to its parent.
+fn sections(root: ElementRef<'_>) -> Vec<Section<'_>> {
+    let parts = root
+        .select(&selectors::get("statement-section"))
+        .map(|section| section.id())
+        .collect::<HashSet<_>>();
+    let mut seen = HashSet::new();
+    root.select(&selectors::get("sample-heading"))
+        .filter_map(|h3| {
+            let element = h3
+                .ancestors()
+                .find(|node| parts.contains(&node.id()))
+                .or_else(|| h3.parent())
+                .and_then(ElementRef::wrap)?;
+            if !seen.insert(element.id()) {
+                return None;
+            }
+            // Without the copy buttons in the headings of the samples
+            let heading = h3.text().next().unwrap_or_default().trim().to_owned();
+            Some(Section {
+                heading,
+                h3,
+                element,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone)]
 pub struct AtCoder {
     client: Client,
 }
@@ -15,23 +222,366 @@ pub struct AtCoder {
 #[derive(Debug)]
 pub struct ContestInfo {
     problems: Vec<Problem>,
+    /// Start and end time, if shown in the header
+    duration: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>,
+    /// Logged in and not registered yet, with the form to register
+    registrable: bool,
+    /// Whether the contest is rated, if shown in the header of the top page
+    rated: Option<bool>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Problem {
     pub id: String,
-    pub _name: String,
+    pub name: String,
     pub url: String,
-    pub _tle: String,
+    pub tle: String,
     pub _mle: String,
+    /// From the score table on the top page of the contest, if it has one
+    pub score: Option<Score>,
 }
 
-#[derive(Debug, Clone)]
+/// How a problem is scored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// The points for an acceptance
+    Points(u64),
+    /// By the scores of the cases, as in the heuristic contests
+    Partial,
+}
+
+impl Score {
+    /// Parses a cell of the score table (e.g. `100`, `600 (部分点あり)`).
+    fn parse(cell: &str) -> Option<Self> {
+        let digits = cell.trim().split(|c: char| !c.is_ascii_digit()).next()?;
+        digits.parse().ok().map(Score::Points)
+    }
+
+    /// In hundredths of points as in the standings, for the acceptance
+    pub fn hundredths(&self) -> Option<i64> {
+        match *self {
+            Score::Points(points) => Some(points as i64 * 100),
+            Score::Partial => None,
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Points(points) => write!(f, "{}", points),
+            Score::Partial => write!(f, "partial"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestCase {
     pub input: String,
     pub output: String,
 }
 
+/// Judge status of a submission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Ac,
+    Wa,
+    Tle,
+    Mle,
+    Re,
+    Ce,
+    Ole,
+    Qle,
+    Ie,
+    /// Waiting for judge, or being judged with the number of the cases done so far and the worst
+    /// verdict of them
+    Wj {
+        progress: Option<(usize, usize)>,
+        worst: Option<Box<Verdict>>,
+    },
+    /// Waiting for rejudge
+    Wr,
+    Other(String),
+}
+
+impl Verdict {
+    /// Parses the label in the submissions table (e.g. `AC`, `WJ`, `3/15 TLE`).
+    ///
+    /// During judging, the label shows the progress and the worst verdict so far.
+    pub fn parse(label: &str) -> Self {
+        let mut words = label.split_whitespace();
+        let first = words.next().unwrap_or_default();
+        if let Some((done, total)) = first.split_once('/') {
+            if let (Ok(done), Ok(total)) = (done.parse(), total.parse()) {
+                return Verdict::Wj {
+                    progress: Some((done, total)),
+                    worst: words.next().map(|w| Box::new(Verdict::parse(w))),
+                };
+            }
+        }
+        match first {
+            "AC" => Verdict::Ac,
+            "WA" => Verdict::Wa,
+            "TLE" => Verdict::Tle,
+            "MLE" => Verdict::Mle,
+            "RE" => Verdict::Re,
+            "CE" => Verdict::Ce,
+            "OLE" => Verdict::Ole,
+            "QLE" => Verdict::Qle,
+            "IE" => Verdict::Ie,
+            "WJ" | "Judging" => Verdict::Wj {
+                progress: None,
+                worst: None,
+            },
+            "WR" => Verdict::Wr,
+            _ => Verdict::Other(label.trim().to_owned()),
+        }
+    }
+
+    /// Whether the judge has finished
+    pub fn is_judged(&self) -> bool {
+        !matches!(self, Verdict::Wj { .. } | Verdict::Wr)
+    }
+
+    pub fn style(&self) -> Style {
+        match self {
+            Verdict::Ac => Style::new().green(),
+            // Already failing
+            Verdict::Wj {
+                worst: Some(worst), ..
+            } if **worst != Verdict::Ac => Style::new().yellow().dim(),
+            Verdict::Wj { .. } | Verdict::Wr => Style::new().dim(),
+            _ => Style::new().yellow(),
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Verdict::Ac => "AC",
+            Verdict::Wa => "WA",
+            Verdict::Tle => "TLE",
+            Verdict::Mle => "MLE",
+            Verdict::Re => "RE",
+            Verdict::Ce => "CE",
+            Verdict::Ole => "OLE",
+            Verdict::Qle => "QLE",
+            Verdict::Ie => "IE",
+            Verdict::Wj {
+                progress: Some((done, total)),
+                worst,
+            } => {
+                let s = match worst {
+                    Some(worst) => format!("{}/{} {}", done, total, worst),
+                    None => format!("{}/{}", done, total),
+                };
+                return f.pad(&s);
+            }
+            Verdict::Wj { progress: None, .. } => "WJ",
+            Verdict::Wr => "WR",
+            Verdict::Other(s) => s,
+        };
+        f.pad(s)
+    }
+}
+
+/// A row of the submissions table
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub id: u64,
+    /// e.g. `2021-08-01 21:05:23+0900`
+    pub time: String,
+    /// Task screen name (e.g. `abc999_a`)
+    pub task: String,
+    /// e.g. `A - Foo`
+    pub task_name: String,
+    pub language: String,
+    pub score: String,
+    pub verdict: Verdict,
+    /// Absent while judging
+    pub exec_time: Option<String>,
+    pub memory: Option<String>,
+}
+
+/// The judge result on the page of a submission
+#[derive(Debug, Clone)]
+pub struct SubmissionResult {
+    pub verdict: Verdict,
+    /// The cases judged so far
+    pub cases: Vec<CaseResult>,
+}
+
+/// A row of the table of the cases on the page of a submission
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// e.g. `sample_01.txt`
+    pub name: String,
+    pub verdict: Verdict,
+    pub exec_time: String,
+    pub memory: String,
+}
+
+/// An editorial in the list of the editorials of a contest
+#[derive(Debug, Clone)]
+pub struct Editorial {
+    /// Task screen name (e.g. `abc999_a`), or `None` for the editorials of the whole contest
+    pub task: Option<String>,
+    /// Path in AtCoder (e.g. `/contests/abc999/editorial/1234`), or a URL of another site
+    pub url: String,
+    pub official: bool,
+    pub author: Option<String>,
+}
+
+/// The choices of the submission form of a contest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitForm {
+    /// Task screen names (e.g. `abc999_a`) and the names (e.g. `A - Foo`)
+    pub tasks: Vec<(String, String)>,
+    /// IDs (e.g. `5054`) and the names (e.g. `Rust (rustc 1.70.0)`) of the languages, by task
+    /// screen name
+    pub languages: BTreeMap<String, Vec<(String, String)>>,
+}
+
+impl SubmitForm {
+    /// The task screen name of the problem, by the task screen name or by the prefix of the name
+    pub fn task(&self, problem_id: &str) -> Result<&str> {
+        self.tasks
+            .iter()
+            .find(|(screen_name, name)| {
+                screen_name == problem_id
+                    || name
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_lowercase()
+                        .starts_with(&problem_id.to_lowercase())
+            })
+            .map(|(screen_name, _)| &**screen_name)
+            .with_context(|| format!("Problem not found: {}", problem_id))
+    }
+
+    /// The ID and the name of the language of the task named `language` (e.g. `Python (PyPy
+    /// 3.10-v7.3.12)`), or else of the first one whose first word starts with it (e.g. `Rust`)
+    pub fn language(&self, task: &str, language: &str) -> Option<(&str, &str)> {
+        let languages = self.languages.get(task)?;
+        let language = language.to_lowercase();
+        languages
+            .iter()
+            .find(|(_, name)| name.to_lowercase() == language)
+            .or_else(|| {
+                languages.iter().find(|(_, name)| {
+                    name.split_whitespace()
+                        .next()
+                        .unwrap_or("")
+                        .to_lowercase()
+                        .starts_with(&language)
+                })
+            })
+            .map(|(id, name)| (&**id, &**name))
+    }
+}
+
+/// Standings from `/contests/<contest>/standings/json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Standings {
+    pub task_info: Vec<StandingsTask>,
+    pub standings_data: Vec<StandingsRow>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StandingsTask {
+    /// e.g. `A`
+    pub assignment: String,
+    /// e.g. `abc999_a`
+    pub task_screen_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StandingsRow {
+    pub rank: u64,
+    pub user_screen_name: String,
+    /// By task screen name, only for the tasks submitted to
+    pub task_results: BTreeMap<String, StandingsResult>,
+    pub total_result: StandingsResult,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct StandingsResult {
+    /// In hundredths of a point
+    pub score: i64,
+    /// In nanoseconds from the start, including the penalty
+    pub elapsed: i64,
+    /// Submitted after the standings were frozen
+    pub pending: bool,
+    pub frozen: bool,
+}
+
+/// A contest in `/users/<user>/history/json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContestResult {
+    pub is_rated: bool,
+    pub place: u64,
+    pub old_rating: i64,
+    pub new_rating: i64,
+    /// Absent for the contests before the performances were introduced
+    #[serde(default)]
+    pub performance: Option<i64>,
+    /// e.g. `abc999.contest.atcoder.jp`
+    pub contest_screen_name: String,
+    /// e.g. `2021-01-02T22:40:00+09:00`
+    pub end_time: String,
+}
+
+impl Standings {
+    pub fn is_frozen(&self) -> bool {
+        self.standings_data.iter().any(|row| {
+            row.total_result.frozen
+                || row
+                    .task_results
+                    .values()
+                    .any(|result| result.frozen || result.pending)
+        })
+    }
+
+    pub fn row(&self, user: &str) -> Option<&StandingsRow> {
+        self.standings_data
+            .iter()
+            .find(|row| row.user_screen_name.eq_ignore_ascii_case(user))
+    }
+}
+
+impl Submission {
+    pub fn submitted_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_str(&self.time, "%Y-%m-%d %H:%M:%S%z").ok()
+    }
+
+    /// Whether this is for the problem `problem_id` (e.g. `e` or `abc999_e`)
+    pub fn is_for(&self, problem_id: &str) -> bool {
+        let problem_id = problem_id.to_lowercase();
+        let task = self.task.to_lowercase();
+        task == problem_id || task.rsplit('_').next() == Some(&problem_id)
+    }
+}
+
+impl Problem {
+    /// Time limit parsed from the task list (e.g. `2 sec`, `500 msec`)
+    pub fn time_limit(&self) -> Option<Duration> {
+        let mut it = self.tle.split_whitespace();
+        let n = it.next()?.parse::<f64>().ok()?;
+        match it.next()? {
+            "sec" => Some(Duration::from_secs_f64(n)),
+            "msec" | "ms" => Some(Duration::from_secs_f64(n / 1000.)),
+            _ => None,
+        }
+    }
+}
+
 impl ContestInfo {
     pub fn problem(&self, id: &str) -> Option<&Problem> {
         self.problems
@@ -39,66 +589,82 @@ impl ContestInfo {
             .find(|p| p.id.to_lowercase() == id.to_lowercase())
     }
 
+    /// The problem of the task screen name (e.g. `abc999_a`)
+    pub fn problem_by_task(&self, task_screen_name: &str) -> Option<&Problem> {
+        self.problems
+            .iter()
+            .find(|p| p.url.rsplit('/').next() == Some(task_screen_name))
+    }
+
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
     pub fn problem_ids_lowercase(&self) -> Vec<String> {
         self.problems.iter().map(|p| p.id.to_lowercase()).collect()
     }
+
+    pub fn start_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.duration.map(|(start, _)| start)
+    }
+
+    pub fn end_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.duration.map(|(_, end)| end)
+    }
+
+    pub fn registrable(&self) -> bool {
+        self.registrable
+    }
+
+    pub fn rated(&self) -> Option<bool> {
+        self.rated
+    }
+}
+
+/// Username and password in `ATCODER_USERNAME` and `ATCODER_PASSWORD`, to login without the
+/// prompt e.g. on CI.
+pub fn env_credentials() -> Option<(String, String)> {
+    let var = |key| env::var(key).ok().filter(|s| !s.is_empty());
+    Some((var("ATCODER_USERNAME")?, var("ATCODER_PASSWORD")?))
 }
 
 impl AtCoder {
+    /// The session cookie in `ATCODER_SESSION` (the value of `REVEL_SESSION`) is used if set.
     pub fn new(session_file: &Path) -> Result<AtCoder> {
-        Ok(Self {
-            client: Client::new(session_file, ATCODER_ENDPOINT)?,
-        })
+        let client = Client::new(session_file, &ENDPOINT)?;
+        if let Some(session) = env::var("ATCODER_SESSION").ok().filter(|s| !s.is_empty()) {
+            client.add_cookie(&format!("REVEL_SESSION={}", session))?;
+        }
+        Ok(Self { client })
     }
 
+    // Logs in with the credentials in the environment if not logged in yet.
     async fn check_login(&self) -> Result<()> {
-        let _ = self
-            .username()
-            .await?
-            .with_context(|| "You are not logged in. Please login first.")?;
-        Ok(())
+        if self.username().await?.is_some() {
+            return Ok(());
+        }
+        let (username, password) = env_credentials()
+            .ok_or(Failure::Auth)
+            .with_context(|| tr!(PleaseLogin))?;
+        self.login(&username, &password).await
     }
 
     pub async fn username(&self) -> Result<Option<String>> {
-        let doc = self.http_get("/").await?;
-        let doc = Html::parse_document(&doc);
-
-        let r = doc
-            .select(&Selector::parse("li a[href^=\"/users/\"]").unwrap())
-            .next();
-
-        if r.is_none() {
-            return Ok(None);
-        }
+        Ok(parse_username(&self.http_get("/").await?))
+    }
 
-        Ok(Some(
-            r.unwrap().value().attr("href").unwrap()[7..].to_owned(),
-        ))
+    /// Current time on AtCoder, from the `Date` header
+    pub async fn server_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let date = self.client.date(&ENDPOINT.parse::<Url>()?).await?;
+        date.map(|date| Ok(DateTime::parse_from_rfc2822(&date)?.with_timezone(&Utc)))
+            .transpose()
     }
 
     pub async fn login(&self, username: &str, password: &str) -> Result<()> {
-        let document = self.http_get("/login").await?;
-        let document = Html::parse_document(&document);
-
-        let csrf_token = document
-            .select(&Selector::parse("input[name=\"csrf_token\"]").unwrap())
-            .next()
-            .with_context(|| "cannot find csrf_token")?;
-
-        let csrf_token = csrf_token
-            .value()
-            .attr("value")
-            .with_context(|| "cannot find csrf_token")?;
-
+        // For the token of the session before logging in
+        self.http_get("/login").await?;
         let res = self
-            .http_post_form(
-                "/login",
-                &[
-                    ("username", username),
-                    ("password", password),
-                    ("csrf_token", csrf_token),
-                ],
-            )
+            .http_post_form_with_csrf("/login", &[("username", username), ("password", password)])
             .await?;
 
         let res = Html::parse_document(&res);
@@ -112,10 +678,10 @@ impl AtCoder {
             .select(&Selector::parse("div.alert-danger").unwrap())
             .next()
         {
-            bail!(
+            return Err(anyhow::Error::new(Failure::Auth).context(format!(
                 "Login failed: {}",
                 err.last_child().unwrap().value().as_text().unwrap().trim()
-            );
+            )));
         }
 
         // On success:
@@ -131,267 +697,331 @@ impl AtCoder {
             return Ok(());
         }
 
-        Err(anyhow!("Login failed: Unknown error"))
+        Err(anyhow::Error::new(Failure::Auth).context("Login failed: Unknown error"))
     }
 
     pub async fn problem_ids_from_score_table(
         &self,
         contest_id: &str,
     ) -> Result<Option<Vec<String>>> {
-        let doc = self.http_get(&format!("/contests/{}", contest_id)).await?;
+        match self.score_table(contest_id).await {
+            Err(err) if is_http_error(&err, reqwest::StatusCode::NOT_FOUND) => {
+                bail!("Contest `{}` does not exist", contest_id)
+            }
+            table => Ok(table?.map(|rows| rows.into_iter().map(|(id, _)| id).collect())),
+        }
+    }
 
-        Html::parse_document(&doc)
-            .select(&Selector::parse("#contest-statement > .lang > .lang-ja table").unwrap())
-            .filter(|table| {
-                let header = table
-                    .select(&Selector::parse("thead > tr > th").unwrap())
-                    .flat_map(|r| r.text())
-                    .collect::<Vec<_>>();
-                header == ["Task", "Score"] || header == ["問題", "点数"]
-            })
-            .exactly_one()
-            .ok()
-            .map(|table| {
-                table
-                    .select(&Selector::parse("tbody > tr").unwrap())
-                    .map(|tr| {
-                        let text = tr
-                            .select(&Selector::parse("td").unwrap())
-                            .flat_map(|r| r.text())
-                            .collect::<Vec<_>>();
-                        match text.len() {
-                            2 => Ok(text[0].to_owned()),
-                            _ => Err(anyhow!("could not parse the table")),
-                        }
-                    })
-                    .collect()
-            })
-            .transpose()
+    /// The rows of the score table on the top page of the contest (e.g. `("A", "100")`), if it
+    /// has one
+    async fn score_table(&self, contest_id: &str) -> Result<Option<Vec<(String, String)>>> {
+        parse_score_table(&self.http_get(&format!("/contests/{}", contest_id)).await?)
+    }
+
+    /// ID of the running contest of the series (e.g. `abc`), or else of the next upcoming one, or
+    /// else of the last one
+    pub async fn latest_contest(&self, series: &str) -> Result<Option<String>> {
+        let doc = self.http_get("/contests/").await?;
+        let doc = Html::parse_document(&doc);
+        for table in [
+            "#contest-table-action",
+            "#contest-table-upcoming",
+            "#contest-table-recent",
+        ] {
+            let id = doc
+                .select(&Selector::parse(&format!("{} a[href]", table)).unwrap())
+                .filter_map(|a| a.value().attr("href")?.strip_prefix("/contests/"))
+                .find(|id| {
+                    id.strip_prefix(series)
+                        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                });
+            if let Some(id) = id {
+                return Ok(Some(id.to_owned()));
+            }
+        }
+        Ok(None)
     }
 
     pub async fn contest_info(&self, contest_id: &str) -> Result<ContestInfo> {
-        let doc = self
-            .retrieve_text_or_error_message(&format!("/contests/{}/tasks", contest_id), || {
+        let path = format!("/contests/{}/tasks", contest_id);
+        let tasks = || {
+            self.retrieve_text_or_error_message(&path, || {
                 format!(
                     "You are not participating in `{}`, or it does not yet exist",
                     contest_id,
                 )
             })
-            .await?;
+        };
+        let doc = match tasks().await {
+            Ok(doc) => doc,
+            // Told apart by the top page of the contest, which is public
+            Err(err) if is_http_error(&err, reqwest::StatusCode::NOT_FOUND) => {
+                match self.contest_access(contest_id).await {
+                    // Not as not found, not to be guessed in `new`
+                    Ok(ContestAccess::Restricted(notice)) => {
+                        bail!("You cannot take part in `{}`: {}", contest_id, notice);
+                    }
+                    Ok(ContestAccess::Registrable) => {
+                        return Err(err.context(Unregistered {
+                            contest_id: contest_id.to_owned(),
+                        }));
+                    }
+                    _ => return Err(err),
+                }
+            }
+            Err(err) => return Err(err),
+        };
 
-        let doc = Html::parse_document(&doc);
-        let sel_problem = Selector::parse("table tbody tr").unwrap();
+        let mut info = parse_contest_info(&doc);
 
-        let mut problems = vec![];
+        // The problem list has no scores nor the registration, which are on the top page.
+        let top = self
+            .http_get(&format!("/contests/{}", contest_id))
+            .await
+            .ok();
+        if let Some(Ok(Some(table))) = top.as_deref().map(parse_score_table) {
+            for problem in &mut info.problems {
+                problem.score = table
+                    .iter()
+                    .find(|(id, _)| id.trim().eq_ignore_ascii_case(&problem.id))
+                    .and_then(|(_, score)| Score::parse(score));
+            }
+        } else if top
+            .as_deref()
+            .map_or(contest_id.starts_with("ahc"), parse_heuristic)
+        {
+            // The heuristic contests have no score tables, told by the name without the top page.
+            for problem in &mut info.problems {
+                problem.score = Some(Score::Partial);
+            }
+        }
+        if let Some(top) = &top {
+            // Only the logged-in users can register.
+            info.registrable = parse_username(top).is_some()
+                && parse_contest_access(top) == ContestAccess::Registrable;
+            info.rated = parse_rated(top);
+        }
 
-        for row in doc.select(&sel_problem) {
-            let sel_td = Selector::parse("td").unwrap();
-            let mut it = row.select(&sel_td);
-            let c1 = it.next().unwrap();
-            let c2 = it.next().unwrap();
-            let c3 = it.next().unwrap();
-            let c4 = it.next().unwrap();
+        Ok(info)
+    }
 
-            let id = c1
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .unwrap()
-                .inner_html();
+    /// Whether the user can register for the contest, from its top page
+    pub async fn contest_access(&self, contest_id: &str) -> Result<ContestAccess> {
+        let doc = self.http_get(&format!("/contests/{}", contest_id)).await?;
+        Ok(parse_contest_access(&doc))
+    }
 
-            let name = c2
-                .select(&Selector::parse("a").unwrap())
+    /// Registers for the contest with the form in its top page.
+    pub async fn register(&self, contest_id: &str) -> Result<()> {
+        self.check_login().await?;
+        let doc = self.http_get(&format!("/contests/{}", contest_id)).await?;
+        ensure!(
+            Html::parse_document(&doc)
+                .select(&selectors::get("register-form"))
                 .next()
-                .unwrap()
-                .inner_html();
+                .is_some(),
+            "Registration for `{}` is not open",
+            contest_id
+        );
 
-            let url = c2
-                .select(&Selector::parse("a").unwrap())
-                .next()
-                .unwrap()
-                .value()
-                .attr("href")
-                .unwrap();
-
-            let tle = c3.inner_html();
-            let mle = c4.inner_html();
-
-            problems.push(Problem {
-                id: id.trim().to_owned(),
-                _name: name.trim().to_owned(),
-                url: url.trim().to_owned(),
-                _tle: tle.trim().to_owned(),
-                _mle: mle.trim().to_owned(),
-            });
+        let res = self
+            .http_post_form_with_csrf(&format!("/contests/{}/register", contest_id), &[])
+            .await?;
+        if let ContestAccess::Restricted(notice) = parse_contest_access(&res) {
+            bail!("Failed to register for `{}`: {}", contest_id, notice);
         }
-
-        Ok(ContestInfo { problems })
+        println!("Registered for `{}`", contest_id);
+        Ok(())
     }
 
-    pub async fn test_cases(&self, problem_url: &str) -> Result<Vec<TestCase>> {
-        let doc = self.http_get(problem_url).await?;
-
-        let doc = Html::parse_document(&doc);
+    /// The official and user editorials of the contest, in the order of the page
+    pub async fn editorials(&self, contest_id: &str) -> Result<Vec<Editorial>> {
+        let doc = self
+            .retrieve_text_or_error_message(&format!("/contests/{}/editorial", contest_id), || {
+                format!("`{}` does not exist", contest_id)
+            })
+            .await?;
+        Ok(parse_editorials(&doc))
+    }
 
-        let h3_sel = Selector::parse("h3").unwrap();
-
-        let mut inputs_ja = vec![];
-        let mut outputs_ja = vec![];
-        let mut inputs_en = vec![];
-        let mut outputs_en = vec![];
-
-        for r in doc.select(&h3_sel) {
-            let p = ElementRef::wrap(r.parent().unwrap()).unwrap();
-            let label = p.select(&h3_sel).next().unwrap().inner_html();
-            let label = label.trim();
-            // dbg!(r.parent().unwrap().first_child().unwrap().value());
-
-            // let label = r
-            //     .prev_sibling()
-            //     .unwrap()
-            //     .first_child()
-            //     .unwrap()
-            //     .value()
-            //     .as_text()
-            //     .unwrap();
-
-            let f = || {
-                p.select(&Selector::parse("pre").unwrap())
-                    .next()
-                    .unwrap()
-                    .text()
-                    .exactly_one()
-                    .map(|s| s.trim().to_owned())
-                    .unwrap_or_default()
-            };
-            if label.starts_with("入力例") {
-                inputs_ja.push(f());
-            }
-            if label.starts_with("出力例") {
-                outputs_ja.push(f());
-            }
+    /// HTML of the whole page of an editorial in AtCoder.
+    pub async fn editorial_page(&self, path: &str) -> Result<String> {
+        self.http_get_public(path).await
+    }
 
-            if label.starts_with("Sample Input") {
-                inputs_en.push(f());
-            }
-            if label.starts_with("Sample Output") {
-                outputs_en.push(f());
-            }
-        }
+    /// HTML of the whole page of the problem.
+    pub async fn problem_page(&self, problem_url: &str) -> Result<String> {
+        self.http_get_public(problem_url).await
+    }
 
-        let (inputs, outputs) = if !inputs_ja.is_empty() && inputs_ja.len() == outputs_ja.len() {
-            (inputs_ja, outputs_ja)
-        } else if !inputs_en.is_empty() && inputs_en.len() == outputs_en.len() {
-            (inputs_en, outputs_en)
-        } else {
-            bail!(
-                "Could not scrape sample test cases (JA inputs: {}, JA outputs: {}, EN inputs: \
-                 {}, EN outputs: {})",
-                inputs_ja.len(),
-                outputs_ja.len(),
-                inputs_en.len(),
-                outputs_en.len(),
-            );
-        };
+    /// HTML of the statement of the problem in Japanese, or in English if `english`.
+    ///
+    /// The whole statement is returned if it is not separated by languages.
+    pub async fn statement_html(&self, problem_url: &str, english: bool) -> Result<String> {
+        parse_statement_html(&self.http_get_public(problem_url).await?, english)
+    }
 
-        let mut ret = vec![];
-        for i in 0..inputs.len() {
-            ret.push(TestCase {
-                input: inputs[i].clone(),
-                output: outputs[i].clone(),
-            });
-        }
-        Ok(ret)
+    /// Downloads a file referred in a page, e.g. an image in a statement.
+    pub async fn download(&self, src: &str) -> Result<Vec<u8>> {
+        let url = ENDPOINT.parse::<Url>()?.join(src)?;
+        self.client.get_bytes(&url).await
     }
 
-    pub async fn submit(
+    /// My submissions in the contest on `page` (starting from 1, newest first) of the table, and
+    /// the number of the pages.
+    pub async fn submissions(
         &self,
         contest_id: &str,
-        problem_id: &str,
-        source_code: &str,
-    ) -> Result<()> {
-        self.check_login().await?;
+        page: usize,
+    ) -> Result<(Vec<Submission>, usize)> {
+        let mut path = format!("/contests/{}/submissions/me", contest_id);
+        if page > 1 {
+            path += &format!("?page={}", page);
+        }
+        let doc = self
+            .retrieve_text_or_error_message(&path, || format!("`{}` does not exist", contest_id))
+            .await?;
+        parse_submissions(&doc)
+    }
 
+    /// Source code of the submission, which is of mine or public (e.g. after the contest)
+    pub async fn submission_source(&self, contest_id: &str, id: u64) -> Result<String> {
         let doc = self
-            .http_get(&format!("/contests/{}/submit", contest_id))
+            .retrieve_text_or_error_message(
+                &format!("/contests/{}/submissions/{}", contest_id, id),
+                || format!("Submission {} of `{}` does not exist", id, contest_id),
+            )
+            .await?;
+        parse_submission_source(&doc)
+            .with_context(|| format!("Could not find the source code of submission {}", id))
+    }
+
+    /// Verdict of the submission and of each of its cases, which change while judging
+    pub async fn submission_result(&self, contest_id: &str, id: u64) -> Result<SubmissionResult> {
+        let doc = self
+            .retrieve_text_or_error_message(
+                &format!("/contests/{}/submissions/{}", contest_id, id),
+                || format!("Submission {} of `{}` does not exist", id, contest_id),
+            )
             .await?;
+        parse_submission_result(&doc)
+            .with_context(|| format!("Could not find the result of submission {}", id))
+    }
 
-        let (task_screen_name, language_id, language_name, csrf_token) = {
-            let doc = Html::parse_document(&doc);
+    pub async fn standings(&self, contest_id: &str) -> Result<Standings> {
+        let json = self
+            .retrieve_text_or_error_message(
+                &format!("/contests/{}/standings/json", contest_id),
+                || format!("`{}` does not exist", contest_id),
+            )
+            .await?;
+        serde_json::from_str(&json).with_context(|| "Failed to parse the standings")
+    }
 
-            let task_screen_name = (|| {
-                for r in doc.select(
-                    &Selector::parse("select[name=\"data.TaskScreenName\"] option").unwrap(),
-                ) {
-                    if r.inner_html()
-                        .split_whitespace()
-                        .next()
-                        .unwrap()
-                        .to_lowercase()
-                        .starts_with(&problem_id.to_lowercase())
-                    {
-                        return Ok(r.value().attr("value").unwrap());
-                    }
-                }
-                Err(anyhow!("Problem not found: {}", problem_id))
-            })()?;
-
-            let (language_id, language_name) = (|| {
-                for r in doc.select(
-                    &Selector::parse(&format!(
-                        "div[id=\"select-lang-{}\"] select option",
-                        &task_screen_name
-                    ))
-                    .unwrap(),
-                ) {
-                    if r.inner_html()
-                        .split_whitespace()
-                        .next()
-                        .unwrap_or("")
-                        .to_lowercase()
-                        .starts_with("rust")
-                    {
-                        return Ok((r.value().attr("value").unwrap(), r.inner_html()));
-                    }
+    /// Current rating of the user, or `None` if unrated
+    pub async fn rating(&self, user: &str) -> Result<Option<i64>> {
+        Ok(self
+            .rating_history(user)
+            .await?
+            .iter()
+            .rev()
+            .find(|result| result.is_rated)
+            .map(|result| result.new_rating))
+    }
+
+    /// The contests the user participated in, from the oldest
+    pub async fn rating_history(&self, user: &str) -> Result<Vec<ContestResult>> {
+        // Public, without logging in
+        let json = self
+            .http_get(&format!("/users/{}/history/json", user))
+            .await
+            .map_err(|err| {
+                if is_http_error(&err, reqwest::StatusCode::NOT_FOUND) {
+                    err.context(format!("User `{}` does not exist", user))
+                } else {
+                    err
                 }
-                Err(anyhow!(
-                    "Rust seems to be not available in problem {}...",
-                    problem_id
-                ))
-            })()?;
-
-            let csrf_token = doc
-                .select(&Selector::parse("input[name=\"csrf_token\"]").unwrap())
-                .next()
-                .unwrap()
-                .value()
-                .attr("value")
-                .unwrap();
-
-            (
-                task_screen_name.to_owned(),
-                language_id.to_owned(),
-                language_name,
-                csrf_token.to_owned(),
-            )
+            })?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse the history of `{}`", user))
+    }
+
+    pub async fn submit(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+        source_code: &str,
+        language: &str,
+    ) -> Result<u64> {
+        // The choices fetched today, if the task and the language are in them and the token is
+        // in the top page fetched to check the login
+        let cached = match cache::load_submit_form(contest_id) {
+            Some(form)
+                if form
+                    .task(problem_id)
+                    .is_ok_and(|task| form.language(task, language).is_some()) =>
+            {
+                self.check_login().await?;
+                Some(form).filter(|_| self.client.csrf_token().is_some())
+            }
+            _ => None,
+        };
+        let form = match cached {
+            Some(form) => form,
+            None => self.submit_form(contest_id).await?,
         };
+        let task_screen_name = form.task(problem_id)?.to_owned();
+        let (language_id, language_name) = form
+            .language(&task_screen_name, language)
+            .map(|(id, name)| (id.to_owned(), name.to_owned()))
+            .with_context(|| {
+                format!(
+                    "{} seems to be not available in problem {}...",
+                    language, problem_id
+                )
+            })?;
+
+        // Not to take the newest submission before submitting for the new one
+        let last_id = self
+            .submissions(contest_id, 1)
+            .await?
+            .0
+            .iter()
+            .map(|s| s.id)
+            .max();
 
-        let _ = self
-            .http_post_form(
+        // Redirected to the submissions after submitting, or back to the form if rejected
+        let page = self
+            .http_post_form_with_csrf(
                 &format!("/contests/{}/submit", contest_id),
                 &[
                     ("data.TaskScreenName", &task_screen_name),
                     ("data.LanguageId", &language_id),
                     ("sourceCode", source_code),
-                    ("csrf_token", &csrf_token),
                 ],
             )
             .await?;
+        let id = parse_submit_response(&page, &task_screen_name, last_id)?;
 
         println!(
-            "Submitted to problem `{}`, using language `{}`",
-            task_screen_name, language_name
+            "Submitted to problem `{}`, using language `{}` (submission {})",
+            task_screen_name, language_name, id
         );
-        Ok(())
+        Ok(id)
+    }
+
+    /// The submission form of the contest, which needs logging in. The choices are cached for
+    /// the day once the tasks are in it.
+    pub async fn submit_form(&self, contest_id: &str) -> Result<SubmitForm> {
+        self.check_login().await?;
+        let doc = self
+            .http_get(&format!("/contests/{}/submit", contest_id))
+            .await?;
+        let form = parse_submit_form(&doc)?;
+        if !form.tasks.is_empty() {
+            cache::save_submit_form(contest_id, &form)?;
+        }
+        Ok(form)
     }
 
     async fn retrieve_text_or_error_message<T: fmt::Display, F: FnOnce() -> T>(
@@ -401,31 +1031,633 @@ impl AtCoder {
     ) -> Result<String> {
         match self.http_get(path).await {
             Err(err) if is_http_error(&err, reqwest::StatusCode::NOT_FOUND) => {
-                Err(match self.username().await {
-                    Ok(username) => err.context(if username.is_some() {
-                        anyhow!("{}", context_on_logged_in())
+                if self.username().await?.is_some() {
+                    return Err(err.context(anyhow!("{}", context_on_logged_in())));
+                }
+                let (username, password) = match env_credentials() {
+                    Some(credentials) => credentials,
+                    None => return Err(err.context(anyhow!("{}", tr!(PleaseLogin)))),
+                };
+                self.login(&username, &password).await?;
+                self.http_get(path).await.map_err(|err| {
+                    if is_http_error(&err, reqwest::StatusCode::NOT_FOUND) {
+                        err.context(anyhow!("{}", context_on_logged_in()))
                     } else {
-                        anyhow!("You are not logged in. Please login first")
-                    }),
-                    Err(err) => err,
-                })?
+                        err
+                    }
+                })
             }
             ret => Ok(ret?),
         }
     }
 
+    /// The page, with the CSRF token in it kept for [`http_post_form_with_csrf`]
+    ///
+    /// [`http_post_form_with_csrf`]: Self::http_post_form_with_csrf
     async fn http_get(&self, path: &str) -> Result<String> {
+        let doc = self
+            .client
+            .get(&format!("{}{}", *ENDPOINT, path).parse::<Url>()?)
+            .await?;
+        self.keep_csrf_token(&doc);
+        Ok(doc)
+    }
+
+    /// GET of a page which is the same without logging in, cached as it is. Without the token,
+    /// which is not of the session.
+    async fn http_get_public(&self, path: &str) -> Result<String> {
         self.client
-            .get(&format!("{}{}", ATCODER_ENDPOINT, path).parse::<Url>()?)
+            .get_public(&format!("{}{}", *ENDPOINT, path).parse::<Url>()?)
             .await
     }
 
     async fn http_post_form(&self, path: &str, form: &[(&str, &str)]) -> Result<String> {
-        self.client
-            .post_form(
-                &format!("{}{}", ATCODER_ENDPOINT, path).parse::<Url>()?,
-                form,
-            )
-            .await
+        let doc = self
+            .client
+            .post_form(&format!("{}{}", *ENDPOINT, path).parse::<Url>()?, form)
+            .await?;
+        self.keep_csrf_token(&doc);
+        Ok(doc)
+    }
+
+    fn keep_csrf_token(&self, doc: &str) {
+        if let Some(token) = parse_csrf_token(doc) {
+            self.client.set_csrf_token(Some(token));
+        }
+    }
+
+    /// Posts the form with the CSRF token of the session, taken from the top page unless seen
+    /// in a page already. The form is posted again once with a new token if the form is shown
+    /// again for the token rejected, e.g. after the session has been renewed.
+    async fn http_post_form_with_csrf(&self, path: &str, form: &[(&str, &str)]) -> Result<String> {
+        let mut retried = false;
+        loop {
+            let csrf_token = match self.client.csrf_token() {
+                Some(csrf_token) => csrf_token,
+                None => {
+                    self.http_get("/").await?;
+                    self.client
+                        .csrf_token()
+                        .with_context(|| "cannot find csrf_token")?
+                }
+            };
+            let mut fields = form.to_vec();
+            fields.push(("csrf_token", &csrf_token));
+            let doc = self.http_post_form(path, &fields).await?;
+            if retried || !is_csrf_rejection(&doc) {
+                return Ok(doc);
+            }
+            warn!("{}", tr!(CsrfRetry));
+            // The form shown again may have a new one.
+            if self.client.csrf_token().as_deref() == Some(&*csrf_token) {
+                self.client.set_csrf_token(None);
+            }
+            retried = true;
+        }
+    }
+}
+
+/// Whether the user can take part in a contest
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContestAccess {
+    /// Registered, or no registration is needed (or it is closed)
+    Open,
+    /// The form to register is shown.
+    Registrable,
+    /// With the notice, e.g. of an invitation-only contest or of the bounds of the rating
+    Restricted(String),
+}
+
+// e.g. `このコンテストにはアクセスできません`, `You cannot register for this contest.`
+static NO_ACCESS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)アクセスできません|参加できません|参加登録できません|招待|cannot (access|register|participate)|not (allowed|eligible)|invit").unwrap()
+});
+
+fn parse_contest_access(doc: &str) -> ContestAccess {
+    let doc = Html::parse_document(doc);
+    if let Some(notice) = doc
+        .select(&selectors::get("contest-notice"))
+        .map(|e| e.text().collect::<String>().trim().to_owned())
+        .find(|notice| NO_ACCESS.is_match(notice))
+    {
+        return ContestAccess::Restricted(notice);
     }
+    if doc
+        .select(&selectors::get("register-form"))
+        .next()
+        .is_some()
+    {
+        return ContestAccess::Registrable;
+    }
+    ContestAccess::Open
+}
+
+/// The rows of the score table on the top page of the contest, if it has one
+fn parse_score_table(doc: &str) -> Result<Option<Vec<(String, String)>>> {
+    Html::parse_document(doc)
+        .select(&Selector::parse("#contest-statement > .lang > .lang-ja table").unwrap())
+        .filter(|table| {
+            let header = table
+                .select(&Selector::parse("thead > tr > th").unwrap())
+                .flat_map(|r| r.text())
+                .collect::<Vec<_>>();
+            header == ["Task", "Score"] || header == ["問題", "点数"]
+        })
+        .exactly_one()
+        .ok()
+        .map(|table| {
+            table
+                .select(&Selector::parse("tbody > tr").unwrap())
+                .map(|tr| {
+                    let text = tr
+                        .select(&Selector::parse("td").unwrap())
+                        .flat_map(|r| r.text())
+                        .collect::<Vec<_>>();
+                    match text.len() {
+                        2 => Ok((text[0].to_owned(), text[1].to_owned())),
+                        _ => Err(anyhow!("could not parse the table")),
+                    }
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+// e.g. `Rated対象: ~ 1999`, `Rated Range: -` for the unrated contests
+static RATED_RANGE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Rated(?:対象| Range)\s*:\s*(\S+)").unwrap());
+
+/// Whether the contest is rated for anyone, from the header of its top page
+fn parse_rated(doc: &str) -> Option<bool> {
+    let text = Html::parse_document(doc)
+        .root_element()
+        .text()
+        .collect::<String>();
+    Some(RATED_RANGE.captures(&text)?[1] != *"-")
+}
+
+// e.g. `AtCoder Heuristic Contest 999`, `THIRD プログラミングコンテスト 2022 (マラソン)`
+static HEURISTIC_TITLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)heuristic|marathon|ヒューリスティック|マラソン").unwrap());
+
+/// Whether the contest is a heuristic one, from the title in the header of its top page
+fn parse_heuristic(doc: &str) -> bool {
+    Html::parse_document(doc)
+        .select(&selectors::get("contest-title"))
+        .any(|title| HEURISTIC_TITLE.is_match(&title.text().collect::<String>()))
+}
+
+/// The contest whose problems are shown only to the registered users
+#[derive(Debug)]
+pub struct Unregistered {
+    pub contest_id: String,
+}
+
+impl fmt::Display for Unregistered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Register for `{}` on {} to see the problems",
+            self.contest_id,
+            page_url(&format!("/contests/{}", self.contest_id)),
+        )
+    }
+}
+
+impl std::error::Error for Unregistered {}
+
+/// Series of the contests whose latest one is given by the name only (e.g. `new abc`)
+const CONTEST_SERIES: &[&str] = &["abc", "arc", "agc", "ahc"];
+
+/// A contest given on the command line
+pub enum ContestSpec {
+    Id(String),
+    /// The latest contest of the series
+    Latest(String),
+}
+
+/// Parses the contest ID itself, a URL of the contest or of a page in it (e.g.
+/// `https://atcoder.jp/contests/abc400/tasks`), or the name of a series.
+pub fn parse_contest_spec(s: &str) -> Result<ContestSpec> {
+    let id = if s.starts_with("https://") || s.starts_with("http://") {
+        let url = s.parse::<Url>()?;
+        let segments = url.path_segments().map(Iterator::collect::<Vec<_>>);
+        match (url.host_str(), segments.as_deref()) {
+            (Some("atcoder.jp"), Some(["contests", id, ..])) if !id.is_empty() => id.to_string(),
+            _ => bail!("`{}` is not a URL of a contest on AtCoder", s),
+        }
+    } else {
+        let id = s.to_lowercase();
+        if CONTEST_SERIES.contains(&id.as_str()) {
+            return Ok(ContestSpec::Latest(id));
+        }
+        id
+    };
+    ensure!(
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "`{}` is not a contest ID",
+        s
+    );
+    Ok(ContestSpec::Id(id))
+}
+
+/// Contest ID and task screen name of the URL of a task (e.g.
+/// `https://atcoder.jp/contests/abc999/tasks/abc999_a`)
+pub fn parse_task_url(url: &str) -> Option<(String, String)> {
+    let url = url.parse::<Url>().ok()?;
+    let segments = url.path_segments()?.collect::<Vec<_>>();
+    match (url.host_str()?, segments.as_slice()) {
+        ("atcoder.jp", ["contests", contest_id, "tasks", task]) => {
+            Some((contest_id.to_string(), task.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Problem IDs guessed from the contest ID for the regular contests (e.g. `a` to `g` for recent
+/// ABCs), used before the problem list becomes available.
+pub fn guess_problem_ids(contest_id: &str) -> Option<Vec<String>> {
+    let contest_id = contest_id.to_lowercase();
+    let (kind, number) = contest_id.split_at(contest_id.len().min(3));
+    let number = number.parse::<u32>().ok()?;
+    let last = match kind {
+        "abc" if number >= 319 => 'g',
+        "abc" if number >= 212 => 'h',
+        "abc" if number >= 126 => 'f',
+        "abc" => 'd',
+        "arc" if number >= 104 => 'f',
+        "agc" => 'f',
+        "ahc" => 'a',
+        _ => return None,
+    };
+    Some(('a'..=last).map(|c| c.to_string()).collect())
+}
+
+// The editorials follow the heading of their task.
+/// The problems and the duration of the contest in the list of the problems
+fn parse_contest_info(doc: &str) -> ContestInfo {
+    let doc = Html::parse_document(doc);
+    let sel_problem = selectors::get("task-row");
+
+    let mut problems = vec![];
+
+    for row in doc.select(&sel_problem) {
+        let sel_td = Selector::parse("td").unwrap();
+        let mut it = row.select(&sel_td);
+        let c1 = it.next().unwrap();
+        let c2 = it.next().unwrap();
+        let c3 = it.next().unwrap();
+        let c4 = it.next().unwrap();
+
+        let id = c1
+            .select(&Selector::parse("a").unwrap())
+            .next()
+            .unwrap()
+            .inner_html();
+
+        let name = c2
+            .select(&Selector::parse("a").unwrap())
+            .next()
+            .unwrap()
+            .inner_html();
+
+        let url = c2
+            .select(&Selector::parse("a").unwrap())
+            .next()
+            .unwrap()
+            .value()
+            .attr("href")
+            .unwrap();
+
+        let tle = c3.inner_html();
+        let mle = c4.inner_html();
+
+        problems.push(Problem {
+            id: id.trim().to_owned(),
+            name: name.trim().to_owned(),
+            url: url.trim().to_owned(),
+            tle: tle.trim().to_owned(),
+            _mle: mle.trim().to_owned(),
+            score: None,
+        });
+    }
+
+    let duration = doc
+        .select(&Selector::parse(".contest-duration time").unwrap())
+        .map(|time| DateTime::parse_from_str(&time.inner_html(), "%Y-%m-%d %H:%M:%S%z").ok())
+        .collect::<Option<Vec<_>>>()
+        .and_then(|times| times.into_iter().collect_tuple());
+
+    ContestInfo {
+        problems,
+        duration,
+        registrable: false,
+        rated: None,
+    }
+}
+
+fn parse_editorials(doc: &str) -> Vec<Editorial> {
+    let doc = Html::parse_document(doc);
+    let task_link = Selector::parse(r#"a[href*="/tasks/"]"#).unwrap();
+    let editorial_link = Selector::parse(r#"a[href*="/editorial/"], a[href^="http"]"#).unwrap();
+    let label = Selector::parse("span.label").unwrap();
+    let username = Selector::parse("a.username").unwrap();
+
+    let mut task = None;
+    let mut editorials = vec![];
+    for item in doc.select(&selectors::get("editorial-item")) {
+        if item.value().name() != "li" {
+            task = item
+                .select(&task_link)
+                .next()
+                .and_then(|a| a.value().attr("href"))
+                .and_then(|href| href.rsplit('/').next())
+                .map(ToOwned::to_owned);
+            continue;
+        }
+        let url = match item
+            .select(&editorial_link)
+            .find(|a| a.value().classes().all(|class| class != "username"))
+            .and_then(|a| a.value().attr("href"))
+        {
+            Some(url) => url.to_owned(),
+            None => continue,
+        };
+        let official = item.select(&label).any(|label| {
+            let text = label.text().collect::<String>();
+            text.contains("公式") || text.contains("Official")
+        });
+        let author = item
+            .select(&username)
+            .next()
+            .map(|a| a.text().collect::<String>().trim().to_owned());
+        editorials.push(Editorial {
+            task: task.clone(),
+            url,
+            official,
+            author,
+        });
+    }
+    editorials
+}
+
+// e.g. `提出間隔が短すぎます。5 秒後に再度提出してください。`, `Wait 1 minute before submitting again.`
+static COOLDOWN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(\d+)\s*(秒|分|seconds?|secs?|minutes?|mins?)").unwrap());
+// e.g. `同じ内容の提出が既にあります`, `You have already submitted the same code.`
+static DUPLICATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)同じ|same (code|source|submission)").unwrap());
+
+fn parse_username(doc: &str) -> Option<String> {
+    Html::parse_document(doc)
+        .select(&selectors::get("username"))
+        .next()
+        .and_then(|r| Some(r.value().attr("href")?.get(7..)?.to_owned()))
+}
+
+/// The CSRF token in the script of every page of AtCoder, or in a form of the page
+fn parse_csrf_token(doc: &str) -> Option<String> {
+    static CSRF_TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"csrfToken = "([^"]*)""#).unwrap());
+    let token = match CSRF_TOKEN.captures(doc) {
+        Some(captures) => captures[1].to_owned(),
+        // Not to parse every page
+        None if doc.contains("csrf_token") => Html::parse_document(doc)
+            .select(&selectors::get("csrf-token"))
+            .next()?
+            .value()
+            .attr("value")?
+            .to_owned(),
+        None => return None,
+    };
+    Some(token).filter(|token| !token.is_empty())
+}
+
+// The whole banner of AtCoder for the token rejected
+static CSRF_REJECTION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(不正なリクエストです。|Invalid request\.)$").unwrap());
+
+/// Whether the page is the form shown again with the banner for the CSRF token rejected
+fn is_csrf_rejection(doc: &str) -> bool {
+    let doc = Html::parse_document(doc);
+    doc.select(&selectors::get("csrf-token")).next().is_some()
+        && doc
+            .select(&selectors::get("submit-error"))
+            .any(|banner| CSRF_REJECTION.is_match(banner.text().collect::<String>().trim()))
+}
+
+/// HTML of the statement in the page of the problem in Japanese, or in English if `english`
+pub fn parse_statement_html(doc: &str, english: bool) -> Result<String> {
+    let doc = Html::parse_document(doc);
+    let statement = doc
+        .select(&selectors::get("statement"))
+        .next()
+        .with_context(|| "Could not find the problem statement")?;
+    let lang = Selector::parse(if english { ".lang-en" } else { ".lang-ja" }).unwrap();
+    Ok(statement
+        .select(&lang)
+        .next()
+        .unwrap_or(statement)
+        .inner_html())
+}
+
+fn parse_submit_form(doc: &str) -> Result<SubmitForm> {
+    let doc = Html::parse_document(doc);
+    let tasks = doc
+        .select(&selectors::get("submit-task"))
+        .filter_map(|r| {
+            let screen_name = r.value().attr("value")?;
+            Some((screen_name.to_owned(), r.inner_html().trim().to_owned()))
+        })
+        .collect::<Vec<_>>();
+    let languages = tasks
+        .iter()
+        .map(|(screen_name, _)| {
+            let languages = doc
+                .select(&selectors::for_task("submit-language", screen_name))
+                .filter_map(|r| {
+                    let id = r.value().attr("value")?;
+                    Some((id.to_owned(), r.inner_html().trim().to_owned()))
+                })
+                .collect();
+            (screen_name.clone(), languages)
+        })
+        .collect();
+    Ok(SubmitForm { tasks, languages })
+}
+
+fn parse_submission_source(doc: &str) -> Option<String> {
+    Html::parse_document(doc)
+        .select(&selectors::get("submission-code"))
+        .next()
+        .map(|code| code.text().collect())
+}
+
+fn parse_submission_result(doc: &str) -> Option<SubmissionResult> {
+    let doc = Html::parse_document(doc);
+    let sel_td = Selector::parse("td").unwrap();
+    let sel_label = selectors::get("submission-verdict");
+
+    let text = |e: ElementRef<'_>| e.text().collect::<String>().trim().to_owned();
+
+    let verdict = doc
+        .select(&selectors::get("submission-status"))
+        .next()
+        .map(text)?;
+    let cases = doc
+        .select(&selectors::get("submission-case-row"))
+        .filter_map(|row| {
+            let cells = row.select(&sel_td).collect::<Vec<_>>();
+            // The rows of the other tables have a header cell (e.g. the summary of the sets).
+            if cells.len() != 4 {
+                return None;
+            }
+            let verdict = cells[1].select(&sel_label).next().map(text)?;
+            Some(CaseResult {
+                name: text(cells[0]),
+                verdict: Verdict::parse(&verdict),
+                exec_time: text(cells[2]),
+                memory: text(cells[3]),
+            })
+        })
+        .collect();
+    Some(SubmissionResult {
+        verdict: Verdict::parse(&verdict),
+        cases,
+    })
+}
+
+/// ID of the submission just made to the task, the newest one after `last_id` in the
+/// submissions the submission is redirected to. Without it, the banner of the rejection is taken
+/// from the page, e.g. for the same code submitted again or for submitting too often.
+fn parse_submit_response(doc: &str, task_screen_name: &str, last_id: Option<u64>) -> Result<u64> {
+    let submission = parse_submissions(doc).ok().and_then(|(submissions, _)| {
+        submissions
+            .into_iter()
+            .find(|s| s.task == task_screen_name && last_id.is_none_or(|id| s.id > id))
+    });
+    if let Some(submission) = submission {
+        return Ok(submission.id);
+    }
+
+    let banner = Html::parse_document(doc)
+        .select(&selectors::get("submit-error"))
+        .map(|e| e.text().collect::<String>().trim().to_owned())
+        .find(|message| !message.is_empty());
+    let message = match banner {
+        Some(message) => message,
+        None => bail!(
+            "The submission is not found in the submissions after submitting. The session may \
+             have expired; run `cargo atcoder login` and check the submissions."
+        ),
+    };
+    if let Some(caps) = COOLDOWN.captures(&message) {
+        let n = caps[1].parse::<u64>()?;
+        let unit = if caps[2].starts_with('分') || caps[2].to_lowercase().starts_with('m') {
+            60
+        } else {
+            1
+        };
+        return Err(Rejection::Cooldown(Duration::from_secs(n * unit), message).into());
+    }
+    if DUPLICATE.is_match(&message) {
+        return Err(Rejection::Duplicate(message).into());
+    }
+    Err(Rejection::Other(message).into())
+}
+
+/// The submission rejected by AtCoder, with the banner of the form
+#[derive(Debug)]
+pub enum Rejection {
+    /// Submitting too often, with the time to wait
+    Cooldown(Duration, String),
+    /// The same code submitted again
+    Duplicate(String),
+    Other(String),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Rejection::Cooldown(wait, message) => {
+                tr!(SubmissionCooldown, format!("{:?}", wait), message)
+            }
+            Rejection::Duplicate(message) => tr!(SubmissionDuplicate, message),
+            Rejection::Other(message) => tr!(SubmissionRejected, message),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+fn parse_submissions(doc: &str) -> Result<(Vec<Submission>, usize)> {
+    let doc = Html::parse_document(doc);
+    let sel_td = Selector::parse("td").unwrap();
+    let sel_a = Selector::parse("a").unwrap();
+    let sel_label = selectors::get("submission-verdict");
+
+    let text = |e: ElementRef<'_>| e.text().collect::<String>().trim().to_owned();
+
+    let mut ret = vec![];
+    for row in doc.select(&selectors::get("submission-row")) {
+        let cells = row.select(&sel_td).collect::<Vec<_>>();
+        if cells.len() < 7 {
+            // e.g. "No submissions"
+            continue;
+        }
+
+        let task_link = cells[1]
+            .select(&sel_a)
+            .next()
+            .with_context(|| "Task link not found in the submissions table")?;
+        let task = task_link
+            .value()
+            .attr("href")
+            .and_then(|href| href.rsplit('/').next())
+            .unwrap_or_default()
+            .to_owned();
+
+        // Execution time and memory are merged into the verdict cell while judging.
+        let verdict_cell = cells[6];
+        let verdict = verdict_cell
+            .select(&sel_label)
+            .next()
+            .map(text)
+            .unwrap_or_else(|| text(verdict_cell));
+        let judging = verdict_cell.value().attr("colspan").is_some();
+        let (exec_time, memory) = if !judging && cells.len() >= 9 {
+            (Some(text(cells[7])), Some(text(cells[8])))
+        } else {
+            (None, None)
+        };
+
+        let id = row
+            .select(&sel_a)
+            .filter_map(|a| a.value().attr("href"))
+            .filter(|href| href.contains("/submissions/"))
+            .find_map(|href| href.rsplit('/').next()?.parse().ok())
+            .with_context(|| "Submission ID not found in the submissions table")?;
+
+        ret.push(Submission {
+            id,
+            time: text(cells[0]),
+            task,
+            task_name: text(task_link),
+            language: text(cells[3]),
+            score: text(cells[4]),
+            verdict: Verdict::parse(&verdict),
+            exec_time,
+            memory,
+        });
+    }
+
+    let num_pages = doc
+        .select(&selectors::get("pagination"))
+        .filter_map(|a| text(a).parse().ok())
+        .max()
+        .unwrap_or(1);
+    Ok((ret, num_pages))
 }
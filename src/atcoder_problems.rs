@@ -0,0 +1,137 @@
+//! AtCoder Problems (<https://kenkoooo.com/atcoder/>), for the problems of the past contests with
+//! their points and estimated difficulties, and the submissions of the users.
+
+use crate::{cache::cache_dir, http::Client, recording};
+use anyhow::{Context as _, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    time::{Duration, SystemTime},
+};
+use url::Url;
+
+const ENDPOINT: &str = "https://kenkoooo.com";
+
+/// The lists of all the problems are updated daily, and large enough to be cached for that long.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Maximum number of the submissions in a response of the API
+const SUBMISSIONS_PER_REQUEST: usize = 500;
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Problem {
+    /// Task screen name (e.g. `abc999_a`)
+    pub id: String,
+    pub contest_id: String,
+    /// e.g. `A. Sum`
+    pub title: String,
+    pub point: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ProblemModel {
+    /// Rating of the users who solve the problem with the probability 50%, unclipped below 400
+    pub difficulty: Option<f64>,
+    #[serde(default)]
+    pub is_experimental: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Submission {
+    pub epoch_second: i64,
+    pub problem_id: String,
+    pub result: String,
+}
+
+impl ProblemModel {
+    /// The difficulty on the scale of the ratings, which are clipped below 400 in the same way
+    pub(crate) fn clipped_difficulty(&self) -> Option<f64> {
+        self.difficulty.map(|difficulty| {
+            if difficulty >= 400. {
+                difficulty
+            } else {
+                400. / ((400. - difficulty) / 400.).exp()
+            }
+        })
+    }
+}
+
+impl Problem {
+    pub(crate) fn url(&self) -> String {
+        format!(
+            "https://atcoder.jp/contests/{}/tasks/{}",
+            self.contest_id, self.id
+        )
+    }
+}
+
+pub(crate) struct AtCoderProblems {
+    client: Client,
+}
+
+impl AtCoderProblems {
+    pub(crate) fn new() -> Result<Self> {
+        Ok(Self {
+            client: Client::without_session(ENDPOINT)?,
+        })
+    }
+
+    /// All the problems with the points
+    pub(crate) async fn problems(&self) -> Result<Vec<Problem>> {
+        self.resource("merged-problems.json").await
+    }
+
+    /// The estimated difficulties by the task screen names
+    pub(crate) async fn problem_models(&self) -> Result<HashMap<String, ProblemModel>> {
+        self.resource("problem-models.json").await
+    }
+
+    /// The task screen names of the problems the user got AC for
+    pub(crate) async fn solved(&self, user: &str) -> Result<HashSet<String>> {
+        let mut solved = HashSet::new();
+        let mut from_second = 0;
+        loop {
+            let url = Url::parse_with_params(
+                &format!("{}/atcoder/atcoder-api/v3/user/submissions", ENDPOINT),
+                &[("user", user), ("from_second", &from_second.to_string())],
+            )?;
+            let submissions =
+                serde_json::from_str::<Vec<Submission>>(&self.client.get(&url).await?)
+                    .with_context(|| format!("Failed to parse the submissions of `{}`", user))?;
+            for submission in &submissions {
+                if submission.result == "AC" {
+                    solved.insert(submission.problem_id.clone());
+                }
+            }
+            match submissions.iter().map(|s| s.epoch_second).max() {
+                Some(last) if submissions.len() >= SUBMISSIONS_PER_REQUEST => {
+                    from_second = last + 1
+                }
+                _ => return Ok(solved),
+            }
+        }
+    }
+
+    // Read from the cache if fetched within `CACHE_TTL`, unless replaying.
+    async fn resource<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let path = cache_dir()?.join("atcoder-problems").join(name);
+        let fresh = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age < CACHE_TTL);
+        let json = if fresh && !recording::is_replaying() {
+            fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+        } else {
+            let url = format!("{}/atcoder/resources/{}", ENDPOINT, name).parse::<Url>()?;
+            let json = self.client.get(&url).await?;
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(&path, &json)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            json
+        };
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse `{}`", name))
+    }
+}
@@ -0,0 +1,192 @@
+//! Panics of the solutions in the local tests, shown with the lines of the source in the
+//! package.
+
+use console::Style;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{fs, path::Path};
+
+static LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+):(\d+):(\d+)$").unwrap());
+static FRAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*\d+: (.+)$").unwrap());
+
+pub(crate) struct Location {
+    file: String,
+    line: usize,
+    column: usize,
+}
+
+impl Location {
+    fn parse(s: &str) -> Option<Self> {
+        let caps = LOCATION.captures(s.trim())?;
+        Some(Location {
+            file: caps[1].trim_start_matches("./").to_owned(),
+            line: caps[2].parse().ok()?,
+            column: caps[3].parse().ok()?,
+        })
+    }
+
+    // Relative to the package, or absolute in it
+    fn in_package(&self, package_dir: &Path) -> bool {
+        let path = Path::new(&self.file);
+        if path.is_absolute() {
+            path.starts_with(package_dir)
+        } else {
+            package_dir.join(path).is_file()
+        }
+    }
+
+    fn source_line(&self, package_dir: &Path) -> Option<String> {
+        let source = fs::read_to_string(package_dir.join(&self.file)).ok()?;
+        Some(source.lines().nth(self.line.checked_sub(1)?)?.to_owned())
+    }
+}
+
+/// A panic found in the stderr of a solution, with the backtrace if `RUST_BACKTRACE` was set
+pub(crate) struct Panic {
+    pub message: String,
+    pub location: Option<Location>,
+    /// Function and location of each frame from the innermost
+    frames: Vec<(String, Option<Location>)>,
+}
+
+/// Parses both `thread 'main' panicked at src/main.rs:1:2:\nmessage` (Rust 1.73 and later)
+/// and `thread 'main' panicked at 'message', src/main.rs:1:2`.
+pub(crate) fn parse(stderr: &str) -> Option<Panic> {
+    let mut lines = stderr.lines();
+    let head = lines
+        .by_ref()
+        .filter(|line| line.starts_with("thread '"))
+        .find_map(|line| Some(line.split_once(" panicked at ")?.1))?;
+    let (message, location) = match head.strip_suffix(':').and_then(Location::parse) {
+        Some(location) => {
+            let message = lines
+                .by_ref()
+                .take_while(|line| !line.starts_with("note: ") && *line != "stack backtrace:")
+                .collect::<Vec<_>>()
+                .join("\n");
+            (message, Some(location))
+        }
+        None => match head.rsplit_once("', ") {
+            Some((message, location)) => (
+                message.trim_start_matches('\'').to_owned(),
+                Location::parse(location),
+            ),
+            None => (head.to_owned(), None),
+        },
+    };
+
+    let mut frames = vec![];
+    let mut lines = stderr
+        .lines()
+        .skip_while(|line| *line != "stack backtrace:")
+        .peekable();
+    while let Some(line) = lines.next() {
+        if let Some(caps) = FRAME.captures(line) {
+            let location = lines
+                .peek()
+                .and_then(|next| next.trim_start().strip_prefix("at "))
+                .and_then(Location::parse);
+            if location.is_some() {
+                lines.next();
+            }
+            frames.push((caps[1].to_owned(), location));
+        }
+    }
+
+    Some(Panic {
+        message,
+        location,
+        frames,
+    })
+}
+
+/// Removes the backtrace from the stderr.
+pub(crate) fn strip(stderr: &str) -> String {
+    let mut lines = vec![];
+    let mut in_backtrace = false;
+    for line in stderr.lines() {
+        if line == "stack backtrace:" {
+            in_backtrace = true;
+        } else if in_backtrace && line.starts_with("note: ") {
+            in_backtrace = false;
+        } else if !in_backtrace {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+impl Panic {
+    /// The operation like `multiply` if the panic is an arithmetic overflow
+    pub(crate) fn overflow(&self) -> Option<&str> {
+        self.message
+            .strip_prefix("attempt to ")?
+            .strip_suffix(" with overflow")
+    }
+
+    /// Prints the message and the frames in the package with their source lines.
+    pub(crate) fn print(&self, package_dir: &Path) {
+        let red = Style::new().red();
+        let cyan = Style::new().cyan();
+        let bold = Style::new().bold();
+
+        println!(
+            "{}: {}",
+            red.apply_to("panicked"),
+            bold.apply_to(&self.message)
+        );
+        if let Some(operation) = self.overflow() {
+            println!(
+                "{}: the {} below overflowed. It wraps silently without the overflow checks as \
+                 on the judge, which often results in WA.",
+                cyan.apply_to("note"),
+                operation,
+            );
+        }
+
+        // The location of the panic, and then the frames in the package
+        let mut locations = Vec::<(&Location, Option<&str>)>::new();
+        let candidates = self.location.iter().map(|l| (l, None)).chain(
+            self.frames
+                .iter()
+                .filter_map(|(function, l)| Some((l.as_ref()?, Some(function.as_str())))),
+        );
+        for (location, function) in candidates.filter(|(l, _)| l.in_package(package_dir)) {
+            match locations
+                .iter_mut()
+                .find(|(l, _)| l.file == location.file && l.line == location.line)
+            {
+                Some((_, f)) => *f = f.or(function),
+                None => locations.push((location, function)),
+            }
+        }
+
+        for (location, function) in locations {
+            println!(
+                "  {} {}:{}:{}{}",
+                cyan.apply_to("-->"),
+                location.file,
+                location.line,
+                location.column,
+                function.map(|f| format!(" in {}", f)).unwrap_or_default(),
+            );
+            if let Some(line) = location.source_line(package_dir) {
+                let number = location.line.to_string();
+                println!("{} {}", " ".repeat(number.len() + 2), cyan.apply_to("|"));
+                println!(
+                    "  {} {} {}",
+                    cyan.apply_to(&number),
+                    cyan.apply_to("|"),
+                    line
+                );
+                println!(
+                    "{} {} {}{}",
+                    " ".repeat(number.len() + 2),
+                    cyan.apply_to("|"),
+                    " ".repeat(location.column.saturating_sub(1)),
+                    red.apply_to("^"),
+                );
+            }
+        }
+    }
+}
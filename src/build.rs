@@ -0,0 +1,351 @@
+use crate::{
+    config::BuildServer,
+    project::{self, package_dir},
+};
+use anyhow::{ensure, Context as _, Result};
+use cargo_metadata::{camino::Utf8PathBuf, Message, Metadata, Package, Target};
+use console::Style;
+use log::debug;
+use std::{
+    fs,
+    io::BufReader,
+    path::Path,
+    process::{Command, Stdio},
+    str,
+};
+
+/// Profile for the local runs with `--release`, with the overflow checks and the debug assertions
+/// that the release profile for the submissions does not have
+pub(crate) const LOCAL_PROFILE: &str = "local";
+
+/// Arguments of `cargo build` for an optimized build to run locally: `--profile local` if the
+/// manifest of `package` or of the workspace has it (written by `new`), `--release` otherwise
+pub(crate) fn release_args(package: &Package) -> Vec<&'static str> {
+    let manifest_path = package.manifest_path.as_std_path();
+    let has_local_profile = Some(manifest_path.to_owned())
+        .into_iter()
+        .chain(project::workspace_manifest(manifest_path.parent().unwrap()))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|manifest| manifest.parse::<toml_edit::Document>().ok())
+        .any(|manifest| !manifest["profile"][LOCAL_PROFILE].is_none());
+    if has_local_profile {
+        vec!["--profile", LOCAL_PROFILE]
+    } else {
+        vec!["--release"]
+    }
+}
+
+/// Builds the bin target `bin_name` of `package`.
+///
+/// Compiler diagnostics for the bin are printed with the problem ID prefixed, and the ones for
+/// the other targets (e.g. a lib or a dependency failing to compile) as they are. Returns the
+/// path to the built executable, or `None` if the build failed.
+pub(crate) fn build_bin(
+    package: &Package,
+    bin_name: &str,
+    release: bool,
+) -> Result<Option<Utf8PathBuf>> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build")
+        .args(if release {
+            release_args(package)
+        } else {
+            vec![]
+        })
+        .arg("--bin")
+        .arg(bin_name)
+        .arg("--manifest-path")
+        .arg(&package.manifest_path)
+        .arg(message_format_arg());
+    run(cmd, bin_name)
+}
+
+/// Builds the bin target `bin_name` of `package` in release mode for `target` with `program`
+/// (`cargo` or `cross`).
+pub(crate) fn build_bin_for_target(
+    program: &str,
+    metadata: &Metadata,
+    package: &Package,
+    bin_name: &str,
+    target: &str,
+) -> Result<Option<Utf8PathBuf>> {
+    let mut cmd = Command::new(program);
+    cmd.arg("build")
+        .arg(format!("--target={}", target))
+        .arg("--release")
+        .arg("--bin")
+        .arg(bin_name)
+        .arg(message_format_arg())
+        .current_dir({
+            // `cross` does not work with `--manifest-path <absolute path>`.
+            package
+                .manifest_path
+                .parent()
+                .expect("`manifest_path` should end with \"Cargo.toml\"")
+        });
+
+    Ok(run(cmd, bin_name)?.map(|executable| {
+        if executable.exists() {
+            executable
+        } else {
+            // `cross` reports the paths inside the container.
+            let file_name = executable.file_name().unwrap_or(bin_name);
+            metadata
+                .target_directory
+                .join(target)
+                .join("release")
+                .join(file_name)
+        }
+    }))
+}
+
+/// Builds the bin target `bin_name` of `package` in release mode for `target` with
+/// [`cargo-zigbuild`](https://github.com/rust-cross/cargo-zigbuild), which links with `zig cc`.
+///
+/// The Rust standard library for `target` is installed with `rustup` if missing, and the
+/// executable is stripped by the linker since the host `strip` may not handle ELF files.
+pub(crate) fn build_bin_with_zig(
+    package: &Package,
+    bin_name: &str,
+    target: &str,
+) -> Result<Option<Utf8PathBuf>> {
+    let status = Command::new("rustup")
+        .args(["target", "add", target])
+        .current_dir(package_dir(package))
+        .stdout(Stdio::null())
+        .status()
+        .with_context(|| "Failed to run `rustup`")?;
+    ensure!(
+        status.success(),
+        "Failed to install `{}` with `rustup`",
+        target
+    );
+
+    let mut cmd = Command::new("cargo");
+    cmd.arg("zigbuild")
+        .arg(format!("--target={}", target))
+        .arg("--release")
+        .arg("--bin")
+        .arg(bin_name)
+        .arg("--manifest-path")
+        .arg(&package.manifest_path)
+        .arg(message_format_arg())
+        .env("CARGO_PROFILE_RELEASE_STRIP", "symbols");
+    run(cmd, bin_name)
+}
+
+/// Builds the bin target `bin_name` of `package` in release mode for `target` inside WSL.
+///
+/// The paths are translated between Windows and Linux with `wslpath`.
+pub(crate) fn build_bin_in_wsl(
+    package: &Package,
+    bin_name: &str,
+    target: &str,
+) -> Result<Option<Utf8PathBuf>> {
+    let package_dir = package
+        .manifest_path
+        .parent()
+        .expect("`manifest_path` should end with \"Cargo.toml\"");
+
+    let mut cmd = Command::new("wsl");
+    cmd.arg("--cd")
+        .arg(wslpath(package_dir.as_ref(), false)?)
+        // `~/.cargo/bin` is usually added to `$PATH` in the login shell.
+        .args(["--exec", "bash", "-lc", "exec cargo \"$@\"", "cargo"])
+        .arg("build")
+        .arg(format!("--target={}", target))
+        .arg("--release")
+        .arg("--bin")
+        .arg(bin_name)
+        .arg(message_format_arg());
+
+    run(cmd, bin_name)?
+        .map(|executable| wslpath(executable.as_ref(), true).map(Into::into))
+        .transpose()
+}
+
+/// Builds the bin target `bin_name` of `package` in release mode for `target` on the build server.
+///
+/// The workspace is sent with `tar` over SSH, and the executable is stripped there and copied back
+/// with `scp` to the place where `cargo build --target <target> --release` would put it.
+pub(crate) fn build_bin_on_server(
+    server: &BuildServer,
+    metadata: &Metadata,
+    package: &Package,
+    bin_name: &str,
+    target: &str,
+) -> Result<Option<Utf8PathBuf>> {
+    let workspace_root = &metadata.workspace_root;
+    let remote_root = format!(
+        "{}/{}",
+        server.dir.trim_end_matches('/'),
+        workspace_root.file_name().unwrap_or("workspace"),
+    );
+
+    let mut tar = Command::new("tar");
+    tar.arg("-cf").arg("-").arg("-C").arg(workspace_root);
+    if let Ok(target_dir) = metadata.target_directory.strip_prefix(workspace_root) {
+        tar.arg(format!("--exclude=./{}", target_dir));
+    }
+    let mut tar = tar
+        .arg(".")
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| "Failed to run `tar`")?;
+    let status = ssh(server)
+        .arg(format!(
+            "mkdir -p {0} && tar -xf - -C {0}",
+            shell_quote(&remote_root),
+        ))
+        .stdin(tar.stdout.take().unwrap())
+        .status()?;
+    ensure!(
+        tar.wait()?.success() && status.success(),
+        "Failed to send the source to `{}`",
+        server.host,
+    );
+
+    let manifest_path = package
+        .manifest_path
+        .strip_prefix(workspace_root)
+        .unwrap_or(&package.manifest_path);
+    let script = format!(
+        "cd {} && exec cargo build --target={} --release --bin {} --manifest-path {} {}",
+        shell_quote(&remote_root),
+        shell_quote(target),
+        shell_quote(bin_name),
+        shell_quote(manifest_path.as_str()),
+        message_format_arg(),
+    );
+    let mut cmd = ssh(server);
+    // `~/.cargo/bin` is usually added to `$PATH` in the login shell.
+    cmd.arg(format!("bash -lc {}", shell_quote(&script)));
+    let remote_executable = match run(cmd, bin_name)? {
+        Some(executable) => executable,
+        None => return Ok(None),
+    };
+
+    let status = ssh(server)
+        .arg(format!(
+            "strip -s {}",
+            shell_quote(remote_executable.as_str())
+        ))
+        .status()?;
+    ensure!(status.success(), "strip failed");
+
+    let executable = metadata
+        .target_directory
+        .join(target)
+        .join("release")
+        .join(remote_executable.file_name().unwrap_or(bin_name));
+    fs::create_dir_all(executable.parent().unwrap())?;
+    let status = Command::new("scp")
+        .arg("-q")
+        .arg(format!("{}:{}", server.host, remote_executable))
+        .arg(&executable)
+        .status()
+        .with_context(|| "Failed to run `scp`")?;
+    ensure!(
+        status.success(),
+        "Failed to copy `{}` from `{}`",
+        remote_executable,
+        server.host,
+    );
+
+    Ok(Some(executable))
+}
+
+fn ssh(server: &BuildServer) -> Command {
+    let mut cmd = Command::new("ssh");
+    cmd.arg(&server.host);
+    cmd
+}
+
+/// Quotes the argument for the shell unless it is plain, e.g. for the remote command which is
+/// interpreted by the login shell on the server.
+pub(crate) fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=%".contains(c))
+    {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Translates `path` into the Windows path (`to_windows`) or the WSL path with `wslpath`.
+pub(crate) fn wslpath(path: &Path, to_windows: bool) -> Result<String> {
+    let output = Command::new("wsl")
+        .args(["--exec", "wslpath", "-a"])
+        .arg(if to_windows { "-w" } else { "-u" })
+        .arg(path)
+        .output()?;
+    ensure!(
+        output.status.success(),
+        "Failed to translate `{}`: {}",
+        path.display(),
+        String::from_utf8_lossy(&output.stderr).trim_end(),
+    );
+    Ok(str::from_utf8(&output.stdout)?.trim_end().to_owned())
+}
+
+// Package IDs are not compared since they differ inside the `cross` container.
+fn is_bin(target: &Target, bin_name: &str) -> bool {
+    target.name == bin_name && target.kind.iter().any(|k| k == "bin")
+}
+
+fn message_format_arg() -> &'static str {
+    if console::colors_enabled_stderr() {
+        "--message-format=json-diagnostic-rendered-ansi"
+    } else {
+        "--message-format=json"
+    }
+}
+
+fn run(mut cmd: Command, bin_name: &str) -> Result<Option<Utf8PathBuf>> {
+    debug!("Running {:?}", cmd);
+
+    let mut child = cmd.stdout(Stdio::piped()).spawn()?;
+
+    let prefix = Style::new()
+        .bold()
+        .apply_to(format!("[{}]", bin_name))
+        .for_stderr();
+
+    let mut executable = None;
+
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    for message in Message::parse_stream(stdout) {
+        match message.with_context(|| "Failed to read the output of `cargo build`")? {
+            Message::CompilerMessage(msg) => {
+                if let Some(rendered) = &msg.message.rendered {
+                    let is_bin = is_bin(&msg.target, bin_name);
+                    for line in rendered.trim_end().lines() {
+                        if is_bin {
+                            eprintln!("{} {}", prefix, line);
+                        } else {
+                            eprintln!("{}", line);
+                        }
+                    }
+                    eprintln!();
+                }
+            }
+            Message::CompilerArtifact(artifact) if is_bin(&artifact.target, bin_name) => {
+                executable = artifact.executable;
+            }
+            _ => {}
+        }
+    }
+
+    if !child.wait()?.success() {
+        return Ok(None);
+    }
+    executable.map(Some).with_context(|| {
+        format!(
+            "`cargo build` did not produce the executable for `{}`",
+            bin_name
+        )
+    })
+}
@@ -0,0 +1,307 @@
+//! On-disk cache under the user's cache directory, and the results of the last tests under the
+//! state directory.
+
+pub(crate) use crate::paths::cache_dir;
+
+use crate::{
+    atcoder::{SubmitForm, TestCase},
+    paths::state_dir,
+};
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+/// The entries of the cache directory, which are all fetched or generated again when needed.
+/// The session and the records in it (in the tests or by older versions) are not.
+const ENTRIES: &[&str] = &[
+    "atcoder-problems",
+    "doctor",
+    "editorials",
+    "failures",
+    "layouts",
+    "library-checker-output.txt",
+    "library-checker-problems",
+    "pages",
+    "samples",
+    "sources",
+    "statements",
+    "submit",
+    "verdicts",
+];
+
+/// The entries of the cache which exist
+pub(crate) fn entries() -> Result<Vec<PathBuf>> {
+    let dir = cache_dir()?;
+    Ok(ENTRIES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.exists())
+        .collect())
+}
+
+#[derive(Serialize, Deserialize)]
+struct Samples {
+    cases: Vec<TestCase>,
+}
+
+fn samples_path(contest_id: &str, problem_id: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?
+        .join("samples")
+        .join(contest_id)
+        .join(problem_id.to_lowercase())
+        .with_extension("toml"))
+}
+
+/// Sample cases of the problem fetched before, if any.
+pub(crate) fn load_samples(contest_id: &str, problem_id: &str) -> Option<Vec<TestCase>> {
+    let s = fs::read_to_string(samples_path(contest_id, problem_id).ok()?).ok()?;
+    toml::from_str::<Samples>(&s)
+        .ok()
+        .map(|samples| samples.cases)
+}
+
+pub(crate) fn save_samples(contest_id: &str, problem_id: &str, cases: &[TestCase]) -> Result<()> {
+    let path = samples_path(contest_id, problem_id)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    let samples = Samples {
+        cases: cases.to_vec(),
+    };
+    fs::write(&path, toml::to_string(&samples)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Writes the page of the problem whose samples could not be scraped, returning the path.
+pub(crate) fn save_sample_diagnostic(
+    contest_id: &str,
+    problem_id: &str,
+    contents: &str,
+) -> Result<PathBuf> {
+    let path = samples_path(contest_id, problem_id)?.with_extension("diagnostic.html");
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn statements_dir(contest_id: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?.join("statements").join(contest_id))
+}
+
+fn statement_path(contest_id: &str, problem_id: &str, english: bool) -> Result<PathBuf> {
+    Ok(statements_dir(contest_id)?.join(format!(
+        "{}.{}.html",
+        problem_id.to_lowercase(),
+        if english { "en" } else { "ja" },
+    )))
+}
+
+/// HTML of the statement of the problem fetched before, if any.
+pub(crate) fn load_statement(contest_id: &str, problem_id: &str, english: bool) -> Option<String> {
+    fs::read_to_string(statement_path(contest_id, problem_id, english).ok()?).ok()
+}
+
+pub(crate) fn save_statement(
+    contest_id: &str,
+    problem_id: &str,
+    english: bool,
+    html: &str,
+) -> Result<()> {
+    let path = statement_path(contest_id, problem_id, english)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, html).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Path to save the image at `src` in the statements of the contest.
+///
+/// The images are named after the last segments of the URLs, which are unique in a contest.
+pub(crate) fn image_path(contest_id: &str, src: &str) -> Result<PathBuf> {
+    let name = src
+        .split(['?', '#'])
+        .next()
+        .and_then(|s| s.rsplit('/').next())
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .with_context(|| format!("Invalid image URL: `{}`", src))?;
+    Ok(statements_dir(contest_id)?.join("images").join(name))
+}
+
+/// Path to save the page of the editorial at `path` (e.g. `/contests/abc999/editorial/1234`)
+pub(crate) fn editorial_path(contest_id: &str, path: &str) -> Result<PathBuf> {
+    let name = path
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .with_context(|| format!("Invalid editorial URL: `{}`", path))?;
+    Ok(cache_dir()?
+        .join("editorials")
+        .join(contest_id)
+        .join(name)
+        .with_extension("html"))
+}
+
+fn verdicts_path(contest_id: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?
+        .join("verdicts")
+        .join(contest_id)
+        .with_extension("toml"))
+}
+
+/// Verdicts of my submissions seen before, by submission ID
+pub(crate) fn load_verdicts(contest_id: &str) -> BTreeMap<u64, String> {
+    let verdicts = verdicts_path(contest_id)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|s| toml::from_str::<BTreeMap<String, String>>(&s).ok())
+        .unwrap_or_default();
+    verdicts
+        .into_iter()
+        .filter_map(|(id, verdict)| Some((id.parse().ok()?, verdict)))
+        .collect()
+}
+
+pub(crate) fn save_verdicts(contest_id: &str, verdicts: &BTreeMap<u64, String>) -> Result<()> {
+    let path = verdicts_path(contest_id)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    let verdicts = verdicts
+        .iter()
+        .map(|(id, verdict)| (id.to_string(), verdict))
+        .collect::<BTreeMap<_, _>>();
+    fs::write(&path, toml::to_string(&verdicts)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// In the state directory since they are not fetched again
+fn results_path(contest_id: &str, problem_id: &str) -> Result<PathBuf> {
+    Ok(state_dir()?
+        .join("results")
+        .join(contest_id)
+        .join(problem_id)
+        .with_extension("toml"))
+}
+
+/// Verdicts of the samples (e.g. `AC`, `WA`) in the last runs of `test` of the bin, by the
+/// 1-indexed sample number
+pub(crate) fn load_results(contest_id: &str, problem_id: &str) -> BTreeMap<usize, String> {
+    let results = results_path(contest_id, problem_id)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|s| toml::from_str::<BTreeMap<String, String>>(&s).ok())
+        .unwrap_or_default();
+    results
+        .into_iter()
+        .filter_map(|(case_no, verdict)| Some((case_no.parse().ok()?, verdict)))
+        .collect()
+}
+
+pub(crate) fn save_results(
+    contest_id: &str,
+    problem_id: &str,
+    results: &BTreeMap<usize, String>,
+) -> Result<()> {
+    let path = results_path(contest_id, problem_id)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    let results = results
+        .iter()
+        .map(|(case_no, verdict)| (case_no.to_string(), verdict))
+        .collect::<BTreeMap<_, _>>();
+    fs::write(&path, toml::to_string(&results)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A page with the validators of its response, to be fetched again by a conditional GET
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Page {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_modified: Option<String>,
+    pub(crate) body: String,
+}
+
+/// How long a page is kept since fetched or found not modified last
+const PAGE_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+// Named after the hash of the URL, which may have a query
+fn page_path(url: &str) -> Result<PathBuf> {
+    let hash = data_encoding::HEXLOWER.encode(&Sha256::digest(url.as_bytes()));
+    Ok(cache_dir()?.join("pages").join(hash).with_extension("toml"))
+}
+
+/// The page at `url` fetched before with an `ETag` or a `Last-Modified`, if any.
+pub(crate) fn load_page(url: &str) -> Option<Page> {
+    let s = fs::read_to_string(page_path(url).ok()?).ok()?;
+    toml::from_str(&s).ok()
+}
+
+/// Saves the page, removing the ones not used for [`PAGE_LIFETIME`].
+pub(crate) fn save_page(url: &str, page: &Page) -> Result<()> {
+    let path = page_path(url)?;
+    let dir = path.parent().unwrap();
+    fs::create_dir_all(dir)?;
+    for entry in fs::read_dir(dir)?.flatten() {
+        let unused = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|elapsed| elapsed > PAGE_LIFETIME);
+        if unused {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    fs::write(&path, toml::to_string(page)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedSubmitForm {
+    /// The local date of the fetch, e.g. `2021-08-01`
+    date: String,
+    form: SubmitForm,
+}
+
+fn submit_form_path(contest_id: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?
+        .join("submit")
+        .join(contest_id)
+        .with_extension("toml"))
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// The choices of the submission form of the contest fetched today, without the CSRF token.
+pub(crate) fn load_submit_form(contest_id: &str) -> Option<SubmitForm> {
+    let s = fs::read_to_string(submit_form_path(contest_id).ok()?).ok()?;
+    let cached = toml::from_str::<CachedSubmitForm>(&s).ok()?;
+    (cached.date == today()).then_some(cached.form)
+}
+
+pub(crate) fn save_submit_form(contest_id: &str, form: &SubmitForm) -> Result<()> {
+    let path = submit_form_path(contest_id)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    let cached = CachedSubmitForm {
+        date: today(),
+        form: form.clone(),
+    };
+    fs::write(&path, toml::to_string(&cached)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn source_path(contest_id: &str, id: u64) -> Result<PathBuf> {
+    Ok(cache_dir()?
+        .join("sources")
+        .join(contest_id)
+        .join(id.to_string()))
+}
+
+/// Source code of the submission fetched before, if any.
+pub(crate) fn load_source(contest_id: &str, id: u64) -> Option<String> {
+    fs::read_to_string(source_path(contest_id, id).ok()?).ok()
+}
+
+pub(crate) fn save_source(contest_id: &str, id: u64, source: &str) -> Result<()> {
+    let path = source_path(contest_id, id)?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,75 @@
+//! Behavior for scripts and CI: the global `--non-interactive` flag and the exit codes.
+//!
+//! | Code | Meaning                                       |
+//! |------|-----------------------------------------------|
+//! | 0    | Succeeded (all the samples passed)            |
+//! | 1    | Some samples failed                           |
+//! | 2    | The solution failed to build                  |
+//! | 3    | Failed to access AtCoder or not logged in     |
+//! | 4    | Other errors, including invalid arguments     |
+//! | 130  | Interrupted by Ctrl-C                         |
+
+use crate::http::is_network_error;
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use structopt::{clap::ArgMatches, StructOpt};
+
+#[derive(StructOpt)]
+pub(crate) struct NonInteractiveOpt {
+    /// Never prompt, failing (or taking the safe choice) where an answer is needed
+    #[structopt(long, global = true)]
+    non_interactive: bool,
+}
+
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn init(matches: &[&ArgMatches<'_>]) {
+    let non_interactive = matches
+        .iter()
+        .any(|matches| NonInteractiveOpt::from_clap(matches).non_interactive);
+    NON_INTERACTIVE.store(non_interactive, Ordering::Relaxed);
+}
+
+/// Whether the user can answer prompts
+pub(crate) fn interactive() -> bool {
+    !NON_INTERACTIVE.load(Ordering::Relaxed) && console::user_attended()
+}
+
+/// Outcome with its own exit code, as the root cause of an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Failure {
+    Test,
+    Build,
+    Auth,
+    Interrupted,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Failure::Test => "some samples did not pass",
+            Failure::Build => "the build failed",
+            Failure::Auth => "not authenticated",
+            Failure::Interrupted => "interrupted",
+        })
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Exit code of the errors without their own code, and of the usage errors of clap, whose own
+/// code 1 would read as failing samples
+pub(crate) const OTHER_EXIT_CODE: i32 = 4;
+
+pub(crate) fn exit_code(err: &anyhow::Error) -> i32 {
+    match err.downcast_ref::<Failure>() {
+        Some(Failure::Test) => 1,
+        Some(Failure::Build) => 2,
+        Some(Failure::Auth) => 3,
+        Some(Failure::Interrupted) => 130,
+        None if is_network_error(err) => 3,
+        None => OTHER_EXIT_CODE,
+    }
+}
@@ -0,0 +1,235 @@
+//! `complexity`: estimates the time complexity of a solution from its running times on generated
+//! inputs of increasing sizes.
+//!
+//! The inputs are described in a TOML file:
+//!
+//! ```toml
+//! generator = "e_gen" # bin run with the seed and the size as the arguments (default: `e_gen`)
+//! max = 200000 # size at the maximum constraints
+//! sizes = [1000, 10000, 100000] # (default: doubling from 1000 up to `max`)
+//! ```
+
+use crate::{
+    build, execute,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    parse_duration,
+    project::package_dir,
+    variant_bin, DEFAULT_TIME_LIMIT,
+};
+use anyhow::{ensure, Context as _, Result};
+use log::info;
+use serde::Deserialize;
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct ComplexityOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// Description of the inputs to generate
+    #[structopt(long = "gen", value_name("PATH"), default_value("gen.toml"))]
+    gen: PathBuf,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Time limit of the problem, for the projected time (e.g. 2s)
+    #[structopt(long, value_name("DURATION"), parse(try_from_str = parse_duration))]
+    time_limit: Option<chrono::Duration>,
+    /// Measure `src/bin/<problem-id>_<variant>.rs` instead
+    #[structopt(long, value_name("NAME"))]
+    variant: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Gen {
+    generator: Option<String>,
+    max: u64,
+    #[serde(default)]
+    sizes: Vec<u64>,
+}
+
+const MIN_SIZE: u64 = 1000;
+
+/// Runs on larger inputs are skipped once a run takes this many times the time limit.
+const SLOW_FACTOR: u32 = 2;
+
+/// Name of a complexity and the growth of the time with the size
+type Model = (&'static str, fn(f64) -> f64);
+
+/// Candidates of the complexity, from the lowest
+const MODELS: &[Model] = &[
+    ("1", |_| 1.),
+    ("log n", |n| n.ln()),
+    ("n", |n| n),
+    ("n log n", |n| n * n.ln()),
+    ("n^2", |n| n * n),
+    ("n^2 log n", |n| n * n * n.ln()),
+    ("n^3", |n| n * n * n),
+];
+
+pub fn complexity(opt: ComplexityOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let problem_id = &opt.problem_id;
+    // Relative to the package unless found from the CWD, which may be in `src/bin`
+    let gen_path = if opt.gen.is_relative() && !opt.gen.exists() {
+        package_dir(package).join(&opt.gen)
+    } else {
+        opt.gen.clone()
+    };
+    let gen = fs::read_to_string(&gen_path)
+        .with_context(|| format!("Failed to read {}", gen_path.display()))?;
+    let gen = toml::from_str::<Gen>(&gen)
+        .with_context(|| format!("Failed to parse {}", gen_path.display()))?;
+    let time_limit = opt
+        .time_limit
+        .and_then(|time_limit| time_limit.to_std().ok())
+        .unwrap_or(DEFAULT_TIME_LIMIT);
+
+    let sizes = if gen.sizes.is_empty() {
+        let mut sizes = vec![];
+        let mut size = MIN_SIZE.min(gen.max);
+        while size < gen.max {
+            sizes.push(size);
+            size *= 2;
+        }
+        sizes.push(gen.max);
+        sizes
+    } else {
+        gen.sizes.clone()
+    };
+    ensure!(
+        sizes.windows(2).all(|w| w[0] < w[1]),
+        "`sizes` in {} are not increasing",
+        gen_path.display()
+    );
+
+    let solution = variant_bin(package, problem_id, opt.variant.as_deref())?;
+    let generator = gen
+        .generator
+        .clone()
+        .unwrap_or_else(|| format!("{}_gen", problem_id));
+    package.find_bin(&generator)?;
+    let build = |bin: &str| {
+        build::build_bin(package, bin, true)?.with_context(|| format!("Failed to build `{}`", bin))
+    };
+    let (solution_path, generator_path) = (build(&solution)?, build(&generator)?);
+
+    println!("{:>10} | {:>10}", "n", "time");
+    let mut samples = vec![];
+    // The size and the length of the last input, which the next one is to exceed
+    let mut last_input = None;
+    for (seed, &size) in sizes.iter().enumerate() {
+        let mut cmd = Command::new(&generator_path);
+        cmd.arg(seed.to_string()).arg(size.to_string());
+        let (input, timed_out) = execute(cmd, b"", time_limit * 10)?;
+        ensure!(
+            !timed_out && input.status.success(),
+            "`{}` failed for size {}:\n{}",
+            generator,
+            size,
+            String::from_utf8_lossy(&input.stderr)
+        );
+        if let Some((last_size, last_len)) = last_input {
+            ensure!(
+                input.stdout.len() > last_len,
+                "`{}` generated {} bytes for size {}, not more than {} bytes for size {}. Does \
+                 it take the size as the second argument?",
+                generator,
+                input.stdout.len(),
+                size,
+                last_len,
+                last_size,
+            );
+        }
+        last_input = Some((size, input.stdout.len()));
+
+        let start = Instant::now();
+        let (output, timed_out) = execute(
+            Command::new(&solution_path),
+            &input.stdout,
+            time_limit * SLOW_FACTOR,
+        )?;
+        let elapsed = start.elapsed();
+        ensure!(
+            timed_out || output.status.success(),
+            "`{}` failed for size {}:\n{}",
+            solution,
+            size,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        if timed_out {
+            println!("{:>10} | {:>10}", size, format!(">{:?}", elapsed));
+            break;
+        }
+        println!("{:>10} | {:>10.3?}", size, elapsed);
+        samples.push((size as f64, elapsed.as_secs_f64()));
+    }
+    println!();
+
+    ensure!(
+        samples.len() >= 3,
+        "Too few sizes measured to estimate the complexity. Give smaller `sizes` in {}",
+        gen_path.display()
+    );
+    let (model, projected) = fit(&samples, gen.max as f64);
+    info!("estimated: O({})", model);
+    info!(
+        "projected at n = {}: {:.3?} (time limit {:?})",
+        gen.max,
+        Duration::from_secs_f64(projected.max(0.)),
+        time_limit
+    );
+    Ok(())
+}
+
+/// Fits `a + c f(n)` to the samples for each model by least squares, picking the model with the
+/// least residual, and returns it with the time projected at `max`.
+fn fit(samples: &[(f64, f64)], max: f64) -> (&'static str, f64) {
+    let count = samples.len() as f64;
+    MODELS
+        .iter()
+        .filter_map(|&(name, f)| {
+            let xs = samples.iter().map(|&(n, _)| f(n)).collect::<Vec<_>>();
+            let mean_x = xs.iter().sum::<f64>() / count;
+            let mean_t = samples.iter().map(|&(_, t)| t).sum::<f64>() / count;
+            let var = xs.iter().map(|x| (x - mean_x).powi(2)).sum::<f64>();
+            let (a, c) = if var == 0. {
+                (mean_t, 0.)
+            } else {
+                let cov = xs
+                    .iter()
+                    .zip(samples)
+                    .map(|(x, &(_, t))| (x - mean_x) * (t - mean_t))
+                    .sum::<f64>();
+                let c = cov / var;
+                (mean_t - c * mean_x, c)
+            };
+            // Times do not decrease with the size.
+            if c < 0. {
+                return None;
+            }
+            let residual = xs
+                .iter()
+                .zip(samples)
+                .map(|(x, &(_, t))| (t - a - c * x).powi(2))
+                .sum::<f64>();
+            Some((name, residual, a + c * f(max)))
+        })
+        // The lower one of the models fitting almost equally
+        .fold(None, |best: Option<(&str, f64, f64)>, model| match best {
+            Some(best) if best.1 <= model.1 * 1.05 => Some(best),
+            _ => Some(model),
+        })
+        .map(|(name, _, projected)| (name, projected))
+        .expect("the constant model always fits")
+}
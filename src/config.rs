@@ -1,7 +1,8 @@
+use crate::paths;
 use anyhow::{Context as _, Result};
 use serde::Deserialize;
 use std::path::PathBuf;
-use std::{env, fs};
+use std::{collections::BTreeMap, fs};
 use toml::Value;
 use toml_edit::Document;
 
@@ -12,6 +13,19 @@ pub struct Config {
     pub profile: Profile,
     pub dependencies: Value,
     pub project: Project,
+    #[serde(rename = "build-server")]
+    pub build_server: Option<BuildServer>,
+    pub watch: Option<Watch>,
+    pub workspace: Option<Workspace>,
+    pub http: Option<Http>,
+    #[serde(rename = "library-checker")]
+    pub library_checker: Option<LibraryChecker>,
+    /// Transforms applied in order to the source before submitting it
+    #[serde(default)]
+    pub preprocess: Vec<PreprocessStep>,
+    /// CSS selectors used instead of the compiled-in ones, by the names in `selectors`
+    #[serde(default)]
+    pub selectors: BTreeMap<String, String>,
 }
 
 #[allow(dead_code)]
@@ -19,9 +33,24 @@ pub struct Config {
 pub struct AtCoder {
     pub submit_via_binary: bool,
     pub use_cross: bool,
+    #[serde(default)]
+    pub use_wsl: bool,
     pub binary_column: usize,
     pub update_interval: u64,
     pub strip_path: Option<String>,
+    /// Language of the messages (`en` or `ja`)
+    pub lang: Option<String>,
+    /// Show the remaining time of the contest and the penalty in the terminal title
+    #[serde(default)]
+    pub timer: bool,
+    /// Stack size of the solutions run locally (`256MiB` by default, or `unlimited`)
+    pub stack_size: Option<String>,
+    /// Remove the debug prints not gated by `cfg(debug_assertions)` at submit
+    #[serde(default)]
+    pub strip_debug: bool,
+    /// Users shown by `standings --rivals` and watched by `watch`
+    #[serde(default)]
+    pub rivals: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -34,18 +63,134 @@ pub struct Profile {
 #[derive(Clone, Debug, Deserialize)]
 pub struct Project {
     pub template: String,
+    /// Directory of the projects created by `new`, where `{contest_id}` and `{date}` (of the start
+    /// of the contest, or today) are replaced (`{contest_id}` by default)
+    pub dir: Option<String>,
     pub rustc_version: Option<String>,
+    /// Template of the brute-force solutions created by `brute`
+    pub brute_template: Option<String>,
+    /// Write the samples to `tests/<problem-id>/in_N.txt` and `out_N.txt` in the projects
+    #[serde(default)]
+    pub sample_files: bool,
+    /// Fetch the statements, the editorials and the submission form after `new`, as `--prefetch`
+    #[serde(default)]
+    pub prefetch: bool,
+    /// Templates used instead of `template` for the problems matching the keys (`d`, or ranges
+    /// of the IDs like `a-c`)
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+    /// Bins for experiments ignored by `test --all`, `submit`, `verify` and `watch`, in addition
+    /// to `scratch_*` (names, or patterns with `*`)
+    #[serde(default)]
+    pub scratch_bins: Vec<String>,
+    /// Add the projects created by `new` in a workspace to its members, with the profiles in the
+    /// root of the workspace
+    #[serde(default)]
+    pub workspace: bool,
+}
+
+impl Project {
+    /// Template of the solution of the problem, which is `template` unless overridden
+    pub fn template_for(&self, problem_id: &str) -> &str {
+        // Orders `a` < ... < `h` < `ex`
+        let key = |id: &str| (id.len(), id.to_lowercase());
+        let id = key(problem_id);
+        self.templates
+            .iter()
+            .find(|(pattern, _)| match pattern.split_once('-') {
+                Some((start, end)) => key(start.trim()) <= id && id <= key(end.trim()),
+                None => key(pattern.trim()) == id,
+            })
+            .map_or(&self.template, |(_, template)| template)
+    }
+}
+
+/// Remote Linux machine to build binaries on over SSH
+#[derive(Clone, Debug, Deserialize)]
+pub struct BuildServer {
+    /// SSH destination (`[user@]hostname` or a host in `~/.ssh/config`)
+    pub host: String,
+    /// Working directory on the server, relative to the home directory
+    #[serde(default = "default_build_server_dir")]
+    pub dir: String,
+}
+
+/// Settings of `watch`
+#[cfg_attr(not(feature = "watch"), allow(dead_code))]
+#[derive(Clone, Debug, Deserialize)]
+pub struct Watch {
+    /// Shell command run after AC, with `CARGO_ATCODER_PROBLEM_ID` set
+    pub on_ac: Option<String>,
+    /// Shell command run when a rival gets AC for a problem not solved yet, with
+    /// `CARGO_ATCODER_PROBLEM_ID` and `CARGO_ATCODER_RIVAL` set
+    pub on_rival_ac: Option<String>,
+}
+
+/// Settings of `workspace`
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Workspace {
+    /// `tmux` or `zellij`
+    pub multiplexer: Option<String>,
+    /// Command of the editor pane, given the source (`$VISUAL` or `$EDITOR` by default)
+    pub editor: Option<String>,
+}
+
+/// Requests to AtCoder, to keep the load on it low
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Http {
+    /// `User-Agent` of the requests (`cargo-atcoder/<version> (+<repository>)` by default)
+    pub user_agent: Option<String>,
+    /// Minimum interval between the starts of the requests (ms)
+    pub interval: Option<u64>,
+    /// Maximum number of the requests in flight at the same time
+    pub max_concurrency: Option<usize>,
+    /// Timeout of each request (s)
+    pub timeout: Option<u64>,
+    /// Timeout of each request together with the wait for its turn (s, twice `timeout` by
+    /// default)
+    pub total_timeout: Option<u64>,
+    /// IP version to connect over (4 or 6), or both with the fallback by default
+    pub ip_version: Option<u8>,
+}
+
+/// Problems of Library Checker for `verify`
+#[derive(Clone, Debug, Deserialize)]
+pub struct LibraryChecker {
+    /// Clone of `library-checker-problems` used instead of the one in the cache directory
+    pub repo: Option<PathBuf>,
+}
+
+/// A step of `preprocess`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PreprocessStep {
+    /// Replaces the matches of the regex, where `$1`, `${name}` in `replacement` refer to the
+    /// groups
+    Replace {
+        pattern: String,
+        #[serde(default)]
+        replacement: String,
+    },
+    /// Expands the lines matching the regex, whose first group is a path relative to the file,
+    /// into the contents of the file (`include!("path");` by default)
+    Include { pattern: Option<String> },
+    /// Removes the comments, including the doc comments
+    StripComments,
+}
+
+fn default_build_server_dir() -> String {
+    "cargo-atcoder-build".to_owned()
 }
 
 const DEFAULT_CONFIG_STR: &str = include_str!("../config/cargo-atcoder.toml");
 
+/// The path of the config, which may not exist yet
+pub(crate) fn config_file() -> Result<PathBuf> {
+    Ok(paths::config_dir()?.join("cargo-atcoder.toml"))
+}
+
 fn config_path() -> Result<PathBuf> {
-    let config_path = if let Some(path) = env::var_os("CARGO_ATCODER_TEST_CONFIG_DIR") {
-        path.into()
-    } else {
-        dirs::config_dir().with_context(|| "Failed to get config directory")?
-    }
-    .join("cargo-atcoder.toml");
+    let config_path = config_file()?;
 
     if !config_path.exists() {
         fs::create_dir_all(config_path.parent().unwrap())?;
@@ -71,3 +216,9 @@ pub fn read_config_preserving() -> Result<Document> {
     let config_path = config_path()?;
     Ok(fs::read_to_string(&config_path)?.parse::<Document>()?)
 }
+
+pub fn write_config_preserving(config: &Document) -> Result<()> {
+    let config_path = config_path()?;
+    fs::write(&config_path, config.to_string())
+        .with_context(|| format!("Failed to write: `{}`", config_path.display()))
+}
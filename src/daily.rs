@@ -0,0 +1,146 @@
+//! `daily`: a problem a day slightly above the rating, with the streak of the days whose problems
+//! have been solved, recorded in the state directory.
+
+use crate::{
+    atcoder::AtCoder,
+    atcoder_problems::AtCoderProblems,
+    paths::state_dir,
+    practice::{self, XorShift},
+    session_file,
+};
+use anyhow::{ensure, Context as _, Result};
+use chrono::{Duration, NaiveDate};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct DailyOpt {
+    /// User to pick the problem for (default: the logged-in user)
+    #[structopt(long, value_name("USERNAME"))]
+    user: Option<String>,
+    /// Upper bound of the difficulty above the rating
+    #[structopt(long, value_name("DIFFICULTY"), default_value("200"))]
+    above: u32,
+    /// Directory of the project (default: `daily-<date>`)
+    #[structopt(long, value_name("PATH"))]
+    dir: Option<PathBuf>,
+    /// Skip warming-up after creating the project
+    #[structopt(long)]
+    skip_warmup: bool,
+}
+
+/// The problems picked by the date (e.g. `2021-01-02`)
+#[derive(Default, Serialize, Deserialize)]
+struct Picks {
+    #[serde(default)]
+    days: BTreeMap<String, String>,
+}
+
+fn picks_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("daily.toml"))
+}
+
+// Not reset on a broken file, which has the streak
+fn load_picks() -> Result<Picks> {
+    let path = picks_path()?;
+    match fs::read_to_string(&path) {
+        Ok(s) => toml::from_str(&s).with_context(|| format!("Failed to parse {}", path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Picks::default()),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn save_picks(picks: &Picks) -> Result<()> {
+    let path = picks_path()?;
+    fs::write(&path, toml::to_string(picks)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub async fn daily(opt: DailyOpt) -> Result<()> {
+    let atc = AtCoder::new(&session_file()?)?;
+    let user = match &opt.user {
+        Some(user) => user.clone(),
+        None => atc
+            .username()
+            .await?
+            .context("Not logged in. Log in or give `--user` to pick the problem for")?,
+    };
+    let api = AtCoderProblems::new()?;
+    let solved = api.solved(&user).await?;
+
+    let today = chrono::Local::now().naive_local().date();
+    let date = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+    let mut picks = load_picks()?;
+
+    let problems = api.problems().await?;
+    let problem = match picks.days.get(&date(today)) {
+        Some(id) => {
+            info!("Today's problem is already picked.");
+            problems
+                .iter()
+                .find(|problem| problem.id == *id)
+                .with_context(|| format!("Problem `{}` not found", id))?
+                .clone()
+        }
+        None => {
+            let rating = atc.rating(&user).await?.unwrap_or(0) as f64;
+            let models = api.problem_models().await?;
+            let max = rating + f64::from(opt.above);
+            let mut candidates = problems
+                .iter()
+                .filter(|problem| !solved.contains(&problem.id))
+                .filter(|problem| {
+                    models
+                        .get(&problem.id)
+                        .filter(|model| !model.is_experimental)
+                        .and_then(|model| model.clipped_difficulty())
+                        .is_some_and(|difficulty| rating <= difficulty && difficulty < max)
+                })
+                .collect::<Vec<_>>();
+            ensure!(
+                !candidates.is_empty(),
+                "No unsolved problems of difficulties from {} to {} found",
+                rating,
+                max
+            );
+            candidates.sort_by(|a, b| a.id.cmp(&b.id));
+            // The same pick for the same day
+            let seed = today.signed_duration_since(NaiveDate::from_ymd(1970, 1, 1));
+            let mut rng = XorShift::new(seed.num_days() as u64);
+            let problem = candidates[(rng.next() % candidates.len() as u64) as usize].clone();
+
+            let dir = opt
+                .dir
+                .clone()
+                .unwrap_or_else(|| format!("daily-{}", date(today)).into());
+            practice::create_project(&dir, std::slice::from_ref(&problem), opt.skip_warmup).await?;
+            picks.days.insert(date(today), problem.id.clone());
+            if let Err(err) = save_picks(&picks) {
+                warn!("failed to record the pick of today: {:#}", err);
+            }
+            problem
+        }
+    };
+    info!("{}: {} ({})", problem.id, problem.title, problem.url());
+
+    // Today does not break the streak until it ends.
+    let done = |day| {
+        picks
+            .days
+            .get(&date(day))
+            .is_some_and(|id| solved.contains(id))
+    };
+    let mut day = today;
+    if !done(day) {
+        day -= Duration::days(1);
+    }
+    let mut streak = 0;
+    while done(day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    info!("Streak: {} days", streak);
+    Ok(())
+}
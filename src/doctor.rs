@@ -0,0 +1,305 @@
+//! `doctor`: checks the environment before a contest.
+
+use crate::{
+    atcoder::AtCoder,
+    cache,
+    config::{read_config, read_config_preserving, Config},
+    metadata, project, session_file,
+};
+use anyhow::{bail, Result};
+use chrono::Utc;
+use console::Style;
+use std::{env, fs, iter, process::Command};
+use structopt::StructOpt;
+
+// The `Date` header has a resolution of one second.
+const CLOCK_SKEW_TOLERANCE_SECS: i64 = 2;
+
+#[derive(StructOpt)]
+pub struct DoctorOpt {
+    /// Contest ID to check the registration for (default: the package name of the current project)
+    contest_id: Option<String>,
+    /// Do not check that the template compiles with the dependencies
+    #[structopt(long)]
+    skip_template: bool,
+}
+
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn ok(&mut self, name: &str, detail: impl AsRef<str>) {
+        println!(
+            "{} {}: {}",
+            Style::new().green().apply_to("  ok"),
+            name,
+            detail.as_ref()
+        );
+    }
+
+    fn warn(&mut self, name: &str, detail: impl AsRef<str>, hint: impl AsRef<str>) {
+        println!(
+            "{} {}: {}",
+            Style::new().yellow().apply_to("warn"),
+            name,
+            detail.as_ref()
+        );
+        println!("     hint: {}", hint.as_ref());
+    }
+
+    fn fail(&mut self, name: &str, detail: impl AsRef<str>, hint: impl AsRef<str>) {
+        self.failures += 1;
+        println!(
+            "{} {}: {}",
+            Style::new().red().apply_to("FAIL"),
+            name,
+            detail.as_ref()
+        );
+        println!("     hint: {}", hint.as_ref());
+    }
+}
+
+pub async fn doctor(opt: DoctorOpt) -> Result<()> {
+    let config = read_config()?;
+    let mut report = Report::default();
+
+    let contest_id = opt.contest_id.or_else(|| {
+        let cwd = env::current_dir().ok()?;
+        let metadata = metadata::cargo_metadata(None, &cwd).ok()?;
+        let package = metadata.root_package()?;
+        // Not checked in the practice sets
+        project::contest_id(package).ok().map(str::to_owned)
+    });
+
+    check_atcoder(&mut report, contest_id.as_deref()).await?;
+    check_rustc(&mut report, &config);
+    check_binary_toolchain(&mut report, &config);
+    if !opt.skip_template {
+        check_template(&mut report, &config)?;
+    }
+
+    println!();
+    if report.failures > 0 {
+        bail!("{} check(s) failed", report.failures);
+    }
+    println!("All checks passed.");
+    Ok(())
+}
+
+async fn check_atcoder(report: &mut Report, contest_id: Option<&str>) -> Result<()> {
+    let atc = AtCoder::new(&session_file()?)?;
+
+    let username = match atc.username().await {
+        Ok(username) => {
+            report.ok("network", "atcoder.jp is reachable");
+            username
+        }
+        Err(err) => {
+            report.fail(
+                "network",
+                err.to_string(),
+                "Check the network connection and the proxy settings (`HTTPS_PROXY`)",
+            );
+            return Ok(());
+        }
+    };
+
+    match &username {
+        Some(username) => report.ok("login", format!("logged in as {}", username)),
+        None => report.fail(
+            "login",
+            "not logged in, or the session has expired",
+            "Run `cargo atcoder login`",
+        ),
+    }
+
+    match atc.server_time().await {
+        Ok(Some(server_time)) => {
+            let skew = Utc::now().signed_duration_since(server_time).num_seconds();
+            if skew.abs() <= CLOCK_SKEW_TOLERANCE_SECS {
+                report.ok("clock", "in sync with atcoder.jp");
+            } else {
+                report.warn(
+                    "clock",
+                    format!("{:+} seconds off from atcoder.jp", skew),
+                    "Synchronize the system clock (e.g. enable NTP) so that the contest timers \
+                     are correct",
+                );
+            }
+        }
+        Ok(None) => report.warn(
+            "clock",
+            "atcoder.jp did not send the time",
+            "Skip this check",
+        ),
+        Err(err) => report.warn("clock", err.to_string(), "Skip this check"),
+    }
+
+    if let (Some(contest_id), Some(_)) = (contest_id, &username) {
+        match atc.contest_info(contest_id).await {
+            Ok(info) => report.ok(
+                "contest",
+                format!("{} problems in `{}`", info.problems().len(), contest_id),
+            ),
+            Err(err) => report.warn(
+                "contest",
+                err.to_string(),
+                format!(
+                    "Register for `{}` on atcoder.jp, or wait for it to start",
+                    contest_id,
+                ),
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn check_rustc(report: &mut Report, config: &Config) {
+    let rustc_version = match &config.project.rustc_version {
+        Some(rustc_version) => rustc_version,
+        None => {
+            report.ok(
+                "rustc",
+                "the toolchain is not pinned (`project.rustc_version`)",
+            );
+            return;
+        }
+    };
+
+    match Command::new("rustup")
+        .args(["run", rustc_version, "rustc", "--version"])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            report.ok("rustc", String::from_utf8_lossy(&output.stdout).trim_end())
+        }
+        Ok(_) => report.fail(
+            "rustc",
+            format!(
+                "Rust {} (the judge version) is not installed",
+                rustc_version
+            ),
+            format!("Run `rustup toolchain install {}`", rustc_version),
+        ),
+        Err(_) => report.fail(
+            "rustc",
+            "rustup is not found",
+            "Install rustup from https://rustup.rs",
+        ),
+    }
+}
+
+fn check_binary_toolchain(report: &mut Report, config: &Config) {
+    let target = &config.profile.target;
+
+    if let Some(server) = &config.build_server {
+        require(report, "ssh", "Install OpenSSH");
+        require(report, "scp", "Install OpenSSH");
+        report.ok("binary", format!("built on `{}`", server.host));
+        return;
+    }
+    if config.atcoder.use_wsl {
+        require(report, "wsl", "Install WSL with `wsl --install`");
+        return;
+    }
+    if config.atcoder.use_cross {
+        require(report, "cross", "Run `cargo install cross`");
+        require(report, "docker", "Install Docker, which `cross` needs");
+    } else {
+        match Command::new("rustup")
+            .args(["target", "list", "--installed"])
+            .output()
+        {
+            Ok(output)
+                if String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|l| l == target) =>
+            {
+                report.ok("binary", format!("`{}` is installed", target));
+            }
+            _ => {
+                let hint = format!("Run `rustup target add {}`", target);
+                let detail = format!("`{}` is not installed", target);
+                if config.atcoder.submit_via_binary {
+                    report.fail("binary", detail, hint);
+                } else {
+                    report.warn("binary", detail, hint);
+                }
+            }
+        }
+    }
+
+    let strip = config.atcoder.strip_path.as_deref().unwrap_or("strip");
+    require(
+        report,
+        strip,
+        "Install GNU binutils and set `atcoder.strip_path`",
+    );
+    if which::which("upx").is_ok() {
+        report.ok("binary", "`upx` is found");
+    } else {
+        report.warn(
+            "binary",
+            "`upx` is not found",
+            "Install UPX to compress the binaries (optional)",
+        );
+    }
+}
+
+fn require(report: &mut Report, program: &str, hint: &str) {
+    if which::which(program).is_ok() {
+        report.ok("binary", format!("`{}` is found", program));
+    } else {
+        report.fail("binary", format!("`{}` is not found", program), hint);
+    }
+}
+
+// Checked in a project under the cache directory so that the dependencies are built only once.
+fn check_template(report: &mut Report, config: &Config) -> Result<()> {
+    let dir = cache::cache_dir()?.join("doctor");
+    fs::create_dir_all(dir.join("src"))?;
+
+    let mut manifest = r#"[package]
+name = "doctor"
+version = "0.1.0"
+edition = "2018"
+"#
+    .parse::<toml_edit::Document>()?;
+    manifest["dependencies"] = read_config_preserving()?["dependencies"].clone();
+    fs::write(dir.join("Cargo.toml"), manifest.to_string())?;
+    match &config.project.rustc_version {
+        Some(rustc_version) => fs::write(dir.join("rust-toolchain"), rustc_version)?,
+        None => {
+            let _ = fs::remove_file(dir.join("rust-toolchain"));
+        }
+    }
+
+    println!("Checking the template (this may take a while for the first time)...");
+    let templates = iter::once(("template".to_owned(), &config.project.template)).chain(
+        config
+            .project
+            .templates
+            .iter()
+            .map(|(problems, template)| (format!("template ({})", problems), template)),
+    );
+    for (name, template) in templates {
+        fs::write(dir.join("src").join("main.rs"), template)?;
+        let status = Command::new("cargo")
+            .args(["check", "--quiet"])
+            .current_dir(&dir)
+            .status()?;
+        if status.success() {
+            report.ok(&name, "compiles with the dependencies");
+        } else {
+            report.fail(
+                &name,
+                "does not compile with the dependencies",
+                "Fix `project.template`, `project.templates` or `[dependencies]` in the config \
+                 file",
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+//! `editorial`: the official and user editorials of the contest after it, with the pages of the
+//! official ones saved to the cache directory for reading offline.
+
+use crate::{
+    atcoder::{self, AtCoder, Editorial},
+    cache, find_problem,
+    metadata::{self, MetadataExt as _},
+    open, project, session_file,
+};
+use anyhow::{Context as _, Result};
+use console::Style;
+use itertools::Itertools as _;
+use log::{info, warn};
+use std::{env, ffi::OsStr, fs, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct EditorialOpt {
+    /// Problem ID (e.g. a, b, ...). The editorials of all the problems are listed if omitted
+    problem_id: Option<String>,
+    /// Open the official editorial of the problem (or the list of the editorials) in the browser
+    #[structopt(long)]
+    open: bool,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+pub async fn editorial(opt: EditorialOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let route = opt
+        .problem_id
+        .as_ref()
+        .and_then(|problem_id| project::routed_task(package, problem_id));
+    let contest_id = match &route {
+        Some((contest_id, _)) => contest_id,
+        None => project::contest_id(package)?,
+    };
+    let atc = AtCoder::new(&session_file()?)?;
+
+    // Task IDs are not always `<contest>_<problem>`, so they are taken from the problem list.
+    let task = match &opt.problem_id {
+        Some(problem_id) => {
+            let contest_info = crate::contest_info_or_register(&atc, contest_id).await?;
+            let problem = find_problem(&contest_info, problem_id, route.as_ref())?;
+            Some(
+                problem
+                    .url
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned(),
+            )
+        }
+        None => None,
+    };
+
+    let mut editorials = atc.editorials(contest_id).await?;
+    if let Some(task) = &task {
+        editorials.retain(|editorial| editorial.task.as_ref() == Some(task));
+    }
+    if editorials.is_empty() {
+        info!(
+            "No editorials of `{}` yet",
+            task.as_deref().unwrap_or(contest_id)
+        );
+        return Ok(());
+    }
+
+    let bold = Style::new().bold();
+    let cyan = Style::new().cyan();
+    for (task, editorials) in &editorials.iter().group_by(|editorial| &editorial.task) {
+        println!("{}", bold.apply_to(task.as_deref().unwrap_or("(contest)")));
+        for editorial in editorials {
+            println!(
+                "  {:<8} {}{}",
+                if editorial.official {
+                    cyan.apply_to("official")
+                } else {
+                    Style::new().apply_to("user")
+                },
+                url(editorial),
+                editorial
+                    .author
+                    .as_ref()
+                    .map_or_else(String::new, |author| format!(" by {}", author)),
+            );
+        }
+    }
+
+    save_official(&atc, contest_id, &editorials).await;
+
+    if opt.open {
+        let url = match &task {
+            Some(_) => editorials
+                .iter()
+                .find(|editorial| editorial.official)
+                .map(url)
+                .unwrap_or_else(|| url(&editorials[0])),
+            None => atcoder::page_url(&format!("/contests/{}/editorial", contest_id)),
+        };
+        open(OsStr::new(&url))?;
+    }
+    Ok(())
+}
+
+fn url(editorial: &Editorial) -> String {
+    if editorial.url.starts_with('/') {
+        atcoder::page_url(&editorial.url)
+    } else {
+        editorial.url.clone()
+    }
+}
+
+/// Saves the pages of the official editorials in AtCoder not saved yet.
+///
+/// Failures are only reported since the editorials are listed.
+async fn save_official(atc: &AtCoder, contest_id: &str, editorials: &[Editorial]) {
+    for editorial in editorials {
+        if !editorial.official || !editorial.url.starts_with('/') {
+            continue;
+        }
+        match save_page(atc, contest_id, editorial).await {
+            Ok(path) => info!("Official editorial saved at {}", path.display()),
+            Err(err) => warn!("failed to save {}: {:#}", url(editorial), err),
+        }
+    }
+}
+
+/// Saves the pages of the official editorials of the contest published already, returning the
+/// number of them.
+pub(crate) async fn prefetch(atc: &AtCoder, contest_id: &str) -> Result<usize> {
+    let mut saved = 0;
+    for editorial in atc.editorials(contest_id).await? {
+        if editorial.official && editorial.url.starts_with('/') {
+            save_page(atc, contest_id, &editorial).await?;
+            saved += 1;
+        }
+    }
+    Ok(saved)
+}
+
+async fn save_page(atc: &AtCoder, contest_id: &str, editorial: &Editorial) -> Result<PathBuf> {
+    let path = cache::editorial_path(contest_id, &editorial.url)?;
+    if !path.exists() {
+        let html = atc.editorial_page(&editorial.url).await?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, html).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,309 @@
+//! `explain`: hints for the verdict of the last failing sample in `test`, from the panic of an
+//! RE, the time of a TLE and the differences of the output of a WA.
+
+use crate::{
+    backtrace,
+    cache::cache_dir,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    project::package_dir,
+    slow_io,
+};
+use anyhow::{bail, Context as _, Result};
+use console::Style;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct ExplainOpt {
+    /// Verdict to explain [possible values: WA, TLE, RE, MLE, CE] (default: the one of the last
+    /// failing sample)
+    verdict: Option<String>,
+    /// [cargo] Package with the target to explain
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+/// The first failing sample of the last run of `test` that failed
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LastFailure {
+    pub problem_id: String,
+    /// 1-indexed
+    pub sample: usize,
+    pub verdict: String,
+    pub input: String,
+    pub expected: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed_ms: u64,
+    pub time_limit_ms: u64,
+}
+
+fn failure_path(contest_id: &str) -> Result<PathBuf> {
+    Ok(cache_dir()?
+        .join("failures")
+        .join(contest_id)
+        .with_extension("toml"))
+}
+
+// Recording is best effort since it is only for `explain`.
+pub(crate) fn record_failure(contest_id: &str, failure: &LastFailure) {
+    let result = failure_path(contest_id).and_then(|path| {
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, toml::to_string(failure)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    });
+    if let Err(err) = result {
+        warn!("failed to record the failure for `explain`: {:#}", err);
+    }
+}
+
+/// Forgets the last failure if it was of the problem, which has passed.
+pub(crate) fn clear_failure(contest_id: &str, problem_id: &str) {
+    if let Ok(path) = failure_path(contest_id) {
+        if load_failure(contest_id).is_some_and(|failure| failure.problem_id == problem_id) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn load_failure(contest_id: &str) -> Option<LastFailure> {
+    let s = fs::read_to_string(failure_path(contest_id).ok()?).ok()?;
+    toml::from_str(&s).ok()
+}
+
+/// General hints of the verdicts
+fn knowledge(verdict: &str) -> Option<&'static [&'static str]> {
+    Some(match verdict {
+        "WA" => &[
+            "Check the corner cases: the minimum and maximum of the constraints, N = 1, all equal \
+             values.",
+            "Overflows wrap silently on the judge. Use `u64`/`i64`, or `checked_*` to find them \
+             (`test --release` checks them too).",
+            "Check the output format: `Yes`/`YES`, the separators, the number of lines and the \
+             precision of floats.",
+            "Compare with a brute-force solution on random inputs with `cargo atcoder stress`.",
+        ],
+        "TLE" => &[
+            "Estimate the number of operations at the maximum constraints; about 10^8 simple \
+             operations run in a second.",
+            "`cargo atcoder complexity` estimates the complexity from the running times on \
+             generated inputs.",
+            "Print with `BufWriter` and read the whole input at once instead of a line at a time.",
+            "Debug prints to stderr take time on the judge too. `submit --strip-debug` removes \
+             them.",
+            "Avoid cloning `Vec`s and `String`s in loops, and `remove(0)` of `Vec` (use \
+             `VecDeque`).",
+            "Memoize the recursions, and check that the loops terminate.",
+        ],
+        "RE" => &[
+            "Index out of bounds: check the sizes of the arrays and the 0/1-indexing of the input.",
+            "`unwrap` on `None`/`Err`: the input may be parsed in a different format from the \
+             statement.",
+            "Stack overflow: deep recursion may need an explicit stack, or a larger one in a \
+             spawned thread.",
+            "Division by zero and overflow checks in debug builds panic too.",
+        ],
+        "MLE" => &[
+            "Estimate the memory at the maximum constraints: `Vec<u64>` of 10^8 elements is \
+             800 MB.",
+            "Avoid keeping all the states when only the last ones are needed (e.g. rolling DP \
+             arrays).",
+        ],
+        "CE" => &[
+            "The judge may use an older Rust or other crate versions; check `rust-toolchain` and \
+             the dependencies.",
+            "`cargo atcoder doctor` checks the environment against the judge.",
+        ],
+        _ => return None,
+    })
+}
+
+pub fn explain(opt: ExplainOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let verdict = opt.verdict.as_deref().map(str::to_uppercase);
+
+    let failure = load_failure(&package.name);
+    let failure = match (failure, &verdict) {
+        (Some(failure), Some(verdict)) if failure.verdict != *verdict => None,
+        (failure, _) => failure,
+    };
+    let verdict = match (&failure, verdict) {
+        (_, Some(verdict)) => verdict,
+        (Some(failure), None) => failure.verdict.clone(),
+        (None, None) => bail!(
+            "No failing samples recorded for `{}`. Give a verdict to explain",
+            package.name
+        ),
+    };
+    let hints = knowledge(&verdict)
+        .with_context(|| format!("Unknown verdict `{}` (WA, TLE, RE, MLE or CE)", verdict))?;
+
+    let cyan = Style::new().cyan();
+    let bold = Style::new().bold();
+    if let Some(failure) = &failure {
+        println!(
+            "{}",
+            bold.apply_to(format!(
+                "`{}` sample {}: {}",
+                failure.problem_id, failure.sample, failure.verdict
+            ))
+        );
+        println!();
+        match failure.verdict.as_str() {
+            "RE" => explain_re(package_dir(package), failure),
+            "TLE" => {
+                explain_tle(failure);
+                if let Ok(target) = package.find_bin(&failure.problem_id) {
+                    if let Ok(source) = fs::read_to_string(&target.src_path) {
+                        let path = target
+                            .src_path
+                            .strip_prefix(package_dir(package))
+                            .unwrap_or(&target.src_path);
+                        slow_io::warn_slow_io(path.as_ref(), &source, None);
+                    }
+                }
+            }
+            "WA" => explain_wa(failure),
+            _ => {}
+        }
+    }
+
+    println!("{}:", cyan.apply_to(format!("hints for {}", verdict)));
+    for hint in hints {
+        println!("  - {}", hint);
+    }
+    Ok(())
+}
+
+fn explain_re(package_dir: &Path, failure: &LastFailure) {
+    let panic = match backtrace::parse(&failure.stderr) {
+        Some(panic) => panic,
+        None => {
+            println!("The solution exited with an error without a panic message.");
+            if failure.stderr.contains("has overflowed its stack") {
+                println!("The stack overflowed. Check the depth of the recursion.");
+            }
+            println!();
+            return;
+        }
+    };
+    let message = &panic.message;
+    let class = if let Some(operation) = panic.overflow() {
+        format!("arithmetic overflow ({})", operation)
+    } else if message.starts_with("index out of bounds") {
+        "index out of bounds".to_owned()
+    } else if message.contains("on a `None` value") {
+        "`unwrap` on `None`".to_owned()
+    } else if message.contains("on an `Err` value") {
+        if message.contains("ParseIntError") || message.contains("ParseFloatError") {
+            "failed to parse the input".to_owned()
+        } else {
+            "`unwrap` on `Err`".to_owned()
+        }
+    } else if message.contains("divide by zero") || message.contains("remainder with a divisor") {
+        "division by zero".to_owned()
+    } else if message.contains("already borrowed") {
+        "`RefCell` borrowed twice".to_owned()
+    } else {
+        "panic".to_owned()
+    };
+    println!("{}: {}", Style::new().red().apply_to("cause"), class);
+    panic.print(package_dir);
+    println!();
+}
+
+fn explain_tle(failure: &LastFailure) {
+    let tokens = failure.input.split_ascii_whitespace().count();
+    println!(
+        "Killed after {:?} (time limit {:?}) on an input of {} bytes, {} lines and {} tokens.",
+        Duration::from_millis(failure.elapsed_ms),
+        Duration::from_millis(failure.time_limit_ms),
+        failure.input.len(),
+        failure.input.lines().count(),
+        tokens,
+    );
+    println!(
+        "The samples are small, so a TLE on them usually means an infinite loop or waiting for \
+         more input than given."
+    );
+    println!();
+}
+
+fn explain_wa(failure: &LastFailure) {
+    for issue in format_issues(&failure.expected, &failure.stdout) {
+        println!("  - {}", issue);
+    }
+    println!();
+}
+
+/// Differences of the output from the expected one, from the likely mistakes in the format
+fn format_issues(expected: &str, actual: &str) -> Vec<String> {
+    let digits = |s: &str| s.split_once('.').map_or(0, |(_, frac)| frac.len());
+    let mut issues = vec![];
+    let expected_tokens = expected.split_whitespace().collect::<Vec<_>>();
+    let actual_tokens = actual.split_whitespace().collect::<Vec<_>>();
+
+    if actual_tokens.is_empty() {
+        issues.push("Nothing was printed. Check that the answer is printed to stdout.".to_owned());
+        return issues;
+    }
+    if actual_tokens.len() > expected_tokens.len() && actual_tokens.starts_with(&expected_tokens) {
+        issues.push(format!(
+            "The expected output is followed by {} extra tokens. Debug prints should go to \
+             stderr with `eprintln!`.",
+            actual_tokens.len() - expected_tokens.len()
+        ));
+    } else if actual_tokens.len() < expected_tokens.len()
+        && expected_tokens.starts_with(&actual_tokens)
+    {
+        issues.push("The output ends before the expected one.".to_owned());
+    } else if actual_tokens.len() != expected_tokens.len() {
+        issues.push(format!(
+            "{} tokens were printed for {} expected.",
+            actual_tokens.len(),
+            expected_tokens.len()
+        ));
+    }
+    if expected.lines().count() != actual.lines().count()
+        && expected_tokens.len() == actual_tokens.len()
+    {
+        issues.push(format!(
+            "{} lines were printed for {} expected. Check the separators (spaces or newlines).",
+            actual.lines().count(),
+            expected.lines().count()
+        ));
+    }
+
+    let mismatch = expected_tokens
+        .iter()
+        .zip(&actual_tokens)
+        .enumerate()
+        .find(|(_, (e, a))| e != a);
+    if let Some((i, (e, a))) = mismatch {
+        if e.eq_ignore_ascii_case(a) {
+            issues.push(format!(
+                "`{}` was printed for `{}`. The case of the letters matters.",
+                a, e
+            ));
+        } else if e.parse::<f64>().is_ok() && a.parse::<f64>().is_ok() && digits(a) < digits(e) {
+            issues.push(format!(
+                "`{}` was printed for `{}`. Print more digits after the decimal point.",
+                a, e
+            ));
+        } else {
+            issues.push(format!("Token {} is `{}` for `{}` expected.", i + 1, a, e));
+        }
+    }
+    issues
+}
@@ -0,0 +1,176 @@
+//! Self-contained HTML reports of long runs (`stress --html` and `verify --html`), to look over
+//! after they finish: the status and the time of each case, a histogram of the times, and the
+//! failing cases with their inputs and outputs.
+
+use anyhow::{Context as _, Result};
+use itertools::Itertools as _;
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::Path, time::Duration};
+
+/// Number of the bins of the histogram of the times
+const BINS: usize = 20;
+
+pub(crate) struct Row {
+    /// e.g. `seed 3`, `abc999.a sample 1`
+    pub(crate) name: String,
+    /// The verdict (e.g. `AC`, `WA`, `CE`)
+    pub(crate) status: String,
+    pub(crate) time: Option<Duration>,
+    /// Link for the case, e.g. the saved input relative to the report or the problem
+    pub(crate) link: Option<String>,
+    /// The input and outputs of the failing case
+    pub(crate) details: Option<String>,
+}
+
+impl Row {
+    fn passed(&self) -> bool {
+        self.status == "AC"
+    }
+}
+
+pub(crate) fn write(path: &Path, title: &str, rows: &[Row]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, to_html(title, rows))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn to_html(title: &str, rows: &[Row]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 2px 8px; text-align: left; vertical-align: top; }}
+.AC {{ color: #2a2; }}
+.WA, .RE, .CE {{ color: #c22; }}
+.TLE {{ color: #c80; }}
+.histogram {{ display: flex; align-items: flex-end; height: 120px; gap: 2px; }}
+.histogram div {{ background: #58c; width: 24px; }}
+pre {{ background: #f6f6f6; padding: 4px; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>Generated at {date}</p>
+"#,
+        title = escape(title),
+        date = chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+    );
+
+    let counts = rows.iter().map(|row| row.status.as_str()).counts();
+    let _ = writeln!(
+        html,
+        "<p>{} cases: {}</p>",
+        rows.len(),
+        counts
+            .iter()
+            .sorted()
+            .map(|(status, count)| format!(
+                r#"<span class="{0}">{0}</span> {1}"#,
+                escape(status),
+                count
+            ))
+            .join(", "),
+    );
+
+    let times = rows.iter().filter_map(|row| row.time).collect::<Vec<_>>();
+    if let Some(&max) = times.iter().max() {
+        let width = (max.as_secs_f64() / BINS as f64).max(1e-3);
+        let mut bins = BTreeMap::<usize, usize>::new();
+        for time in &times {
+            *bins
+                .entry(((time.as_secs_f64() / width) as usize).min(BINS - 1))
+                .or_default() += 1;
+        }
+        let highest = bins.values().copied().max().unwrap_or(1);
+        html += "<h2>Times</h2>\n<div class=\"histogram\">\n";
+        for i in 0..BINS {
+            let count = bins.get(&i).copied().unwrap_or(0);
+            let _ = writeln!(
+                html,
+                r#"<div style="height: {}%" title="{:.0}-{:.0} ms: {}"></div>"#,
+                100 * count / highest,
+                i as f64 * width * 1e3,
+                (i + 1) as f64 * width * 1e3,
+                count,
+            );
+        }
+        let _ = writeln!(html, "</div>\n<p>0 ms to {} ms</p>", max.as_millis());
+    }
+
+    let failures = rows.iter().filter(|row| !row.passed()).count();
+    if failures > 0 {
+        let _ = writeln!(
+            html,
+            "<p><a href=\"#failures\">{} failures</a></p>",
+            failures
+        );
+    }
+
+    html += "<h2>Cases</h2>\n<table>\n<tr><th>Case</th><th>Status</th><th>Time</th></tr>\n";
+    for (i, row) in rows.iter().enumerate() {
+        let name = if row.passed() {
+            escape(&row.name)
+        } else {
+            format!("<a href=\"#case-{}\">{}</a>", i, escape(&row.name))
+        };
+        let _ = writeln!(
+            html,
+            r#"<tr><td>{}</td><td class="{}">{}</td><td>{}</td></tr>"#,
+            name,
+            escape(&row.status),
+            escape(&row.status),
+            row.time
+                .map_or_else(String::new, |time| format!("{} ms", time.as_millis())),
+        );
+    }
+    html += "</table>\n";
+
+    if failures > 0 {
+        html += "<h2 id=\"failures\">Failures</h2>\n";
+        for (i, row) in rows.iter().enumerate().filter(|(_, row)| !row.passed()) {
+            let _ = writeln!(
+                html,
+                r#"<h3 id="case-{}">{} <span class="{}">{}</span></h3>"#,
+                i,
+                escape(&row.name),
+                escape(&row.status),
+                escape(&row.status),
+            );
+            if let Some(link) = &row.link {
+                let _ = writeln!(html, r#"<p><a href="{0}">{0}</a></p>"#, escape(link));
+            }
+            if let Some(details) = &row.details {
+                let _ = writeln!(html, "<pre>{}</pre>", escape(details));
+            }
+        }
+    }
+    html += "</body>\n</html>\n";
+    html
+}
+
+/// Escapes the text of HTML or XML for both the contents and the attributes, dropping the
+/// characters XML 1.0 does not allow (e.g. the escape sequences of the colors).
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&apos;",
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
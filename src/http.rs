@@ -1,92 +1,828 @@
-use anyhow::{anyhow, Result};
+use crate::{cache, config, lock::FileLock, logging::HTTP_TARGET, recording};
+use anyhow::{anyhow, bail, ensure, Result};
 use itertools::Itertools;
+use log::trace;
+use once_cell::sync::{Lazy, OnceCell};
 use reqwest::{
     cookie::{CookieStore, Jar},
-    header::HeaderValue,
-    Client as ReqwestClient, Url,
+    header::{
+        HeaderMap, HeaderValue, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+    redirect::Policy,
+    Client as ReqwestClient, RequestBuilder, Response, StatusCode, Url,
 };
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Write as _},
+    collections::HashMap,
+    env, fmt,
+    fs::{self, File, OpenOptions},
+    future::Future,
+    io::{self, BufRead, BufReader, Write as _},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpStream, ToSocketAddrs as _},
     path::{Path, PathBuf},
-    sync::Arc,
+    process,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
 };
+use structopt::{clap::ArgMatches, StructOpt};
+use tokio::sync::{Semaphore, SemaphorePermit};
 
+// Long enough to keep the connection between the polls of `watch` and `status`.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+const DEFAULT_USER_AGENT: &str = concat!(
+    "cargo-atcoder/",
+    env!("CARGO_PKG_VERSION"),
+    " (+",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+
+// Minimum interval between the starts of requests, not to put a load on AtCoder.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(200);
+
+const DEFAULT_MAX_CONCURRENCY: usize = 3;
+
+// Long enough for a busy AtCoder at the start of a contest, but not to wait forever for a hung
+// connection.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+static SETTINGS: OnceCell<Settings> = OnceCell::new();
+
+struct Settings {
+    user_agent: String,
+    /// Of a request, from the connection to the end of the body
+    timeout: Duration,
+    /// Of an operation, i.e. of the wait for the turn and the request
+    total_timeout: Duration,
+    /// The unspecified address of the IP version to connect over, if only one
+    local_address: Option<IpAddr>,
+    interval: Duration,
+    permits: Semaphore,
+    /// When the next request may start
+    next: tokio::sync::Mutex<tokio::time::Instant>,
+}
+
+#[derive(StructOpt)]
+pub(crate) struct HttpOpt {
+    /// Timeout of each request to AtCoder in seconds (default: `http.timeout` in the config, or
+    /// 30)
+    #[structopt(long, global = true, value_name("SECS"))]
+    timeout: Option<u64>,
+    /// Connect over IPv4 only, e.g. when IPv6 is broken (default: `http.ip_version` in the
+    /// config)
+    #[structopt(long, global = true, conflicts_with("ipv6"))]
+    ipv4: bool,
+    /// Connect over IPv6 only
+    #[structopt(long, global = true)]
+    ipv6: bool,
+}
+
+/// Applies `[http]` in the config and the global `--timeout`, `--ipv4` and `--ipv6` given at any
+/// level to all the requests of the process.
+pub(crate) fn init(matches: &[&ArgMatches<'_>], config: Option<&config::Http>) -> Result<()> {
+    let mut config = config.cloned().unwrap_or_default();
+    for matches in matches {
+        let opt = HttpOpt::from_clap(matches);
+        config.timeout = opt.timeout.or(config.timeout);
+        if opt.ipv4 {
+            config.ip_version = Some(4);
+        } else if opt.ipv6 {
+            config.ip_version = Some(6);
+        }
+    }
+    ensure!(
+        config.max_concurrency != Some(0),
+        "`http.max_concurrency` should be positive"
+    );
+    ensure!(
+        matches!(config.ip_version, None | Some(4) | Some(6)),
+        "`http.ip_version` should be 4 or 6"
+    );
+    ensure!(
+        config.timeout != Some(0) && config.total_timeout != Some(0),
+        "The timeouts should be positive"
+    );
+    let _ = SETTINGS.set(Settings::new(config));
+    Ok(())
+}
+
+fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings::new(Default::default()))
+}
+
+impl Settings {
+    fn new(config: config::Http) -> Self {
+        let timeout = config.timeout.map_or(DEFAULT_TIMEOUT, Duration::from_secs);
+        Self {
+            user_agent: config
+                .user_agent
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_owned()),
+            timeout,
+            // Twice the request by default, for the requests queued behind the others
+            total_timeout: config
+                .total_timeout
+                .map_or(timeout * 2, Duration::from_secs),
+            local_address: match config.ip_version {
+                Some(4) => Some(Ipv4Addr::UNSPECIFIED.into()),
+                Some(6) => Some(Ipv6Addr::UNSPECIFIED.into()),
+                _ => None,
+            },
+            interval: config
+                .interval
+                .map_or(DEFAULT_INTERVAL, Duration::from_millis),
+            permits: Semaphore::new(config.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY)),
+            next: tokio::sync::Mutex::new(tokio::time::Instant::now()),
+        }
+    }
+}
+
+/// Timeout of each request, for the clients other than [`Client`]
+pub(crate) fn timeout() -> Duration {
+    settings().timeout
+}
+
+/// Runs the operation (the wait for the turn and the request) within the total timeout, telling
+/// what timed out.
+async fn with_timeouts<T>(
+    method: &str,
+    url: &Url,
+    operation: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let settings = settings();
+    match tokio::time::timeout(settings.total_timeout, operation).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => {
+            let (timed_out, connect) = match err.downcast_ref::<reqwest::Error>() {
+                Some(err) => (err.is_timeout(), err.is_connect()),
+                None => return Err(err),
+            };
+            let message = if timed_out {
+                format!(
+                    "{} {} timed out after {}s. AtCoder may be busy; try again, or wait longer \
+                     with `--timeout` (or `http.timeout` in the config)",
+                    method,
+                    url,
+                    settings.timeout.as_secs(),
+                )
+            } else if connect {
+                format!("Failed to connect for {} {}", method, url)
+            } else {
+                return Err(err);
+            };
+            let url = url.clone();
+            let diagnosis = tokio::task::spawn_blocking(move || diagnose(&url)).await?;
+            Err(err.context(format!("{}\n{}", message, diagnosis)))
+        }
+        Err(_) => Err(TimeoutError {
+            method: method.to_owned(),
+            url: url.clone(),
+            timeout: settings.total_timeout,
+        }
+        .into()),
+    }
+}
+
+// Short not to keep the user waiting after the failure
+const DIAGNOSIS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The proxy of the environment variables that reqwest uses for the URL, if any
+fn proxy(url: &Url) -> Option<String> {
+    let var = |name: &str| {
+        env::var(name)
+            .or_else(|_| env::var(name.to_uppercase()))
+            .ok()
+            .filter(|value| !value.is_empty())
+    };
+    let host = url.host_str()?;
+    let bypassed = var("no_proxy").is_some_and(|no_proxy| {
+        no_proxy.split(',').map(str::trim).any(|pattern| {
+            pattern == "*"
+                || host == pattern.trim_start_matches('.')
+                || host.ends_with(&format!(".{}", pattern.trim_start_matches('.')))
+        })
+    });
+    if bypassed {
+        return None;
+    }
+    match url.scheme() {
+        "https" => var("https_proxy"),
+        "http" => var("http_proxy"),
+        _ => None,
+    }
+    .or_else(|| var("all_proxy"))
+}
+
+/// What fails on the way to the host, i.e. the DNS lookup and the connections over IPv4 and
+/// IPv6, with a flag to avoid the broken one. Skipped through a proxy, which reaches the host
+/// instead.
+fn diagnose(url: &Url) -> String {
+    let (host, port) = match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => (host.to_owned(), port),
+        _ => return "Diagnosis: no host in the URL".to_owned(),
+    };
+    if let Some(proxy) = proxy(url) {
+        return format!(
+            "Diagnosis: skipped since the requests go through the proxy {}. Check the proxy",
+            proxy
+        );
+    }
+    let mut lines = vec![];
+
+    // In a thread left behind if it hangs, since the lookup cannot be given a timeout
+    let start = Instant::now();
+    let (tx, rx) = std::sync::mpsc::channel();
+    {
+        let host = host.clone();
+        thread::spawn(move || drop(tx.send((&*host, port).to_socket_addrs())));
+    }
+    let host = &*host;
+    let addrs = match rx.recv_timeout(DIAGNOSIS_TIMEOUT) {
+        Ok(Ok(addrs)) => addrs.collect::<Vec<_>>(),
+        Ok(Err(err)) => {
+            return format!(
+                "Diagnosis: DNS lookup of {} failed: {}. Check the DNS settings of the network",
+                host, err
+            )
+        }
+        Err(_) => {
+            return format!(
+                "Diagnosis: DNS lookup of {} did not finish in {}s. Check the DNS settings of the \
+                 network",
+                host,
+                DIAGNOSIS_TIMEOUT.as_secs(),
+            )
+        }
+    };
+    let count = |v4| addrs.iter().filter(|addr| addr.is_ipv4() == v4).count();
+    lines.push(format!(
+        "DNS lookup of {}: {} IPv4 and {} IPv6 addresses in {:.0?}",
+        host,
+        count(true),
+        count(false),
+        start.elapsed(),
+    ));
+
+    let mut connected = vec![];
+    for (v4, family) in [(true, "IPv4"), (false, "IPv6")] {
+        let addr = match addrs.iter().find(|addr| addr.is_ipv4() == v4) {
+            Some(addr) => addr,
+            None => continue,
+        };
+        let start = Instant::now();
+        match TcpStream::connect_timeout(addr, DIAGNOSIS_TIMEOUT) {
+            Ok(_) => {
+                connected.push(v4);
+                lines.push(format!(
+                    "connection to {} ({}): ok in {:.0?}",
+                    addr,
+                    family,
+                    start.elapsed()
+                ));
+            }
+            Err(err) => lines.push(format!("connection to {} ({}): {}", addr, family, err)),
+        }
+    }
+    if let Some(local) = settings().local_address {
+        let (family, flag) = if local.is_ipv4() {
+            ("IPv4", "--ipv4")
+        } else {
+            ("IPv6", "--ipv6")
+        };
+        if count(local.is_ipv4()) == 0 {
+            lines.push(format!(
+                "No {} address to connect to with `{}`",
+                family, flag
+            ));
+        }
+    }
+    match (count(true) > 0, count(false) > 0, &*connected) {
+        (true, true, [true]) => {
+            lines.push("IPv6 is broken; try `--ipv4` (or `http.ip_version = 4`)".to_owned())
+        }
+        (true, true, [false]) => {
+            lines.push("IPv4 is broken; try `--ipv6` (or `http.ip_version = 6`)".to_owned())
+        }
+        (_, _, []) => lines.push("No connection; check the network or the proxy".to_owned()),
+        _ => {}
+    }
+    format!(
+        "Diagnosis:\n{}",
+        lines.iter().map(|line| format!("  {}", line)).join("\n")
+    )
+}
+
+/// Waits for a turn to send a request, which lasts until the permit is dropped.
+async fn throttle() -> SemaphorePermit<'static> {
+    let settings = settings();
+    let permit = settings
+        .permits
+        .acquire()
+        .await
+        .expect("the semaphore is never closed");
+    let mut next = settings.next.lock().await;
+    tokio::time::sleep_until(*next).await;
+    *next = tokio::time::Instant::now() + settings.interval;
+    permit
+}
+
+#[derive(Clone)]
 pub struct Client {
-    client: ReqwestClient,
-    cookie_store: Arc<Jar>,
-    session_file: PathBuf,
+    session: Arc<Session>,
+    /// `None` for the APIs without logging in, whose cookies are not saved
+    session_file: Option<PathBuf>,
     endpoint: String,
 }
 
-impl Drop for Client {
-    fn drop(&mut self) {
-        let result = (|| -> anyhow::Result<()> {
-            let mut file = File::create(&self.session_file)
-                .map_err(|e| anyhow!("failed to open `{}`: {}", self.session_file.display(), e))?;
+/// The client and the cookies of a session file, shared by all the `Client`s in the process so
+/// that a command reuses the connections (multiplexed with HTTP/2 if available) and sees the
+/// cookies set by the others.
+struct Session {
+    client: ReqwestClient,
+    /// Without the cookies and the redirects, for the public pages to be cached
+    anonymous: ReqwestClient,
+    cookie_store: Arc<Jar>,
+    /// The cookies as of the last load or save, to tell the ones changed in this process from
+    /// the ones changed by the other commands running at the same time
+    saved: Mutex<Cookies>,
+    /// The CSRF token of the session seen last in a page
+    csrf_token: Mutex<Option<String>>,
+}
 
-            if let Some(cookie) = self
-                .cookie_store
-                .cookies(&self.endpoint.parse::<Url>().unwrap())
-            {
-                writeln!(&mut file, "{}", cookie.to_str()?)?;
-            }
+pub(crate) type Cookies = Vec<(String, String)>;
 
-            Ok(())
-        })();
+static SESSIONS: Lazy<Mutex<HashMap<Option<PathBuf>, Arc<Session>>>> = Lazy::new(Default::default);
 
-        if let Err(err) = result {
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Err(err) = self.save_session() {
             eprintln!("An error occurred while saving the session: {}", err);
         }
     }
 }
 
-fn load_cookie_store(session_file: &Path, endpoint: &str) -> Result<Jar> {
-    let url = endpoint.parse().unwrap();
-    let jar = reqwest::cookie::Jar::default();
-    let f = File::open(session_file);
+/// The cookies in the session file, in lines of `NAME=value; NAME=value`
+pub(crate) fn read_session_file(session_file: &Path) -> Result<Cookies> {
+    let f = match File::open(session_file) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let mut cookies = vec![];
+    for line in BufReader::new(f).lines() {
+        for (name, value) in parse_cookies(&line?) {
+            upsert(&mut cookies, name, value);
+        }
+    }
+    Ok(cookies)
+}
 
-    if f.is_err() {
-        return Ok(jar);
+/// Writes the cookies to the session file, which should be locked, by renaming a temporary file
+/// to it so it is never seen half-written.
+pub(crate) fn write_session_file(session_file: &Path, cookies: &Cookies) -> Result<()> {
+    let mut content = cookies
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .join("; ");
+    if !content.is_empty() {
+        content += "\n";
+    }
+    let file_name = session_file
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let tmp = session_file.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+    let result = (|| -> io::Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // The session is as good as the password.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, session_file)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
     }
+    result.map_err(|e| anyhow!("failed to write `{}`: {}", session_file.display(), e))
+}
+
+fn parse_cookies(s: &str) -> Cookies {
+    s.split("; ")
+        .filter_map(|cookie| cookie.split_once('='))
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+        .collect()
+}
 
-    for line in BufReader::new(f?).lines() {
-        let v = line?
-            .split("; ")
-            .map(|s| HeaderValue::from_str(s).unwrap())
-            .collect_vec();
-        jar.set_cookies(&mut v.iter(), &url)
+fn upsert(cookies: &mut Cookies, name: String, value: String) {
+    match cookies.iter_mut().find(|(n, _)| *n == name) {
+        Some(cookie) => cookie.1 = value,
+        None => cookies.push((name, value)),
     }
-    Ok(jar)
 }
 
 impl Client {
     pub fn new(session_file: &Path, endpoint: &str) -> Result<Self> {
-        static USER_AGENT: &str = "cargo-atcoder";
+        Self::open(Some(session_file), endpoint)
+    }
 
-        let cookie_store = Arc::new(load_cookie_store(session_file, endpoint)?);
+    /// A client of an API without logging in, e.g. AtCoder Problems.
+    pub fn without_session(endpoint: &str) -> Result<Self> {
+        Self::open(None, endpoint)
+    }
 
-        let client = reqwest::ClientBuilder::new()
-            .cookie_provider(cookie_store.clone())
-            .user_agent(USER_AGENT)
-            .build()?;
+    fn open(session_file: Option<&Path>, endpoint: &str) -> Result<Self> {
+        let session_file = session_file.map(ToOwned::to_owned);
+        let mut sessions = SESSIONS.lock().unwrap();
+        let session = match sessions.get(&session_file) {
+            Some(session) => session.clone(),
+            None => {
+                let cookie_store = Arc::new(Jar::default());
+                let builder = || {
+                    reqwest::ClientBuilder::new()
+                        .user_agent(&settings().user_agent)
+                        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+                        .timeout(settings().timeout)
+                        .local_address(settings().local_address)
+                };
+                let client = builder().cookie_provider(cookie_store.clone()).build()?;
+                let anonymous = builder().redirect(Policy::none()).build()?;
+                let session = Arc::new(Session {
+                    client,
+                    anonymous,
+                    cookie_store,
+                    saved: Mutex::default(),
+                    csrf_token: Mutex::default(),
+                });
+                if let Some(session_file) = &session_file {
+                    let url = endpoint.parse()?;
+                    *session.saved.lock().unwrap() =
+                        session.set_cookies(&read_session_file(session_file)?, &url)?;
+                }
+                sessions.insert(session_file.clone(), session.clone());
+                session
+            }
+        };
 
         Ok(Self {
-            client,
-            cookie_store,
-            session_file: session_file.to_owned(),
+            session,
+            session_file,
             endpoint: endpoint.to_owned(),
         })
     }
 
+    /// Saves the cookies changed in this process, over the ones in the session file which may
+    /// have been changed by the other commands since loaded.
+    ///
+    /// The session file is locked while read and written.
+    fn save_session(&self) -> Result<()> {
+        let session_file = match &self.session_file {
+            Some(session_file) => session_file,
+            None => return Ok(()),
+        };
+        let url = self.endpoint.parse::<Url>()?;
+        let mut saved = self.session.saved.lock().unwrap();
+        let current = self.session.cookies(&url)?;
+        if current == *saved {
+            return Ok(());
+        }
+
+        let _lock = FileLock::exclusive(session_file)?;
+        let mut cookies = read_session_file(session_file)?;
+        cookies.retain(|(name, _)| {
+            !saved.iter().any(|(n, _)| n == name) || current.iter().any(|(n, _)| n == name)
+        });
+        for cookie in &current {
+            if !saved.contains(cookie) {
+                upsert(&mut cookies, cookie.0.clone(), cookie.1.clone());
+            }
+        }
+
+        write_session_file(session_file, &cookies)?;
+
+        *saved = self.session.set_cookies(&cookies, &url)?;
+        Ok(())
+    }
+
+    /// Sets a cookie (e.g. `NAME=value`) for the endpoint.
+    pub fn add_cookie(&self, cookie: &str) -> Result<()> {
+        self.session.cookie_store.set_cookies(
+            &mut [HeaderValue::from_str(cookie)?].iter(),
+            &self.endpoint.parse::<Url>()?,
+        );
+        Ok(())
+    }
+
+    /// The CSRF token of the session, shared by the clients of the same session file
+    pub fn csrf_token(&self) -> Option<String> {
+        self.session.csrf_token.lock().unwrap().clone()
+    }
+
+    pub fn set_csrf_token(&self, token: Option<String>) {
+        *self.session.csrf_token.lock().unwrap() = token;
+    }
+
     pub async fn get(&self, url: &Url) -> Result<String> {
-        let resp = self.client.get(url.clone()).send();
-        Ok(resp.await?.error_for_status()?.text().await?)
+        let req = self.session.client.get(url.clone());
+        Ok(self.send("GET", url, req, false).await?.1)
+    }
+
+    /// Body of the response for `url` without logging in, e.g. for a statement. The page
+    /// fetched before with an `ETag` or a `Last-Modified` is fetched again by a conditional GET,
+    /// and taken from the cache if not modified.
+    ///
+    /// The page is fetched with the session and not cached if it needs logging in, i.e. if
+    /// redirected or rejected without the session.
+    pub async fn get_public(&self, url: &Url) -> Result<String> {
+        let req = self.session.anonymous.get(url.clone());
+        match self.send("GET", url, req, true).await {
+            Ok((status, body)) if status.is_success() => Ok(body),
+            Ok(_) => self.get(url).await,
+            Err(err)
+                if is_http_error(&err, StatusCode::FORBIDDEN)
+                    || is_http_error(&err, StatusCode::NOT_FOUND) =>
+            {
+                self.get(url).await
+            }
+            Err(err) => Err(err),
+        }
     }
 
     pub async fn post_form(&self, url: &Url, form: &[(&str, &str)]) -> Result<String> {
-        let resp = self.client.post(url.clone()).form(form).send();
-        Ok(resp.await?.error_for_status()?.text().await?)
+        let req = self.session.client.post(url.clone()).form(form);
+        Ok(self.send("POST", url, req, false).await?.1)
+    }
+
+    /// Binary body of the response for `url`, e.g. an image.
+    ///
+    /// Not recorded with `--record` since the recordings are text.
+    pub async fn get_bytes(&self, url: &Url) -> Result<Vec<u8>> {
+        if recording::is_replaying() {
+            bail!(
+                "No recorded response for GET {} (binary responses are not recorded)",
+                url
+            );
+        }
+        with_timeouts("GET", url, async {
+            let _permit = throttle().await;
+            let start = Instant::now();
+            let resp = self
+                .session
+                .client
+                .get(url.clone())
+                .send()
+                .await?
+                .error_for_status()?;
+            let status = resp.status();
+            let bytes = resp.bytes().await?;
+            trace!(
+                target: HTTP_TARGET,
+                "GET {} -> {} ({} bytes, {:.0?})",
+                url,
+                status,
+                bytes.len(),
+                start.elapsed(),
+            );
+            Ok(bytes.to_vec())
+        })
+        .await
+    }
+
+    /// Value of the `Date` header in the response for `url`, which is recorded as the body
+    pub async fn date(&self, url: &Url) -> Result<Option<String>> {
+        if let Some(replayed) = recording::replay("HEAD", url) {
+            let (status, date) = replayed?;
+            trace!(target: HTTP_TARGET, "HEAD {} -> {} (replayed)", url, status);
+            if status.is_client_error() || status.is_server_error() {
+                return Err(StatusError {
+                    url: url.clone(),
+                    status,
+                }
+                .into());
+            }
+            return Ok(Some(date).filter(|date| !date.is_empty()));
+        }
+
+        with_timeouts("HEAD", url, async {
+            let _permit = throttle().await;
+            let start = Instant::now();
+            let resp = self
+                .session
+                .client
+                .head(url.clone())
+                .send()
+                .await
+                .inspect_err(|err| trace!(target: HTTP_TARGET, "HEAD {} -> {}", url, err))?;
+            let status = resp.status();
+            trace!(
+                target: HTTP_TARGET,
+                "HEAD {} -> {} ({:.0?})",
+                url,
+                status,
+                start.elapsed(),
+            );
+            if let Err(err) = resp.error_for_status_ref() {
+                recording::record("HEAD", url, status, "")?;
+                return Err(err.into());
+            }
+            let date = resp
+                .headers()
+                .get(reqwest::header::DATE)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned);
+            recording::record("HEAD", url, status, date.as_deref().unwrap_or(""))?;
+            Ok(date)
+        })
+        .await
+    }
+
+    /// Sends the request, with the validators of the cached page if `public`. The page is
+    /// answered by the cache if not modified since it, and cached if it may be.
+    async fn send(
+        &self,
+        method: &str,
+        url: &Url,
+        mut req: RequestBuilder,
+        public: bool,
+    ) -> Result<(StatusCode, String)> {
+        if let Some(replayed) = recording::replay(method, url) {
+            let (status, body) = replayed?;
+            trace!(target: HTTP_TARGET, "{} {} -> {} (replayed)", method, url, status);
+            if status.is_client_error() || status.is_server_error() {
+                return Err(StatusError {
+                    url: url.clone(),
+                    status,
+                }
+                .into());
+            }
+            return Ok((status, body));
+        }
+
+        let cached = if public {
+            cache::load_page(url.as_str())
+        } else {
+            None
+        };
+        if let Some(page) = &cached {
+            if let Some(etag) = &page.etag {
+                req = req.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &page.last_modified {
+                req = req.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        with_timeouts(method, url, async {
+            let _permit = throttle().await;
+            match (trace_response(method, url, req.send()).await, cached) {
+                (Ok((StatusCode::NOT_MODIFIED, _, _)), Some(cached)) => {
+                    // As if fetched, to be replayed without the cache
+                    recording::record(method, url, StatusCode::OK, &cached.body)?;
+                    // Kept as used
+                    cache::save_page(url.as_str(), &cached)?;
+                    Ok((StatusCode::OK, cached.body))
+                }
+                (Ok((status, body, validators)), _) => {
+                    recording::record(method, url, status, &body)?;
+                    if public && status == StatusCode::OK {
+                        if let Some((etag, last_modified)) = validators {
+                            let page = cache::Page {
+                                etag,
+                                last_modified,
+                                body,
+                            };
+                            cache::save_page(url.as_str(), &page)?;
+                            return Ok((status, page.body));
+                        }
+                    }
+                    Ok((status, body))
+                }
+                (Err(err), _) => {
+                    if let Some(status) = err
+                        .downcast_ref::<reqwest::Error>()
+                        .and_then(|e| e.status())
+                    {
+                        recording::record(method, url, status, "")?;
+                    }
+                    Err(err)
+                }
+            }
+        })
+        .await
+    }
+}
+
+impl Session {
+    fn cookies(&self, url: &Url) -> Result<Cookies> {
+        Ok(match self.cookie_store.cookies(url) {
+            Some(cookies) => parse_cookies(cookies.to_str()?),
+            None => vec![],
+        })
+    }
+
+    /// Sets the cookies and returns all the cookies in the jar.
+    fn set_cookies(&self, cookies: &[(String, String)], url: &Url) -> Result<Cookies> {
+        let headers = cookies
+            .iter()
+            .map(|(name, value)| HeaderValue::from_str(&format!("{}={}", name, value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.cookie_store.set_cookies(&mut headers.iter(), url);
+        self.cookies(url)
+    }
+}
+
+/// Error status of a replayed response
+#[derive(Debug)]
+struct StatusError {
+    url: Url,
+    status: StatusCode,
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP status {} for url ({})", self.status, self.url)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// An operation which did not finish within the total timeout
+#[derive(Debug)]
+struct TimeoutError {
+    method: String,
+    url: Url,
+    timeout: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} did not finish in {}s, including the wait for the other requests. Try again, \
+             or wait longer with `http.total_timeout` in the config",
+            self.method,
+            self.url,
+            self.timeout.as_secs(),
+        )
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// The validators of a response, i.e. the `ETag` and the `Last-Modified`, if any of them and if
+/// it may be stored
+type Validators = Option<(Option<String>, Option<String>)>;
+
+// Only the summaries are logged since the bodies and the cookies may contain credentials.
+async fn trace_response(
+    method: &str,
+    url: &Url,
+    resp: impl Future<Output = reqwest::Result<Response>>,
+) -> Result<(StatusCode, String, Validators)> {
+    let start = Instant::now();
+    let resp = resp
+        .await
+        .inspect_err(|err| trace!(target: HTTP_TARGET, "{} {} -> {}", method, url, err))?;
+    let status = resp.status();
+    let resp = resp
+        .error_for_status()
+        .inspect_err(|_| trace!(target: HTTP_TARGET, "{} {} -> {}", method, url, status))?;
+    let final_url = resp.url().clone();
+    let validators = validators(resp.headers());
+    let text = resp.text().await?;
+    trace!(
+        target: HTTP_TARGET,
+        "{} {} -> {}{} ({} bytes, {:.0?})",
+        method,
+        url,
+        status,
+        if final_url != *url {
+            format!(" (redirected to {})", final_url)
+        } else {
+            "".to_owned()
+        },
+        text.len(),
+        start.elapsed(),
+    );
+    Ok((status, text, validators))
+}
+
+fn validators(headers: &HeaderMap) -> Validators {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(ToOwned::to_owned)
+    };
+    if header(CACHE_CONTROL).is_some_and(|v| v.to_lowercase().contains("no-store")) {
+        return None;
+    }
+    match (header(ETAG), header(LAST_MODIFIED)) {
+        (None, None) => None,
+        validators => Some(validators),
     }
 }
 
@@ -94,5 +830,15 @@ pub fn is_http_error(err: &anyhow::Error, status_code: reqwest::StatusCode) -> b
     matches!(
         err.downcast_ref::<reqwest::Error>(),
         Some(err) if err.status() == Some(status_code),
+    ) || matches!(
+        err.downcast_ref::<StatusError>(),
+        Some(err) if err.status == status_code,
     )
 }
+
+/// Whether the request itself failed or got an error status
+pub fn is_network_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.is::<reqwest::Error>() || cause.is::<StatusError>() || cause.is::<TimeoutError>()
+    })
+}
@@ -0,0 +1,194 @@
+//! Translations of the main user-facing messages (the prompts, the test results and the results
+//! of the submissions), selected by the global `--lang` flag or `atcoder.lang` in the config. The
+//! other messages are in English.
+//!
+//! Messages are looked up by [`Msg`] with [`tr!`], which fills `{}` in order like `format!`. To
+//! add a locale, add a variant to [`Lang`] and a table like [`Msg::ja`].
+
+use anyhow::{bail, Result};
+use once_cell::sync::OnceCell;
+use std::{fmt, str::FromStr};
+use structopt::{clap::ArgMatches, StructOpt};
+
+#[derive(StructOpt)]
+pub(crate) struct LangOpt {
+    /// Language of the messages [possible values: en, ja]
+    #[structopt(long, global = true, value_name("LANG"), env = "CARGO_ATCODER_LANG")]
+    lang: Option<Lang>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lang {
+    En,
+    Ja,
+}
+
+impl FromStr for Lang {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "en" => Ok(Lang::En),
+            "ja" => Ok(Lang::Ja),
+            _ => bail!("Unsupported language: `{}` (expected `en` or `ja`)", s),
+        }
+    }
+}
+
+static LANG: OnceCell<Lang> = OnceCell::new();
+
+/// Selects the language by the flag given at any level, or by the config.
+pub(crate) fn init(matches: &[&ArgMatches<'_>], config: Option<&str>) -> Result<()> {
+    let mut lang = config.map(str::parse).transpose()?;
+    for matches in matches {
+        lang = LangOpt::from_clap(matches).lang.or(lang);
+    }
+    let _ = LANG.set(lang.unwrap_or(Lang::En));
+    Ok(())
+}
+
+pub(crate) fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::En)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Msg {
+    Username,
+    Password,
+    InvalidUsername,
+    RememberUsername,
+    LoginSucceeded,
+    LoggedInAs,
+    NotLoggedIn,
+    PleaseLogin,
+    ProblemNotInContest,
+    SampleTestFailed,
+    CodeSizeExceeded,
+    DidNotSubmit,
+    SubmitAnyway,
+    RegisterForContest,
+    ConfirmReset,
+    Passphrase,
+    RepeatPassphrase,
+    PassphraseMismatch,
+    NoSubmissions,
+    RunningTests,
+    TestResultOk,
+    TestResultFailed,
+    CasesSkippedAfterFailure,
+    SubmissionCooldown,
+    SubmissionDuplicate,
+    SubmissionRejected,
+    CsrfRetry,
+    NotRegistered,
+}
+
+impl Msg {
+    fn en(self) -> &'static str {
+        match self {
+            Msg::Username => "Username",
+            Msg::Password => "Password",
+            Msg::InvalidUsername => "A username is 3 to 16 alphanumerics or underscores",
+            Msg::RememberUsername => "Remember the username for the next login?",
+            Msg::LoginSucceeded => "Login succeeded.",
+            Msg::LoggedInAs => "Logged in as {}.",
+            Msg::NotLoggedIn => "Not logged in.",
+            Msg::PleaseLogin => "You are not logged in. Please login first.",
+            Msg::ProblemNotInContest => "Problem `{}` is not contained in this contest",
+            Msg::SampleTestFailed => "Sample test failed. Did not submit.",
+            Msg::CodeSizeExceeded => "Code size limit exceeded. Did not submit.",
+            Msg::DidNotSubmit => "Did not submit.",
+            Msg::SubmitAnyway => "Submit anyway?",
+            Msg::RegisterForContest => "Register for `{}`?",
+            Msg::ConfirmReset => "Delete them?",
+            Msg::Passphrase => "Passphrase",
+            Msg::RepeatPassphrase => "Repeat the passphrase",
+            Msg::PassphraseMismatch => "The passphrases do not match",
+            Msg::NoSubmissions => "No submissions.",
+            Msg::RunningTests => "running {} tests",
+            Msg::TestResultOk => "test_result: {}",
+            Msg::TestResultFailed => "test result: {}. {} passed; {} failed",
+            Msg::CasesSkippedAfterFailure => "{} cases skipped after the first failure",
+            Msg::SubmissionCooldown => {
+                "The submission was rejected for submitting too often. Wait {} before submitting \
+                 again: {}"
+            }
+            Msg::SubmissionDuplicate => {
+                "The submission was rejected since the same code has already been submitted: {}"
+            }
+            Msg::SubmissionRejected => "The submission was rejected: {}",
+            Msg::CsrfRetry => "The CSRF token was rejected. Retrying with a new one",
+            Msg::NotRegistered => {
+                "Not registered for `{}`. Register on {} for the submissions to count"
+            }
+        }
+    }
+
+    fn ja(self) -> &'static str {
+        match self {
+            Msg::Username => "ユーザー名",
+            Msg::Password => "パスワード",
+            Msg::InvalidUsername => "ユーザー名は3〜16文字の英数字またはアンダースコアです",
+            Msg::RememberUsername => "次回のログインのためにユーザー名を保存しますか?",
+            Msg::LoginSucceeded => "ログインしました。",
+            Msg::LoggedInAs => "{} としてログインしています。",
+            Msg::NotLoggedIn => "ログインしていません。",
+            Msg::PleaseLogin => "ログインしていません。先にログインしてください。",
+            Msg::ProblemNotInContest => "問題 `{}` はこのコンテストにありません",
+            Msg::SampleTestFailed => "サンプルのテストに失敗しました。サブミットしませんでした。",
+            Msg::CodeSizeExceeded => "コード長制限を超えています。サブミットしませんでした。",
+            Msg::DidNotSubmit => "サブミットしませんでした。",
+            Msg::SubmitAnyway => "それでもサブミットしますか?",
+            Msg::RegisterForContest => "`{}` に参加登録しますか?",
+            Msg::ConfirmReset => "これらを削除しますか?",
+            Msg::Passphrase => "パスフレーズ",
+            Msg::RepeatPassphrase => "パスフレーズ (確認)",
+            Msg::PassphraseMismatch => "パスフレーズが一致しません",
+            Msg::NoSubmissions => "提出はありません。",
+            Msg::RunningTests => "{} 個のテストを実行します",
+            Msg::TestResultOk => "テスト結果: {}",
+            Msg::TestResultFailed => "テスト結果: {}。{} 個成功、{} 個失敗",
+            Msg::CasesSkippedAfterFailure => "最初の失敗の後の {} 個のケースをスキップしました",
+            Msg::SubmissionCooldown => {
+                "提出間隔が短すぎるため提出が拒否されました。{} 待ってから再度提出してください: {}"
+            }
+            Msg::SubmissionDuplicate => {
+                "同じコードが既に提出されているため提出が拒否されました: {}"
+            }
+            Msg::SubmissionRejected => "提出が拒否されました: {}",
+            Msg::CsrfRetry => "CSRF トークンが拒否されました。新しいトークンで再試行します",
+            Msg::NotRegistered => {
+                "`{}` に参加登録していません。提出を有効にするには {} で参加登録してください"
+            }
+        }
+    }
+
+    pub(crate) fn text(self) -> &'static str {
+        match lang() {
+            Lang::En => self.en(),
+            Lang::Ja => self.ja(),
+        }
+    }
+}
+
+/// Fills `{}` in the message with `args` in order.
+pub(crate) fn format(msg: Msg, args: &[&dyn fmt::Display]) -> String {
+    let mut args = args.iter();
+    let mut parts = msg.text().split("{}");
+    let mut out = parts.next().unwrap_or_default().to_owned();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out += &arg.to_string();
+        }
+        out += part;
+    }
+    out
+}
+
+/// The message in the selected language, with the arguments filled like `format!`
+macro_rules! tr {
+    ($msg:ident $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::format($crate::i18n::Msg::$msg, &[$(&$arg),*])
+    };
+}
+pub(crate) use tr;
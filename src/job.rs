@@ -0,0 +1,65 @@
+//! Windows job objects, used to kill a process together with its descendants.
+
+use std::{io, mem, os::windows::io::AsRawHandle as _, process::Child, ptr};
+use winapi::{
+    shared::minwindef::{DWORD, LPVOID},
+    um::{
+        handleapi::CloseHandle,
+        jobapi2::{
+            AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject, TerminateJobObject,
+        },
+        winnt::{
+            JobObjectExtendedLimitInformation, HANDLE, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        },
+    },
+};
+
+pub(crate) struct Job(HANDLE);
+
+impl Job {
+    /// Creates a job object which kills all the processes in it when closed, and assigns `child`
+    /// to it.
+    pub(crate) fn assign(child: &Child) -> io::Result<Self> {
+        unsafe {
+            let handle = CreateJobObjectW(ptr::null_mut(), ptr::null());
+            if handle.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let job = Job(handle);
+
+            let mut info = mem::zeroed::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            if SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                &mut info as *mut _ as LPVOID,
+                mem::size_of_val(&info) as DWORD,
+            ) == 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+
+            if AssignProcessToJobObject(job.0, child.as_raw_handle() as HANDLE) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(job)
+        }
+    }
+
+    pub(crate) fn terminate(&self) -> io::Result<()> {
+        if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0);
+        }
+    }
+}
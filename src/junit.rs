@@ -0,0 +1,309 @@
+//! JUnit XML reports of the sample tests (`test --report` and `verify --format junit`), for CI
+//! systems and IDEs to show the results. Each problem is a `<testsuite>` and each sample a
+//! `<testcase>`. The cases also keep the times and the failures of the samples for
+//! `verify --html`.
+
+use crate::{atcoder::TestCase, html::escape, CaseStatus};
+use anyhow::{Context as _, Result};
+use std::{fmt::Write as _, fs, path::Path, process::Output, time::Duration};
+
+pub(crate) struct Suite {
+    /// e.g. `abc999.a`
+    pub(crate) name: String,
+    pub(crate) cases: Vec<Case>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Case {
+    /// e.g. `sample 1`
+    pub(crate) name: String,
+    pub(crate) time: Duration,
+    pub(crate) failure: Option<Failure>,
+    /// Why the case is not run, if skipped
+    pub(crate) skipped: Option<String>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Failure {
+    /// The verdict (e.g. `WA`)
+    pub(crate) kind: String,
+    /// Not judged (e.g. the samples could not be fetched), which is an `<error>`
+    pub(crate) error: bool,
+    pub(crate) message: String,
+    /// The diff of the outputs, or the stdout and stderr
+    pub(crate) details: String,
+}
+
+impl Case {
+    /// The case of the sample `case_no` (0-indexed) with the output of the solution
+    pub(crate) fn sample(
+        case_no: usize,
+        status: CaseStatus,
+        time: Duration,
+        test_case: &TestCase,
+        output: &Output,
+        time_limit: Duration,
+    ) -> Self {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let (message, mut details) = match status {
+            CaseStatus::Ac => {
+                return Self {
+                    name: format!("sample {}", case_no + 1),
+                    time,
+                    failure: None,
+                    skipped: None,
+                }
+            }
+            CaseStatus::Wa => (
+                "wrong answer".to_owned(),
+                format!(
+                    "diff (-expected +actual):\n{}",
+                    diff(&test_case.output, &stdout)
+                ),
+            ),
+            CaseStatus::Re => (
+                format!(
+                    "runtime error: exit code: {}",
+                    output.status.code().unwrap_or_default()
+                ),
+                format!("input:\n{}\nstdout:\n{}", test_case.input, stdout),
+            ),
+            CaseStatus::Tle => (
+                format!("time limit exceeded: killed after {:?}", time_limit),
+                format!("stdout:\n{}", stdout),
+            ),
+        };
+        if !stderr.is_empty() {
+            let _ = write!(details, "\nstderr:\n{}", stderr);
+        }
+        Self {
+            name: format!("sample {}", case_no + 1),
+            time,
+            failure: Some(Failure {
+                kind: status.label().to_owned(),
+                error: false,
+                message,
+                details,
+            }),
+            skipped: None,
+        }
+    }
+
+    /// The case which has passed, with only the verdict (e.g. of Library Checker)
+    pub(crate) fn passed(name: String) -> Self {
+        Self::new(name, None, None)
+    }
+
+    /// The case which has failed, with only the verdict (e.g. of Library Checker, or the build)
+    pub(crate) fn failed(name: String, kind: &str, message: &str) -> Self {
+        let failure = Failure {
+            kind: kind.to_owned(),
+            error: false,
+            message: message.to_owned(),
+            details: String::new(),
+        };
+        Self::new(name, Some(failure), None)
+    }
+
+    pub(crate) fn error(name: String, message: String) -> Self {
+        let failure = Failure {
+            kind: "error".to_owned(),
+            error: true,
+            message,
+            details: String::new(),
+        };
+        Self::new(name, Some(failure), None)
+    }
+
+    pub(crate) fn skipped(name: String, message: &str) -> Self {
+        Self::new(name, None, Some(message.to_owned()))
+    }
+
+    fn new(name: String, failure: Option<Failure>, skipped: Option<String>) -> Self {
+        Self {
+            name,
+            time: Duration::default(),
+            failure,
+            skipped,
+        }
+    }
+}
+
+impl Suite {
+    pub(crate) fn new(package: &str, bin: &str) -> Self {
+        Self {
+            name: format!("{}.{}", package, bin),
+            cases: vec![],
+        }
+    }
+
+    fn failures(&self) -> usize {
+        self.count(|case| case.failure.as_ref().is_some_and(|f| !f.error))
+    }
+
+    fn errors(&self) -> usize {
+        self.count(|case| case.failure.as_ref().is_some_and(|f| f.error))
+    }
+
+    fn skipped(&self) -> usize {
+        self.count(|case| case.skipped.is_some())
+    }
+
+    fn count(&self, f: impl Fn(&&Case) -> bool) -> usize {
+        self.cases.iter().filter(f).count()
+    }
+
+    fn time(&self) -> Duration {
+        self.cases.iter().map(|case| case.time).sum()
+    }
+}
+
+pub(crate) fn write(path: &Path, suites: &[Suite]) -> Result<()> {
+    fs::write(path, to_xml(suites)).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub(crate) fn to_xml(suites: &[Suite]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"cargo-atcoder\" tests=\"{}\" failures=\"{}\" errors=\"{}\" \
+         skipped=\"{}\" time=\"{:.3}\">",
+        suites.iter().map(|suite| suite.cases.len()).sum::<usize>(),
+        suites.iter().map(Suite::failures).sum::<usize>(),
+        suites.iter().map(Suite::errors).sum::<usize>(),
+        suites.iter().map(Suite::skipped).sum::<usize>(),
+        suites
+            .iter()
+            .map(Suite::time)
+            .sum::<Duration>()
+            .as_secs_f64(),
+    );
+    for suite in suites {
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" \
+             time=\"{:.3}\">",
+            escape(&suite.name),
+            suite.cases.len(),
+            suite.failures(),
+            suite.errors(),
+            suite.skipped(),
+            suite.time().as_secs_f64(),
+        );
+        for case in &suite.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape(&case.name),
+                escape(&suite.name),
+                case.time.as_secs_f64(),
+            );
+            match (&case.failure, &case.skipped) {
+                (Some(failure), _) => {
+                    let element = if failure.error { "error" } else { "failure" };
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <{0} type=\"{1}\" message=\"{2}\">{3}</{0}>\n    </testcase>",
+                        element,
+                        escape(&failure.kind),
+                        escape(&failure.message),
+                        escape(&failure.details),
+                    );
+                }
+                (None, Some(message)) => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                        escape(message),
+                    );
+                }
+                (None, None) => xml += "/>\n",
+            }
+        }
+        xml += "  </testsuite>\n";
+    }
+    xml += "</testsuites>\n";
+    xml
+}
+
+/// The most cells of the table of the LCS, over which the lines in the middle are all shown as
+/// changed
+const MAX_LCS_CELLS: usize = 1 << 20;
+
+/// Line diff of the outputs, with `-` for the expected lines and `+` for the actual ones
+pub(crate) fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    let same = |e: &str, a: &str| e.trim_end() == a.trim_end();
+
+    // Only the lines between the common prefix and suffix are compared.
+    let prefix = expected
+        .iter()
+        .zip(&actual)
+        .take_while(|(e, a)| same(e, a))
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(e, a)| same(e, a))
+        .count();
+    let (middle_expected, middle_actual) = (
+        &expected[prefix..expected.len() - suffix],
+        &actual[prefix..actual.len() - suffix],
+    );
+
+    let mut diff = String::new();
+    for line in &actual[..prefix] {
+        let _ = writeln!(diff, " {}", line);
+    }
+    if middle_expected.len().saturating_mul(middle_actual.len()) > MAX_LCS_CELLS {
+        for line in middle_expected {
+            let _ = writeln!(diff, "-{}", line);
+        }
+        for line in middle_actual {
+            let _ = writeln!(diff, "+{}", line);
+        }
+    } else {
+        lcs_diff(&mut diff, middle_expected, middle_actual, same);
+    }
+    for line in &actual[actual.len() - suffix..] {
+        let _ = writeln!(diff, " {}", line);
+    }
+    diff
+}
+
+fn lcs_diff(
+    diff: &mut String,
+    expected: &[&str],
+    actual: &[&str],
+    same: impl Fn(&str, &str) -> bool,
+) {
+    // Lengths of the longest common subsequences of the suffixes
+    let mut lcs = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if same(expected[i], actual[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && same(expected[i], actual[j]) {
+            let _ = writeln!(diff, " {}", actual[j]);
+            i += 1;
+            j += 1;
+        } else if j == actual.len() || (i < expected.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            let _ = writeln!(diff, "-{}", expected[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(diff, "+{}", actual[j]);
+            j += 1;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Problems of [Library Checker](https://judge.yosupo.jp) for `verify`.
+//!
+//! The full testcases and the checker are generated by `generate.py` in a clone of
+//! `library-checker-problems`, which needs `python3` and a C++ compiler.
+
+use crate::{cache::cache_dir, config::read_config, execute, i18n::tr, logging, CaseStatus};
+use anyhow::{ensure, Context as _, Result};
+use console::Style;
+use log::info;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+const REPO_URL: &str = "https://github.com/yosupo06/library-checker-problems";
+
+static PROBLEM_URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"https://judge\.yosupo\.jp/problem/([0-9A-Za-z_]+)").unwrap());
+
+/// Name of the problem in the URL (e.g. `unionfind`)
+pub(crate) fn problem_name(url: &str) -> Option<String> {
+    Some(PROBLEM_URL.captures(url)?[1].to_owned())
+}
+
+pub(crate) fn problem_url(name: &str) -> String {
+    format!("https://judge.yosupo.jp/problem/{}", name)
+}
+
+/// A problem with the testcases and the checker generated
+pub(crate) struct Problem {
+    dir: PathBuf,
+    time_limit: Duration,
+}
+
+#[derive(Deserialize)]
+struct Info {
+    timelimit: f64,
+}
+
+// The clone in `[library-checker]` of the config, or in the cache directory
+fn repo() -> Result<PathBuf> {
+    if let Some(repo) = read_config()?.library_checker.and_then(|c| c.repo) {
+        return Ok(repo);
+    }
+    let repo = cache_dir()?.join("library-checker-problems");
+    if !repo.is_dir() {
+        info!("Cloning {} ...", REPO_URL);
+        let status = Command::new("git")
+            .args(["clone", "--depth", "1", REPO_URL])
+            .arg(&repo)
+            .status()
+            .with_context(|| "Failed to execute `git`")?;
+        ensure!(status.success(), "Failed to clone {}", REPO_URL);
+    }
+    Ok(repo)
+}
+
+/// Finds the problem in the clone, generating the testcases and the checker unless done.
+pub(crate) fn prepare(name: &str) -> Result<Problem> {
+    let repo = repo()?;
+    let dir = fs::read_dir(&repo)
+        .with_context(|| format!("Failed to read {}", repo.display()))?
+        .filter_map(Result::ok)
+        .map(|category| category.path().join(name))
+        .find(|dir| dir.join("info.toml").is_file())
+        .with_context(|| format!("No problem `{}` in {}", name, repo.display()))?;
+
+    let problem = Problem {
+        time_limit: Duration::from_secs_f64(
+            toml::from_str::<Info>(&fs::read_to_string(dir.join("info.toml"))?)?.timelimit,
+        ),
+        dir,
+    };
+    if !problem.checker().is_file() || !problem.dir.join("in").is_dir() {
+        info!("Generating the testcases of `{}` ...", name);
+        let status = Command::new("python3")
+            .args(["generate.py", "-p", name])
+            .current_dir(&repo)
+            .status()
+            .with_context(|| "Failed to execute `python3`")?;
+        ensure!(
+            status.success(),
+            "Failed to generate the testcases of `{}`",
+            name
+        );
+    }
+    Ok(problem)
+}
+
+impl Problem {
+    fn checker(&self) -> PathBuf {
+        self.dir
+            .join("checker")
+            .with_extension(env::consts::EXE_EXTENSION)
+    }
+
+    /// Runs the binary on all the testcases, judging the outputs by the checker.
+    pub(crate) fn run(&self, binary: &Path) -> Result<Vec<(usize, CaseStatus)>> {
+        let mut inputs = fs::read_dir(self.dir.join("in"))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        inputs.sort();
+        // The checker reads the output from a file.
+        let actual = cache_dir()?.join("library-checker-output.txt");
+
+        let mut results = vec![];
+        for (i, input) in inputs.iter().enumerate() {
+            let case = input.file_stem().unwrap_or_default().to_string_lossy();
+            let answer = self.dir.join("out").join(&*case).with_extension("out");
+            let pb = logging::spinner(format!("test {} ...", case));
+            let (output, timed_out) =
+                execute(Command::new(binary), &fs::read(input)?, self.time_limit)?;
+            let status = if timed_out {
+                CaseStatus::Tle
+            } else if !output.status.success() {
+                CaseStatus::Re
+            } else {
+                fs::write(&actual, &output.stdout)?;
+                let checked = Command::new(self.checker())
+                    .arg(input)
+                    .arg(&actual)
+                    .arg(&answer)
+                    .output()
+                    .with_context(|| format!("Failed to execute {}", self.checker().display()))?;
+                if checked.status.success() {
+                    CaseStatus::Ac
+                } else {
+                    CaseStatus::Wa
+                }
+            };
+            pb.finish_and_clear();
+            info!(
+                "test {} ... {}",
+                case,
+                status.style().apply_to(status.label())
+            );
+            results.push((i, status));
+        }
+
+        let failed = results
+            .iter()
+            .filter(|&&(_, status)| status != CaseStatus::Ac)
+            .count();
+        if failed == 0 {
+            info!("{}", tr!(TestResultOk, Style::new().green().apply_to("ok")));
+        } else {
+            info!(
+                "{}",
+                tr!(
+                    TestResultFailed,
+                    Style::new().red().apply_to("FAILED"),
+                    results.len() - failed,
+                    failed
+                )
+            );
+        }
+        Ok(results)
+    }
+}
@@ -0,0 +1,81 @@
+//! Advisory locks of files, held by the commands running at the same time (e.g. `watch` in a
+//! terminal and `submit` in another) while they touch the shared files.
+
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// An exclusive lock of a file, released when dropped.
+pub(crate) struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Blocks until locking `<path>.lock`, which is created if missing. The file itself is not
+    /// locked since it may be replaced by renaming another file to it.
+    pub(crate) fn exclusive(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path(path))?;
+        lock(&file)?;
+        Ok(Self { _file: file })
+    }
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".lock");
+    path.into()
+}
+
+#[cfg(unix)]
+fn lock(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd as _;
+
+    loop {
+        // SAFETY: the descriptor is valid while `file` lives.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+#[cfg(windows)]
+fn lock(file: &File) -> io::Result<()> {
+    use std::{mem, os::windows::io::AsRawHandle as _};
+    use winapi::um::{
+        fileapi::LockFileEx,
+        minwinbase::{LOCKFILE_EXCLUSIVE_LOCK, OVERLAPPED},
+        winnt::HANDLE,
+    };
+
+    // SAFETY: the handle is valid while `file` lives, and `overlapped` outlives the call.
+    unsafe {
+        let mut overlapped = mem::zeroed::<OVERLAPPED>();
+        if LockFileEx(
+            file.as_raw_handle() as HANDLE,
+            LOCKFILE_EXCLUSIVE_LOCK,
+            0,
+            !0,
+            !0,
+            &mut overlapped,
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn lock(_: &File) -> io::Result<()> {
+    Ok(())
+}
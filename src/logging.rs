@@ -0,0 +1,157 @@
+//! Logging with the verbosity given by the global `-v`/`--quiet` flags.
+//!
+//! `info!` goes to stdout like the results of the commands, and the others go to stderr.
+
+use console::Style;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::atomic::{AtomicBool, Ordering};
+use structopt::{clap::ArgMatches, StructOpt};
+
+#[derive(StructOpt)]
+pub(crate) struct LogOpt {
+    /// Use verbose output (-vv for more)
+    #[structopt(short, long, parse(from_occurrences), global = true)]
+    verbose: u8,
+    /// Print only warnings and errors
+    #[structopt(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Dump summaries of HTTP requests and responses
+    #[structopt(long, global = true)]
+    trace_http: bool,
+}
+
+/// Target of the logs of HTTP requests
+pub(crate) const HTTP_TARGET: &str = "cargo_atcoder::http";
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+struct Logger {
+    level: LevelFilter,
+    trace_http: bool,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if !metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            // Logs from the dependencies are too noisy.
+            return metadata.level() <= Level::Error;
+        }
+        metadata.level() <= self.level || self.trace_http && metadata.target() == HTTP_TARGET
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let (label, style) = match record.level() {
+            Level::Info => {
+                println!("{}", record.args());
+                return;
+            }
+            Level::Error => ("error", Style::new().red().bold()),
+            Level::Warn => ("warning", Style::new().yellow().bold()),
+            Level::Debug => ("debug", Style::new().cyan()),
+            Level::Trace => ("trace", Style::new().dim()),
+        };
+        eprintln!("{}: {}", style.apply_to(label).for_stderr(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+pub(crate) fn init(matches: &[&ArgMatches<'_>]) {
+    #[cfg(windows)]
+    enable_virtual_terminal();
+
+    let mut opt = LogOpt {
+        verbose: 0,
+        quiet: false,
+        trace_http: false,
+    };
+    for matches in matches {
+        let sub = LogOpt::from_clap(matches);
+        opt.verbose += sub.verbose;
+        opt.quiet |= sub.quiet;
+        opt.trace_http |= sub.trace_http;
+    }
+
+    let level = match (opt.quiet, opt.verbose) {
+        (true, _) => LevelFilter::Warn,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    QUIET.store(opt.quiet, Ordering::Relaxed);
+
+    let logger = Logger {
+        level,
+        trace_http: opt.trace_http,
+    };
+    log::set_max_level(if opt.trace_http {
+        LevelFilter::Trace
+    } else {
+        level
+    });
+    log::set_boxed_logger(Box::new(logger)).expect("should be called only once");
+}
+
+/// Turns on the escape sequences of the colors in the consoles of Windows 10 and later, or turns
+/// off the colors if not supported (e.g. in the legacy console).
+#[cfg(windows)]
+fn enable_virtual_terminal() {
+    use winapi::um::{
+        consoleapi::{GetConsoleMode, SetConsoleMode},
+        processenv::GetStdHandle,
+        winbase::{STD_ERROR_HANDLE, STD_OUTPUT_HANDLE},
+        wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+    };
+
+    for (std_handle, set_colors_enabled) in [
+        (STD_OUTPUT_HANDLE, console::set_colors_enabled as fn(bool)),
+        (STD_ERROR_HANDLE, console::set_colors_enabled_stderr),
+    ] {
+        unsafe {
+            let handle = GetStdHandle(std_handle);
+            let mut mode = 0;
+            // Not a console, e.g. redirected
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                continue;
+            }
+            if SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) == 0 {
+                set_colors_enabled(false);
+            }
+        }
+    }
+}
+
+/// Whether `-v` is given.
+pub(crate) fn verbose() -> bool {
+    log::log_enabled!(Level::Debug)
+}
+
+/// Progress bar with `len` steps, hidden with `--quiet`.
+pub(crate) fn progress_bar(len: u64, prefix: &'static str) -> ProgressBar {
+    if QUIET.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new(len);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{prefix:>12.cyan.bold} [{bar:25}] {pos}/{len}")
+            .progress_chars("=> "),
+    );
+    pb.set_prefix(prefix);
+    pb
+}
+
+/// Spinner shown while a long step runs, hidden with `--quiet`.
+pub(crate) fn spinner(msg: String) -> ProgressBar {
+    if QUIET.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new_spinner();
+    pb.set_message(msg);
+    pb.enable_steady_tick(100);
+    pb
+}
@@ -1,14 +1,23 @@
 use std::{
-    env, fs,
-    io::Write,
+    collections::BTreeMap,
+    env,
+    ffi::OsStr,
+    fs,
+    io::{self, Read, Write},
+    iter, mem,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
+    process::{self, Command, Output, Stdio},
+    slice, thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, ensure, Context as _, Result};
 use bytesize::ByteSize;
 use cargo_metadata::{Metadata, Package, Target};
 use console::Style;
+use futures::{stream, StreamExt as _};
+use itertools::Itertools as _;
+use log::{info, warn};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use sha2::digest::Digest;
@@ -16,39 +25,104 @@ use structopt::StructOpt;
 
 use crate::metadata::{MetadataExt as _, PackageExt as _};
 
+mod ahc;
 mod atcoder;
+mod atcoder_problems;
+mod backtrace;
+mod build;
+mod cache;
+mod ci;
+mod complexity;
 mod config;
+mod daily;
+mod doctor;
+mod editorial;
+mod explain;
+mod html;
 mod http;
+mod i18n;
+#[cfg(windows)]
+mod job;
+mod junit;
+mod library_checker;
+mod lock;
+mod logging;
 mod metadata;
+mod minify;
+mod note;
+mod paths;
+mod practice;
+mod prefetch;
+mod preprocess;
+mod print;
+mod project;
+mod race;
+mod rating;
+mod recording;
+mod reset;
+mod review;
+mod selectors;
+mod self_update;
+mod session;
+mod shutdown;
+mod slow_io;
+mod stack;
+mod statement;
+mod stats;
+mod stress;
+mod table;
+mod template;
+mod testcase;
+mod timer;
+mod verify;
 
 #[cfg(feature = "watch")]
 mod watch;
+mod workspace;
 
 use atcoder::*;
 use config::{read_config, read_config_preserving, Config};
+use i18n::tr;
 
 fn session_file() -> Result<PathBuf> {
-    let dir = if let Some(dir) = env::var_os("CARGO_ATCODER_TEST_CACHE_DIR") {
-        dir.into()
-    } else {
-        dirs::cache_dir()
-            .with_context(|| "failed to get cache dir")?
-            .join("cargo-atcoder")
-    };
+    Ok(paths::state_dir()?.join("session.txt"))
+}
+
+// The username remembered for the next `login`. The password is never saved.
+fn username_file() -> Result<PathBuf> {
+    Ok(paths::state_dir()?.join("username.txt"))
+}
 
-    if !dir.is_dir() {
-        if dir.exists() {
-            bail!("{} is not directory", dir.display());
+/// The contest info, asking to register first if the problems are shown only to the registered
+/// users
+pub(crate) async fn contest_info_or_register(
+    atc: &AtCoder,
+    contest_id: &str,
+) -> Result<ContestInfo> {
+    match atc.contest_info(contest_id).await {
+        Err(err) if err.downcast_ref::<Unregistered>().is_some() => {
+            if !confirm_registration(contest_id)? {
+                return Err(err);
+            }
+            atc.register(contest_id).await?;
+            atc.contest_info(contest_id).await
         }
-        fs::create_dir_all(&dir)?;
+        info => info,
     }
+}
 
-    Ok(dir.join("session.txt"))
+fn confirm_registration(contest_id: &str) -> Result<bool> {
+    let _paused = timer::pause();
+    Ok(ci::interactive()
+        && dialoguer::Confirm::new()
+            .with_prompt(tr!(RegisterForContest, contest_id))
+            .default(true)
+            .interact()?)
 }
 
 #[derive(StructOpt)]
 struct NewOpt {
-    /// Contest ID (e.g. abc123)
+    /// Contest ID (e.g. abc123), URL of the contest, or abc, arc, agc or ahc for the latest one
     contest_id: String,
 
     /// Create src/bin/<NAME>.rs without retrieving actual problem IDs
@@ -58,98 +132,463 @@ struct NewOpt {
     /// Skip warming-up after creating project.
     #[structopt(long)]
     skip_warmup: bool,
+
+    /// Fetch the statements, the editorials and the submission form into the cache while
+    /// warming up (default: `project.prefetch` in the config)
+    #[structopt(long)]
+    prefetch: bool,
+
+    /// Overwrite the edited bins (saved to `<bin>.rs.orig`, or `<bin>.rs.orig.1` and so on)
+    /// and the manifest when the project already exists
+    #[structopt(long)]
+    force: bool,
+
+    /// Directory of the project (default: `project.dir` in the config, or the contest ID)
+    #[structopt(long, value_name("PATH"))]
+    dir: Option<PathBuf>,
 }
 
-async fn new_project(opt: NewOpt) -> Result<()> {
+async fn new_project(mut opt: NewOpt) -> Result<()> {
     let config = read_config()?;
 
+    opt.contest_id = match parse_contest_spec(&opt.contest_id)? {
+        ContestSpec::Id(id) => id,
+        ContestSpec::Latest(series) => {
+            let atc = AtCoder::new(&session_file()?)?;
+            let id = atc
+                .latest_contest(&series)
+                .await?
+                .with_context(|| format!("No recent contests of `{}` found", series))?;
+            info!("The latest contest of `{}` is `{}`", series, id);
+            id
+        }
+    };
+
+    // The task pages are available only if the problem list is.
+    let mut contest = None;
+    let mut provisional = false;
+
     let bins = if !opt.bins.is_empty() {
         opt.bins
     } else {
         let atc = AtCoder::new(&session_file()?)?;
 
-        match atc.contest_info(&opt.contest_id).await {
-            Ok(info) => info.problem_ids_lowercase(),
-            Err(err) if http::is_http_error(&err, reqwest::StatusCode::NOT_FOUND) => atc
-                .problem_ids_from_score_table(&opt.contest_id)
-                .await?
-                .map(|ss| ss.iter().map(|s| s.to_lowercase()).collect())
-                .with_context(|| {
-                    err.context("could not find problem names. please specify names with `--bins`")
-                })?,
+        match contest_info_or_register(&atc, &opt.contest_id).await {
+            Ok(info) => {
+                // The problems may be public to the ones not registered, who would find out
+                // after the start that the submissions are not counted. Not asked for the
+                // unrated ones.
+                if info.registrable() && info.rated() != Some(false) {
+                    if confirm_registration(&opt.contest_id)? {
+                        atc.register(&opt.contest_id).await?;
+                    } else {
+                        warn!(
+                            "{}",
+                            tr!(
+                                NotRegistered,
+                                opt.contest_id,
+                                page_url(&format!("/contests/{}", opt.contest_id)),
+                            )
+                        );
+                    }
+                }
+                let bins = info.problem_ids_lowercase();
+                contest = Some((atc, info));
+                bins
+            }
+            Err(err) if http::is_http_error(&err, reqwest::StatusCode::NOT_FOUND) => {
+                // Reconciled in `test` and `submit` once the contest starts
+                provisional = true;
+                let bins = match atc.problem_ids_from_score_table(&opt.contest_id).await? {
+                    Some(ids) => {
+                        info!("The problem list is not available yet. Using the score table.");
+                        ids.iter().map(|s| s.to_lowercase()).collect()
+                    }
+                    None => guess_problem_ids(&opt.contest_id)
+                        .inspect(|ids| {
+                            info!(
+                                "The problem list is not available yet. Assuming problems {}.",
+                                ids.join(", "),
+                            )
+                        })
+                        .with_context(|| {
+                            err.context(
+                                "could not find problem names. please specify names with `--bins`",
+                            )
+                        })?,
+                };
+                bins
+            }
             Err(err) => Err(err)?,
         }
     };
 
-    let dir = Path::new(&opt.contest_id);
-    if dir.is_dir() || dir.is_file() {
-        bail!("Directory {} already exists", dir.display());
-    }
+    let dir = match &opt.dir {
+        Some(dir) => dir.clone(),
+        None => {
+            let date = contest
+                .as_ref()
+                .and_then(|(_, info)| info.start_time())
+                .map_or_else(|| chrono::Local::now().naive_local(), |t| t.naive_local())
+                .date();
+            let pattern = config.project.dir.as_deref().unwrap_or("{contest_id}");
+            project::dir_name(pattern, &opt.contest_id, date)?.into()
+        }
+    };
+    let dir = dir.as_path();
+
+    // Rerunning `new` resumes the project, e.g. after it has failed midway.
+    let resuming = dir.join("Cargo.toml").is_file();
+    if resuming {
+        let manifest =
+            fs::read_to_string(dir.join("Cargo.toml"))?.parse::<toml_edit::Document>()?;
+        let name = manifest["package"]["name"].as_str().unwrap_or_default();
+        ensure!(
+            name == opt.contest_id,
+            "Directory {} already has the project of `{}`, not `{}`",
+            dir.display(),
+            name,
+            opt.contest_id
+        );
+        info!(
+            "Directory {} already exists. Creating only what is missing.",
+            dir.display()
+        );
+    } else {
+        if dir.is_dir() || dir.is_file() {
+            bail!("Directory {} already exists", dir.display());
+        }
 
-    let stat = Command::new("cargo")
-        .arg("new")
-        .arg(&opt.contest_id)
-        .status()?;
-    if !stat.success() {
-        bail!("Failed to create project: {}", &opt.contest_id);
+        // `cargo new` adds the package to the members of the nearest workspace even if excluded,
+        // which is only done for the ones not excluded if opted in with `project.workspace`.
+        let untouched = match project::enclosing_workspaces(&env::current_dir()?.join(dir)).next() {
+            Some((path, excluded)) if excluded || !config.project.workspace => {
+                fs::read(&path).ok().map(|manifest| (manifest, path))
+            }
+            _ => None,
+        };
+        // The package is named after the contest, whatever the directory is.
+        let stat = Command::new("cargo")
+            .arg("new")
+            .arg("--name")
+            .arg(&opt.contest_id)
+            .arg(dir)
+            .status()?;
+        if let Some((manifest, path)) = untouched {
+            fs::write(&path, manifest)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        if !stat.success() {
+            bail!("Failed to create project: {}", &opt.contest_id);
+        }
+        fs::remove_file(dir.join("src").join("main.rs"))?;
     }
 
     if let Some(rustc_version) = &config.project.rustc_version {
-        fs::write(dir.join("rust-toolchain"), rustc_version)?;
+        if opt.force || !dir.join("rust-toolchain").exists() {
+            fs::write(dir.join("rust-toolchain"), rustc_version)?;
+        }
     }
 
-    fs::remove_file(dir.join("src").join("main.rs"))?;
-    fs::create_dir(dir.join("src").join("bin"))?;
+    fs::create_dir_all(dir.join("src").join("bin"))?;
 
-    for bin in bins {
-        fs::write(
-            dir.join("src").join("bin").join(bin).with_extension("rs"),
-            &config.project.template,
-        )?;
+    for bin in &bins {
+        let path = dir.join("src").join("bin").join(bin).with_extension("rs");
+        let template = config.project.template_for(bin);
+        match fs::read_to_string(&path) {
+            Ok(source) if source == template => {}
+            Ok(_) if !opt.force => {
+                info!("Keeping {}, which has been edited", path.display());
+            }
+            Ok(_) => {
+                // Never discards the solution, nor the one saved by the previous `--force`.
+                let backup = iter::once("rs.orig".to_owned())
+                    .chain((1..).map(|i| format!("rs.orig.{}", i)))
+                    .map(|extension| path.with_extension(extension))
+                    .find(|backup| !backup.exists())
+                    .unwrap();
+                fs::rename(&path, &backup)?;
+                fs::write(&path, template)?;
+                info!(
+                    "Overwrote {}, saving the edited one to {}",
+                    path.display(),
+                    backup.display()
+                );
+            }
+            Err(_) => fs::write(&path, template)?,
+        }
+    }
+    stats::record_created(&opt.contest_id, &bins);
+
+    // In a workspace (a monorepo of the contests) if opted in, the profiles are of the root.
+    let workspace = project::workspace_manifest(&dir.canonicalize()?);
+    if let (Some(workspace), false) = (&workspace, config.project.workspace) {
+        warn!(
+            "{} is in the workspace of {}. Set `project.workspace = true` in the config to add \
+             it to the members, or add it to `workspace.exclude`",
+            dir.display(),
+            workspace.display(),
+        );
     }
+    let workspace = workspace.filter(|_| config.project.workspace);
 
     let toml_file = dir.join("Cargo.toml");
     let mut manifest = fs::read_to_string(&toml_file)?.parse::<toml_edit::Document>()?;
-    let conf_preserved = read_config_preserving()?;
-    manifest["dependencies"] = conf_preserved["dependencies"].clone();
-    manifest["dev-dependencies"] = conf_preserved["dev-dependencies"].clone();
-    manifest["profile"] = toml_edit::Item::Table({
-        let mut tbl = toml_edit::Table::new();
-        tbl.set_implicit(true);
-        tbl
-    });
-    manifest["profile"]["release"] = conf_preserved["profile"]["release"].clone();
+    // The profile (or the dependencies in a workspace) is written last, so the manifest is
+    // complete if it is there.
+    let complete = match &workspace {
+        Some(_) => manifest["dependencies"]
+            .as_table()
+            .is_some_and(|deps| !deps.is_empty()),
+        None => manifest.as_table().contains_key("profile"),
+    };
+    if opt.force || !complete {
+        let conf_preserved = read_config_preserving()?;
+        manifest["dependencies"] = conf_preserved["dependencies"].clone();
+        manifest["dev-dependencies"] = conf_preserved["dev-dependencies"].clone();
+        let mut profiles = toml_edit::Item::Table({
+            let mut tbl = toml_edit::Table::new();
+            tbl.set_implicit(true);
+            tbl
+        });
+        profiles["release"] = conf_preserved["profile"]["release"].clone();
+        profiles[build::LOCAL_PROFILE] = conf_preserved["profile"][build::LOCAL_PROFILE].clone();
+        match &workspace {
+            Some(workspace) => {
+                if project::add_workspace_member(workspace, &dir.canonicalize()?, &profiles)? {
+                    info!(
+                        "Added {} to the members of {}",
+                        dir.display(),
+                        workspace.display()
+                    );
+                }
+            }
+            None => manifest["profile"] = profiles,
+        }
+    }
+    if provisional {
+        project::mark_provisional_bins(&mut manifest);
+    }
 
     fs::write(toml_file, manifest.to_string())?;
 
-    println!("Creating project done.");
+    info!("Creating project done.");
+
+    if let Some((atc, contest_info)) = &contest {
+        fetch_samples(atc, &opt.contest_id, contest_info).await;
+        if config.project.sample_files {
+            for problem in contest_info.problems() {
+                if project::has_sample_files(dir, &problem.id) {
+                    continue;
+                }
+                if let Some(test_cases) = cache::load_samples(&opt.contest_id, &problem.id) {
+                    project::save_sample_files(dir, &problem.id, &test_cases)?;
+                }
+            }
+        }
+    }
 
+    let prefetch = {
+        let (enabled, contest_id) = (opt.prefetch || config.project.prefetch, &opt.contest_id);
+        let contest = &contest;
+        async move {
+            if let (true, Some((atc, contest_info))) = (enabled, contest) {
+                prefetch::prefetch(atc, contest_id, contest_info).await;
+            }
+        }
+    };
     if !opt.skip_warmup {
-        let metadata = metadata::cargo_metadata(None, format!("./{}", opt.contest_id).as_ref())?;
-        warmup_for(&metadata, Some(&[&opt.contest_id]))?;
-        println!("Warming up done.");
+        let metadata = metadata::cargo_metadata(None, dir)?;
+        let contest_id = opt.contest_id.clone();
+        let warmup =
+            tokio::task::spawn_blocking(move || warmup_for(&metadata, Some(&[&contest_id])));
+        let ((), warmed_up) = tokio::join!(prefetch, warmup);
+        warmed_up??;
+        info!("Warming up done.");
+    } else {
+        prefetch.await;
     }
 
     Ok(())
 }
 
+// Capped by `http.max_concurrency` too
+const FETCH_CONCURRENCY: usize = 3;
+
+/// Fetches the sample cases of the problems not cached yet, a few pages at a time.
+///
+/// Failures are only reported since `test` fetches the pages again.
+async fn fetch_samples(atc: &AtCoder, contest_id: &str, contest_info: &ContestInfo) {
+    let problems = contest_info
+        .problems()
+        .iter()
+        .filter(|problem| cache::load_samples(contest_id, &problem.id).is_none())
+        .collect::<Vec<_>>();
+    if problems.is_empty() {
+        return;
+    }
+
+    let pb = logging::progress_bar(problems.len() as u64, "Fetching");
+
+    let results = stream::iter(&problems)
+        .map(|problem| {
+            let pb = &pb;
+            async move {
+                let result = match fetch_test_cases(atc, contest_id, problem).await {
+                    Ok(test_cases) => cache::save_samples(contest_id, &problem.id, &test_cases),
+                    Err(err) => Err(err),
+                };
+                pb.inc(1);
+                (problem, result)
+            }
+        })
+        .buffer_unordered(FETCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+
+    for (problem, result) in results {
+        if let Err(err) = result {
+            warn!("failed to fetch the samples of `{}`: {}", problem.id, err);
+        }
+    }
+}
+
+/// Sample cases of `problem`, read from the sample files of the project if any, or from the
+/// cache if they have been fetched.
+async fn sample_cases(
+    atc: &AtCoder,
+    package: &Package,
+    problem: &Problem,
+) -> Result<Vec<TestCase>> {
+    let dir = project::package_dir(package);
+    if let Some(test_cases) = project::load_sample_files(dir, &problem.id)? {
+        return Ok(test_cases);
+    }
+    let contest_id = &project::routed_task(package, &problem.id)
+        .map_or_else(|| package.name.clone(), |(contest_id, _)| contest_id);
+    let test_cases = match cache::load_samples(contest_id, &problem.id) {
+        Some(test_cases) => test_cases,
+        None => {
+            let test_cases = fetch_test_cases(atc, contest_id, problem).await?;
+            cache::save_samples(contest_id, &problem.id, &test_cases)?;
+            test_cases
+        }
+    };
+    if read_config()?.project.sample_files {
+        project::save_sample_files(dir, &problem.id, &test_cases)?;
+    }
+    Ok(test_cases)
+}
+
+/// Scrapes the sample cases of `problem`, saving the page for `testcase import` if it fails.
+async fn fetch_test_cases(
+    atc: &AtCoder,
+    contest_id: &str,
+    problem: &Problem,
+) -> Result<Vec<TestCase>> {
+    let html = atc.problem_page(&problem.url).await?;
+    parse_test_cases(&html)
+        .map_err(|err| testcase::scrape_failure(contest_id, &problem.id, &html, err))
+}
+
+/// Creates the bins of the problems that were not known in `new`, once the problem list is
+/// available. Returns whether the project is changed, i.e. the metadata is to be reloaded.
+fn reconcile_bins(package: &Package, contest_info: &ContestInfo) -> Result<bool> {
+    if !project::has_provisional_bins(package) {
+        return Ok(false);
+    }
+    let config = read_config()?;
+    let bins = package
+        .solution_bins(&config.project.scratch_bins)
+        .into_iter()
+        .map(|t| t.name.clone())
+        .collect::<Vec<_>>();
+    let problem_ids = contest_info.problem_ids_lowercase();
+
+    let bin_dir = project::package_dir(package).join("src").join("bin");
+    for problem_id in problem_ids.iter().filter(|id| !bins.contains(id)) {
+        let path = bin_dir.join(problem_id).with_extension("rs");
+        fs::write(&path, config.project.template_for(problem_id))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        info!("Created {} for problem `{}`", path.display(), problem_id);
+        stats::record_created(&package.name, slice::from_ref(problem_id));
+    }
+    let is_variant = |bin: &str| {
+        bin.split_once('_')
+            .is_some_and(|(problem_id, _)| problem_ids.iter().any(|id| id == problem_id))
+    };
+    for bin in bins
+        .iter()
+        .filter(|bin| !problem_ids.contains(bin) && !is_variant(bin))
+    {
+        warn!("`{}` is not a problem in this contest", bin);
+    }
+    project::clear_provisional_bins(package)?;
+    Ok(true)
+}
+
 async fn login() -> Result<()> {
-    let username = dialoguer::Input::<String>::new()
-        .with_prompt("Username")
-        .interact()?;
+    if let Some((username, password)) = env_credentials() {
+        let atc = AtCoder::new(&session_file()?)?;
+        atc.login(&username, &password).await?;
+        info!("{}", tr!(LoginSucceeded));
+        return Ok(());
+    }
+    if !ci::interactive() {
+        return Err(anyhow::Error::new(ci::Failure::Auth)
+            .context("Set `ATCODER_USERNAME` and `ATCODER_PASSWORD` to login without the prompt"));
+    }
+
+    let remembered = fs::read_to_string(username_file()?)
+        .ok()
+        .map(|s| s.trim().to_owned())
+        .filter(|s| !s.is_empty());
+
+    let mut input = dialoguer::Input::<String>::new();
+    input
+        .with_prompt(tr!(Username))
+        .validate_with(|username: &String| validate_username(username));
+    if let Some(username) = &remembered {
+        input.default(username.clone());
+    }
+    let username = input.interact()?;
 
+    // Empty passwords are asked again.
     let password = dialoguer::Password::new()
-        .with_prompt("Password")
+        .with_prompt(tr!(Password))
         .interact()?;
 
     let atc = AtCoder::new(&session_file()?)?;
     atc.login(&username, &password).await?;
 
-    println!("Login succeeded.");
+    info!("{}", tr!(LoginSucceeded));
+
+    if remembered.as_ref() != Some(&username)
+        && dialoguer::Confirm::new()
+            .with_prompt(tr!(RememberUsername))
+            .default(true)
+            .interact()?
+    {
+        fs::write(username_file()?, &username)?;
+    }
 
     Ok(())
 }
 
+// AtCoder IDs are 3 to 16 alphanumerics or underscores.
+fn validate_username(username: &str) -> Result<(), String> {
+    let valid = (3..=16).contains(&username.len())
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(tr!(InvalidUsername))
+    }
+}
+
 fn clear_session() -> Result<()> {
     let path = session_file()?;
     if path.is_file() {
@@ -161,12 +600,13 @@ fn clear_session() -> Result<()> {
 #[derive(StructOpt)]
 struct TestOpt {
     /// Problem ID (e.g. a, b, ...)
-    problem_id: String,
+    #[structopt(required_unless("all"))]
+    problem_id: Option<String>,
     /// Specify case number to test (e.g. 1, 2, ...)
     #[structopt(conflicts_with = "custom")]
     case_num: Vec<usize>,
-    /// [cargo] Package with the target to test
-    #[structopt(short, long, value_name("SPEC"))]
+    /// [cargo] Package with the target to test, or the contest in a workspace of the contests
+    #[structopt(short, long, visible_alias("contest"), value_name("SPEC"))]
     package: Option<String>,
     /// [cargo] Path to Cargo.toml
     #[structopt(long, value_name("PATH"))]
@@ -177,12 +617,47 @@ struct TestOpt {
     /// Submit if test passed
     #[structopt(short, long)]
     submit: bool,
+    /// Submit without confirmation after the contest has ended, with `--submit`
+    #[structopt(long, requires("submit"))]
+    practice: bool,
     /// [cargo build] Use --release flag to compile
     #[structopt(long)]
     release: bool,
-    /// Use verbose output
-    #[structopt(short, long)]
-    verbose: bool,
+    /// Test all problems and show a summary
+    #[structopt(long, conflicts_with_all(&["problem-id", "case-num", "custom", "submit"]))]
+    all: bool,
+    /// Test only the specified case (can be repeated)
+    #[structopt(
+        long = "case",
+        value_name("N"),
+        number_of_values(1),
+        conflicts_with_all(&["custom", "all"])
+    )]
+    cases: Vec<usize>,
+    /// Skip the specified case (can be repeated)
+    #[structopt(
+        long,
+        value_name("N"),
+        number_of_values(1),
+        conflicts_with_all(&["custom", "all"])
+    )]
+    skip: Vec<usize>,
+    /// Test `src/bin/<problem-id>_<variant>.rs` instead (e.g. `v2` for `e_v2.rs`)
+    #[structopt(long, value_name("NAME"), conflicts_with = "all")]
+    variant: Option<String>,
+    /// Write the results as JUnit XML to the file (e.g. `junit.xml`)
+    #[structopt(long, value_name("PATH"), conflicts_with = "custom")]
+    report: Option<PathBuf>,
+    /// Run the cases that failed last time first, and stop at the first failing case
+    #[structopt(long, conflicts_with_all(&["custom", "all"]))]
+    fail_fast: bool,
+    /// Test again whenever the source or the library changes (needs the `watch` feature)
+    #[structopt(long, conflicts_with_all(&["custom", "all", "submit", "report"]))]
+    watch_files: bool,
+    /// Kill the solution at the time limit times <FACTOR> (e.g. 1.5) as TLE, instead of waiting
+    /// until it exits
+    #[structopt(long, value_name("FACTOR"), conflicts_with = "custom")]
+    time_limit_margin: Option<f64>,
 }
 
 async fn test(opt: TestOpt) -> Result<()> {
@@ -190,21 +665,68 @@ async fn test(opt: TestOpt) -> Result<()> {
     let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
     let package = metadata.query_for_member(opt.package.as_deref())?;
     let atc = AtCoder::new(&session_file()?)?;
-    let problem_id = opt.problem_id;
-    let contest_id = &package.name;
-    let contest_info = atc.contest_info(contest_id).await?;
+    let route = match &opt.problem_id {
+        Some(problem_id) if !opt.all => project::routed_task(package, problem_id),
+        _ => None,
+    };
+    let contest_id = match &route {
+        Some((contest_id, _)) => contest_id,
+        None => project::contest_id(package)?,
+    };
+    let contest_info = contest_info_or_register(&atc, contest_id).await?;
+    let reloaded;
+    let package = if route.is_none() && reconcile_bins(package, &contest_info)? {
+        reloaded = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+        reloaded.query_for_member(opt.package.as_deref())?
+    } else {
+        package
+    };
+    let config = read_config()?;
+    timer::start(&atc, &config, contest_id, &contest_info).await;
+
+    if opt.all {
+        let flags = RunFlags {
+            release: opt.release,
+            verbose: logging::verbose(),
+            fail_fast: false,
+            time_limit_margin: opt.time_limit_margin,
+        };
+        return test_all(
+            &atc,
+            package,
+            &contest_info,
+            &config.project.scratch_bins,
+            flags,
+            opt.report.as_deref(),
+        )
+        .await;
+    }
 
-    let problem = contest_info
-        .problem(&problem_id)
-        .with_context(|| format!("Problem `{}` is not contained in this contest", &problem_id))?;
+    let problem_id = opt.problem_id.expect("should be present unless `--all`");
+
+    let problem = &find_problem(&contest_info, &problem_id, route.as_ref())?;
+    let bin = variant_bin(package, &problem_id, opt.variant.as_deref())?;
+    stats::record_tested(contest_id, &problem_id);
 
     if opt.custom {
-        return test_custom(package, &problem_id, opt.release);
+        return test_custom(package, &bin, opt.release, None);
+    }
+    // Before testing as `submit` does. The past contests of a practice set are not asked for.
+    if opt.submit && !check_contest_time(&contest_info, opt.practice || route.is_some())? {
+        println!("{}", tr!(DidNotSubmit));
+        return Ok(());
     }
 
-    let test_cases = atc.test_cases(&problem.url).await?;
+    let test_cases = sample_cases(&atc, package, problem).await?;
+
+    let selected = opt
+        .case_num
+        .iter()
+        .chain(&opt.cases)
+        .copied()
+        .collect::<Vec<_>>();
 
-    for &cn in opt.case_num.iter() {
+    for &cn in selected.iter().chain(&opt.skip) {
         if cn == 0 || cn > test_cases.len() {
             bail!(
                 "Case num {} is not found in problem {} samples",
@@ -216,79 +738,311 @@ async fn test(opt: TestOpt) -> Result<()> {
 
     let mut tcs = vec![];
     for (i, tc) in test_cases.into_iter().enumerate() {
-        if opt.case_num.is_empty() || opt.case_num.contains(&(i + 1)) {
+        if (selected.is_empty() || selected.contains(&(i + 1))) && !opt.skip.contains(&(i + 1)) {
             tcs.push((i, tc));
         }
     }
 
-    let passed = test_samples(package, &problem_id, &tcs, opt.release, opt.verbose)?;
-    if passed && opt.submit {
-        let Target { src_path, .. } = package.find_bin(&problem_id)?;
-        let source = fs::read(src_path).with_context(|| format!("Failed to read {}", src_path))?;
-        atc.submit(contest_id, &problem_id, &String::from_utf8_lossy(&source))
-            .await?;
+    let mut last_results = cache::load_results(contest_id, &bin);
+    if opt.fail_fast {
+        // The cases that failed last time first, since they are likely to fail again
+        tcs.sort_by_key(|(i, _)| last_results.get(&(i + 1)).is_none_or(|label| label == "AC"));
+    }
+
+    if opt.watch_files {
+        #[cfg(not(feature = "watch"))]
+        bail!("`--watch-files` needs cargo-atcoder built with `--features watch`");
+        #[cfg(feature = "watch")]
+        {
+            let time_limit = problem.time_limit().unwrap_or(DEFAULT_TIME_LIMIT);
+            let flags = RunFlags {
+                release: opt.release,
+                verbose: logging::verbose(),
+                fail_fast: opt.fail_fast,
+                time_limit_margin: opt.time_limit_margin,
+            };
+            return watch::test_on_change(package, package.find_bin(&bin)?, || {
+                run_samples(package, &bin, &tcs, time_limit, flags, None)
+            });
+        }
+    }
+
+    let mut report = opt
+        .report
+        .as_ref()
+        .map(|_| junit::Suite::new(&package.name, &bin));
+    let results = run_samples(
+        package,
+        &bin,
+        &tcs,
+        problem.time_limit().unwrap_or(DEFAULT_TIME_LIMIT),
+        RunFlags {
+            release: opt.release,
+            verbose: logging::verbose(),
+            fail_fast: opt.fail_fast,
+            time_limit_margin: opt.time_limit_margin,
+        },
+        report.as_mut(),
+    )?;
+    if let Some(results) = &results {
+        last_results.extend(
+            results
+                .iter()
+                .map(|&(i, status)| (i + 1, status.label().to_owned())),
+        );
+        if let Err(err) = cache::save_results(contest_id, &bin, &last_results) {
+            warn!("{:#}", err);
+        }
+    }
+    if let (Some(path), Some(report)) = (&opt.report, &report) {
+        junit::write(path, slice::from_ref(report))?;
+    }
+    if results.is_some() {
+        let Target { src_path, .. } = package.find_bin(&bin)?;
+        if let Ok(source) = fs::read_to_string(src_path) {
+            let statement = cache::load_statement(contest_id, &problem.id, false)
+                .or_else(|| cache::load_statement(contest_id, &problem.id, true));
+            let path = src_path
+                .strip_prefix(project::package_dir(package))
+                .unwrap_or(src_path);
+            slow_io::warn_slow_io(path.as_ref(), &source, statement.as_deref());
+        }
+    }
+    ensure_samples_passed(&results)?;
+    if opt.submit {
+        // The same source as `submit --skip-test` sends, with the settings and the config
+        let mut args = vec!["submit", "--skip-test", &problem_id];
+        if let Some(variant) = &opt.variant {
+            args.extend(["--variant", variant]);
+        }
+        let submit_opt = SubmitOpt::from_iter_safe(args)?;
+        let submitter = Submitter {
+            opt: &submit_opt,
+            cwd: &cwd,
+            metadata: &metadata,
+            package,
+            atc: &atc,
+            config: &config,
+        };
+        submitter.submit(&problem_id, problem).await?;
     }
 
     Ok(())
 }
 
+/// The problem of the bin in the contest, which is the task of `route` for the bins routed to
+/// the tasks of other contests (see [`project::routed_task`]). The ID is of the bin in both cases.
+fn find_problem(
+    contest_info: &ContestInfo,
+    problem_id: &str,
+    route: Option<&(String, String)>,
+) -> Result<Problem> {
+    let problem = match route {
+        Some((contest_id, task)) => contest_info
+            .problem_by_task(task)
+            .with_context(|| format!("Task `{}` is not found in `{}`", task, contest_id))?,
+        None => contest_info
+            .problem(problem_id)
+            .with_context(|| tr!(ProblemNotInContest, problem_id))?,
+    };
+    Ok(Problem {
+        id: if route.is_some() {
+            problem_id.to_owned()
+        } else {
+            problem.id.clone()
+        },
+        ..problem.clone()
+    })
+}
+
+const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(2);
+
+/// Name of the bin target for the variant of the solution (e.g. `e_v2`), or for the problem
+fn variant_bin(package: &Package, problem_id: &str, variant: Option<&str>) -> Result<String> {
+    let variant = match variant {
+        Some(variant) => variant,
+        None => return Ok(problem_id.to_owned()),
+    };
+    let bin = format!("{}_{}", problem_id, variant);
+    package.find_bin(&bin).with_context(|| {
+        format!(
+            "Create `src/bin/{}.rs` for the variant `{}` of problem `{}`",
+            bin, variant, problem_id
+        )
+    })?;
+    Ok(bin)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaseStatus {
+    Ac,
+    Wa,
+    Re,
+    Tle,
+}
+
+impl CaseStatus {
+    fn label(self) -> &'static str {
+        match self {
+            CaseStatus::Ac => "AC",
+            CaseStatus::Wa => "WA",
+            CaseStatus::Re => "RE",
+            CaseStatus::Tle => "TLE",
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            CaseStatus::Ac => Style::new().green(),
+            CaseStatus::Tle => Style::new().yellow(),
+            CaseStatus::Wa | CaseStatus::Re => Style::new().red(),
+        }
+    }
+}
+
+/// How `run_samples` runs the samples
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RunFlags {
+    /// [cargo build] Use --release flag to compile
+    pub(crate) release: bool,
+    /// Show the stderr of the passing cases too
+    pub(crate) verbose: bool,
+    /// Stop at the first failing case
+    pub(crate) fail_fast: bool,
+    /// Kill the solution at the time limit times this as TLE, or wait until it exits if `None`
+    pub(crate) time_limit_margin: Option<f64>,
+}
+
+#[cfg_attr(not(feature = "watch"), allow(dead_code))]
 fn test_samples(
     package: &Package,
     problem_id: &str,
     test_cases: &[(usize, TestCase)],
+    time_limit: Duration,
     release: bool,
     verbose: bool,
 ) -> Result<bool> {
-    let build_status = Command::new("cargo")
-        .arg("build")
-        .args(if release { vec!["--release"] } else { vec![] })
-        .arg("--bin")
-        .arg(problem_id)
-        .arg("--manifest-path")
-        .arg(&package.manifest_path)
-        .status()?;
-
-    if !build_status.success() {
-        return Ok(false);
+    let flags = RunFlags {
+        release,
+        verbose,
+        ..RunFlags::default()
+    };
+    let results = run_samples(package, problem_id, test_cases, time_limit, flags, None)?;
+    Ok(results.is_some_and(|results| results.iter().all(|&(_, status)| status == CaseStatus::Ac)))
+}
+
+/// Fails with the exit code for the results of `run_samples` unless all the samples passed.
+fn ensure_samples_passed(results: &Option<Vec<(usize, CaseStatus)>>) -> Result<()> {
+    match results {
+        None => Err(ci::Failure::Build.into()),
+        Some(results) if results.iter().all(|&(_, status)| status == CaseStatus::Ac) => Ok(()),
+        Some(_) => Err(ci::Failure::Test.into()),
     }
+}
+
+// returns `None` if the build failed
+fn run_samples(
+    package: &Package,
+    problem_id: &str,
+    test_cases: &[(usize, TestCase)],
+    time_limit: Duration,
+    flags: RunFlags,
+    report: Option<&mut junit::Suite>,
+) -> Result<Option<Vec<(usize, CaseStatus)>>> {
+    let binary_file = match build::build_bin(package, problem_id, flags.release)? {
+        Some(binary_file) => binary_file,
+        None => {
+            if let Some(report) = report {
+                report.cases.push(junit::Case::failed(
+                    "build".to_owned(),
+                    "CE",
+                    "the build failed",
+                ));
+            }
+            return Ok(None);
+        }
+    };
+    run_cases(
+        package,
+        problem_id,
+        &|| Command::new(&binary_file),
+        test_cases,
+        time_limit,
+        flags,
+        report,
+    )
+}
 
+/// Runs the solution by `command` on the samples, e.g. of another language than Rust.
+fn run_cases(
+    package: &Package,
+    problem_id: &str,
+    command: &dyn Fn() -> Command,
+    test_cases: &[(usize, TestCase)],
+    time_limit: Duration,
+    flags: RunFlags,
+    mut report: Option<&mut junit::Suite>,
+) -> Result<Option<Vec<(usize, CaseStatus)>>> {
+    let RunFlags {
+        verbose,
+        fail_fast,
+        time_limit_margin,
+        ..
+    } = flags;
+    let kill_at = time_limit_margin.map_or(Duration::MAX, |margin| time_limit.mul_f64(margin));
     let test_case_num = test_cases.len();
 
-    println!("running {} tests", test_case_num);
+    println!("{}", tr!(RunningTests, test_case_num));
 
+    let mut results = vec![];
     let mut fails = vec![];
     let green = Style::new().green();
     let red = Style::new().red();
     let cyan = Style::new().cyan();
+    let mut record = |i, status, elapsed, test_case: &TestCase, output: &Output| {
+        if let Some(report) = report.as_deref_mut() {
+            report.cases.push(junit::Case::sample(
+                i, status, elapsed, test_case, output, kill_at,
+            ));
+        }
+    };
 
     for &(i, ref test_case) in test_cases.iter() {
-        let mut child = Command::new("cargo")
-            .arg("run")
-            .args(if release { vec!["--release"] } else { vec![] })
-            .arg("-q")
-            .arg("--bin")
-            .arg(problem_id)
-            .arg("--manifest-path")
-            .arg(&package.manifest_path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
+        if fail_fast && !fails.is_empty() {
+            break;
+        }
+        let pb = logging::spinner(format!("test sample {} ...", i + 1));
+        let mut command = command();
+        // Backtraces of panics to show where they happened, unless set by the user
+        if env::var_os("RUST_BACKTRACE").is_none() {
+            command.env("RUST_BACKTRACE", "1");
+        }
+        let start = Instant::now();
+        let (output, timed_out) = execute(command, test_case.input.as_bytes(), kill_at)?;
+        let elapsed = start.elapsed();
+        pb.finish_and_clear();
 
-        child
-            .stdin
-            .as_mut()
-            .unwrap()
-            .write_all(test_case.input.as_bytes())?;
+        if timed_out {
+            println!(
+                "test sample {} ... {} (time limit exceeded)",
+                i + 1,
+                red.apply_to("FAILED")
+            );
+            results.push((i, CaseStatus::Tle));
+            record(i, CaseStatus::Tle, elapsed, test_case, &output);
+            fails.push((i, CaseStatus::Tle, output, elapsed));
+            continue;
+        }
 
-        let output = child.wait_with_output()?;
         if !output.status.success() {
             println!("test sample {} ... {}", i + 1, red.apply_to("FAILED"));
-            fails.push((i, false, output));
+            results.push((i, CaseStatus::Re));
+            record(i, CaseStatus::Re, elapsed, test_case, &output);
+            fails.push((i, CaseStatus::Re, output, elapsed));
             continue;
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stdout = String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n");
 
         let cmp_res = cmp_output(&stdout, &test_case.output);
         let ferr = if let Some(ferr) = cmp_res.1 {
@@ -307,9 +1061,13 @@ fn test_samples(
                 red.apply_to("FAILED"),
                 ferr
             );
-            fails.push((i, true, output));
+            results.push((i, CaseStatus::Wa));
+            record(i, CaseStatus::Wa, elapsed, test_case, &output);
+            fails.push((i, CaseStatus::Wa, output, elapsed));
         } else {
             println!("test sample {} ... {}{}", i + 1, green.apply_to("ok"), ferr);
+            results.push((i, CaseStatus::Ac));
+            record(i, CaseStatus::Ac, elapsed, test_case, &output);
             if verbose && !output.stderr.is_empty() {
                 println!("stderr:");
                 print_lines(&String::from_utf8_lossy(&output.stderr));
@@ -317,35 +1075,42 @@ fn test_samples(
             }
         }
     }
+    if results.len() < test_case_num {
+        println!(
+            "{}",
+            tr!(CasesSkippedAfterFailure, test_case_num - results.len())
+        );
+    }
     println!();
 
     let fail_num = fails.len();
 
-    for (case_no, exec_success, output) in fails {
-        println!("---- sample {} ----", case_no + 1);
-
-        if !exec_success {
-            println!(
-                "{}: exit code: {}",
-                red.apply_to("runtime error"),
-                output.status.code().unwrap_or_default(),
+    match fails.first() {
+        Some((case_no, status, output, elapsed)) => {
+            let tc = &test_cases.iter().find(|r| r.0 == *case_no).unwrap().1;
+            explain::record_failure(
+                &package.name,
+                &explain::LastFailure {
+                    problem_id: problem_id.to_owned(),
+                    sample: case_no + 1,
+                    verdict: status.label().to_owned(),
+                    input: tc.input.clone(),
+                    expected: tc.output.clone(),
+                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                    elapsed_ms: elapsed.as_millis() as u64,
+                    time_limit_ms: time_limit.as_millis() as u64,
+                },
             );
-            println!();
-
-            if !output.stdout.is_empty() {
-                println!("stdout:");
-                print_lines(&String::from_utf8_lossy(&output.stdout));
-                println!();
-            }
+        }
+        None => explain::clear_failure(&package.name, problem_id),
+    }
 
-            if !output.stderr.is_empty() {
-                println!("stderr:");
-                print_lines(&String::from_utf8_lossy(&output.stderr));
-                println!();
-            }
-        } else {
-            let tc = &test_cases.iter().find(|r| r.0 == case_no).unwrap().1;
+    for (case_no, status, output, _) in fails {
+        println!("---- sample {} ----", case_no + 1);
+        let tc = &test_cases.iter().find(|r| r.0 == case_no).unwrap().1;
 
+        if status == CaseStatus::Wa {
             println!("{}:", cyan.apply_to("input"));
             print_lines(&tc.input);
             println!();
@@ -363,54 +1128,241 @@ fn test_samples(
                 print_lines(&String::from_utf8_lossy(&output.stderr));
                 println!();
             }
+        } else {
+            if status == CaseStatus::Tle {
+                println!(
+                    "{}: killed after {:?}",
+                    red.apply_to("time limit exceeded"),
+                    kill_at,
+                );
+            } else {
+                println!(
+                    "{}: exit code: {}",
+                    red.apply_to("runtime error"),
+                    output.status.code().unwrap_or_default(),
+                );
+            }
+            println!();
+
+            let mut stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            if status == CaseStatus::Re {
+                if let Some(panic) = backtrace::parse(&stderr) {
+                    panic.print(project::package_dir(package));
+                    println!();
+                    // The frames are shown above.
+                    if !verbose {
+                        stderr = backtrace::strip(&stderr);
+                    }
+                }
+
+                println!("{}:", cyan.apply_to("input"));
+                print_lines(&tc.input);
+                println!();
+            }
+
+            if !output.stdout.is_empty() {
+                println!("stdout:");
+                print_lines(&String::from_utf8_lossy(&output.stdout));
+                println!();
+            }
+
+            if !stderr.is_empty() {
+                println!("stderr:");
+                print_lines(&stderr);
+                println!();
+            }
         }
     }
 
     if fail_num == 0 {
-        println!("test_result: {}", green.apply_to("ok"));
+        println!("{}", tr!(TestResultOk, green.apply_to("ok")));
         println!();
-        Ok(true)
     } else {
         println!(
-            "test result: {}. {} passed; {} failed",
-            red.apply_to("FAILED"),
-            test_case_num - fail_num,
-            fail_num
+            "{}",
+            tr!(
+                TestResultFailed,
+                red.apply_to("FAILED"),
+                test_case_num - fail_num,
+                fail_num
+            )
         );
         println!();
-        Ok(false)
     }
-}
 
-const ERROR_THRESHOLD: f64 = 1e-6;
-
-#[derive(Debug)]
-struct FloatError {
-    abs_error: f64,
-    rel_error: f64,
+    Ok(Some(results))
 }
 
-// returns (accepted?, maximum float error if float value exists)
-fn cmp_output(reference: &str, out: &str) -> (bool, Option<FloatError>) {
-    let mut max_error = None;
+// Runs `cmd` with `input` as stdin, killing it when `time_limit` is exceeded.
+// returns (output, timed out?)
+fn execute(mut cmd: Command, input: &[u8], time_limit: Duration) -> Result<(Output, bool)> {
+    let start = Instant::now();
 
-    let ws1 = reference.split_whitespace().collect::<Vec<_>>();
-    let ws2 = out.split_whitespace().collect::<Vec<_>>();
+    let mut spawned = shutdown::spawn(
+        stack::apply(&mut cmd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )
+    .with_context(|| format!("Failed to execute {:?}", cmd))?;
+    let child = &mut spawned.child;
+
+    let stdin = {
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_owned();
+        // the process may exit without reading the whole input
+        thread::spawn(move || drop(stdin.write_all(&input)))
+    };
+    let stdout = read_to_end_in_background(child.stdout.take().unwrap());
+    let stderr = read_to_end_in_background(child.stderr.take().unwrap());
 
-    if ws1.len() != ws2.len() {
-        return (false, None);
-    }
+    let (status, timed_out) = loop {
+        if let Some(status) = spawned.child.try_wait()? {
+            break (status, false);
+        }
+        if start.elapsed() > time_limit || shutdown::interrupted() {
+            spawned.kill()?;
+            break (spawned.child.wait()?, true);
+        }
+        thread::sleep(Duration::from_millis(5));
+    };
 
-    for i in 0..ws1.len() {
-        let w1 = ws1[i];
-        let w2 = ws2[i];
+    let _ = stdin.join();
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap()?,
+        stderr: stderr.join().unwrap()?,
+    };
+    shutdown::check()?;
+    Ok((output, timed_out))
+}
 
-        if (is_float(w1) || is_float(w2))
-            && (is_float(w1) || is_integer(w1))
-            && (is_float(w2) || is_integer(w2))
+fn read_to_end_in_background(
+    mut r: impl Read + Send + 'static,
+) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buf = vec![];
+        r.read_to_end(&mut buf)?;
+        Ok(buf)
+    })
+}
+
+async fn test_all(
+    atc: &AtCoder,
+    package: &Package,
+    contest_info: &ContestInfo,
+    scratch_bins: &[String],
+    flags: RunFlags,
+    report: Option<&Path>,
+) -> Result<()> {
+    let mut rows = vec![];
+    let mut suites = vec![];
+
+    for problem in contest_info.problems() {
+        let bin = match package
+            .solution_bins(scratch_bins)
+            .into_iter()
+            .find(|t| t.name.to_lowercase() == problem.id.to_lowercase())
         {
-            let f1 = w1.parse::<f64>().unwrap();
-            let f2 = w2.parse::<f64>().unwrap();
+            Some(bin) => bin,
+            None => continue,
+        };
+
+        println!("==== {} ====", bin.name);
+
+        let test_cases = sample_cases(atc, package, problem)
+            .await?
+            .into_iter()
+            .enumerate()
+            .collect::<Vec<_>>();
+        let mut suite = junit::Suite::new(&package.name, &bin.name);
+        let results = run_samples(
+            package,
+            &bin.name,
+            &test_cases,
+            problem.time_limit().unwrap_or(DEFAULT_TIME_LIMIT),
+            flags,
+            report.map(|_| &mut suite),
+        )?;
+        rows.push((&bin.name, results));
+        suites.push(suite);
+    }
+
+    let id_width = rows
+        .iter()
+        .map(|(id, _)| id.len())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+    let case_num = rows
+        .iter()
+        .flat_map(|(_, results)| results.as_ref().map(Vec::len))
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "{:<width$} | {}",
+        "problem",
+        (1..=case_num).map(|i| format!("{:<4}", i)).join(""),
+        width = id_width,
+    );
+    for (id, results) in &rows {
+        let cells = match results {
+            Some(results) => results
+                .iter()
+                .map(|&(_, status)| {
+                    status
+                        .style()
+                        .apply_to(format!("{:<4}", status.label()))
+                        .to_string()
+                })
+                .join(""),
+            None => Style::new().red().apply_to("CE").to_string(),
+        };
+        println!("{:<width$} | {}", id, cells, width = id_width);
+    }
+    println!();
+
+    if let Some(path) = report {
+        junit::write(path, &suites)?;
+    }
+    if rows.iter().any(|(_, results)| results.is_none()) {
+        return Err(ci::Failure::Build.into());
+    }
+    rows.iter()
+        .try_for_each(|(_, results)| ensure_samples_passed(results))
+}
+
+const ERROR_THRESHOLD: f64 = 1e-6;
+
+#[derive(Debug)]
+struct FloatError {
+    abs_error: f64,
+    rel_error: f64,
+}
+
+// returns (accepted?, maximum float error if float value exists)
+// Line endings are not significant since the outputs are compared word by word.
+fn cmp_output(reference: &str, out: &str) -> (bool, Option<FloatError>) {
+    let mut max_error = None;
+
+    let ws1 = reference.split_whitespace().collect::<Vec<_>>();
+    let ws2 = out.split_whitespace().collect::<Vec<_>>();
+
+    if ws1.len() != ws2.len() {
+        return (false, None);
+    }
+
+    for i in 0..ws1.len() {
+        let w1 = ws1[i];
+        let w2 = ws2[i];
+
+        if (is_float(w1) || is_float(w2))
+            && (is_float(w1) || is_integer(w1))
+            && (is_float(w2) || is_integer(w2))
+        {
+            let f1 = w1.parse::<f64>().unwrap();
+            let f2 = w2.parse::<f64>().unwrap();
 
             let abs_error = (f1 - f2).abs();
             let rel_error = abs_error / f1.abs();
@@ -452,31 +1404,30 @@ fn is_integer(w: &str) -> bool {
     INTEGER_RE.is_match(w)
 }
 
-fn test_custom(package: &Package, problem_id: &str, release: bool) -> Result<()> {
-    let build_status = Command::new("cargo")
-        .arg("build")
-        .args(if release { vec!["--release"] } else { vec![] })
-        .arg("--bin")
-        .arg(problem_id)
-        .arg("--manifest-path")
-        .arg(&package.manifest_path)
-        .status()?;
-
-    ensure!(build_status.success(), "Build failed");
-
-    println!("input test case:");
+// Reads the input from `input` if specified, otherwise from stdin.
+fn test_custom(
+    package: &Package,
+    problem_id: &str,
+    release: bool,
+    input: Option<&Path>,
+) -> Result<()> {
+    let binary_file =
+        build::build_bin(package, problem_id, release)?.with_context(|| "Build failed")?;
+
+    let stdin = if let Some(input) = input {
+        fs::File::open(input)
+            .with_context(|| format!("Failed to open {}", input.display()))?
+            .into()
+    } else {
+        println!("input test case:");
+        Stdio::inherit()
+    };
 
     let red = Style::new().red();
     let cyan = Style::new().cyan();
 
-    let child = Command::new("cargo")
-        .arg("run")
-        .args(if release { vec!["--release"] } else { vec![] })
-        .arg("-q")
-        .arg("--bin")
-        .arg(problem_id)
-        .arg("--manifest-path")
-        .arg(&package.manifest_path)
+    let child = stack::apply(&mut Command::new(&binary_file))
+        .stdin(stdin)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()?;
@@ -516,6 +1467,29 @@ fn test_custom(package: &Package, problem_id: &str, release: bool) -> Result<()>
     Ok(())
 }
 
+/// Opens the file or the URL with the default application.
+fn open(target: &OsStr) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = cmd
+        .arg(target)
+        .status()
+        .with_context(|| format!("Failed to run {:?}", cmd))?;
+    ensure!(
+        status.success(),
+        "Failed to open {}",
+        target.to_string_lossy()
+    );
+    Ok(())
+}
+
 fn print_lines(s: &str) {
     for (i, line) in s.lines().enumerate() {
         println!("{:6} | {}", i + 1, line);
@@ -523,15 +1497,57 @@ fn print_lines(s: &str) {
 }
 
 #[derive(StructOpt)]
-struct SubmitOpt {
+struct RunOpt {
     /// Problem ID (must be same as binary name)
     problem_id: String,
-    /// [cargo] Package with the target to submit
+    /// [cargo] Package with the target to run
     #[structopt(short, long, value_name("SPEC"))]
     package: Option<String>,
     /// [cargo] Path to Cargo.toml
     #[structopt(long, value_name("PATH"))]
     manifest_path: Option<PathBuf>,
+    /// Read input from the file
+    #[structopt(short, long, value_name("PATH"), conflicts_with = "stdin")]
+    input: Option<PathBuf>,
+    /// Read input from stdin (default)
+    #[structopt(long)]
+    stdin: bool,
+    /// [cargo build] Use --release flag to compile
+    #[structopt(long)]
+    release: bool,
+}
+
+fn run(opt: RunOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    package.find_bin(&opt.problem_id)?;
+    test_custom(
+        package,
+        &opt.problem_id,
+        opt.release,
+        opt.input.as_deref().filter(|_| !opt.stdin),
+    )
+}
+
+#[derive(StructOpt)]
+struct SubmitOpt {
+    /// Problem IDs (must be same as binary names), submitted one by one, optionally followed by
+    /// the max column number of generated binary
+    #[structopt(required_unless("all-passing"), value_name("PROBLEM-ID|COLUMN"))]
+    problem_ids: Vec<String>,
+    /// Submit all the problems whose samples pass
+    #[structopt(long)]
+    all_passing: bool,
+    /// Interval between the submissions of multiple problems (e.g. `10s`)
+    #[structopt(long, value_name("DURATION"), default_value("5s"), parse(try_from_str = parse_duration))]
+    interval: chrono::Duration,
+    /// [cargo] Package with the target to submit, or the contest in a workspace of the contests
+    #[structopt(short, long, visible_alias("contest"), value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
     /// Force submit even if test fails
     #[structopt(short, long)]
     force: bool,
@@ -544,7 +1560,8 @@ struct SubmitOpt {
     /// Submit source code directory (overwrite config)
     #[structopt(long, conflicts_with = "bin")]
     source: bool,
-    /// Max column number of generated binary
+    /// Taken from the last of `problem_ids` if it is a number, which no problem ID is
+    #[structopt(skip)]
     column: Option<usize>,
     /// Do no use upx unless available
     #[structopt(long)]
@@ -552,54 +1569,451 @@ struct SubmitOpt {
     /// [cargo build] Use --release on pre-test (submission always uses --release)
     #[structopt(long)]
     release: bool,
+    /// Minify the code if it exceeds the size limit
+    #[structopt(long)]
+    minify: bool,
+    /// Remove `eprintln!`, `eprint!`, `dbg!` and `debug!` not gated by `cfg(debug_assertions)`
+    #[structopt(long)]
+    strip_debug: bool,
+    /// Submit this file instead of the bin target (saved for subsequent submits)
+    #[structopt(long, value_name("PATH"), conflicts_with = "bin")]
+    file: Option<PathBuf>,
+    /// Submit the source read from stdin instead of the bin target, without testing
+    #[structopt(long, conflicts_with_all(&["bin", "file", "variant"]))]
+    stdin: bool,
+    /// Language to submit in, the whole name or the prefix of its first word (e.g. `Python`)
+    /// (saved for subsequent submits)
+    #[structopt(long, value_name("NAME"))]
+    language: Option<String>,
+    /// Command to run the `--file` with on the samples in the package directory (e.g. `python3
+    /// e.py`), unless it is a bin target (saved for subsequent submits)
+    #[structopt(long, value_name("COMMAND"), conflicts_with = "stdin")]
+    run: Option<String>,
+    /// Team member to record as the submitter, shown in `status` and `submissions`
+    #[structopt(long, value_name("NAME"), env = "CARGO_ATCODER_MEMBER")]
+    member: Option<String>,
+    /// Submit without confirmation after the contest has ended
+    #[structopt(long)]
+    practice: bool,
+    /// Submit `src/bin/<problem-id>_<variant>.rs` instead (e.g. `v2` for `e_v2.rs`), recorded
+    /// to be shown in `status` and `submissions`
+    #[structopt(long, value_name("NAME"), conflicts_with = "file")]
+    variant: Option<String>,
 }
 
-async fn submit(opt: SubmitOpt) -> Result<()> {
+async fn submit(mut opt: SubmitOpt) -> Result<()> {
+    if let Some(column) = opt.problem_ids.last().and_then(|arg| arg.parse().ok()) {
+        opt.problem_ids.pop();
+        opt.column = Some(column);
+    }
+    ensure!(
+        !opt.all_passing || opt.problem_ids.is_empty(),
+        "The problem IDs cannot be given with `--all-passing`",
+    );
     let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
     let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
     let package = metadata.query_for_member(opt.package.as_deref())?;
     let atc = AtCoder::new(&session_file()?)?;
     let config = read_config()?;
 
-    let contest_id = &package.name;
-    let problem_id = opt.problem_id;
-    let contest_info = atc.contest_info(contest_id).await?;
-    let problem = contest_info
-        .problem(&problem_id)
-        .with_context(|| format!("Problem `{}` is not contained in this contest", &problem_id))?;
-
-    let test_passed = if opt.skip_test {
-        true
+    let route = match opt.problem_ids.as_slice() {
+        [problem_id] if !opt.all_passing => project::routed_task(package, problem_id),
+        problem_ids => {
+            ensure!(
+                problem_ids
+                    .iter()
+                    .all(|problem_id| project::routed_task(package, problem_id).is_none()),
+                "The problems routed to the tasks of other contests are submitted one at a time"
+            );
+            None
+        }
+    };
+    let contest_id = match &route {
+        Some((contest_id, _)) => contest_id,
+        None => project::contest_id(package)?,
+    };
+    let contest_info = contest_info_or_register(&atc, contest_id).await?;
+    let reloaded;
+    let package = if route.is_none() && reconcile_bins(package, &contest_info)? {
+        reloaded = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+        reloaded.query_for_member(opt.package.as_deref())?
     } else {
-        let test_cases = atc
-            .test_cases(&problem.url)
-            .await?
+        package
+    };
+    timer::start(&atc, &config, contest_id, &contest_info).await;
+    let problem_ids = if opt.all_passing {
+        let bins = package.solution_bins(&config.project.scratch_bins);
+        contest_info
+            .problem_ids_lowercase()
             .into_iter()
-            .enumerate()
-            .collect::<Vec<_>>();
-        test_samples(package, &problem_id, &test_cases, opt.release, false)?
+            .filter(|id| bins.iter().any(|t| &t.name == id))
+            .collect()
+    } else {
+        opt.problem_ids.clone()
     };
-
-    if !test_passed && !opt.force {
-        println!("Sample test failed. Did not submit.");
+    ensure!(!problem_ids.is_empty(), "No problems to submit");
+    let problems = problem_ids
+        .iter()
+        .map(|problem_id| {
+            let problem = find_problem(&contest_info, problem_id, route.as_ref())?;
+            Ok((problem_id, problem))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let batch = opt.all_passing || problems.len() > 1;
+    ensure!(
+        !batch || (opt.file.is_none() && opt.variant.is_none() && !opt.stdin),
+        "`--file`, `--stdin` and `--variant` can be given for a single problem only",
+    );
+    // The past contests of a practice set are not asked for.
+    if !check_contest_time(&contest_info, opt.practice || route.is_some())? {
+        println!("{}", tr!(DidNotSubmit));
         return Ok(());
     }
 
-    let via_bin = opt.bin || (config.atcoder.submit_via_binary && !opt.source);
-    let target = package.find_bin(&problem_id)?;
-    let source = if !via_bin {
-        let Target { src_path, .. } = target;
-        fs::read(src_path).with_context(|| format!("Failed to read {}", src_path))?
+    let submitter = Submitter {
+        opt: &opt,
+        cwd: &cwd,
+        metadata: &metadata,
+        package,
+        atc: &atc,
+        config: &config,
+    };
+    if !batch {
+        let (problem_id, problem) = &problems[0];
+        return submitter.submit(problem_id, problem).await.map(drop);
+    }
+
+    // Serially with the interval not to be rate limited. The errors of a problem do not stop the
+    // others, except for Ctrl-C.
+    let mut results = vec![];
+    let mut submitted = false;
+    for (problem_id, problem) in problems {
+        if submitted {
+            tokio::time::sleep(opt.interval.to_std()?).await;
+        }
+        println!("==== {} ====", problem_id);
+        let result = submitter.submit(problem_id, &problem).await;
+        let interrupted = match &result {
+            Ok(_) => {
+                submitted = true;
+                false
+            }
+            Err(err) => match err.downcast_ref::<ci::Failure>() {
+                Some(ci::Failure::Test | ci::Failure::Build) => false,
+                Some(ci::Failure::Interrupted) => true,
+                _ => {
+                    warn!("failed to submit `{}`: {:#}", problem_id, err);
+                    false
+                }
+            },
+        };
+        results.push((problem_id, result));
+        if interrupted {
+            break;
+        }
+    }
+
+    let id_width = results
+        .iter()
+        .map(|(id, _)| id.len())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+    println!("{:<width$} | submission", "problem", width = id_width);
+    for (id, result) in &results {
+        let cell = match result {
+            Ok(submission_id) => submission_id.to_string(),
+            Err(err) => Style::new()
+                .red()
+                .apply_to(match err.downcast_ref::<ci::Failure>() {
+                    Some(ci::Failure::Build) => "CE",
+                    Some(ci::Failure::Test) => "samples failed",
+                    _ => "error",
+                })
+                .to_string(),
+        };
+        println!("{:<width$} | {}", id, cell, width = id_width);
+    }
+
+    // Skipping the failing ones is expected with `--all-passing`, but not the other errors.
+    let skipped = |err: &anyhow::Error| {
+        opt.all_passing
+            && matches!(
+                err.downcast_ref::<ci::Failure>(),
+                Some(ci::Failure::Test | ci::Failure::Build),
+            )
+    };
+    match results
+        .into_iter()
+        .filter_map(|(_, result)| result.err())
+        .find(|err| !skipped(err))
+    {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// What is shared by the submissions of the problems given to `submit`
+struct Submitter<'a> {
+    opt: &'a SubmitOpt,
+    cwd: &'a Path,
+    metadata: &'a Metadata,
+    package: &'a Package,
+    atc: &'a AtCoder,
+    config: &'a Config,
+}
+
+impl Submitter<'_> {
+    /// Tests and submits the solution, returning the ID of the submission.
+    async fn submit(&self, problem_id: &str, problem: &Problem) -> Result<u64> {
+        let Submitter {
+            opt,
+            cwd,
+            metadata,
+            package,
+            atc,
+            config,
+        } = *self;
+        let route = project::routed_task(package, problem_id);
+        let (contest_id, task) = match &route {
+            Some((contest_id, task)) => (contest_id.as_str(), task.as_str()),
+            None => (project::contest_id(package)?, problem_id),
+        };
+        let bin = variant_bin(package, problem_id, opt.variant.as_deref())?;
+        ensure!(
+            !metadata::is_scratch_bin(&bin, &config.project.scratch_bins),
+            "`{}` is a scratchpad, which is not submitted",
+            bin
+        );
+
+        let saved = project::problem_setting(package, problem_id);
+        // `--variant` takes priority over the saved file of the problem
+        let (saved_file, saved_run) = match opt.variant {
+            None => (saved.file, saved.run),
+            Some(_) => (None, None),
+        };
+        let setting = project::ProblemSetting {
+            file: opt.file.as_ref().map(|file| cwd.join(file)).or(saved_file),
+            language: opt.language.clone().or(saved.language),
+            run: opt.run.clone().or(saved_run),
+            ..saved
+        };
+
+        let tested = if opt.skip_test {
+            Ok(())
+        } else if opt.stdin {
+            info!("Skipping sample tests for the source from stdin");
+            Ok(())
+        } else {
+            let test_cases = sample_cases(atc, package, problem)
+                .await?
+                .into_iter()
+                .enumerate()
+                .collect::<Vec<_>>();
+            let time_limit = problem.time_limit().unwrap_or(DEFAULT_TIME_LIMIT);
+            let flags = RunFlags {
+                release: opt.release,
+                ..RunFlags::default()
+            };
+            // A file among the bins is built as usual.
+            let results = match &setting.file {
+                Some(file) => match bin_of_file(package, file) {
+                    Some(bin) => {
+                        run_samples(package, &bin.name, &test_cases, time_limit, flags, None)?
+                    }
+                    None => {
+                        let command = setting.run.as_deref().with_context(|| {
+                            format!(
+                                "Give the command to run `{}` on the samples with `--run`, or \
+                                 skip them with `--skip-test`",
+                                file.display()
+                            )
+                        })?;
+                        let dir = project::package_dir(package);
+                        run_cases(
+                            package,
+                            problem_id,
+                            &|| {
+                                let mut cmd = shell(command);
+                                cmd.current_dir(dir);
+                                cmd
+                            },
+                            &test_cases,
+                            time_limit,
+                            flags,
+                            None,
+                        )?
+                    }
+                },
+                None => run_samples(package, &bin, &test_cases, time_limit, flags, None)?,
+            };
+            ensure_samples_passed(&results)
+        };
+
+        if let Err(err) = tested {
+            if !opt.force {
+                println!("{}", tr!(SampleTestFailed));
+                return Err(err);
+            }
+        }
+
+        let via_bin = opt.bin || (config.atcoder.submit_via_binary && !opt.source);
+        // with the directory of the source for `include` of `preprocess`
+        let (source, dir) = if opt.stdin {
+            let mut source = vec![];
+            io::stdin()
+                .read_to_end(&mut source)
+                .with_context(|| "Failed to read the source from stdin")?;
+            (source, Some(cwd))
+        } else if let Some(file) = &setting.file {
+            let source =
+                fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?;
+            (source, file.parent())
+        } else if !via_bin {
+            let Target { src_path, .. } = package.find_bin(&bin)?;
+            let source =
+                fs::read(src_path).with_context(|| format!("Failed to read {}", src_path))?;
+            (source, src_path.parent().map(AsRef::as_ref))
+        } else {
+            let target = package.find_bin(&bin)?;
+            info!("Submitting via binary...");
+            let source =
+                gen_binary_source(metadata, package, target, config, opt.column, opt.no_upx)?;
+            (source, None)
+        };
+        // Only Rust sources are preprocessed.
+        let source = match dir {
+            Some(dir) if setting.language.is_none() => {
+                let source = preprocess::preprocess(
+                    &config.preprocess,
+                    &String::from_utf8_lossy(&source),
+                    dir,
+                )?;
+                strip_debug_prints(source, opt.strip_debug || config.atcoder.strip_debug)
+                    .into_bytes()
+            }
+            _ => source,
+        };
+        let source = check_source_size(source, opt.minify);
+        ensure!(
+            source.len() as u64 <= SOURCE_SIZE_LIMIT.as_u64(),
+            "{}",
+            tr!(CodeSizeExceeded),
+        );
+
+        let submission_id = atc
+            .submit(
+                contest_id,
+                task,
+                &String::from_utf8_lossy(&source),
+                setting.language.as_deref().unwrap_or(DEFAULT_LANGUAGE),
+            )
+            .await?;
+        println!();
+
+        // What is actually submitted, under which the submission is annotated
+        let submitted = if opt.stdin {
+            None
+        } else if let Some(file) = &setting.file {
+            let file = file
+                .strip_prefix(project::package_dir(package))
+                .unwrap_or(file);
+            Some(file.to_string_lossy().replace('\\', "/"))
+        } else {
+            opt.variant.clone()
+        };
+        record_submission(
+            package,
+            submission_id,
+            opt.member.as_deref(),
+            submitted.as_deref(),
+        );
+
+        if opt.file.is_some() || opt.language.is_some() || opt.run.is_some() {
+            project::save_problem_setting(package, problem_id, &setting)?;
+        }
+
+        Ok(submission_id)
+    }
+}
+
+/// The bin target whose source is the file
+fn bin_of_file<'a>(package: &'a Package, file: &Path) -> Option<&'a Target> {
+    let file = file.canonicalize().ok()?;
+    package.targets.iter().find(|target| {
+        target.kind.iter().any(|kind| kind == "bin")
+            && target.src_path.as_std_path().canonicalize().ok().as_ref() == Some(&file)
+    })
+}
+
+/// The command line run by the shell, e.g. `--run` of `submit`
+fn shell(command: &str) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
     } else {
-        println!("Submitting via binary...");
-        gen_binary_source(&metadata, package, target, &config, opt.column, opt.no_upx)?
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
     };
+    cmd.arg(command);
+    cmd
+}
 
-    atc.submit(contest_id, &problem_id, &String::from_utf8_lossy(&source))
-        .await?;
-    println!();
+/// Refuses to submit before the contest starts, and asks for confirmation after it ends since
+/// the submission is unrated, which is easy to miss in virtual participation.
+fn check_contest_time(contest_info: &ContestInfo, practice: bool) -> Result<bool> {
+    let now = chrono::Utc::now();
+    if let Some(start) = contest_info.start_time() {
+        ensure!(
+            now >= start,
+            "The contest has not started yet (starts at {}). Did not submit.",
+            start.format("%Y-%m-%d %H:%M:%S %z"),
+        );
+    }
+    let end = match contest_info.end_time() {
+        Some(end) if now > end && !practice => end,
+        _ => return Ok(true),
+    };
+    let message = format!(
+        "The contest ended at {}. The submission does not affect the rating.",
+        end.format("%Y-%m-%d %H:%M:%S %z"),
+    );
+    ensure!(
+        ci::interactive(),
+        "{} Pass `--practice` to submit anyway.",
+        message,
+    );
+    warn!("{}", message);
+    let _paused = timer::pause();
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(tr!(SubmitAnyway))
+        .default(false)
+        .interact()?;
+    Ok(confirmed)
+}
 
-    Ok(())
+/// Records the member and the variant of the submission. Failures are only warned since the
+/// submission itself has succeeded.
+fn record_submission(
+    package: &Package,
+    submission_id: u64,
+    member: Option<&str>,
+    variant: Option<&str>,
+) {
+    let result = (|| {
+        if let Some(member) = member {
+            project::save_member(package, submission_id, member)?;
+        }
+        if let Some(variant) = variant {
+            project::save_variant(package, submission_id, variant)?;
+        }
+        Ok::<_, anyhow::Error>(())
+    })();
+    if let Err(err) = result {
+        warn!("failed to record the submission: {:#}", err);
+    }
 }
 
 fn gen_binary_source(
@@ -614,14 +2028,25 @@ fn gen_binary_source(
         .with_context(|| format!("Failed to read {}", bin.src_path))?;
 
     let target = &config.profile.target;
-    let binary_file = metadata
-        .target_directory
-        .join(target)
-        .join("release")
-        .join(&bin.name);
 
-    let program = if config.atcoder.use_cross {
+    let program = if config.build_server.is_some() {
+        "ssh"
+    } else if config.atcoder.use_wsl {
+        "wsl"
+    } else if config.atcoder.use_cross {
         "cross"
+    } else if needs_cross_toolchain(target) {
+        if which::which("cargo-zigbuild").is_ok() {
+            "cargo-zigbuild"
+        } else if which::which("cross").is_ok() {
+            "cross"
+        } else {
+            bail!(
+                "Build failed. Building for `{}` on Apple Silicon needs `cargo-zigbuild` \
+                 (`brew install zig && cargo install cargo-zigbuild`) or `cross`.",
+                target,
+            );
+        }
     } else {
         "cargo"
     };
@@ -630,41 +2055,49 @@ fn gen_binary_source(
         bail!("Build failed. {} not found.", program);
     }
 
-    let status = Command::new(program)
-        .arg("build")
-        .arg(format!("--target={}", target))
-        .arg("--release")
-        .arg("--bin")
-        .arg(&bin.name)
-        .current_dir({
-            // `cross` does not work with `--manifest-path <absolute path>`.
-            package
-                .manifest_path
-                .parent()
-                .expect("`manifest_path` should end with \"Cargo.toml\"")
-        })
-        .status()?;
+    let binary_file = if let Some(server) = &config.build_server {
+        // Stripped on the server.
+        build::build_bin_on_server(server, metadata, package, &bin.name, target)?
+            .with_context(|| "Build failed")?
+    } else if program == "cargo-zigbuild" {
+        // Stripped by the linker.
+        build::build_bin_with_zig(package, &bin.name, target)?.with_context(|| "Build failed")?
+    } else {
+        let binary_file = if config.atcoder.use_wsl {
+            build::build_bin_in_wsl(package, &bin.name, target)?
+        } else {
+            build::build_bin_for_target(program, metadata, package, &bin.name, target)?
+        }
+        .with_context(|| "Build failed")?;
 
-    ensure!(status.success(), "Build failed");
+        let size = ByteSize::b(get_file_size(&binary_file)?);
+        info!("Built binary size: {}", size);
 
-    let size = ByteSize::b(get_file_size(&binary_file)?);
-    println!("Built binary size: {}", size);
+        let strip = match config.atcoder.strip_path {
+            Some(ref p) => p,
+            None => "strip",
+        };
+        let status = if config.atcoder.use_wsl {
+            Command::new("wsl")
+                .arg("--exec")
+                .arg(strip)
+                .arg("-s")
+                .arg(build::wslpath(binary_file.as_ref(), false)?)
+                .status()?
+        } else {
+            Command::new(strip).arg("-s").arg(&binary_file).status()?
+        };
+        ensure!(status.success(), "strip failed");
 
-    let status = Command::new(match config.atcoder.strip_path {
-        Some(ref p) => p,
-        None => "strip",
-    })
-    .arg("-s")
-    .arg(&binary_file)
-    .status()?;
-    ensure!(status.success(), "strip failed");
+        binary_file
+    };
 
     let size = ByteSize::b(get_file_size(&binary_file)?);
-    println!("Stripped binary size: {}", size);
+    info!("Stripped binary size: {}", size);
 
     if let Ok(upx_path) = which::which("upx") {
         if !no_upx {
-            println!("upx found. Use upx to compress binary.");
+            info!("upx found. Use upx to compress binary.");
             let status = Command::new(upx_path)
                 .arg("--best")
                 .arg("-qq")
@@ -672,10 +2105,10 @@ fn gen_binary_source(
                 .status()?;
             ensure!(status.success(), "upx failed");
             let size = ByteSize::b(get_file_size(&binary_file)?);
-            println!("Compressed binary size: {}", size);
+            info!("Compressed binary size: {}", size);
         }
     } else if !no_upx {
-        println!("upx not found. Binary is not compressed.");
+        info!("upx not found. Binary is not compressed.");
     }
 
     let code = {
@@ -700,15 +2133,78 @@ fn gen_binary_source(
     };
 
     let size = ByteSize::b(code.len() as u64);
-    println!("Bundled code size: {}", size);
+    info!("Bundled code size: {}", size);
 
-    let size_limit = ByteSize::kib(512);
+    Ok(code.bytes().collect::<Vec<u8>>())
+}
+
+// The default linker on aarch64 macOS cannot link x86_64 Linux binaries.
+fn needs_cross_toolchain(target: &str) -> bool {
+    cfg!(all(target_os = "macos", target_arch = "aarch64"))
+        && target.starts_with("x86_64-")
+        && target.contains("-linux-")
+}
 
-    if size > size_limit {
-        println!("Code size limit exceeded: larger than {}", size_limit);
+const SOURCE_SIZE_LIMIT: ByteSize = ByteSize::kib(512);
+
+/// Removes the debug prints if `strip`, or warns about them otherwise since the output to stderr
+/// takes time on the judge too.
+fn strip_debug_prints(source: String, strip: bool) -> String {
+    let (stripped, removed) = minify::strip_debug_prints(&source);
+    if removed.is_empty() {
+        return source;
+    }
+    let list = removed
+        .iter()
+        .map(|(line, name)| format!("`{}!` at line {}", name, line))
+        .join(", ");
+    if strip {
+        info!("Removed the debug prints: {}", list);
+        stripped
+    } else {
+        warn!(
+            "Submitting the debug prints not gated by `cfg(debug_assertions)`: {}. \
+             `--strip-debug` removes them",
+            list
+        );
+        source
     }
+}
 
-    Ok(code.bytes().collect::<Vec<u8>>())
+// Warn when the code is larger than 90% of the limit.
+const SOURCE_SIZE_WARNING_RATIO: f64 = 0.9;
+
+fn check_source_size(source: Vec<u8>, minify: bool) -> Vec<u8> {
+    let yellow = Style::new().yellow();
+    let red = Style::new().red();
+
+    let source = if minify && source.len() as u64 > SOURCE_SIZE_LIMIT.as_u64() {
+        let minified = minify::minify(&String::from_utf8_lossy(&source)).into_bytes();
+        info!("Minified code size: {}", ByteSize::b(minified.len() as u64));
+        minified
+    } else {
+        source
+    };
+
+    let size = ByteSize::b(source.len() as u64);
+    if size > SOURCE_SIZE_LIMIT {
+        println!(
+            "{}: {} is larger than {}{}",
+            red.apply_to("Code size limit exceeded"),
+            size,
+            SOURCE_SIZE_LIMIT,
+            if minify { "" } else { " (try `--minify`)" },
+        );
+    } else if size.as_u64() as f64 > SOURCE_SIZE_LIMIT.as_u64() as f64 * SOURCE_SIZE_WARNING_RATIO {
+        println!(
+            "{}: {} is close to the limit {}",
+            yellow.apply_to("Code size warning"),
+            size,
+            SOURCE_SIZE_LIMIT,
+        );
+    }
+
+    source
 }
 
 fn get_file_size(path: impl AsRef<Path>) -> Result<u64> {
@@ -735,18 +2231,755 @@ fn split_lines(s: &str, w: usize) -> String {
     ret
 }
 
-async fn info() -> Result<()> {
+#[derive(StructOpt)]
+struct StatementOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Show the statement in English
+    #[structopt(long)]
+    en: bool,
+}
+
+async fn statement(opt: StatementOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    let route = project::routed_task(package, &opt.problem_id);
+    let contest_id = match &route {
+        Some((contest_id, _)) => contest_id,
+        None => project::contest_id(package)?,
+    };
+    let contest_info = contest_info_or_register(&atc, contest_id).await?;
+    timer::start(&atc, &read_config()?, contest_id, &contest_info).await;
+
+    let problem = &find_problem(&contest_info, &opt.problem_id, route.as_ref())?;
+    let html = statement_html(&atc, contest_id, problem, opt.en).await?;
+    let images = statement_images(&atc, contest_id, &html).await;
+    print!(
+        "{}",
+        statement::render(&html, &images, statement::ImageProtocol::detect())
+    );
+    Ok(())
+}
+
+#[derive(StructOpt)]
+struct OpenOpt {
+    /// Problem ID (e.g. a, b, ...). The top page of the contest is opened if omitted
+    problem_id: Option<String>,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Open my submissions (of the problem if specified)
+    #[structopt(long, conflicts_with_all(&["standings", "clar"]))]
+    submissions: bool,
+    /// Open the standings
+    #[structopt(long, conflicts_with_all(&["problem-id", "clar"]))]
+    standings: bool,
+    /// Open the clarifications
+    #[structopt(long, conflicts_with("problem-id"))]
+    clar: bool,
+    /// Print the URL instead of opening it in the browser
+    #[structopt(long)]
+    url: bool,
+}
+
+async fn open_page(opt: OpenOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let route = opt
+        .problem_id
+        .as_ref()
+        .and_then(|problem_id| project::routed_task(package, problem_id));
+    let contest_id = match &route {
+        Some((contest_id, _)) => contest_id,
+        None => project::contest_id(package)?,
+    };
+
+    let path = if let Some(problem_id) = &opt.problem_id {
+        // Task IDs are not always `<contest>_<problem>`, so they are taken from the problem list.
+        let atc = AtCoder::new(&session_file()?)?;
+        let contest_info = contest_info_or_register(&atc, contest_id).await?;
+        let problem = find_problem(&contest_info, problem_id, route.as_ref())?;
+        if opt.submissions {
+            let task = problem.url.rsplit('/').next().unwrap();
+            format!("/contests/{}/submissions/me?f.Task={}", contest_id, task)
+        } else {
+            problem.url.clone()
+        }
+    } else if opt.submissions {
+        format!("/contests/{}/submissions/me", contest_id)
+    } else if opt.standings {
+        format!("/contests/{}/standings", contest_id)
+    } else if opt.clar {
+        format!("/contests/{}/clarifications", contest_id)
+    } else {
+        format!("/contests/{}", contest_id)
+    };
+    let url = atcoder::page_url(&path);
+
+    if opt.url {
+        println!("{}", url);
+        Ok(())
+    } else {
+        open(OsStr::new(&url))
+    }
+}
+
+/// HTML of the statement, cached since it does not change during the contest
+async fn statement_html(
+    atc: &AtCoder,
+    contest_id: &str,
+    problem: &Problem,
+    english: bool,
+) -> Result<String> {
+    if let Some(html) = cache::load_statement(contest_id, &problem.id, english) {
+        return Ok(html);
+    }
+    let html = atc.statement_html(&problem.url, english).await?;
+    cache::save_statement(contest_id, &problem.id, english, &html)?;
+    Ok(html)
+}
+
+/// Downloads the images in the statement into the cache unless they have been, and returns
+/// their paths by `src`.
+async fn statement_images(
+    atc: &AtCoder,
+    contest_id: &str,
+    html: &str,
+) -> BTreeMap<String, PathBuf> {
+    let mut images = BTreeMap::new();
+    for src in statement::image_sources(html) {
+        let result = async {
+            let path = cache::image_path(contest_id, &src)?;
+            if !path.is_file() {
+                let data = atc.download(&src).await?;
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(&path, data)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+            }
+            Ok::<_, anyhow::Error>(path)
+        }
+        .await;
+        match result {
+            Ok(path) => {
+                images.insert(src, path);
+            }
+            Err(err) => warn!("failed to download `{}`: {}", src, err),
+        }
+    }
+    images
+}
+
+#[derive(StructOpt)]
+struct StatusOpt {
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Show the submissions once without waiting for the judge
+    #[structopt(long)]
+    once: bool,
+}
+
+async fn status(opt: StatusOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    let config = read_config()?;
+    let contest_id = project::contest_id(package)?;
+
+    let term = console::Term::stdout();
+    let mut printed_lines = 0;
+    let mut rejudged = BTreeMap::new();
+    let members = project::load_members(package);
+    let variants = project::load_variants(package);
+
+    loop {
+        let (submissions, _) = atc.submissions(contest_id, 1).await?;
+        rejudged.extend(detect_rejudges(contest_id, &submissions)?);
+        stats::record_accepted(contest_id, &submissions);
+        let judged = submissions.iter().all(|s| s.verdict.is_judged());
+        let done = opt.once || judged;
+
+        // Updated in place on a terminal, and printed only once otherwise.
+        if term.is_term() || done {
+            let lines = submission_lines(
+                &submissions,
+                &rejudged,
+                &[("Member", &members), ("Variant", &variants)],
+            );
+            term.clear_last_lines(printed_lines)?;
+            for line in &lines {
+                println!("{}", line);
+            }
+            printed_lines = lines.len();
+        }
+
+        if done {
+            warn_rejudges(&rejudged);
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(config.atcoder.update_interval)).await;
+    }
+}
+
+#[derive(StructOpt)]
+struct SubmissionsOpt {
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Show only the submissions for the problem (e.g. e)
+    #[structopt(long, value_name("PROBLEM_ID"))]
+    problem: Option<String>,
+    /// Show only the submissions with the verdict (e.g. WA, TLE, WJ)
+    #[structopt(long, value_name("VERDICT"), parse(from_str = Verdict::parse))]
+    verdict: Option<Verdict>,
+    /// Show only the submissions within the duration (e.g. 30m, 1h, 2d)
+    #[structopt(long, value_name("DURATION"), parse(try_from_str = parse_duration))]
+    since: Option<chrono::Duration>,
+    /// Show only the submissions recorded with `submit --member`
+    #[structopt(long, value_name("NAME"))]
+    member: Option<String>,
+    /// Print the source code of the submission (mine, or public after the contest) instead
+    #[structopt(
+        long,
+        value_name("ID"),
+        conflicts_with_all(&["problem", "verdict", "since", "member"])
+    )]
+    source: Option<u64>,
+}
+
+fn parse_duration(s: &str) -> Result<chrono::Duration> {
+    let unit_pos = s
+        .find(|c: char| !c.is_ascii_digit())
+        .with_context(|| format!("missing unit (s, m, h or d) in `{}`", s))?;
+    let n = s[..unit_pos].parse::<i64>()?;
+    match &s[unit_pos..] {
+        "s" => Ok(chrono::Duration::seconds(n)),
+        "m" => Ok(chrono::Duration::minutes(n)),
+        "h" => Ok(chrono::Duration::hours(n)),
+        "d" => Ok(chrono::Duration::days(n)),
+        unit => bail!("unknown unit `{}` (expected s, m, h or d)", unit),
+    }
+}
+
+async fn submissions(opt: SubmissionsOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    // The problem routed to the task of another contest is looked up there.
+    let route = opt
+        .problem
+        .as_deref()
+        .and_then(|problem| project::routed_task(package, problem));
+    let (contest_id, problem) = match &route {
+        Some((contest_id, task)) => (contest_id.as_str(), Some(task.as_str())),
+        None => (project::contest_id(package)?, opt.problem.as_deref()),
+    };
+
+    if let Some(id) = opt.source {
+        print!("{}", submission_source(&atc, contest_id, id).await?);
+        return Ok(());
+    }
+
+    let since = opt.since.map(|since| chrono::Utc::now() - since);
+    let members = project::load_members(package);
+    let variants = project::load_variants(package);
+
+    let mut filtered = vec![];
+    let mut rejudged = BTreeMap::new();
+    let mut page = 1;
+    'pages: loop {
+        let (submissions, num_pages) = atc.submissions(contest_id, page).await?;
+        rejudged.extend(detect_rejudges(contest_id, &submissions)?);
+        stats::record_accepted(contest_id, &submissions);
+        for submission in submissions {
+            // Newest first
+            if let (Some(since), Some(time)) = (since, submission.submitted_at()) {
+                if time < since {
+                    break 'pages;
+                }
+            }
+            let matches_problem = problem.is_none_or(|p| submission.is_for(p));
+            let matches_verdict = opt
+                .verdict
+                .as_ref()
+                .is_none_or(|v| mem::discriminant(v) == mem::discriminant(&submission.verdict));
+            let matches_member = opt
+                .member
+                .as_ref()
+                .is_none_or(|m| members.get(&submission.id) == Some(m));
+            if matches_problem && matches_verdict && matches_member {
+                filtered.push(submission);
+            }
+        }
+        if page >= num_pages {
+            break;
+        }
+        page += 1;
+    }
+
+    if filtered.is_empty() {
+        info!("{}", tr!(NoSubmissions));
+    } else {
+        let annotations = [("Member", &members), ("Variant", &variants)];
+        for line in submission_lines(&filtered, &rejudged, &annotations) {
+            println!("{}", line);
+        }
+    }
+    warn_rejudges(&rejudged);
+    Ok(())
+}
+
+/// Source code of the submission, cached since it never changes
+async fn submission_source(atc: &AtCoder, contest_id: &str, id: u64) -> Result<String> {
+    if let Some(source) = cache::load_source(contest_id, id) {
+        return Ok(source);
+    }
+    let source = atc.submission_source(contest_id, id).await?;
+    cache::save_source(contest_id, id, &source)?;
+    Ok(source)
+}
+
+#[derive(StructOpt)]
+struct ResultOpt {
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Submission ID (default: my newest submission in the contest)
+    submission_id: Option<u64>,
+}
+
+async fn result(opt: ResultOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    let contest_id = project::contest_id(package)?;
+
+    let id = match opt.submission_id {
+        Some(id) => id,
+        None => match atc.submissions(contest_id, 1).await?.0.first() {
+            Some(submission) => submission.id,
+            None => {
+                info!("{}", tr!(NoSubmissions));
+                return Ok(());
+            }
+        },
+    };
+
+    let result = atc.submission_result(contest_id, id).await?;
+    println!(
+        "Submission {}: {}",
+        id,
+        result.verdict.style().apply_to(&result.verdict)
+    );
+    if !result.cases.is_empty() {
+        for line in case_lines(&result.cases) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+fn case_lines(cases: &[CaseResult]) -> Vec<String> {
+    let header = ["Case", "Verdict", "Exec", "Memory"].map(str::to_owned);
+    let rows = cases
+        .iter()
+        .map(|c| {
+            vec![
+                c.name.clone(),
+                c.verdict.style().apply_to(&c.verdict).to_string(),
+                c.exec_time.clone(),
+                c.memory.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    table::lines(&header, &rows)
+}
+
+#[derive(StructOpt)]
+struct StandingsOpt {
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Number of the rows from the top to show besides mine
+    #[structopt(long, default_value = "10")]
+    top: usize,
+    /// Show only the rows of the rivals in the config and mine
+    #[structopt(long, conflicts_with("top"))]
+    rivals: bool,
+}
+
+async fn standings(opt: StandingsOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let atc = AtCoder::new(&session_file()?)?;
+    let contest_id = project::contest_id(package)?;
+
+    let standings = atc.standings(contest_id).await?;
+    let username = atc.username().await?;
+    let me = username.as_deref().and_then(|u| standings.row(u));
+    let frozen = standings.is_frozen();
+
+    if frozen {
+        println!(
+            "Standings of {} {}",
+            contest_id,
+            Style::new().cyan().bold().apply_to("(frozen)")
+        );
+    } else {
+        println!("Standings of {}", contest_id);
+    }
+    let mut rows = if opt.rivals {
+        let rivals = read_config()?.atcoder.rivals;
+        ensure!(
+            !rivals.is_empty(),
+            "No rivals in the config. Give them in `atcoder.rivals`"
+        );
+        for rival in &rivals {
+            if standings.row(rival).is_none() {
+                info!("{} is not in the standings", rival);
+            }
+        }
+        standings
+            .standings_data
+            .iter()
+            .filter(|row| {
+                rivals
+                    .iter()
+                    .any(|rival| row.user_screen_name.eq_ignore_ascii_case(rival))
+            })
+            .collect::<Vec<_>>()
+    } else {
+        standings
+            .standings_data
+            .iter()
+            .take(opt.top)
+            .collect::<Vec<_>>()
+    };
+    if let Some(me) = me.filter(|me| !rows.iter().any(|row| std::ptr::eq(*row, *me))) {
+        rows.push(me);
+        rows.sort_by_key(|row| row.rank);
+    }
+    for line in standings_lines(&standings, &rows, me) {
+        println!("{}", line);
+    }
+
+    let me = match me {
+        Some(me) if frozen => me,
+        _ => return Ok(()),
+    };
+    let mut submissions = vec![];
+    let mut page = 1;
+    loop {
+        let (mut s, num_pages) = atc.submissions(contest_id, page).await?;
+        submissions.append(&mut s);
+        if page >= num_pages {
+            break;
+        }
+        page += 1;
+    }
+    // For the full scores of the problems, which the standings lack
+    let contest_info = atc.contest_info(contest_id).await.ok();
+    let pending = pending_gains(&standings, me, &submissions, contest_info.as_ref());
+    if pending.is_empty() {
+        println!("No pending submissions of mine. Rank: {}", me.rank);
+        return Ok(());
+    }
+    let (best, worst) = rank_range(&standings, me, pending.values().sum());
+    println!(
+        "Pending: {}. Rank: {} (all accepted) to {} (all rejected)",
+        pending.keys().join(", "),
+        best,
+        worst,
+    );
+    Ok(())
+}
+
+/// Score (in hundredths) that each task with my pending submissions would add if accepted, by
+/// the assignment (e.g. `A`).
+///
+/// Pending ones are those not reflected in the frozen standings: marked pending there, still
+/// being judged, or accepted with a higher score than shown. Unknown scores are the full scores
+/// of the problems, or else estimated by the best score of the others.
+fn pending_gains(
+    standings: &Standings,
+    me: &StandingsRow,
+    submissions: &[Submission],
+    contest_info: Option<&ContestInfo>,
+) -> BTreeMap<String, i64> {
+    let mut gains = BTreeMap::new();
+    for task in &standings.task_info {
+        let mine = me.task_results.get(&task.task_screen_name);
+        let current = mine.map_or(0, |r| r.score);
+        let best_of_others = standings
+            .standings_data
+            .iter()
+            .filter_map(|row| row.task_results.get(&task.task_screen_name))
+            .filter(|r| !r.pending)
+            .map(|r| r.score)
+            .max()
+            .unwrap_or(0);
+        let full_score = contest_info
+            .and_then(|info| info.problem_by_task(&task.task_screen_name))
+            .and_then(|problem| problem.score)
+            .and_then(|score| score.hundredths());
+        let submissions = submissions
+            .iter()
+            .filter(|s| s.task == task.task_screen_name)
+            .collect::<Vec<_>>();
+
+        let accepted = submissions
+            .iter()
+            .filter(|s| s.verdict == Verdict::Ac)
+            .filter_map(|s| s.score.parse::<f64>().ok())
+            .map(|score| (score * 100.).round() as i64)
+            .max()
+            .filter(|&score| score > current);
+        let judging =
+            mine.is_some_and(|r| r.pending) || submissions.iter().any(|s| !s.verdict.is_judged());
+        let gain = match (accepted, judging) {
+            (Some(score), false) => score,
+            (accepted, true) => accepted
+                .unwrap_or(0)
+                .max(full_score.unwrap_or(best_of_others)),
+            (None, false) => continue,
+        } - current;
+        if gain > 0 {
+            gains.insert(task.assignment.clone(), gain);
+        }
+    }
+    gains
+}
+
+/// My best and worst possible ranks on the frozen standings, breaking ties by the time shown.
+fn rank_range(standings: &Standings, me: &StandingsRow, gain: i64) -> (usize, usize) {
+    let mine = &me.total_result;
+    let others = standings
+        .standings_data
+        .iter()
+        .filter(|row| !std::ptr::eq(*row, me))
+        .map(|row| &row.total_result)
+        .collect::<Vec<_>>();
+    let rank = |score: i64, ahead_on_tie: fn(i64, i64) -> bool| {
+        1 + others
+            .iter()
+            .filter(|r| {
+                r.score > score || (r.score == score && ahead_on_tie(r.elapsed, mine.elapsed))
+            })
+            .count()
+    };
+    (
+        rank(mine.score + gain, |theirs, mine| theirs < mine),
+        rank(mine.score, |theirs, mine| theirs <= mine),
+    )
+}
+
+fn standings_lines(
+    standings: &Standings,
+    rows: &[&StandingsRow],
+    me: Option<&StandingsRow>,
+) -> Vec<String> {
+    let points = |score: i64| {
+        if score % 100 == 0 {
+            (score / 100).to_string()
+        } else {
+            format!("{:.2}", score as f64 / 100.)
+        }
+    };
+    let elapsed = |ns: i64| {
+        let secs = ns / 1_000_000_000;
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    };
+
+    let mut header = vec!["Rank".to_owned(), "User".to_owned(), "Score".to_owned()];
+    header.extend(standings.task_info.iter().map(|t| t.assignment.clone()));
+    let cells = rows
+        .iter()
+        .map(|row| {
+            let mut cells = vec![
+                row.rank.to_string(),
+                row.user_screen_name.clone(),
+                format!(
+                    "{} ({})",
+                    points(row.total_result.score),
+                    elapsed(row.total_result.elapsed)
+                ),
+            ];
+            cells.extend(standings.task_info.iter().map(|task| {
+                match row.task_results.get(&task.task_screen_name) {
+                    Some(r) if r.pending => "?".to_owned(),
+                    Some(r) if r.score > 0 => points(r.score),
+                    Some(_) => "-".to_owned(),
+                    None => "".to_owned(),
+                }
+            }));
+            cells
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = table::lines(&header, &cells);
+    for (row, line) in rows.iter().zip(&mut lines[1..]) {
+        if me.is_some_and(|me| std::ptr::eq(*row, me)) {
+            *line = Style::new().bold().apply_to(&line).to_string();
+        }
+    }
+    lines
+}
+
+/// Compares the verdicts with the ones seen before and records them.
+///
+/// Returns the previous verdicts of the submissions whose verdicts have changed, i.e. have been
+/// rejudged.
+pub(crate) fn detect_rejudges(
+    contest_id: &str,
+    submissions: &[Submission],
+) -> Result<BTreeMap<u64, String>> {
+    let mut seen = cache::load_verdicts(contest_id);
+    let mut rejudged = BTreeMap::new();
+    for submission in submissions.iter().filter(|s| s.verdict.is_judged()) {
+        let verdict = submission.verdict.to_string();
+        if let Some(prev) = seen.insert(submission.id, verdict.clone()) {
+            if prev != verdict {
+                rejudged.insert(submission.id, prev);
+            }
+        }
+    }
+    cache::save_verdicts(contest_id, &seen)?;
+    Ok(rejudged)
+}
+
+pub(crate) fn warn_rejudges(rejudged: &BTreeMap<u64, String>) {
+    if !rejudged.is_empty() {
+        warn!(
+            "{} submission(s) have been rejudged since the last check: {}",
+            rejudged.len(),
+            rejudged.keys().join(", "),
+        );
+    }
+}
+
+// The verdicts of rejudged submissions are highlighted with the previous ones, and the members
+// are shown if any of the submissions are recorded with them.
+fn submission_lines(
+    submissions: &[Submission],
+    rejudged: &BTreeMap<u64, String>,
+    // Columns recorded by `submit` (e.g. members), shown only if any of the submissions has one
+    annotations: &[(&str, &BTreeMap<u64, String>)],
+) -> Vec<String> {
+    let annotations = annotations
+        .iter()
+        .filter(|(_, records)| submissions.iter().any(|s| records.contains_key(&s.id)))
+        .collect::<Vec<_>>();
+    let mut header = [
+        "Time", "Task", "Language", "Score", "Verdict", "Exec", "Memory", "ID",
+    ]
+    .map(str::to_owned)
+    .to_vec();
+    header.extend(annotations.iter().map(|(name, _)| (*name).to_owned()));
+    let rows = submissions
+        .iter()
+        .map(|s| {
+            let verdict = match rejudged.get(&s.id) {
+                Some(prev) => Style::new()
+                    .magenta()
+                    .bold()
+                    .apply_to(format!("{} (was {})", s.verdict, prev)),
+                None => s.verdict.style().apply_to(s.verdict.to_string()),
+            };
+            let mut row = vec![
+                s.time.get(5..19).unwrap_or(&s.time).to_owned(),
+                s.task_name.clone(),
+                s.language.clone(),
+                s.score.clone(),
+                verdict.to_string(),
+                s.exec_time.clone().unwrap_or_default(),
+                s.memory.clone().unwrap_or_default(),
+                s.id.to_string(),
+            ];
+            row.extend(
+                annotations
+                    .iter()
+                    .map(|(_, records)| records.get(&s.id).cloned().unwrap_or_default()),
+            );
+            row
+        })
+        .collect::<Vec<_>>();
+
+    table::lines(&header, &rows)
+}
+
+#[derive(StructOpt)]
+struct InfoOpt {
+    /// Contest ID to show the problems of, with the time limits and the scores
+    contest_id: Option<String>,
+}
+
+async fn info(opt: InfoOpt) -> Result<()> {
     let atc = AtCoder::new(&session_file()?)?;
 
     if let Some(username) = atc.username().await? {
-        println!("Logged in as {}.", username);
+        println!("{}", tr!(LoggedInAs, username));
     } else {
-        println!("Not logged in.");
+        println!("{}", tr!(NotLoggedIn));
+    }
+
+    if let Some(contest_id) = &opt.contest_id {
+        let contest_info = contest_info_or_register(&atc, contest_id).await?;
+        println!();
+        for line in problem_lines(contest_info.problems()) {
+            println!("{}", line);
+        }
     }
 
     Ok(())
 }
 
+fn problem_lines(problems: &[Problem]) -> Vec<String> {
+    let header = ["Problem", "Name", "Time limit", "Score"];
+    let rows = problems
+        .iter()
+        .map(|problem| {
+            vec![
+                problem.id.clone(),
+                problem.name.clone(),
+                problem.tle.clone(),
+                problem
+                    .score
+                    .map_or_else(|| "-".to_owned(), |score| score.to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    table::lines(&header.map(str::to_owned), &rows)
+}
+
 #[derive(StructOpt, Debug)]
 struct WarmupOpt {
     /// [cargo] Package(s) to warm up
@@ -776,7 +3009,7 @@ fn warmup_for(metadata: &Metadata, specs: Option<&[impl AsRef<str>]>) -> Result<
 
     for member in members {
         if let Some(first_bin) = member.all_bins().first() {
-            println!("Warming up debug build for `{}`...", member.name);
+            info!("Warming up debug build for `{}`...", member.name);
 
             let stat = Command::new("cargo")
                 .arg("build")
@@ -787,28 +3020,88 @@ fn warmup_for(metadata: &Metadata, specs: Option<&[impl AsRef<str>]>) -> Result<
                 .status()?;
 
             if !stat.success() {
-                eprintln!("Failed to warm-up");
+                warn!("Failed to warm-up");
             }
 
-            println!("Warming up release build for `{}`...", member.name);
+            info!("Warming up release build for `{}`...", member.name);
 
             let stat = Command::new("cargo")
                 .arg("build")
                 .arg("--manifest-path")
                 .arg(&member.manifest_path)
-                .arg("--release")
+                .args(build::release_args(member))
                 .arg("--bin")
                 .arg(&first_bin.name)
                 .status()?;
 
             if !stat.success() {
-                eprintln!("Failed to warm-up");
+                warn!("Failed to warm-up");
             }
         }
     }
     Ok(())
 }
 
+#[derive(StructOpt)]
+struct PrintOpt {
+    /// Problem ID (must be same as binary name)
+    problem_id: String,
+    /// [cargo] Package with the target to print
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Output file (default: <problem-id>.ps, or <problem-id>.pdf with `--pdf`)
+    #[structopt(short, long, value_name("PATH"))]
+    output: Option<PathBuf>,
+    /// Convert to PDF with `ps2pdf` (Ghostscript)
+    #[structopt(long)]
+    pdf: bool,
+    /// Team member to show on the header
+    #[structopt(long, value_name("NAME"), env = "CARGO_ATCODER_MEMBER")]
+    member: Option<String>,
+}
+
+fn print(opt: PrintOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let src_path = match project::problem_setting(package, &opt.problem_id).file {
+        Some(file) => file,
+        None => package.find_bin(&opt.problem_id)?.src_path.clone().into(),
+    };
+    let source = fs::read_to_string(&src_path)
+        .with_context(|| format!("Failed to read {}", src_path.display()))?;
+
+    let mut title = format!("{} {}", package.name, opt.problem_id);
+    if let Some(member) = &opt.member {
+        title += &format!(" ({})", member);
+    }
+    let ps = print::postscript(&title, &source);
+
+    let ext = if opt.pdf { "pdf" } else { "ps" };
+    let problem_id = &opt.problem_id;
+    let output = opt
+        .output
+        .unwrap_or_else(|| Path::new(problem_id).with_extension(ext));
+    if opt.pdf {
+        let ps_path = env::temp_dir().join(format!("cargo-atcoder-print-{}.ps", process::id()));
+        fs::write(&ps_path, ps)?;
+        let status = Command::new("ps2pdf")
+            .arg(&ps_path)
+            .arg(&output)
+            .status()
+            .with_context(|| "Failed to run `ps2pdf`. Install Ghostscript, or omit `--pdf`");
+        let _ = fs::remove_file(&ps_path);
+        ensure!(status?.success(), "Failed to convert to PDF");
+    } else {
+        fs::write(&output, ps).with_context(|| format!("Failed to write {}", output.display()))?;
+    }
+    info!("Wrote {}", output.display());
+    Ok(())
+}
+
 #[derive(StructOpt)]
 struct GenBinaryOpt {
     /// Problem ID to make binary
@@ -825,6 +3118,9 @@ struct GenBinaryOpt {
     /// Do not use UPX even if it is available
     #[structopt(long)]
     no_upx: bool,
+    /// Minify the code if it exceeds the size limit
+    #[structopt(long)]
+    minify: bool,
 }
 
 fn gen_binary(opt: GenBinaryOpt) -> Result<()> {
@@ -833,12 +3129,13 @@ fn gen_binary(opt: GenBinaryOpt) -> Result<()> {
     let (target, package) = metadata.find_bin(&opt.problem_id)?;
     let config = read_config()?;
     let src = gen_binary_source(&metadata, package, target, &config, opt.column, opt.no_upx)?;
+    let src = check_source_size(src, opt.minify);
     let filename = opt
         .output
         .clone()
         .unwrap_or_else(|| PathBuf::from(format!("{}-bin.rs", opt.problem_id)));
     fs::write(&filename, &src)?;
-    println!("Wrote code to `{}`", filename.display());
+    info!("Wrote code to `{}`", filename.display());
     Ok(())
 }
 
@@ -846,7 +3143,27 @@ fn gen_binary(opt: GenBinaryOpt) -> Result<()> {
 #[structopt(bin_name("cargo"))]
 enum Opt {
     #[structopt(name = "atcoder")]
-    AtCoder(OptAtCoder),
+    AtCoder(AtCoderOpt),
+}
+
+#[derive(StructOpt)]
+struct AtCoderOpt {
+    // Read in `logging::init`, `ci::init`, `paths::init`, `recording::init`, `i18n::init` and
+    // `http::init` instead since they may also be given to the subcommands
+    #[structopt(flatten)]
+    _log: logging::LogOpt,
+    #[structopt(flatten)]
+    _non_interactive: ci::NonInteractiveOpt,
+    #[structopt(flatten)]
+    _state_dir: paths::StateDirOpt,
+    #[structopt(flatten)]
+    _record: recording::RecordOpt,
+    #[structopt(flatten)]
+    _lang: i18n::LangOpt,
+    #[structopt(flatten)]
+    _http: http::HttpOpt,
+    #[structopt(subcommand)]
+    cmd: OptAtCoder,
 }
 
 #[derive(StructOpt)]
@@ -859,16 +3176,77 @@ enum OptAtCoder {
     // Logout,
     /// Clear session data (cookie store in HTTP client)
     ClearSession,
-    /// Show session information
-    Info,
+    /// Delete the session, the cache or the config, after listing them
+    Reset(reset::ResetOpt),
+    /// Export the session to log in on another machine, or import it
+    Session(session::SessionOpt),
+    /// Show session information, and the problems of a contest if given
+    Info(InfoOpt),
     /// Warmup (pre-compile dependencies)
     Warmup(WarmupOpt),
     /// Test sample cases
     Test(TestOpt),
+    /// Run a solution with custom input without testing
+    Run(RunOpt),
+    /// Show the problem statement
+    Statement(StatementOpt),
+    /// Open the page of the contest (or of the problem) in the browser
+    Open(OpenOpt),
+    /// Show the status of my submissions, updating until they are judged
+    Status(StatusOpt),
+    /// List my submissions in the contest
+    Submissions(SubmissionsOpt),
+    /// Show the verdict of each case of a submission
+    Result(ResultOpt),
     /// Submit solution
     Submit(SubmitOpt),
+    /// Show the standings, with the range of my rank while they are frozen
+    Standings(StandingsOpt),
+    /// Show the rating history of a user as a chart and a table
+    Rating(rating::RatingOpt),
+    /// List the editorials of the contest, saving the official ones for reading offline
+    Editorial(editorial::EditorialOpt),
+    /// Create a brute-force solution and an input generator for `stress`
+    Brute(stress::BruteOpt),
+    /// Compare the solution with the brute-force one on random inputs
+    Stress(stress::StressOpt),
+    /// Estimate the time complexity of a solution from the running times on generated inputs
+    Complexity(complexity::ComplexityOpt),
+    /// Compare the running times and the memory usage of two solutions on the same inputs
+    Race(race::RaceOpt),
     /// Generate rustified binary
     GenBinary(GenBinaryOpt),
+    /// Generate a printable PostScript (or PDF) of the source with line numbers
+    Print(PrintOpt),
+    /// Check the environment before a contest
+    Doctor(doctor::DoctorOpt),
+    /// Score solutions locally for heuristic contests
+    Ahc(ahc::AhcOpt),
+    /// Manage the template for new projects
+    Template(template::TemplateOpt),
+    /// Import sample cases that could not be scraped
+    Testcase(testcase::TestcaseOpt),
+    /// Run the samples of all the solutions with their problems given, and report the results
+    Verify(verify::VerifyOpt),
+    /// Show the solve times of the problems recorded by `new`, `test` and `status`
+    Stats(stats::StatsOpt),
+    /// Explain the verdict of the last failing sample in `test`, with hints
+    Explain(explain::ExplainOpt),
+    /// Create a project of the problems of the past contests picked by the points
+    Practice(practice::PracticeOpt),
+    /// Create a project of a problem a day slightly above the rating, and show the streak
+    Daily(daily::DailyOpt),
+    /// Edit the notes on a problem in `notes/<problem-id>.md`
+    Note(note::NoteOpt),
+    /// Mark a problem solved with the help of the editorial or hints, to review it later
+    Mark(review::MarkOpt),
+    /// Create a project of the marked problems due for review
+    Review(review::ReviewOpt),
+    /// Update cargo-atcoder to the latest release
+    SelfUpdate(self_update::SelfUpdateOpt),
+
+    /// Open the editor, the tests, the submissions and a shell for a contest in tmux or zellij
+    Workspace(workspace::WorkspaceOpt),
 
     /// [WIP] Watch filesystem for automatic submission
     #[cfg(feature = "watch")]
@@ -876,22 +3254,99 @@ enum OptAtCoder {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let Opt::AtCoder(opt) = Opt::from_args();
+async fn main() {
+    shutdown::init();
+    let result = dispatch().await;
+    timer::stop();
+    if let Err(err) = result {
+        eprintln!("Error: {:?}", err);
+        process::exit(ci::exit_code(&err));
+    }
+}
+
+async fn dispatch() -> Result<()> {
+    let matches = match Opt::clap().get_matches_safe() {
+        Ok(matches) => matches,
+        // `--help` and `--version`, which print to stdout and exit with 0
+        Err(err) if !err.use_stderr() => err.exit(),
+        Err(err) => {
+            eprintln!("{}", err.message);
+            process::exit(ci::OTHER_EXIT_CODE);
+        }
+    };
+    let Opt::AtCoder(AtCoderOpt { cmd, .. }) = Opt::from_clap(&matches);
+
+    // The global flags may be given before or after the subcommands, and clap records them in
+    // the matches of that level only.
+    let mut levels = vec![&matches];
+    while let (_, Some(sub)) = levels.last().unwrap().subcommand() {
+        levels.push(sub);
+    }
+    logging::init(&levels);
+    ci::init(&levels);
+    paths::init(&levels);
+    paths::migrate()?;
+    recording::init(&levels)?;
+
+    // `reset` works even with a broken config, which `reset --config` fixes, and does not create
+    // the config just to read the language.
+    if let OptAtCoder::Reset(opt) = cmd {
+        let lang = fs::read_to_string(config::config_file()?)
+            .ok()
+            .and_then(|s| toml::from_str::<Config>(&s).ok())
+            .and_then(|config| config.atcoder.lang);
+        i18n::init(&levels, lang.as_deref())?;
+        return reset::reset(opt);
+    }
 
-    let _ = read_config()?; // for checking config syntax
+    let config = read_config()?; // also for checking config syntax
+    i18n::init(&levels, config.atcoder.lang.as_deref())?;
+    http::init(&levels, config.http.as_ref())?;
+    selectors::init(&config.selectors)?;
+    stack::init(config.atcoder.stack_size.as_deref())?;
 
     use OptAtCoder::*;
-    match opt {
+    match cmd {
         New(opt) => new_project(opt).await,
         Login => login().await,
         // Logout => unimplemented!(),
         ClearSession => clear_session(),
-        Info => info().await,
+        Reset(_) => unreachable!(),
+        Session(opt) => session::session(opt),
+        Info(opt) => info(opt).await,
         Warmup(opt) => warmup(opt),
         Test(opt) => test(opt).await,
+        Run(opt) => run(opt),
+        Statement(opt) => statement(opt).await,
+        Open(opt) => open_page(opt).await,
+        Status(opt) => status(opt).await,
+        Result(opt) => result(opt).await,
+        Submissions(opt) => submissions(opt).await,
         Submit(opt) => submit(opt).await,
+        Standings(opt) => standings(opt).await,
+        Editorial(opt) => editorial::editorial(opt).await,
+        Rating(opt) => rating::rating(opt).await,
+        Brute(opt) => stress::brute(opt),
+        Stress(opt) => stress::stress(opt),
+        Complexity(opt) => complexity::complexity(opt),
+        Race(opt) => race::race(opt),
         GenBinary(opt) => gen_binary(opt),
+        Print(opt) => print(opt),
+        Doctor(opt) => doctor::doctor(opt).await,
+        Ahc(opt) => ahc::ahc(opt).await,
+        Template(opt) => template::template(opt).await,
+        Testcase(opt) => testcase::testcase(opt),
+        Verify(opt) => verify::verify(opt).await,
+        Stats(opt) => stats::stats(opt),
+        Explain(opt) => explain::explain(opt),
+        Practice(opt) => practice::practice(opt).await,
+        Daily(opt) => daily::daily(opt).await,
+        Note(opt) => note::note(opt),
+        Mark(opt) => review::mark(opt).await,
+        Review(opt) => review::review(opt).await,
+        SelfUpdate(opt) => self_update::self_update(opt).await,
+
+        Workspace(opt) => workspace::workspace(opt).await,
 
         #[cfg(feature = "watch")]
         Watch(opt) => watch::watch(opt).await,
@@ -3,6 +3,8 @@ use cargo_metadata::{Metadata, MetadataCommand, Package, Resolve, Target};
 use std::{env, path::Path, process::Command, str};
 use url::Url;
 
+/// Metadata of the project containing `cwd`, which is found in the ancestors like `cargo` does,
+/// so that the commands work in `src/bin` and so on.
 pub(crate) fn cargo_metadata(manifest_path: Option<&Path>, cwd: &Path) -> anyhow::Result<Metadata> {
     // with `--no-deps`, `cargo metadata` does not update the lockfile properly.
     let mut cmd = MetadataCommand::new();
@@ -10,6 +12,13 @@ pub(crate) fn cargo_metadata(manifest_path: Option<&Path>, cwd: &Path) -> anyhow
         cmd.manifest_path(manifest_path);
     }
     cmd.current_dir(cwd).exec().map_err(|err| match err {
+        cargo_metadata::Error::CargoMetadata { stderr } if stderr.contains("could not find") => {
+            anyhow!(
+                "{}\nRun in the directory of a project created by `cargo atcoder new`, or give \
+                 `--manifest-path`",
+                stderr.trim_end().trim_start_matches("error: ")
+            )
+        }
         cargo_metadata::Error::CargoMetadata { stderr } => anyhow!("{}", stderr.trim_end()),
         err => err.into(),
     })
@@ -43,6 +52,9 @@ impl MetadataExt for Metadata {
         let stdout = str::from_utf8(&output.stdout)?.trim_end();
         let stderr = str::from_utf8(&output.stderr)?.trim_end();
         if !output.status.success() {
+            if spec.is_none() && stderr.contains("virtual manifest") {
+                bail!("Give the package of the contest with `-p` in the root of the workspace");
+            }
             bail!("{}", stderr.trim_start_matches("error: "));
         }
 
@@ -73,6 +85,8 @@ impl MetadataExt for Metadata {
 
 pub(crate) trait PackageExt {
     fn all_bins(&self) -> Vec<&Target>;
+    /// The bins except the scratchpads, which are `scratch_*` and the ones matching the patterns
+    fn solution_bins(&self, scratch_bins: &[String]) -> Vec<&Target>;
     fn find_bin<'a>(&'a self, name: &str) -> anyhow::Result<&'a Target>;
 }
 
@@ -81,6 +95,12 @@ impl PackageExt for Package {
         all_bins(self).collect()
     }
 
+    fn solution_bins(&self, scratch_bins: &[String]) -> Vec<&Target> {
+        all_bins(self)
+            .filter(|t| !is_scratch_bin(&t.name, scratch_bins))
+            .collect()
+    }
+
     fn find_bin<'a>(&'a self, name: &str) -> anyhow::Result<&'a Target> {
         all_bins(self)
             .find(|t| t.name == name)
@@ -101,3 +121,31 @@ fn all_bins(package: &Package) -> impl Iterator<Item = &Target> {
         .iter()
         .filter(|Target { kind, .. }| kind.contains(&"bin".to_owned()))
 }
+
+/// Whether the bin is a scratchpad for experiments, which is not tested, submitted or verified
+/// with the others. The patterns are names with `*` for any string.
+pub(crate) fn is_scratch_bin(name: &str, scratch_bins: &[String]) -> bool {
+    name.starts_with("scratch_")
+        || scratch_bins.iter().any(|pattern| {
+            let mut parts = pattern.split('*');
+            let first = parts.next().unwrap_or_default();
+            let mut rest = match name.strip_prefix(first) {
+                Some(rest) => rest,
+                None => return false,
+            };
+            let parts = parts.collect::<Vec<_>>();
+            match parts.split_last() {
+                // no `*`
+                None => rest.is_empty(),
+                Some((last, middle)) => {
+                    for part in middle {
+                        match rest.find(part) {
+                            Some(i) => rest = &rest[i + part.len()..],
+                            None => return false,
+                        }
+                    }
+                    rest.ends_with(last)
+                }
+            }
+        })
+}
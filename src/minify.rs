@@ -0,0 +1,431 @@
+/// Minifies Rust source code without changing its meaning.
+///
+/// Comments are removed, blank lines are dropped, indentation and runs of
+/// whitespace are collapsed. String, raw string and char literals are copied
+/// verbatim.
+pub(crate) fn minify(src: &str) -> String {
+    let cs = src.chars().collect::<Vec<_>>();
+    let mut ret = String::with_capacity(src.len());
+    let mut i = 0;
+
+    while i < cs.len() {
+        let c = cs[i];
+        let next = cs.get(i + 1).copied();
+
+        if c == '/' && next == Some('/') {
+            while i < cs.len() && cs[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < cs.len() {
+                if cs[i] == '/' && cs.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if cs[i] == '*' && cs.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            push_space(&mut ret);
+        } else if c == '"' {
+            i = copy_string(&cs, i, &mut ret);
+        } else if let Some(hashes) = raw_string_prefix(&cs, i) {
+            i = copy_raw_string(&cs, i, hashes, &mut ret);
+        } else if c == '\'' {
+            i = copy_char_or_lifetime(&cs, i, &mut ret);
+        } else if c == '\n' {
+            while ret.ends_with(' ') {
+                ret.pop();
+            }
+            if !ret.is_empty() && !ret.ends_with('\n') {
+                ret.push('\n');
+            }
+            i += 1;
+        } else if c.is_whitespace() {
+            push_space(&mut ret);
+            i += 1;
+        } else {
+            ret.push(c);
+            i += 1;
+        }
+    }
+
+    while ret.ends_with(' ') {
+        ret.pop();
+    }
+    if !ret.is_empty() && !ret.ends_with('\n') {
+        ret.push('\n');
+    }
+    ret
+}
+
+/// Removes the comments, keeping the rest of the source as is. Lines with only comments are
+/// dropped.
+pub(crate) fn strip_comments(src: &str) -> String {
+    let cs = src.chars().collect::<Vec<_>>();
+    let mut ret = String::with_capacity(src.len());
+    let mut line_start = 0;
+    let mut commented = false;
+    let mut i = 0;
+
+    while i < cs.len() {
+        let c = cs[i];
+        let next = cs.get(i + 1).copied();
+
+        if c == '/' && next == Some('/') {
+            while i < cs.len() && cs[i] != '\n' {
+                i += 1;
+            }
+            commented = true;
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < cs.len() {
+                if cs[i] == '/' && cs.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if cs[i] == '*' && cs.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            commented = true;
+        } else if c == '"' {
+            i = copy_string(&cs, i, &mut ret);
+        } else if let Some(hashes) = raw_string_prefix(&cs, i) {
+            i = copy_raw_string(&cs, i, hashes, &mut ret);
+        } else if c == '\'' {
+            i = copy_char_or_lifetime(&cs, i, &mut ret);
+        } else if c == '\n' {
+            if !end_line(&mut ret, line_start, commented) {
+                ret.push('\n');
+            }
+            line_start = ret.len();
+            commented = false;
+            i += 1;
+        } else {
+            ret.push(c);
+            i += 1;
+        }
+    }
+
+    end_line(&mut ret, line_start, commented);
+    ret
+}
+
+/// Macros of the debug prints removed by `strip_debug_prints`
+const DEBUG_MACROS: &[&str] = &["eprintln", "eprint", "dbg", "debug"];
+
+/// Removes the debug prints (`eprintln!`, `eprint!`, `dbg!` and `debug!`) except the ones gated
+/// by `#[cfg(debug_assertions)]` or `cfg!(debug_assertions)` and in `macro_rules!`, returning the
+/// line and the macro of each removed one. `dbg!(x)` in an expression is replaced with `(x)`.
+pub(crate) fn strip_debug_prints(src: &str) -> (String, Vec<(usize, &'static str)>) {
+    let cs = src.chars().collect::<Vec<_>>();
+    let mut ret = String::with_capacity(src.len());
+    let mut removed = vec![];
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut stripped = false;
+    // Whether each of the open brackets is gated, and whether the next block is
+    let mut gated = Vec::<bool>::new();
+    let mut gate_next = false;
+    let mut i = 0;
+
+    while i < cs.len() {
+        let c = cs[i];
+        let next = cs.get(i + 1).copied();
+        let boundary = i == 0 || !(is_ident_char(cs[i - 1]) || cs[i - 1] == ':');
+        let prev_len = ret.len();
+
+        if c == '/' && (next == Some('/') || next == Some('*')) {
+            let mut comment = String::new();
+            i = skip_comment(&cs, i, &mut comment);
+            ret += &comment;
+        } else if c == '"' {
+            i = copy_string(&cs, i, &mut ret);
+        } else if let Some(hashes) = raw_string_prefix(&cs, i) {
+            i = copy_raw_string(&cs, i, hashes, &mut ret);
+        } else if c == '\'' {
+            i = copy_char_or_lifetime(&cs, i, &mut ret);
+        } else if let Some(gate) = [
+            "#[cfg(debug_assertions)]",
+            "cfg!(debug_assertions)",
+            "macro_rules!",
+        ]
+        .iter()
+        .find(|gate| (c == '#' || boundary) && starts_with(&cs, i, gate))
+        {
+            ret += gate;
+            i += gate.chars().count();
+            gate_next = true;
+        } else if let Some((name, open)) = DEBUG_MACROS
+            .iter()
+            .filter(|_| boundary && !gate_next && !gated.last().copied().unwrap_or(false))
+            .find_map(|name| Some((*name, debug_macro_call(&cs, i, name)?)))
+        {
+            let close = skip_group(&cs, open);
+            let statement = ret
+                .trim_end()
+                .chars()
+                .last()
+                .is_none_or(|c| matches!(c, ';' | '{' | '}'));
+            let end = (close..cs.len())
+                .find(|&j| !cs[j].is_whitespace())
+                .filter(|&j| statement && cs[j] == ';')
+                .map_or(close, |j| j + 1);
+            if end == close {
+                // used as an expression
+                if name == "dbg" {
+                    ret.push('(');
+                    ret.extend(&cs[open + 1..close - 1]);
+                    ret.push(')');
+                } else {
+                    ret += "()";
+                }
+            }
+            removed.push((line, name));
+            line += cs[i..end].iter().filter(|&&c| c == '\n').count();
+            stripped = true;
+            i = end;
+            continue;
+        } else if c == '\n' {
+            if stripped {
+                let len = ret.trim_end_matches([' ', '\t']).len().max(line_start);
+                ret.truncate(len);
+            }
+            if !(stripped && ret.len() == line_start) {
+                ret.push('\n');
+            }
+            line_start = ret.len();
+            stripped = false;
+            line += 1;
+            i += 1;
+            continue;
+        } else {
+            match c {
+                '{' | '(' | '[' => {
+                    gated.push(gated.last().copied().unwrap_or(false) || (gate_next && c == '{'));
+                    if c == '{' {
+                        gate_next = false;
+                    }
+                }
+                '}' | ')' | ']' => {
+                    gated.pop();
+                }
+                ';' => gate_next = false,
+                _ => {}
+            }
+            ret.push(c);
+            i += 1;
+        }
+        line += ret[prev_len..].matches('\n').count();
+    }
+
+    if stripped && ret[line_start..].trim().is_empty() {
+        ret.truncate(line_start);
+    }
+    (ret, removed)
+}
+
+fn starts_with(cs: &[char], i: usize, s: &str) -> bool {
+    s.chars()
+        .enumerate()
+        .all(|(k, c)| cs.get(i + k) == Some(&c))
+}
+
+// Index of the opening bracket if `name!(`, `name![` or `name!{` starts at `i`
+fn debug_macro_call(cs: &[char], i: usize, name: &str) -> Option<usize> {
+    let mut j = i + name.chars().count();
+    if !starts_with(cs, i, name) || cs.get(j) != Some(&'!') {
+        return None;
+    }
+    j += 1;
+    while cs.get(j).is_some_and(|c| c.is_whitespace()) {
+        j += 1;
+    }
+    matches!(cs.get(j), Some('(' | '[' | '{')).then_some(j)
+}
+
+// Returns the index after the bracket closing the one at `open`.
+fn skip_group(cs: &[char], open: usize) -> usize {
+    let mut scratch = String::new();
+    let mut depth = 0;
+    let mut i = open;
+    while i < cs.len() {
+        let c = cs[i];
+        let next = cs.get(i + 1).copied();
+        if c == '/' && (next == Some('/') || next == Some('*')) {
+            i = skip_comment(cs, i, &mut scratch);
+        } else if c == '"' {
+            i = copy_string(cs, i, &mut scratch);
+        } else if let Some(hashes) = raw_string_prefix(cs, i) {
+            i = copy_raw_string(cs, i, hashes, &mut scratch);
+        } else if c == '\'' {
+            i = copy_char_or_lifetime(cs, i, &mut scratch);
+        } else {
+            match c {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+    i
+}
+
+// Copies the line comment or the (nested) block comment starting at `start`.
+fn skip_comment(cs: &[char], start: usize, ret: &mut String) -> usize {
+    let mut i = start;
+    if cs.get(i + 1) == Some(&'/') {
+        while i < cs.len() && cs[i] != '\n' {
+            ret.push(cs[i]);
+            i += 1;
+        }
+        return i;
+    }
+    let mut depth = 0;
+    while i < cs.len() {
+        if cs[i] == '/' && cs.get(i + 1) == Some(&'*') {
+            depth += 1;
+            *ret += "/*";
+            i += 2;
+        } else if cs[i] == '*' && cs.get(i + 1) == Some(&'/') {
+            depth -= 1;
+            *ret += "*/";
+            i += 2;
+            if depth == 0 {
+                break;
+            }
+        } else {
+            ret.push(cs[i]);
+            i += 1;
+        }
+    }
+    i
+}
+
+// Drops the trailing whitespace left by a comment. Returns whether nothing else remains on the
+// line, which is then dropped.
+fn end_line(ret: &mut String, line_start: usize, commented: bool) -> bool {
+    if !commented {
+        return false;
+    }
+    let len = ret.trim_end_matches([' ', '\t']).len().max(line_start);
+    ret.truncate(len);
+    ret.len() == line_start
+}
+
+fn push_space(ret: &mut String) {
+    if !ret.is_empty() && !ret.ends_with('\n') && !ret.ends_with(' ') {
+        ret.push(' ');
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// `"..."`, also used for the body of `b"..."`
+fn copy_string(cs: &[char], start: usize, ret: &mut String) -> usize {
+    ret.push(cs[start]);
+    let mut i = start + 1;
+    while i < cs.len() {
+        ret.push(cs[i]);
+        match cs[i] {
+            '\\' if i + 1 < cs.len() => {
+                ret.push(cs[i + 1]);
+                i += 2;
+            }
+            '"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+// Returns the number of `#`s if a raw string (`r#"..."#` or `br#"..."#`) starts at `i`.
+fn raw_string_prefix(cs: &[char], i: usize) -> Option<usize> {
+    if i > 0 && is_ident_char(cs[i - 1]) {
+        return None;
+    }
+    let mut j = i;
+    if cs.get(j) == Some(&'b') {
+        j += 1;
+    }
+    if cs.get(j) != Some(&'r') {
+        return None;
+    }
+    j += 1;
+    let hashes = cs[j..].iter().take_while(|&&c| c == '#').count();
+    if cs.get(j + hashes) == Some(&'"') {
+        Some(hashes)
+    } else {
+        None
+    }
+}
+
+fn copy_raw_string(cs: &[char], start: usize, hashes: usize, ret: &mut String) -> usize {
+    let mut i = start;
+    while cs[i] != '"' {
+        ret.push(cs[i]);
+        i += 1;
+    }
+    ret.push('"');
+    i += 1;
+    while i < cs.len() {
+        if cs[i] == '"'
+            && cs[i + 1..]
+                .iter()
+                .take(hashes)
+                .filter(|&&c| c == '#')
+                .count()
+                == hashes
+        {
+            ret.extend(&cs[i..i + 1 + hashes]);
+            return i + 1 + hashes;
+        }
+        ret.push(cs[i]);
+        i += 1;
+    }
+    i
+}
+
+// `'a'`, `'\n'`, `'\u{3042}'` or a lifetime/label such as `'a`
+fn copy_char_or_lifetime(cs: &[char], start: usize, ret: &mut String) -> usize {
+    let end = if cs.get(start + 1) == Some(&'\\') {
+        cs.get(start + 3..)
+            .and_then(|rest| rest.iter().position(|&c| c == '\''))
+            .map(|p| start + 3 + p + 1)
+    } else if cs.get(start + 2) == Some(&'\'') {
+        Some(start + 3)
+    } else {
+        None
+    };
+
+    match end {
+        Some(end) if end <= cs.len() => {
+            ret.extend(&cs[start..end]);
+            end
+        }
+        _ => {
+            ret.push('\'');
+            start + 1
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! `note`: notes on a problem in `notes/<problem-id>.md` of the project, edited in `$VISUAL` or
+//! `$EDITOR`.
+
+use crate::{
+    metadata::{self, MetadataExt as _},
+    project::{self, package_dir},
+};
+use anyhow::{ensure, Context as _, Result};
+use std::{env, fs, path::PathBuf, process::Command};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct NoteOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// Print the path to the notes instead of opening them in the editor
+    #[structopt(long)]
+    path: bool,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+pub fn note(opt: NoteOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let problem_id = &opt.problem_id;
+    let path = project::note_path(package_dir(package), problem_id);
+
+    if !path.exists() {
+        // The task is known without the problem list only if routed.
+        let url = match project::routed_task(package, problem_id) {
+            Some((contest_id, task)) => format!(
+                "https://atcoder.jp/contests/{}/tasks/{}\n\n",
+                contest_id, task
+            ),
+            None => "".to_owned(),
+        };
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(
+            &path,
+            format!("# {} {}\n\n{}", package.name, problem_id, url),
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    if opt.path {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|s| !s.is_empty()))
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_owned());
+    // The editor may be given with arguments (e.g. `code -w`).
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(&editor).arg(&path);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(&path);
+        cmd
+    };
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run `{}`", editor))?;
+    ensure!(status.success(), "`{}` failed", editor);
+    Ok(())
+}
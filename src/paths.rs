@@ -0,0 +1,181 @@
+//! The directories of the config, the cache and the state (the session and the records), in the
+//! places of the platform or all in the one given by the global `--state-dir` for a portable install.
+//!
+//! | | Linux | macOS | Windows |
+//! |---|---|---|---|
+//! | config | `$XDG_CONFIG_HOME/cargo-atcoder` | `~/Library/Application Support/cargo-atcoder` | `%APPDATA%\cargo-atcoder` |
+//! | cache | `$XDG_CACHE_HOME/cargo-atcoder` | `~/Library/Caches/cargo-atcoder` | `%LOCALAPPDATA%\cargo-atcoder` |
+//! | state | `$XDG_STATE_HOME/cargo-atcoder` | `~/Library/Application Support/cargo-atcoder` | `%LOCALAPPDATA%\cargo-atcoder` |
+
+use anyhow::{bail, Context as _, Result};
+use log::warn;
+use once_cell::sync::OnceCell;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+use structopt::{clap::ArgMatches, StructOpt};
+
+#[derive(StructOpt)]
+pub(crate) struct StateDirOpt {
+    /// Directory of the config, the cache and the session, instead of the ones of the platform
+    #[structopt(
+        long,
+        global = true,
+        value_name("PATH"),
+        env = "CARGO_ATCODER_STATE_DIR"
+    )]
+    state_dir: Option<PathBuf>,
+}
+
+static STATE_DIR: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+/// Takes `--state-dir` given at any level.
+pub(crate) fn init(matches: &[&ArgMatches<'_>]) {
+    let mut state_dir = None;
+    for matches in matches {
+        state_dir = StateDirOpt::from_clap(matches).state_dir.or(state_dir);
+    }
+    let _ = STATE_DIR.set(state_dir);
+}
+
+fn portable() -> Option<&'static Path> {
+    STATE_DIR.get().and_then(Option::as_deref)
+}
+
+/// The directory of `cargo-atcoder.toml`
+pub(crate) fn config_dir() -> Result<PathBuf> {
+    if let Some(dir) = env::var_os("CARGO_ATCODER_TEST_CONFIG_DIR") {
+        return Ok(dir.into());
+    }
+    if let Some(dir) = portable() {
+        return Ok(dir.to_owned());
+    }
+    Ok(dirs::config_dir()
+        .with_context(|| "Failed to get config directory")?
+        .join("cargo-atcoder"))
+}
+
+pub(crate) fn cache_dir() -> Result<PathBuf> {
+    let dir = if let Some(dir) = env::var_os("CARGO_ATCODER_TEST_CACHE_DIR") {
+        dir.into()
+    } else if let Some(dir) = portable() {
+        dir.join("cache")
+    } else {
+        dirs::cache_dir()
+            .with_context(|| "failed to get cache dir")?
+            .join("cargo-atcoder")
+    };
+    create_dir(dir)
+}
+
+/// The directory of the session, the remembered username and the records (e.g. the daily picks,
+/// the review schedule and the stats), which are not to be lost with the cache. The tests keep
+/// them in the cache.
+pub(crate) fn state_dir() -> Result<PathBuf> {
+    let dir = if let Some(dir) = env::var_os("CARGO_ATCODER_TEST_CACHE_DIR") {
+        dir.into()
+    } else if let Some(dir) = portable() {
+        dir.to_owned()
+    } else {
+        platform_state_dir()
+            .with_context(|| "Failed to get state directory")?
+            .join("cargo-atcoder")
+    };
+    create_dir(dir)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_state_dir() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| Some(dirs::home_dir()?.join(".local").join("state")))
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn platform_state_dir() -> Option<PathBuf> {
+    dirs::data_local_dir()
+}
+
+fn create_dir(dir: PathBuf) -> Result<PathBuf> {
+    if !dir.is_dir() {
+        if dir.exists() {
+            bail!("{} is not directory", dir.display());
+        }
+        fs::create_dir_all(&dir)?;
+    }
+    Ok(dir)
+}
+
+/// Moves the config and the session from the places of the older versions, unless they are in
+/// the new places already.
+pub(crate) fn migrate() -> Result<()> {
+    if env::var_os("CARGO_ATCODER_TEST_CONFIG_DIR").is_some()
+        || env::var_os("CARGO_ATCODER_TEST_CACHE_DIR").is_some()
+        || portable().is_some()
+    {
+        return Ok(());
+    }
+
+    // The config was directly in the config directory, which was `~/Library/Preferences` on
+    // macOS before `dirs` 3.
+    let config = config_dir()?.join("cargo-atcoder.toml");
+    for dir in [dirs::config_dir(), dirs::preference_dir()]
+        .iter()
+        .flatten()
+    {
+        migrate_file(&dir.join("cargo-atcoder.toml"), &config)?;
+    }
+
+    // The session and the records were in the cache.
+    let (cache, state) = (cache_dir()?, state_dir()?);
+    if cache != state {
+        for name in [
+            "session.txt",
+            "username.txt",
+            "daily.toml",
+            "review.toml",
+            "results",
+            "stats",
+        ] {
+            migrate_file(&cache.join(name), &state.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the file or the directory.
+fn migrate_file(from: &Path, to: &Path) -> Result<()> {
+    if !from.exists() || to.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(to.parent().unwrap())?;
+    // Copied if on another file system
+    if fs::rename(from, to).is_err() {
+        copy_all(from, to)
+            .and_then(|_| {
+                if from.is_dir() {
+                    fs::remove_dir_all(from)
+                } else {
+                    fs::remove_file(from)
+                }
+            })
+            .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+    }
+    // On stderr, not to mix with the output of e.g. `session export`
+    warn!("Moved {} to {}", from.display(), to.display());
+    Ok(())
+}
+
+fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        return fs::copy(from, to).map(drop);
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_all(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! `practice`: a project of the problems picked from the past contests by the points, each bin
+//! routed to its task with `task` in `[package.metadata.cargo-atcoder.problems]`.
+
+use crate::{
+    atcoder::AtCoder,
+    atcoder_problems::{AtCoderProblems, Problem},
+    metadata, new_project,
+    project::{self, ProblemSetting},
+    session_file, NewOpt,
+};
+use anyhow::{bail, ensure, Context as _, Result};
+use log::info;
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct PracticeOpt {
+    /// Points of the problems to pick (e.g. 400)
+    #[structopt(long, value_name("POINTS"))]
+    points: u32,
+    /// Number of the problems to pick
+    #[structopt(long, value_name("N"), default_value("10"))]
+    count: usize,
+    /// Series of the contests to pick from [possible values: abc, arc, agc]
+    #[structopt(long, value_name("SERIES"), default_value("abc"))]
+    series: String,
+    /// Skip the problems already solved
+    #[structopt(long)]
+    exclude_solved: bool,
+    /// User whose solved problems are skipped (default: the logged-in user)
+    #[structopt(long, value_name("USERNAME"), requires("exclude-solved"))]
+    user: Option<String>,
+    /// Seed of the random pick (default: the current time)
+    #[structopt(long, value_name("SEED"))]
+    seed: Option<u64>,
+    /// Directory of the project (default: `practice-<points>-<date>`)
+    #[structopt(long, value_name("PATH"))]
+    dir: Option<PathBuf>,
+    /// Skip warming-up after creating the project
+    #[structopt(long)]
+    skip_warmup: bool,
+}
+
+pub async fn practice(opt: PracticeOpt) -> Result<()> {
+    let api = AtCoderProblems::new()?;
+    let series = opt.series.to_lowercase();
+    let mut candidates = api
+        .problems()
+        .await?
+        .into_iter()
+        .filter(|problem| {
+            problem.point == Some(opt.points.into())
+                && problem.contest_id.starts_with(&series)
+                && problem.id.starts_with(&problem.contest_id)
+        })
+        .collect::<Vec<_>>();
+
+    if opt.exclude_solved {
+        let user = match &opt.user {
+            Some(user) => user.clone(),
+            None => AtCoder::new(&session_file()?)?
+                .username()
+                .await?
+                .context("Not logged in. Log in or give `--user` for the solved problems")?,
+        };
+        let solved = api.solved(&user).await?;
+        candidates.retain(|problem| !solved.contains(&problem.id));
+    }
+    ensure!(
+        !candidates.is_empty(),
+        "No problems of {} points found in `{}`",
+        opt.points,
+        series
+    );
+    if candidates.len() < opt.count {
+        info!(
+            "Only {} problems of {} points found in `{}`",
+            candidates.len(),
+            opt.points,
+            series
+        );
+    }
+
+    let mut rng = XorShift::new(opt.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64)
+    }));
+    // A partial Fisher-Yates shuffle, in the order of the contests for the bin names
+    let count = opt.count.min(candidates.len());
+    for i in 0..count {
+        let j = i + (rng.next() % (candidates.len() - i) as u64) as usize;
+        candidates.swap(i, j);
+    }
+    candidates.truncate(count);
+    candidates.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let date = chrono::Local::now().naive_local().date();
+    let dir = opt
+        .dir
+        .clone()
+        .unwrap_or_else(|| format!("practice-{}-{}", opt.points, date.format("%Y-%m-%d")).into());
+    create_project(&dir, &candidates, opt.skip_warmup).await?;
+
+    for problem in &candidates {
+        info!("{}: {} ({})", problem.id, problem.title, problem.url());
+    }
+    Ok(())
+}
+
+/// Creates a project at `dir` with a bin for each problem, routed to its task.
+///
+/// The package is named after the directory, and marked not to be looked up as a contest.
+pub(crate) async fn create_project(
+    dir: &Path,
+    problems: &[Problem],
+    skip_warmup: bool,
+) -> Result<()> {
+    if dir.exists() {
+        bail!("Directory {} already exists", dir.display());
+    }
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .with_context(|| format!("No name of the package in {}", dir.display()))?;
+
+    new_project(NewOpt {
+        contest_id: name,
+        bins: problems.iter().map(|problem| problem.id.clone()).collect(),
+        skip_warmup,
+        prefetch: false,
+        force: false,
+        dir: Some(dir.to_owned()),
+    })
+    .await?;
+
+    let metadata = metadata::cargo_metadata(None, dir)?;
+    let package = metadata
+        .root_package()
+        .context("No package in the new project")?;
+    project::mark_practice(package)?;
+    for problem in problems {
+        project::save_problem_setting(
+            package,
+            &problem.id,
+            &ProblemSetting {
+                task: Some(problem.url()),
+                ..Default::default()
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// xorshift64, enough to pick the problems
+pub(crate) struct XorShift(u64);
+
+impl XorShift {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Mixed so that the seed 0 does not make the state 0
+        Self(seed ^ 0x2545_f491_4f6c_dd1d)
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
@@ -0,0 +1,81 @@
+//! `new --prefetch`: fetches the statements in both languages with their images, the official
+//! editorials published already and the submission form of the contest into the cache while the
+//! project is warmed up, so that `statement`, `editorial` and `submit` do not wait for AtCoder.
+//!
+//! Failures are only reported since the commands fetch what is missing anyway.
+
+use crate::{
+    atcoder::{self, AtCoder, ContestInfo, Problem},
+    cache, editorial, statement_images, FETCH_CONCURRENCY,
+};
+use anyhow::Result;
+use futures::{stream, StreamExt as _};
+use log::{debug, info, warn};
+
+pub(crate) async fn prefetch(atc: &AtCoder, contest_id: &str, contest_info: &ContestInfo) {
+    let statements = stream::iter(contest_info.problems())
+        .map(|problem| async move {
+            let result = prefetch_statements(atc, contest_id, problem).await;
+            if let Err(err) = &result {
+                warn!(
+                    "failed to prefetch the statement of `{}`: {}",
+                    problem.id, err
+                );
+            }
+            result.is_ok()
+        })
+        .buffer_unordered(FETCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .filter(|&ok| ok)
+        .count();
+
+    // Not published until the end of the contest
+    let editorials = match editorial::prefetch(atc, contest_id).await {
+        Ok(editorials) => editorials,
+        Err(err) => {
+            debug!("No editorials of `{}` to prefetch: {:#}", contest_id, err);
+            0
+        }
+    };
+
+    let submit = match atc.submit_form(contest_id).await {
+        Ok(_) => ", and the submission form",
+        Err(err) => {
+            warn!("failed to prefetch the submission form: {:#}", err);
+            ""
+        }
+    };
+
+    info!(
+        "Prefetched {} statements, {} editorials{}.",
+        statements, editorials, submit
+    );
+}
+
+/// Saves the statement in Japanese and in English from one fetch of the page, and the images
+/// in them.
+async fn prefetch_statements(atc: &AtCoder, contest_id: &str, problem: &Problem) -> Result<()> {
+    let mut htmls = vec![];
+    let mut page = None;
+    for english in [false, true] {
+        let html = match cache::load_statement(contest_id, &problem.id, english) {
+            Some(html) => html,
+            None => {
+                if page.is_none() {
+                    page = Some(atc.problem_page(&problem.url).await?);
+                }
+                let html = atcoder::parse_statement_html(page.as_deref().unwrap(), english)?;
+                cache::save_statement(contest_id, &problem.id, english, &html)?;
+                html
+            }
+        };
+        htmls.push(html);
+    }
+    htmls.dedup();
+    for html in &htmls {
+        statement_images(atc, contest_id, html).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Transforms of the source before submitting it, configured as the ordered `[[preprocess]]`
+//! steps in the config.
+
+use crate::{config::PreprocessStep, minify};
+use anyhow::{ensure, Context as _, Result};
+use regex::{Regex, RegexBuilder};
+use std::{fs, path::Path};
+
+const DEFAULT_INCLUDE_PATTERN: &str = r#"^[ \t]*include!\(\s*"([^"]+)"\s*\);?[ \t]*$"#;
+
+/// Files included in files included in ... are expanded up to this depth.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Applies the steps to the source. The paths of `include` are relative to `dir`.
+pub(crate) fn preprocess(steps: &[PreprocessStep], source: &str, dir: &Path) -> Result<String> {
+    let mut source = source.to_owned();
+    for step in steps {
+        source = match step {
+            PreprocessStep::Replace {
+                pattern,
+                replacement,
+            } => regex(pattern)?
+                .replace_all(&source, replacement.as_str())
+                .into_owned(),
+            PreprocessStep::Include { pattern } => {
+                let pattern = regex(pattern.as_deref().unwrap_or(DEFAULT_INCLUDE_PATTERN))?;
+                include(&pattern, &source, dir, 0)?
+            }
+            PreprocessStep::StripComments => minify::strip_comments(&source),
+        };
+    }
+    Ok(source)
+}
+
+fn regex(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .multi_line(true)
+        .build()
+        .with_context(|| format!("Invalid pattern in `preprocess`: `{}`", pattern))
+}
+
+fn include(pattern: &Regex, source: &str, dir: &Path, depth: usize) -> Result<String> {
+    ensure!(
+        depth < MAX_INCLUDE_DEPTH,
+        "Too deeply nested includes in `preprocess` (recursive?)"
+    );
+    let mut ret = String::with_capacity(source.len());
+    let mut last = 0;
+    for caps in pattern.captures_iter(source) {
+        let (whole, path) = (caps.get(0).unwrap(), caps.get(1));
+        let path = dir.join(
+            path.with_context(|| {
+                format!(
+                    "The pattern of `include` has no group for the path: `{}`",
+                    pattern
+                )
+            })?
+            .as_str(),
+        );
+        let included = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {} to include", path.display()))?;
+        ret += &source[last..whole.start()];
+        ret += include(pattern, &included, path.parent().unwrap_or(dir), depth + 1)?.trim_end();
+        last = whole.end();
+    }
+    ret += &source[last..];
+    Ok(ret)
+}
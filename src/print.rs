@@ -0,0 +1,227 @@
+//! Printable PostScript of source files with line numbers and syntax highlighting, for team
+//! contests where the printed code is shared.
+
+use std::fmt::Write as _;
+
+const PAGE_WIDTH: f64 = 595.; // A4
+const PAGE_HEIGHT: f64 = 842.;
+const MARGIN: f64 = 40.;
+const FONT_SIZE: f64 = 9.;
+const LINE_HEIGHT: f64 = 11.;
+// Courier is 0.6 em wide.
+const COLUMNS: usize =
+    ((PAGE_WIDTH - 2. * MARGIN) / (FONT_SIZE * 0.6)) as usize - LINE_NUMBER_WIDTH;
+const LINE_NUMBER_WIDTH: usize = 6;
+const LINES_PER_PAGE: usize =
+    ((PAGE_HEIGHT - 2. * MARGIN - 2. * LINE_HEIGHT) / LINE_HEIGHT) as usize;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Plain,
+    Keyword,
+    Comment,
+    Literal,
+}
+
+impl Class {
+    // Distinguished without colors for monochrome printers
+    fn postscript(self) -> &'static str {
+        match self {
+            Class::Plain => "R setfont 0 setgray",
+            Class::Keyword => "B setfont 0 setgray",
+            Class::Comment => "I setfont 0.4 setgray",
+            Class::Literal => "R setfont 0.3 setgray",
+        }
+    }
+}
+
+/// Renders the Rust source as PostScript with `title` on the header of each page.
+pub(crate) fn postscript(title: &str, source: &str) -> String {
+    // (line number, characters) of the lines wrapped at `COLUMNS`
+    let mut rows = vec![];
+    for (i, line) in highlight(source).into_iter().enumerate() {
+        if line.is_empty() {
+            rows.push((Some(i + 1), vec![]));
+        }
+        for (j, chunk) in line.chunks(COLUMNS).enumerate() {
+            rows.push(((j == 0).then(|| i + 1), chunk.to_vec()));
+        }
+    }
+    let pages = rows.chunks(LINES_PER_PAGE).collect::<Vec<_>>();
+
+    let mut ps = String::new();
+    writeln!(ps, "%!PS-Adobe-3.0").unwrap();
+    writeln!(ps, "%%Title: ({})", ps_string(title)).unwrap();
+    writeln!(ps, "%%Pages: {}", pages.len().max(1)).unwrap();
+    writeln!(
+        ps,
+        "%%DocumentMedia: A4 {} {} 0 () ()",
+        PAGE_WIDTH, PAGE_HEIGHT
+    )
+    .unwrap();
+    writeln!(ps, "%%EndComments").unwrap();
+    for (name, font) in [
+        ("R", "Courier"),
+        ("B", "Courier-Bold"),
+        ("I", "Courier-Oblique"),
+    ] {
+        writeln!(
+            ps,
+            "/{} /{} findfont {} scalefont def",
+            name, font, FONT_SIZE
+        )
+        .unwrap();
+    }
+    writeln!(ps, "/H /Helvetica-Bold findfont 10 scalefont def").unwrap();
+    writeln!(ps, "%%EndProlog").unwrap();
+
+    for (p, page) in pages.iter().enumerate() {
+        writeln!(ps, "%%Page: {} {}", p + 1, p + 1).unwrap();
+        let mut y = PAGE_HEIGHT - MARGIN;
+        writeln!(
+            ps,
+            "H setfont 0 setgray {} {} moveto ({}) show",
+            MARGIN,
+            y,
+            ps_string(&format!("{}    {}/{}", title, p + 1, pages.len())),
+        )
+        .unwrap();
+        y -= 2. * LINE_HEIGHT;
+
+        for (number, chars) in page.iter() {
+            let number = number.map(|n| n.to_string()).unwrap_or_default();
+            writeln!(
+                ps,
+                "{} {} {} moveto ({:>width$} ) show",
+                Class::Comment.postscript(),
+                MARGIN,
+                y,
+                number,
+                width = LINE_NUMBER_WIDTH - 1,
+            )
+            .unwrap();
+            for (class, run) in runs(chars) {
+                writeln!(ps, "{} ({}) show", class.postscript(), ps_string(&run)).unwrap();
+            }
+            y -= LINE_HEIGHT;
+        }
+        writeln!(ps, "showpage").unwrap();
+    }
+    writeln!(ps, "%%EOF").unwrap();
+    ps
+}
+
+// Consecutive characters of the same class
+fn runs(chars: &[(char, Class)]) -> Vec<(Class, String)> {
+    let mut runs = Vec::<(Class, String)>::new();
+    for &(c, class) in chars {
+        match runs.last_mut() {
+            Some((last, run)) if *last == class => run.push(c),
+            _ => runs.push((class, c.to_string())),
+        }
+    }
+    runs
+}
+
+/// The text in a PostScript string `(...)`. The standard fonts only have ASCII characters.
+fn ps_string(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// Classifies the characters of each line.
+fn highlight(source: &str) -> Vec<Vec<(char, Class)>> {
+    let chars = source.replace('\t', "    ").chars().collect::<Vec<_>>();
+    let mut lines = vec![vec![]];
+    let mut push = |c: char, class: Class| {
+        if c == '\n' {
+            lines.push(vec![]);
+        } else if c != '\r' {
+            lines.last_mut().unwrap().push((c, class));
+        }
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                push(chars[i], Class::Comment);
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    push('*', Class::Comment);
+                    push('/', Class::Comment);
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                    continue;
+                }
+                push(chars[i], Class::Comment);
+                i += 1;
+            }
+        } else if c == '"' {
+            push(c, Class::Literal);
+            i += 1;
+            while i < chars.len() {
+                let c = chars[i];
+                push(c, Class::Literal);
+                i += 1;
+                if c == '\\' && i < chars.len() {
+                    push(chars[i], Class::Literal);
+                    i += 1;
+                } else if c == '"' {
+                    break;
+                }
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word = chars[start..i].iter().collect::<String>();
+            let class = if KEYWORDS.contains(&&*word) {
+                Class::Keyword
+            } else if word.starts_with(|c: char| c.is_ascii_digit()) {
+                Class::Literal
+            } else {
+                Class::Plain
+            };
+            for c in word.chars() {
+                push(c, class);
+            }
+        } else {
+            push(c, Class::Plain);
+            i += 1;
+        }
+    }
+    if lines.last().is_some_and(Vec::is_empty) {
+        lines.pop();
+    }
+    lines
+}
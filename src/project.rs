@@ -0,0 +1,402 @@
+//! Per-project settings, stored in `[package.metadata.cargo-atcoder]` of the project's `Cargo.toml`,
+//! and records and sample files in the project directory.
+
+use crate::atcoder::{parse_task_url, TestCase};
+use anyhow::{anyhow, ensure, Context as _, Result};
+use cargo_metadata::Package;
+use chrono::NaiveDate;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Document, Item, Table};
+
+const METADATA_KEY: &str = "cargo-atcoder";
+
+// Set by `new` if the bins were created before the problem list became available
+const PROVISIONAL_BINS_KEY: &str = "provisional-bins";
+
+// Set by `practice`, whose package is named after the directory and not after a contest
+const PRACTICE_KEY: &str = "practice";
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ProblemSetting {
+    /// Source file to submit instead of `src/bin/<problem-id>.rs`
+    pub file: Option<PathBuf>,
+    /// Language name to select in the submission form
+    pub language: Option<String>,
+    /// Command to run `file` with on the samples (e.g. `python3 e.py`), in the package directory
+    pub run: Option<String>,
+    /// Bin to compare the outputs with in `stress` (e.g. `e_brute`)
+    pub reference: Option<String>,
+    /// Bin to generate the inputs in `stress` (e.g. `e_gen`)
+    pub generator: Option<String>,
+    /// URL of the task of another contest to test and submit the bin for, in the practice sets
+    /// mixing the problems of contests
+    pub task: Option<String>,
+}
+
+/// Directory of a new project from the pattern in the config, replacing `{contest_id}` and
+/// `{date}` (e.g. `2021-01-02`)
+pub(crate) fn dir_name(pattern: &str, contest_id: &str, date: NaiveDate) -> Result<String> {
+    let name = pattern
+        .replace("{contest_id}", contest_id)
+        .replace("{date}", &date.format("%Y-%m-%d").to_string());
+    ensure!(
+        !name.contains('{') && !name.contains('}'),
+        "Unknown placeholder in `project.dir`: `{}` (`{{contest_id}}` or `{{date}}`)",
+        pattern
+    );
+    Ok(name)
+}
+
+pub(crate) fn package_dir(package: &Package) -> &Path {
+    package
+        .manifest_path
+        .parent()
+        .expect("`manifest_path` should end with \"Cargo.toml\"")
+        .as_ref()
+}
+
+pub(crate) fn problem_setting(package: &Package, problem_id: &str) -> ProblemSetting {
+    let setting = &package.metadata[METADATA_KEY]["problems"][problem_id];
+    ProblemSetting {
+        file: setting["file"]
+            .as_str()
+            .map(|file| package_dir(package).join(file)),
+        language: setting["language"].as_str().map(ToOwned::to_owned),
+        run: setting["run"].as_str().map(ToOwned::to_owned),
+        reference: setting["reference"].as_str().map(ToOwned::to_owned),
+        generator: setting["generator"].as_str().map(ToOwned::to_owned),
+        task: setting["task"].as_str().map(ToOwned::to_owned),
+    }
+}
+
+/// The contest ID and the task screen name of the task the problem is routed to by `task`
+pub(crate) fn routed_task(package: &Package, problem_id: &str) -> Option<(String, String)> {
+    parse_task_url(problem_setting(package, problem_id).task.as_deref()?)
+}
+
+pub(crate) fn save_problem_setting(
+    package: &Package,
+    problem_id: &str,
+    setting: &ProblemSetting,
+) -> Result<()> {
+    let manifest_path = &package.manifest_path;
+    let mut manifest = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path))?
+        .parse::<Document>()?;
+
+    let mut item = &mut manifest.root;
+    for key in &["package", "metadata", METADATA_KEY, "problems"] {
+        item = implicit_table(&mut item[key]);
+    }
+    let item = &mut item[problem_id];
+    if item.is_none() {
+        *item = toml_edit::table();
+    }
+
+    if let Some(file) = &setting.file {
+        let file = file.strip_prefix(package_dir(package)).unwrap_or(file);
+        item["file"] = toml_edit::value(file.to_string_lossy().replace('\\', "/"));
+    }
+    if let Some(language) = &setting.language {
+        item["language"] = toml_edit::value(language.as_str());
+    }
+    if let Some(run) = &setting.run {
+        item["run"] = toml_edit::value(run.as_str());
+    }
+    if let Some(reference) = &setting.reference {
+        item["reference"] = toml_edit::value(reference.as_str());
+    }
+    if let Some(generator) = &setting.generator {
+        item["generator"] = toml_edit::value(generator.as_str());
+    }
+    if let Some(task) = &setting.task {
+        item["task"] = toml_edit::value(task.as_str());
+    }
+
+    fs::write(manifest_path, manifest.to_string())
+        .with_context(|| format!("Failed to write {}", manifest_path))
+}
+
+/// The contest of the package, which is its name. The practice sets have none, and their bins
+/// are looked up in the contests of their tasks (see [`routed_task`]).
+pub(crate) fn contest_id(package: &Package) -> Result<&str> {
+    ensure!(
+        package.metadata[METADATA_KEY][PRACTICE_KEY].as_bool() != Some(true),
+        "`{}` is a practice set of the tasks of several contests. Give one of its problems",
+        package.name,
+    );
+    Ok(&package.name)
+}
+
+pub(crate) fn mark_practice(package: &Package) -> Result<()> {
+    let manifest_path = &package.manifest_path;
+    let mut manifest = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path))?
+        .parse::<Document>()?;
+
+    let mut item = &mut manifest.root;
+    for key in &["package", "metadata", METADATA_KEY] {
+        item = implicit_table(&mut item[key]);
+    }
+    item[PRACTICE_KEY] = toml_edit::value(true);
+
+    fs::write(manifest_path, manifest.to_string())
+        .with_context(|| format!("Failed to write {}", manifest_path))
+}
+
+/// Whether the bins have to be reconciled with the problem list once it becomes available
+pub(crate) fn has_provisional_bins(package: &Package) -> bool {
+    package.metadata[METADATA_KEY][PROVISIONAL_BINS_KEY].as_bool() == Some(true)
+}
+
+pub(crate) fn mark_provisional_bins(manifest: &mut Document) {
+    let mut item = &mut manifest.root;
+    for key in &["package", "metadata", METADATA_KEY] {
+        item = implicit_table(&mut item[key]);
+    }
+    item[PROVISIONAL_BINS_KEY] = toml_edit::value(true);
+}
+
+pub(crate) fn clear_provisional_bins(package: &Package) -> Result<()> {
+    let manifest_path = &package.manifest_path;
+    let mut manifest = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path))?
+        .parse::<Document>()?;
+
+    if let Some(table) = manifest.root["package"]["metadata"][METADATA_KEY].as_table_mut() {
+        table.remove(PROVISIONAL_BINS_KEY);
+    }
+
+    fs::write(manifest_path, manifest.to_string())
+        .with_context(|| format!("Failed to write {}", manifest_path))
+}
+
+/// Path to the manifest of the workspace containing `dir` (the root of a monorepo of the
+/// contests), if any. The workspaces with `dir` in `exclude` are skipped as by cargo.
+pub(crate) fn workspace_manifest(dir: &Path) -> Option<PathBuf> {
+    enclosing_workspaces(dir).find_map(|(path, excluded)| (!excluded).then_some(path))
+}
+
+/// Manifests of the workspaces in the ancestors of `dir` from the nearest, with whether `dir` is
+/// in their `exclude`
+pub(crate) fn enclosing_workspaces(dir: &Path) -> impl Iterator<Item = (PathBuf, bool)> + '_ {
+    dir.ancestors().skip(1).filter_map(move |root| {
+        let path = root.join("Cargo.toml");
+        let manifest = fs::read_to_string(&path).ok()?.parse::<Document>().ok()?;
+        let workspace = manifest.as_table().get("workspace")?;
+        let excluded = workspace["exclude"].as_array().is_some_and(|exclude| {
+            exclude
+                .iter()
+                .filter_map(|path| path.as_str())
+                .any(|path| dir.starts_with(root.join(path)))
+        });
+        Some((path, excluded))
+    })
+}
+
+/// Adds the project in `dir` to the members of the workspace unless already matched by them,
+/// with the profiles of `profiles` which the workspace does not have since only the ones of the
+/// root are used. Returns whether the members were updated.
+pub(crate) fn add_workspace_member(
+    workspace_manifest: &Path,
+    dir: &Path,
+    profiles: &Item,
+) -> Result<bool> {
+    let root = workspace_manifest.parent().unwrap();
+    let member = dir
+        .strip_prefix(root)
+        .unwrap_or(dir)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let mut manifest = fs::read_to_string(workspace_manifest)
+        .with_context(|| format!("Failed to read {}", workspace_manifest.display()))?
+        .parse::<Document>()?;
+
+    let members = manifest["workspace"]["members"]
+        .as_array()
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|m| m.as_str())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let parent = member.rsplit_once('/').map_or("", |(parent, _)| parent);
+    let matched = members.iter().any(|&pattern| {
+        pattern.trim_end_matches('/') == member
+            || pattern.strip_suffix("/*") == Some(parent)
+            || (pattern == "*" && parent.is_empty())
+    });
+    if !matched {
+        let workspace = implicit_table(&mut manifest["workspace"]);
+        if workspace["members"].is_none() {
+            workspace["members"] = toml_edit::value(toml_edit::Array::default());
+        }
+        workspace["members"]
+            .as_array_mut()
+            .with_context(|| "`workspace.members` should be an array")?
+            .push(member.as_str())
+            .map_err(|_| anyhow!("`workspace.members` should be an array of strings"))?;
+    }
+
+    let mut added_profiles = false;
+    if let Some(profiles) = profiles.as_table() {
+        for (name, profile) in profiles.iter() {
+            if manifest["profile"][name].is_none() {
+                implicit_table(&mut manifest["profile"])[name] = profile.clone();
+                added_profiles = true;
+            }
+        }
+    }
+
+    if !matched || added_profiles {
+        fs::write(workspace_manifest, manifest.to_string())
+            .with_context(|| format!("Failed to write {}", workspace_manifest.display()))?;
+    }
+    Ok(!matched)
+}
+
+// Shared in the project directory by the team members
+fn records_path(package: &Package, name: &str) -> PathBuf {
+    package_dir(package).join(name).with_extension("toml")
+}
+
+fn load_records(package: &Package, name: &str) -> BTreeMap<u64, String> {
+    let records = fs::read_to_string(records_path(package, name))
+        .ok()
+        .and_then(|s| toml::from_str::<BTreeMap<String, String>>(&s).ok())
+        .unwrap_or_default();
+    records
+        .into_iter()
+        .filter_map(|(id, record)| Some((id.parse().ok()?, record)))
+        .collect()
+}
+
+fn save_record(package: &Package, name: &str, submission_id: u64, record: &str) -> Result<()> {
+    let mut records = load_records(package, name)
+        .into_iter()
+        .map(|(id, record)| (id.to_string(), record))
+        .collect::<BTreeMap<_, _>>();
+    records.insert(submission_id.to_string(), record.to_owned());
+    let path = records_path(package, name);
+    fs::write(&path, toml::to_string(&records)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Team members who made the submissions with `submit --member`, by submission ID
+pub(crate) fn load_members(package: &Package) -> BTreeMap<u64, String> {
+    load_records(package, "members")
+}
+
+pub(crate) fn save_member(package: &Package, submission_id: u64, member: &str) -> Result<()> {
+    save_record(package, "members", submission_id, member)
+}
+
+/// Variants (e.g. `v2` for `src/bin/e_v2.rs`) submitted with `submit --variant`, or the files
+/// submitted with `--file`, by submission ID
+pub(crate) fn load_variants(package: &Package) -> BTreeMap<u64, String> {
+    load_records(package, "variants")
+}
+
+pub(crate) fn save_variant(package: &Package, submission_id: u64, variant: &str) -> Result<()> {
+    save_record(package, "variants", submission_id, variant)
+}
+
+/// Notes on the problem in `notes/<problem-id>.md` of the project directory `dir`, kept with the
+/// solution
+pub(crate) fn note_path(dir: &Path, problem_id: &str) -> PathBuf {
+    dir.join("notes")
+        .join(problem_id.to_lowercase())
+        .with_extension("md")
+}
+
+// Samples kept in the project instead of the cache, editable by hand
+fn sample_files_dir(dir: &Path, problem_id: &str) -> PathBuf {
+    dir.join("tests").join(problem_id.to_lowercase())
+}
+
+pub(crate) fn has_sample_files(dir: &Path, problem_id: &str) -> bool {
+    sample_files_dir(dir, problem_id).is_dir()
+}
+
+/// Samples in `tests/<problem-id>/in_N.txt` and `out_N.txt` of the project directory `dir`, if
+/// there are any.
+pub(crate) fn load_sample_files(dir: &Path, problem_id: &str) -> Result<Option<Vec<TestCase>>> {
+    let dir = sample_files_dir(dir, problem_id);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(None),
+    };
+    let mut nums = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_prefix("in_")?.strip_suffix(".txt")?.parse().ok()
+        })
+        .collect::<Vec<usize>>();
+    if nums.is_empty() {
+        return Ok(None);
+    }
+    nums.sort_unstable();
+    // The files may be edited on Windows.
+    let read = |name: String| {
+        let path = dir.join(name);
+        fs::read_to_string(&path)
+            .map(|s| s.replace("\r\n", "\n"))
+            .with_context(|| format!("Failed to read {}", path.display()))
+    };
+    nums.into_iter()
+        .map(|n| {
+            Ok(TestCase {
+                input: read(format!("in_{}.txt", n))?,
+                output: read(format!("out_{}.txt", n))?,
+            })
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+pub(crate) fn save_sample_files(dir: &Path, problem_id: &str, cases: &[TestCase]) -> Result<()> {
+    let dir = sample_files_dir(dir, problem_id);
+    fs::create_dir_all(&dir)?;
+    for (i, case) in cases.iter().enumerate() {
+        for (name, text) in [("in", &case.input), ("out", &case.output)] {
+            let path = dir.join(format!("{}_{}.txt", name, i + 1));
+            fs::write(&path, text)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+    // The samples which are no longer there, which would be loaded with the others
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let stale = name.to_str().is_some_and(|name| {
+            ["in_", "out_"].iter().any(|prefix| {
+                name.strip_prefix(prefix)
+                    .and_then(|name| name.strip_suffix(".txt"))
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .is_some_and(|n| n == 0 || n > cases.len())
+            })
+        });
+        if stale {
+            fs::remove_file(entry.path())
+                .with_context(|| format!("Failed to remove {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn implicit_table(item: &mut Item) -> &mut Item {
+    if item.is_none() {
+        *item = Item::Table({
+            let mut tbl = Table::new();
+            tbl.set_implicit(true);
+            tbl
+        });
+    }
+    item
+}
@@ -0,0 +1,345 @@
+//! `race`: runs two solutions of a problem on the same inputs and compares their running times
+//! and memory usage, to decide which one to submit.
+
+use crate::{
+    build, cache, cmp_output,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    project::{self, package_dir},
+    shutdown::{self, Spawned},
+    stack,
+    stats::print_table,
+    DEFAULT_TIME_LIMIT,
+};
+use anyhow::{bail, ensure, Context as _, Result};
+use bytesize::ByteSize;
+use cargo_metadata::Package;
+use console::Style;
+use itertools::Itertools as _;
+use std::{
+    env, fs,
+    io::{self, Read as _, Write as _},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct RaceOpt {
+    /// Bin of the first solution (e.g. e)
+    first: String,
+    /// Bin of the second solution (e.g. e_v2)
+    second: String,
+    /// Cases to run: `all` for the samples and the inputs saved by `stress`, or the numbers of
+    /// the samples (e.g. `1,3`)
+    #[structopt(long, default_value("all"), value_name("CASES"))]
+    cases: Cases,
+    /// Number of runs of each case, of which the fastest is taken
+    #[structopt(long, default_value("3"), value_name("N"))]
+    runs: usize,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+enum Cases {
+    All,
+    Samples(Vec<usize>),
+}
+
+impl FromStr for Cases {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Cases::All);
+        }
+        s.split(',')
+            .map(|n| match n.trim().parse() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(format!(
+                    "`{}` is neither `all` nor the numbers of samples",
+                    s
+                )),
+            })
+            .collect::<Result<_, _>>()
+            .map(Cases::Samples)
+    }
+}
+
+/// An input with the expected output if it is a sample
+struct Case {
+    name: String,
+    input: Vec<u8>,
+    expected: Option<String>,
+}
+
+/// Fastest of the runs of a solution on a case
+struct Measurement {
+    elapsed: Duration,
+    /// Peak resident set size sampled while running, only on Linux
+    memory: Option<u64>,
+    verdict: &'static str,
+}
+
+pub fn race(opt: RaceOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    ensure!(opt.runs > 0, "`--runs` should be positive");
+
+    // `e` and `e_v2` are of problem `e`.
+    let problem_id = opt.first.split('_').next().unwrap_or(&opt.first);
+    let cases = cases(package, problem_id, &opt.cases)?;
+    let bins = [&opt.first, &opt.second];
+    let mut executables = vec![];
+    for bin in bins {
+        package.find_bin(bin)?;
+        executables.push(build::build_bin(package, bin, true)?.with_context(|| "Build failed")?);
+    }
+
+    let mut rows = vec![];
+    let mut totals = [Duration::ZERO; 2];
+    let mut peaks = [None::<u64>; 2];
+    for case in &cases {
+        let mut row = vec![case.name.clone()];
+        for (i, executable) in executables.iter().enumerate() {
+            let m = measure(executable.as_ref(), case, opt.runs)?;
+            totals[i] += m.elapsed;
+            peaks[i] = peaks[i].max(m.memory);
+            row.push(format_measurement(&m));
+        }
+        rows.push(row);
+    }
+    rows.push(
+        Some("(total)".to_owned())
+            .into_iter()
+            .chain((0..2).map(|i| {
+                format_measurement(&Measurement {
+                    elapsed: totals[i],
+                    memory: peaks[i],
+                    verdict: "",
+                })
+            }))
+            .collect(),
+    );
+
+    let header = ["case", bins[0], bins[1]].map(str::to_owned);
+    print_table(&header, &rows);
+    println!();
+
+    let (faster, slower) = if totals[0] <= totals[1] {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    println!(
+        "{} is {:.2}x as fast as {} in total",
+        Style::new().bold().apply_to(bins[faster]),
+        totals[slower].as_secs_f64() / totals[faster].as_secs_f64().max(1e-9),
+        bins[slower],
+    );
+    Ok(())
+}
+
+fn format_measurement(m: &Measurement) -> String {
+    let mut s = format!("{:.1?}", m.elapsed);
+    if let Some(memory) = m.memory {
+        s += &format!(" {}", ByteSize::b(memory));
+    }
+    if !m.verdict.is_empty() {
+        s += &format!(" {}", m.verdict);
+    }
+    s
+}
+
+/// The samples in the project or in the cache, and the inputs saved by `stress`
+fn cases(package: &Package, problem_id: &str, which: &Cases) -> Result<Vec<Case>> {
+    let samples = match project::load_sample_files(package_dir(package), problem_id)? {
+        Some(samples) => samples,
+        None => cache::load_samples(&package.name, problem_id).unwrap_or_default(),
+    };
+    if let Cases::Samples(nums) = which {
+        if let Some(n) = nums.iter().find(|&&n| n > samples.len()) {
+            bail!("Sample {} of problem `{}` is not found", n, problem_id);
+        }
+    }
+    let mut cases = vec![];
+    for (i, sample) in samples.into_iter().enumerate() {
+        if let Cases::Samples(nums) = which {
+            if !nums.contains(&(i + 1)) {
+                continue;
+            }
+        }
+        cases.push(Case {
+            name: format!("sample {}", i + 1),
+            input: sample.input.into_bytes(),
+            expected: Some(sample.output),
+        });
+    }
+
+    if let Cases::All = which {
+        let dir = package_dir(package).join("stress").join(problem_id);
+        if let Ok(entries) = fs::read_dir(&dir) {
+            let paths = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "in"))
+                .sorted();
+            for path in paths {
+                cases.push(Case {
+                    name: path
+                        .strip_prefix(package_dir(package))
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string(),
+                    input: fs::read(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?,
+                    expected: None,
+                });
+            }
+        }
+    }
+    ensure!(
+        !cases.is_empty(),
+        "No inputs of problem `{}`. Run `cargo atcoder test {}` to fetch the samples",
+        problem_id,
+        problem_id
+    );
+    Ok(cases)
+}
+
+fn measure(executable: &Path, case: &Case, runs: usize) -> Result<Measurement> {
+    let mut best: Option<Measurement> = None;
+    for _ in 0..runs {
+        let Run {
+            status,
+            stdout,
+            elapsed,
+            memory,
+        } = run(executable, &case.input, DEFAULT_TIME_LIMIT)?;
+        let verdict = match status {
+            None => "TLE",
+            Some(status) if !status.success() => "RE",
+            Some(_) => match &case.expected {
+                Some(expected) if !cmp_output(expected, &String::from_utf8_lossy(&stdout)).0 => {
+                    "WA"
+                }
+                _ => "",
+            },
+        };
+        let m = Measurement {
+            elapsed,
+            memory,
+            verdict,
+        };
+        // Slower runs would only be noise, and failures are the same in every run.
+        if !verdict.is_empty() {
+            return Ok(m);
+        }
+        if best.as_ref().is_none_or(|best| m.elapsed < best.elapsed) {
+            best = Some(m);
+        }
+    }
+    Ok(best.expect("`runs` should be positive"))
+}
+
+struct Run {
+    /// `None` if killed after the time limit
+    status: Option<ExitStatus>,
+    stdout: Vec<u8>,
+    elapsed: Duration,
+    memory: Option<u64>,
+}
+
+fn run(executable: &Path, input: &[u8], time_limit: Duration) -> Result<Run> {
+    let start = Instant::now();
+    let mut spawned = shutdown::spawn(
+        stack::apply(&mut Command::new(executable))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null()),
+    )
+    .with_context(|| format!("Failed to execute {}", executable.display()))?;
+    let child = &mut spawned.child;
+
+    let stdin = {
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_owned();
+        // the process may exit without reading the whole input
+        thread::spawn(move || drop(stdin.write_all(&input)))
+    };
+    let stdout = {
+        let mut stdout = child.stdout.take().unwrap();
+        thread::spawn(move || -> io::Result<_> {
+            let mut buf = vec![];
+            stdout.read_to_end(&mut buf)?;
+            Ok(buf)
+        })
+    };
+
+    let (status, elapsed, memory) = wait(&mut spawned, executable, start, time_limit)?;
+    let _ = stdin.join();
+    shutdown::check()?;
+    Ok(Run {
+        status,
+        stdout: stdout.join().unwrap()?,
+        elapsed,
+        memory,
+    })
+}
+
+fn wait(
+    spawned: &mut Spawned,
+    executable: &Path,
+    start: Instant,
+    time_limit: Duration,
+) -> Result<(Option<ExitStatus>, Duration, Option<u64>)> {
+    let mut memory = None;
+    loop {
+        if let Some(status) = spawned.child.try_wait()? {
+            return Ok((Some(status), start.elapsed(), memory));
+        }
+        memory = memory.max(peak_memory(&spawned.child, executable));
+        if start.elapsed() > time_limit || shutdown::interrupted() {
+            spawned.kill()?;
+            spawned.child.wait()?;
+            return Ok((None, start.elapsed(), memory));
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Peak resident set size of the running process so far. The usage reported by `wait4` is not
+/// used since it includes the memory of this process before `exec`.
+#[cfg(target_os = "linux")]
+fn peak_memory(child: &Child, executable: &Path) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", child.id())).ok()?;
+    // Not yet `exec`ed if the name is still of this process. The names are truncated to 15 bytes.
+    let name = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Name:"))?
+        .trim();
+    let file_name = executable.file_name()?.to_str()?;
+    if !file_name.starts_with(name) {
+        return None;
+    }
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix(" kB")?
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_memory(_: &Child, _: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,139 @@
+//! `rating`: the rating history of a user, as a chart in the terminal and a table of the recent
+//! contests.
+
+use crate::{
+    atcoder::{AtCoder, ContestResult},
+    session_file,
+};
+use anyhow::{Context as _, Result};
+use console::Style;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct RatingOpt {
+    /// User to show the history of (default: the logged-in user)
+    user: Option<String>,
+    /// Number of the recent contests in the table
+    #[structopt(long, value_name("N"), default_value("10"))]
+    count: usize,
+}
+
+/// Rated contests in the chart, the latest ones if more
+const CHART_WIDTH: usize = 60;
+const CHART_HEIGHT: usize = 10;
+
+pub async fn rating(opt: RatingOpt) -> Result<()> {
+    let atc = AtCoder::new(&session_file()?)?;
+    let user = match &opt.user {
+        Some(user) => user.clone(),
+        None => atc
+            .username()
+            .await?
+            .context("Not logged in. Log in or give the user")?,
+    };
+    let history = atc.rating_history(&user).await?;
+    let rated = history.iter().filter(|r| r.is_rated).collect::<Vec<_>>();
+    let rating = match rated.last() {
+        Some(last) => last.new_rating,
+        None => {
+            println!("{} has no rated contests.", user);
+            return Ok(());
+        }
+    };
+
+    let highest = rated.iter().map(|r| r.new_rating).max().unwrap_or(rating);
+    println!(
+        "{}: {} (highest {}, {} rated contests)",
+        user,
+        color(rating).apply_to(rating),
+        color(highest).apply_to(highest),
+        rated.len(),
+    );
+    println!();
+    chart(&rated[rated.len().saturating_sub(CHART_WIDTH)..]);
+    println!();
+    table(&history[history.len().saturating_sub(opt.count)..]);
+    Ok(())
+}
+
+/// The colors of the ratings on AtCoder, by 400
+fn color(rating: i64) -> Style {
+    match rating {
+        i64::MIN..=399 => Style::new().color256(8),
+        400..=799 => Style::new().color256(130),
+        800..=1199 => Style::new().green(),
+        1200..=1599 => Style::new().cyan(),
+        1600..=1999 => Style::new().blue(),
+        2000..=2399 => Style::new().yellow(),
+        2400..=2799 => Style::new().color256(208),
+        _ => Style::new().red(),
+    }
+}
+
+fn chart(rated: &[&ContestResult]) {
+    let min = rated.iter().map(|r| r.new_rating).min().unwrap_or(0);
+    let max = rated.iter().map(|r| r.new_rating).max().unwrap_or(0);
+    // The row from the bottom
+    let row = |rating: i64| {
+        if max == min {
+            0
+        } else {
+            ((rating - min) * (CHART_HEIGHT as i64 - 1) / (max - min)) as usize
+        }
+    };
+    for level in (0..CHART_HEIGHT).rev() {
+        let label = if level == CHART_HEIGHT - 1 {
+            max.to_string()
+        } else if level == 0 {
+            min.to_string()
+        } else {
+            "".to_owned()
+        };
+        let line = rated
+            .iter()
+            .map(|r| {
+                if row(r.new_rating) == level {
+                    color(r.new_rating).apply_to("*").to_string()
+                } else {
+                    " ".to_owned()
+                }
+            })
+            .collect::<String>();
+        println!("{:>5} |{}", label, line);
+    }
+    println!("{:>5} +{}", "", "-".repeat(rated.len()));
+}
+
+fn table(history: &[ContestResult]) {
+    println!(
+        "{:<10}  {:<12}  {:>6}  {:>5}  {:>11}",
+        "Date", "Contest", "Rank", "Perf", "Rating"
+    );
+    for result in history.iter().rev() {
+        let contest = result
+            .contest_screen_name
+            .split('.')
+            .next()
+            .unwrap_or_default();
+        let performance = result
+            .performance
+            .map_or_else(|| "-".to_owned(), |p| p.to_string());
+        let rating = if result.is_rated {
+            format!(
+                "{} ({:+})",
+                result.new_rating,
+                result.new_rating - result.old_rating
+            )
+        } else {
+            "-".to_owned()
+        };
+        println!(
+            "{:<10}  {:<12}  {:>6}  {:>5}  {:>11}",
+            result.end_time.get(..10).unwrap_or_default(),
+            contest,
+            result.place,
+            performance,
+            rating,
+        );
+    }
+}
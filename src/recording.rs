@@ -0,0 +1,136 @@
+//! Recording HTTP responses to a file (`--record`) and replaying them (`--replay`), so that
+//! scraping failures can be reproduced without accessing AtCoder.
+
+use anyhow::{Context as _, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::Mutex};
+use structopt::{clap::ArgMatches, StructOpt};
+
+#[derive(StructOpt)]
+pub(crate) struct RecordOpt {
+    /// Record HTTP responses to <PATH>
+    #[structopt(long, value_name("PATH"), global = true, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Replay HTTP responses from <PATH> recorded with `--record` instead of accessing AtCoder
+    #[structopt(long, value_name("PATH"), global = true)]
+    replay: Option<PathBuf>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Response {
+    method: String,
+    url: String,
+    status: u16,
+    body: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Recording {
+    responses: Vec<Response>,
+}
+
+enum Mode {
+    Record(PathBuf, Mutex<Recording>),
+    // The number of the responses replayed so far is kept for each one.
+    Replay(Vec<Response>, Mutex<Vec<usize>>),
+}
+
+static MODE: OnceCell<Mode> = OnceCell::new();
+
+pub(crate) fn init(matches: &[&ArgMatches<'_>]) -> Result<()> {
+    let (mut record, mut replay) = (None, None);
+    for matches in matches {
+        let opt = RecordOpt::from_clap(matches);
+        record = opt.record.or(record);
+        replay = opt.replay.or(replay);
+    }
+
+    let mode = if let Some(path) = record {
+        Mode::Record(path, Mutex::default())
+    } else if let Some(path) = replay {
+        let s = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let recording = toml::from_str::<Recording>(&s)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let counts = vec![0; recording.responses.len()];
+        Mode::Replay(recording.responses, Mutex::new(counts))
+    } else {
+        return Ok(());
+    };
+    let _ = MODE.set(mode);
+    Ok(())
+}
+
+/// The recorded status and body for the request if replaying.
+///
+/// The responses for the same request are replayed in the recorded order, and the last one is
+/// repeated.
+pub(crate) fn replay(method: &str, url: &Url) -> Option<Result<(StatusCode, String)>> {
+    let (responses, counts) = match MODE.get()? {
+        Mode::Replay(responses, counts) => (responses, counts),
+        Mode::Record(..) => return None,
+    };
+    let mut counts = counts.lock().unwrap();
+
+    let candidates = responses
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.method == method && r.url == url.as_str())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let i = match candidates.iter().find(|&&i| counts[i] == 0) {
+        Some(&i) => i,
+        None => match candidates.last() {
+            Some(&i) => i,
+            None => {
+                return Some(Err(anyhow::anyhow!(
+                    "No recorded response for {} {}",
+                    method,
+                    url,
+                )))
+            }
+        },
+    };
+    counts[i] += 1;
+
+    let response = &responses[i];
+    Some(
+        StatusCode::from_u16(response.status)
+            .map(|status| (status, response.body.clone()))
+            .map_err(Into::into),
+    )
+}
+
+/// Whether the responses are replayed instead of accessing AtCoder
+pub(crate) fn is_replaying() -> bool {
+    matches!(MODE.get(), Some(Mode::Replay(..)))
+}
+
+/// Appends the response to the recording if recording.
+pub(crate) fn record(method: &str, url: &Url, status: StatusCode, body: &str) -> Result<()> {
+    let (path, recording) = match MODE.get() {
+        Some(Mode::Record(path, recording)) => (path, recording),
+        _ => return Ok(()),
+    };
+    let mut recording = recording.lock().unwrap();
+
+    recording.responses.push(Response {
+        method: method.to_owned(),
+        url: url.as_str().to_owned(),
+        status: status.as_u16(),
+        body: sanitize(body),
+    });
+    // Written every time so that the responses before a crash are kept.
+    fs::write(path, toml::to_string(&*recording)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// Cookies and form data are never recorded, but the pages contain CSRF tokens.
+fn sanitize(body: &str) -> String {
+    static CSRF_TOKEN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(name="csrf_token" value="|csrfToken = ")[^"]*"#).unwrap());
+    CSRF_TOKEN.replace_all(body, "${1}REDACTED").into_owned()
+}
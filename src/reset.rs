@@ -0,0 +1,83 @@
+//! `reset`: deletes the chosen state of cargo-atcoder, i.e. the session, the cache or the config,
+//! after listing what is deleted and confirming it.
+
+use crate::{cache, ci, config::config_file, i18n::tr, session_file, username_file};
+use anyhow::{bail, Context as _, Result};
+use std::{fs, path::Path};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct ResetOpt {
+    /// Delete the session and the username remembered by `login`
+    #[structopt(long)]
+    session: bool,
+    /// Delete the cache, i.e. the statements, the samples, the submission forms and so on
+    #[structopt(long)]
+    cache: bool,
+    /// Delete the config, which is created again with the defaults
+    #[structopt(long)]
+    config: bool,
+    /// Delete all of them
+    #[structopt(long, conflicts_with_all(&["session", "cache", "config"]))]
+    all: bool,
+    /// Delete without the confirmation
+    #[structopt(short, long)]
+    yes: bool,
+}
+
+pub fn reset(opt: ResetOpt) -> Result<()> {
+    if !(opt.session || opt.cache || opt.config || opt.all) {
+        bail!("Choose what to reset with `--session`, `--cache`, `--config` or `--all`");
+    }
+
+    let session = [session_file()?, username_file()?];
+    let config = config_file()?;
+    let mut paths = vec![];
+    if opt.session || opt.all {
+        paths.extend(session.iter().cloned());
+    }
+    if opt.cache || opt.all {
+        // Not the session and the records, which may be in the cache directory
+        paths.extend(cache::entries()?);
+    }
+    if opt.config || opt.all {
+        paths.push(config);
+    }
+    paths.retain(|path| path.exists());
+
+    if paths.is_empty() {
+        println!("Nothing to reset");
+        return Ok(());
+    }
+    println!("The following will be deleted:");
+    for path in &paths {
+        println!("  {}", path.display());
+    }
+    if !opt.yes {
+        if !ci::interactive() {
+            bail!("Give `--yes` to reset without the confirmation");
+        }
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(tr!(ConfirmReset))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Did not reset");
+            return Ok(());
+        }
+    }
+
+    for path in &paths {
+        remove(path).with_context(|| format!("Failed to delete {}", path.display()))?;
+    }
+    println!("Deleted {} entries", paths.len());
+    Ok(())
+}
+
+fn remove(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
@@ -0,0 +1,201 @@
+//! `mark` and `review`: the problems solved with the help of the editorial or hints come back for
+//! review after growing intervals, until solved alone at every interval. The schedule is recorded
+//! in the state directory.
+
+use crate::{
+    atcoder::AtCoder,
+    atcoder_problems::Problem,
+    find_problem,
+    metadata::{self, MetadataExt as _},
+    paths::state_dir,
+    practice, project, session_file,
+};
+use anyhow::{Context as _, Result};
+use chrono::{Duration, NaiveDate};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, io, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct MarkOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// Solved with the help of the editorial or hints, to review it later
+    #[structopt(long)]
+    assisted: bool,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+#[derive(StructOpt)]
+pub struct ReviewOpt {
+    /// Only list the scheduled problems
+    #[structopt(long)]
+    list: bool,
+    /// Maximum number of the due problems in the project
+    #[structopt(long, value_name("N"), default_value("5"))]
+    count: usize,
+    /// Directory of the project (default: `review-<date>`)
+    #[structopt(long, value_name("PATH"))]
+    dir: Option<PathBuf>,
+    /// Skip warming-up after creating the project
+    #[structopt(long)]
+    skip_warmup: bool,
+}
+
+/// Days until the next review at each level. Solving alone at the last one ends the reviews.
+const INTERVALS: &[i64] = &[1, 3, 7, 14, 30, 60];
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Card {
+    contest_id: String,
+    title: String,
+    /// Index of the interval to the next review in `INTERVALS`
+    level: usize,
+    /// e.g. `2021-01-02`
+    due: String,
+}
+
+/// The scheduled problems by the task screen name
+#[derive(Default, Serialize, Deserialize)]
+struct Schedule {
+    #[serde(default)]
+    problems: BTreeMap<String, Card>,
+}
+
+fn schedule_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("review.toml"))
+}
+
+// Not reset on a broken file, which has the whole schedule
+fn load_schedule() -> Result<Schedule> {
+    let path = schedule_path()?;
+    match fs::read_to_string(&path) {
+        Ok(s) => toml::from_str(&s).with_context(|| format!("Failed to parse {}", path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Schedule::default()),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn save_schedule(schedule: &Schedule) -> Result<()> {
+    let path = schedule_path()?;
+    fs::write(&path, toml::to_string(schedule)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().naive_local().date()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+pub async fn mark(opt: MarkOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let route = project::routed_task(package, &opt.problem_id);
+    let contest_id = match &route {
+        Some((contest_id, _)) => contest_id,
+        None => project::contest_id(package)?,
+    };
+    let atc = AtCoder::new(&session_file()?)?;
+    let contest_info = crate::contest_info_or_register(&atc, contest_id).await?;
+    let problem = find_problem(&contest_info, &opt.problem_id, route.as_ref())?;
+    let task = problem
+        .url
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+
+    let mut schedule = load_schedule()?;
+    let level = match (schedule.problems.get(&task), opt.assisted) {
+        (_, true) => 0,
+        (Some(card), false) if card.level + 1 < INTERVALS.len() => card.level + 1,
+        (Some(_), false) => {
+            schedule.problems.remove(&task);
+            save_schedule(&schedule)?;
+            info!(
+                "Solved `{}` alone at every interval. No more reviews.",
+                task
+            );
+            return Ok(());
+        }
+        (None, false) => {
+            info!(
+                "`{}` is not scheduled for review. Give `--assisted` if solved with help.",
+                task
+            );
+            return Ok(());
+        }
+    };
+    let due = format_date(today() + Duration::days(INTERVALS[level]));
+    info!("`{}` will be reviewed on {}", task, due);
+    schedule.problems.insert(
+        task,
+        Card {
+            contest_id: contest_id.to_owned(),
+            title: problem.name.clone(),
+            level,
+            due,
+        },
+    );
+    save_schedule(&schedule)
+}
+
+pub async fn review(opt: ReviewOpt) -> Result<()> {
+    let schedule = load_schedule()?;
+    let today = format_date(today());
+    if opt.list {
+        for (task, card) in &schedule.problems {
+            info!(
+                "{}: {} ({}, level {})",
+                task, card.title, card.due, card.level
+            );
+        }
+        return Ok(());
+    }
+
+    // The most overdue first
+    let mut due = schedule
+        .problems
+        .iter()
+        .filter(|(_, card)| card.due <= today)
+        .collect::<Vec<_>>();
+    due.sort_by(|(_, a), (_, b)| a.due.cmp(&b.due));
+    due.truncate(opt.count);
+    if due.is_empty() {
+        match schedule.problems.values().map(|card| &card.due).min() {
+            Some(next) => info!("No problems to review. The next review is on {}.", next),
+            None => info!("No problems to review. Mark them with `mark --assisted`."),
+        }
+        return Ok(());
+    }
+
+    let problems = due
+        .iter()
+        .map(|(task, card)| Problem {
+            id: (*task).clone(),
+            contest_id: card.contest_id.clone(),
+            title: card.title.clone(),
+            point: None,
+        })
+        .collect::<Vec<_>>();
+    let dir = opt
+        .dir
+        .clone()
+        .unwrap_or_else(|| format!("review-{}", today).into());
+    practice::create_project(&dir, &problems, opt.skip_warmup).await?;
+    for problem in &problems {
+        info!("{}: {} ({})", problem.id, problem.title, problem.url());
+    }
+    info!("Run `mark <problem-id>` when solved alone, or with `--assisted` when not.");
+    Ok(())
+}
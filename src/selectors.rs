@@ -0,0 +1,91 @@
+//! CSS selectors of the pages of AtCoder, which can be overridden by `[selectors]` in the config
+//! to fix the scraping after a change of the markup without waiting for a release.
+
+use anyhow::{anyhow, bail, Result};
+use itertools::Itertools as _;
+use once_cell::sync::OnceCell;
+use scraper::Selector;
+use std::collections::BTreeMap;
+
+/// The names and the compiled-in selectors, where `{task}` is replaced by the task screen name
+/// (e.g. `abc999_a`)
+const DEFAULTS: &[(&str, &str)] = &[
+    // The page of a problem
+    ("statement", "#task-statement"),
+    ("statement-section", "div.part > section"),
+    ("sample-heading", "h3"),
+    ("sample", "pre"),
+    // The list of the problems
+    ("task-row", "table tbody tr"),
+    // The submission form
+    (
+        "submit-task",
+        r#"select[name="data.TaskScreenName"] option"#,
+    ),
+    (
+        "submit-language",
+        r#"div[id="select-lang-{task}"] select option"#,
+    ),
+    ("csrf-token", r#"input[name="csrf_token"]"#),
+    ("submit-error", "div.alert-danger, div.alert-warning"),
+    // The top page of a contest
+    ("register-form", r#"form[action$="/register"]"#),
+    ("contest-notice", "div.alert-danger, div.alert-warning"),
+    ("contest-title", "a.contest-title"),
+    // The table of the submissions
+    ("submission-row", "table tbody tr"),
+    ("submission-verdict", "span.label"),
+    ("pagination", "ul.pagination li a"),
+    // The page of a submission
+    ("submission-code", "#submission-code"),
+    ("submission-status", "#judge-status"),
+    ("submission-case-row", "table tbody tr"),
+    // The list of the editorials, the headings of the tasks and the editorials under them
+    (
+        "editorial-item",
+        "#main-container h3, #main-container ul li",
+    ),
+    // The navigation bar
+    ("username", r#"li a[href^="/users/"]"#),
+];
+
+static OVERRIDES: OnceCell<BTreeMap<String, String>> = OnceCell::new();
+
+/// Checks the overrides in the config and applies them to the rest of the process.
+pub(crate) fn init(overrides: &BTreeMap<String, String>) -> Result<()> {
+    for (name, selector) in overrides {
+        if !DEFAULTS.iter().any(|(n, _)| n == name) {
+            bail!(
+                "Unknown selector `selectors.{}` (expected one of {})",
+                name,
+                DEFAULTS.iter().map(|(n, _)| n).join(", ")
+            );
+        }
+        Selector::parse(&selector.replace("{task}", "abc999_a"))
+            .map_err(|_| anyhow!("Invalid selector `selectors.{}`: `{}`", name, selector))?;
+    }
+    let _ = OVERRIDES.set(overrides.clone());
+    Ok(())
+}
+
+/// The selector of the name.
+pub(crate) fn get(name: &str) -> Selector {
+    for_task(name, "")
+}
+
+/// The selector of the name, with `{task}` replaced by the task screen name.
+pub(crate) fn for_task(name: &str, task_screen_name: &str) -> Selector {
+    let selector = OVERRIDES
+        .get()
+        .and_then(|overrides| overrides.get(name))
+        .map(String::as_str)
+        .or_else(|| {
+            DEFAULTS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, selector)| *selector)
+        })
+        .unwrap_or_else(|| panic!("unknown selector `{}`", name));
+    Selector::parse(&selector.replace("{task}", task_screen_name))
+        .unwrap_or_else(|_| panic!("invalid selector `{}`, checked in `init`", selector))
+}
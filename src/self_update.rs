@@ -0,0 +1,208 @@
+//! `self-update`: replaces the running executable with the latest prebuilt one on GitHub
+//! Releases.
+
+use crate::http;
+use anyhow::{bail, ensure, Context as _, Result};
+use log::info;
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use structopt::StructOpt;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/tanakh/cargo-atcoder/releases/latest";
+
+/// Name of the asset listing the SHA-256 checksums of the other assets, in the format of
+/// `sha256sum`
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+#[derive(StructOpt)]
+pub struct SelfUpdateOpt {
+    /// Only check whether a newer version is available
+    #[structopt(long)]
+    check: bool,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+// The platforms of the prebuilt binaries in `.github/workflows/rust.yml`
+fn target_triple() -> Option<&'static str> {
+    if cfg!(all(
+        target_arch = "x86_64",
+        target_os = "windows",
+        target_env = "msvc"
+    )) {
+        Some("x86_64-pc-windows-msvc")
+    } else if cfg!(all(target_arch = "x86_64", target_os = "macos")) {
+        Some("x86_64-apple-darwin")
+    } else if cfg!(all(
+        target_arch = "x86_64",
+        target_os = "linux",
+        target_env = "gnu"
+    )) {
+        Some("x86_64-unknown-linux-gnu")
+    } else {
+        None
+    }
+}
+
+pub async fn self_update(opt: SelfUpdateOpt) -> Result<()> {
+    let client = reqwest::ClientBuilder::new()
+        .user_agent("cargo-atcoder")
+        .connect_timeout(http::timeout())
+        .build()?;
+
+    let release = client
+        .get(LATEST_RELEASE_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let release = serde_json::from_str::<Release>(&release)
+        .with_context(|| "Failed to parse the latest release")?;
+
+    let current = Version::parse(env!("CARGO_PKG_VERSION"))?;
+    let latest = Version::parse(release.tag_name.trim_start_matches('v'))
+        .with_context(|| format!("Invalid tag name: `{}`", release.tag_name))?;
+    if latest <= current {
+        info!("cargo-atcoder {} is up to date.", current);
+        return Ok(());
+    }
+    info!(
+        "cargo-atcoder {} is available (current: {}).",
+        latest, current
+    );
+    if opt.check {
+        return Ok(());
+    }
+
+    let triple = target_triple().with_context(|| {
+        "No prebuilt binary is provided for this platform. Run `cargo install cargo-atcoder` \
+         instead."
+    })?;
+    let ext = if cfg!(windows) { "zip" } else { "tar.gz" };
+    let stem = format!("cargo-atcoder-{}-{}", release.tag_name, triple);
+    let asset_name = format!("{}.{}", stem, ext);
+
+    let find_asset = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .with_context(|| format!("`{}` is not found in {}", name, release.tag_name))
+    };
+    let asset = find_asset(&asset_name)?;
+    let checksums = find_asset(CHECKSUMS_ASSET)?;
+
+    let checksums = client
+        .get(&checksums.browser_download_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let expected = checksums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim_start().trim_start_matches('*') == asset_name)
+        .map(|(hash, _)| hash.to_lowercase())
+        .with_context(|| format!("The checksum of `{}` is not found", asset_name))?;
+
+    info!("Downloading {}...", asset_name);
+    let archive = client
+        .get(&asset.browser_download_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let actual = data_encoding::HEXLOWER.encode(&Sha256::digest(&archive));
+    ensure!(
+        actual == expected,
+        "Checksum mismatch for `{}` (expected {}, got {})",
+        asset_name,
+        expected,
+        actual,
+    );
+
+    let work_dir = env::temp_dir().join(format!("cargo-atcoder-self-update-{}", latest));
+    if work_dir.exists() {
+        fs::remove_dir_all(&work_dir)?;
+    }
+    fs::create_dir_all(&work_dir)?;
+    let result = (|| {
+        let archive_path = work_dir.join(&asset_name);
+        fs::write(&archive_path, &archive)?;
+        extract(&archive_path, &work_dir)?;
+        let new_exe = work_dir
+            .join(&stem)
+            .join("cargo-atcoder")
+            .with_extension(env::consts::EXE_EXTENSION);
+        replace_current_exe(&new_exe)
+    })();
+    let _ = fs::remove_dir_all(&work_dir);
+    result?;
+
+    info!("Updated cargo-atcoder to {}.", latest);
+    Ok(())
+}
+
+// `tar` on Windows 10 (bsdtar) can also extract ZIP files.
+fn extract(archive: &Path, dir: &Path) -> Result<()> {
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir)
+        .status()
+        .with_context(|| "Failed to run `tar`")?;
+    ensure!(status.success(), "Failed to extract {}", archive.display());
+    Ok(())
+}
+
+// The running executable cannot be overwritten on Windows, but can be renamed.
+fn replace_current_exe(new_exe: &Path) -> Result<()> {
+    if !new_exe.is_file() {
+        bail!("{} is not found in the archive", new_exe.display());
+    }
+
+    let current_exe = env::current_exe()?.canonicalize()?;
+    let with_suffix = |suffix: &str| -> PathBuf {
+        let mut path = current_exe.clone().into_os_string();
+        path.push(suffix);
+        path.into()
+    };
+    let staged = with_suffix(".new");
+    let old = with_suffix(".old");
+
+    // Copied next to the current one first so that the last rename does not cross file systems.
+    fs::copy(new_exe, &staged).with_context(|| format!("Failed to write {}", staged.display()))?;
+    if old.exists() {
+        fs::remove_file(&old)?;
+    }
+    fs::rename(&current_exe, &old)
+        .with_context(|| format!("Failed to move {}", current_exe.display()))?;
+    if let Err(err) = fs::rename(&staged, &current_exe) {
+        let _ = fs::rename(&old, &current_exe);
+        return Err(err).with_context(|| format!("Failed to replace {}", current_exe.display()));
+    }
+    // The old one is still running on Windows, and is removed in the next update.
+    let _ = fs::remove_file(&old);
+    Ok(())
+}
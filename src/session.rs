@@ -0,0 +1,229 @@
+//! `session`: moves the logged-in session to another machine, through a JSON file optionally
+//! encrypted by a passphrase.
+
+use crate::{
+    ci,
+    http::{read_session_file, write_session_file},
+    i18n::tr,
+    lock::FileLock,
+    session_file, timer, username_file,
+};
+use anyhow::{anyhow, bail, Context as _, Result};
+use chacha20poly1305::{
+    aead::{Aead as _, NewAead as _},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use hmac::Hmac;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    io::{self, Read as _},
+    ops::RangeInclusive,
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub enum SessionOpt {
+    /// Print the session and the username as JSON, to import them on another machine
+    Export(ExportOpt),
+    /// Replace the session and the username by the exported ones
+    Import(ImportOpt),
+}
+
+#[derive(StructOpt)]
+pub struct ExportOpt {
+    /// Encrypt the session by a passphrase (asked, or `ATCODER_SESSION_PASSPHRASE`)
+    #[structopt(long)]
+    passphrase: bool,
+}
+
+#[derive(StructOpt)]
+pub struct ImportOpt {
+    /// File written by `session export` (default: stdin)
+    file: Option<PathBuf>,
+}
+
+/// The number of the iterations of PBKDF2 for new files
+const ITERATIONS: u32 = 100_000;
+
+/// The iterations accepted in a file, not to hang on a tampered one
+const ACCEPTED_ITERATIONS: RangeInclusive<u32> = 10_000..=1_000_000;
+
+#[derive(Serialize, Deserialize)]
+struct Exported {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session: Option<Session>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted: Option<Encrypted>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    cookies: BTreeMap<String, String>,
+}
+
+/// The JSON of a [`Session`] encrypted by ChaCha20-Poly1305 with the key derived from the
+/// passphrase by PBKDF2-HMAC-SHA256, in base64
+#[derive(Serialize, Deserialize)]
+struct Encrypted {
+    salt: String,
+    iterations: u32,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub fn session(opt: SessionOpt) -> Result<()> {
+    match opt {
+        SessionOpt::Export(opt) => export(opt),
+        SessionOpt::Import(opt) => import(opt),
+    }
+}
+
+fn export(opt: ExportOpt) -> Result<()> {
+    let cookies = read_session_file(&session_file()?)?;
+    if cookies.is_empty() {
+        bail!("Not logged in. Log in with `cargo atcoder login` first");
+    }
+    let username = match fs::read_to_string(username_file()?) {
+        Ok(username) => Some(username.trim().to_owned()).filter(|s| !s.is_empty()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let session = Session {
+        username,
+        cookies: cookies.into_iter().collect(),
+    };
+
+    let exported = if opt.passphrase {
+        let passphrase = passphrase(true)?;
+        Exported {
+            version: 1,
+            session: None,
+            encrypted: Some(encrypt(&serde_json::to_vec(&session)?, &passphrase)?),
+        }
+    } else {
+        Exported {
+            version: 1,
+            session: Some(session),
+            encrypted: None,
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&exported)?);
+    Ok(())
+}
+
+fn import(opt: ImportOpt) -> Result<()> {
+    let json = match &opt.file {
+        Some(file) if file.to_str() != Some("-") => fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?,
+        _ => {
+            let mut json = String::new();
+            io::stdin().read_to_string(&mut json)?;
+            json
+        }
+    };
+    let exported = serde_json::from_str::<Exported>(&json)
+        .with_context(|| "Not a file written by `cargo atcoder session export`")?;
+    if exported.version != 1 {
+        bail!(
+            "Unsupported version {} of the session. Update cargo-atcoder",
+            exported.version
+        );
+    }
+    let session = match (exported.session, exported.encrypted) {
+        (Some(session), None) => session,
+        (None, Some(encrypted)) => {
+            let json = decrypt(&encrypted, &passphrase(false)?)?;
+            serde_json::from_slice(&json)?
+        }
+        _ => bail!("Not a file written by `cargo atcoder session export`"),
+    };
+
+    let session_file = session_file()?;
+    let _lock = FileLock::exclusive(&session_file)?;
+    write_session_file(&session_file, &session.cookies.into_iter().collect())?;
+    if let Some(username) = &session.username {
+        fs::write(username_file()?, format!("{}\n", username))?;
+    }
+    match &session.username {
+        Some(username) => info!("Imported the session of `{}`", username),
+        None => info!("Imported the session"),
+    }
+    Ok(())
+}
+
+/// The passphrase in `ATCODER_SESSION_PASSPHRASE`, or asked (twice for a new one)
+fn passphrase(new: bool) -> Result<String> {
+    if let Some(passphrase) = env::var("ATCODER_SESSION_PASSPHRASE")
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        return Ok(passphrase);
+    }
+    if !ci::interactive() {
+        bail!("The passphrase cannot be asked. Set it to `ATCODER_SESSION_PASSPHRASE`");
+    }
+    let _paused = timer::pause();
+    let mut prompt = dialoguer::Password::new();
+    prompt.with_prompt(tr!(Passphrase));
+    if new {
+        prompt.with_confirmation(tr!(RepeatPassphrase), tr!(PassphraseMismatch));
+    }
+    Ok(prompt.interact()?)
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Encrypted> {
+    let mut salt = [0; 16];
+    let mut nonce = [0; 12];
+    getrandom::getrandom(&mut salt)
+        .and_then(|()| getrandom::getrandom(&mut nonce))
+        .with_context(|| "Failed to generate a salt")?;
+    let ciphertext = cipher(passphrase, &salt, ITERATIONS)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow!("Failed to encrypt the session"))?;
+    Ok(Encrypted {
+        salt: data_encoding::BASE64.encode(&salt),
+        iterations: ITERATIONS,
+        nonce: data_encoding::BASE64.encode(&nonce),
+        ciphertext: data_encoding::BASE64.encode(&ciphertext),
+    })
+}
+
+fn decrypt(encrypted: &Encrypted, passphrase: &str) -> Result<Vec<u8>> {
+    let decode = |s: &str| {
+        data_encoding::BASE64
+            .decode(s.as_bytes())
+            .with_context(|| "The encrypted session is broken")
+    };
+    let salt = decode(&encrypted.salt)?;
+    let nonce = decode(&encrypted.nonce)?;
+    let ciphertext = decode(&encrypted.ciphertext)?;
+    if !ACCEPTED_ITERATIONS.contains(&encrypted.iterations) {
+        bail!(
+            "The encrypted session is broken: {} iterations of PBKDF2 (expected {} to {})",
+            encrypted.iterations,
+            ACCEPTED_ITERATIONS.start(),
+            ACCEPTED_ITERATIONS.end(),
+        );
+    }
+    if nonce.len() != 12 {
+        bail!("The encrypted session is broken");
+    }
+
+    cipher(passphrase, &salt, encrypted.iterations)
+        .decrypt(Nonce::from_slice(&nonce), &*ciphertext)
+        .map_err(|_| anyhow!("Wrong passphrase, or the file is broken"))
+}
+
+fn cipher(passphrase: &str, salt: &[u8], iterations: u32) -> ChaCha20Poly1305 {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, iterations, &mut key);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
@@ -0,0 +1,182 @@
+//! Ctrl-C while solutions are running: the handler kills them with all their descendants, and
+//! the one waiting for them returns [`Failure::Interrupted`], which stops the loops running them
+//! (`test`, `watch`, `stress` and so on) and unwinds to `main` like the other errors, so that the
+//! session and the results are saved. A second Ctrl-C exits at once, and so does the first one
+//! while no solution is running, as without the handler.
+//!
+//! The solutions are spawned by [`spawn`] in process groups of their own on Unix, which the
+//! handler kills without waiting for anything, and in job objects on Windows, which are killed
+//! when this process exits anyway. Without a job object (e.g. if not allowed in the job of a CI),
+//! only the solution itself is killed on Windows.
+
+use crate::ci::Failure;
+use std::{
+    io,
+    process::{Child, Command},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The number of the solutions running
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// A solution spawned by [`spawn`], unregistered when dropped
+pub(crate) struct Spawned {
+    pub(crate) child: Child,
+    #[cfg(unix)]
+    slot: Option<usize>,
+    // `TerminateProcess` does not kill the descendants.
+    #[cfg(windows)]
+    job: Option<crate::job::Job>,
+}
+
+/// Spawns the solution to be killed on Ctrl-C, with its descendants. Its stdin should not be the
+/// terminal, which it cannot read from another process group.
+pub(crate) fn spawn(cmd: &mut Command) -> io::Result<Spawned> {
+    RUNNING.fetch_add(1, Ordering::SeqCst);
+    match spawn_registered(cmd) {
+        Ok(spawned) => Ok(spawned),
+        Err(err) => {
+            RUNNING.fetch_sub(1, Ordering::SeqCst);
+            Err(err)
+        }
+    }
+}
+
+impl Spawned {
+    /// Kills the solution with its descendants.
+    pub(crate) fn kill(&mut self) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            if unsafe { libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+                return Ok(());
+            }
+            self.child.kill()
+        }
+        #[cfg(windows)]
+        match &self.job {
+            Some(job) => job.terminate(),
+            None => self.child.kill(),
+        }
+    }
+}
+
+impl Drop for Spawned {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(slot) = self.slot {
+            unix::GROUPS[slot].store(0, Ordering::SeqCst);
+        }
+        RUNNING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Fails with [`Failure::Interrupted`] after Ctrl-C.
+pub(crate) fn check() -> anyhow::Result<()> {
+    if interrupted() {
+        return Err(Failure::Interrupted.into());
+    }
+    Ok(())
+}
+
+/// Installs the handler of Ctrl-C (and of `SIGTERM` and `SIGHUP` on Unix).
+pub(crate) fn init() {
+    #[cfg(unix)]
+    unix::init();
+    #[cfg(windows)]
+    windows::init();
+}
+
+#[cfg(unix)]
+fn spawn_registered(cmd: &mut Command) -> io::Result<Spawned> {
+    use std::os::unix::process::CommandExt as _;
+
+    let child = cmd.process_group(0).spawn()?;
+    let pid = child.id() as libc::pid_t;
+    // The group is killed by the loop waiting for it, if not registered in time or at all.
+    let slot = unix::GROUPS.iter().position(|group| {
+        group
+            .compare_exchange(0, pid, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    });
+    Ok(Spawned { child, slot })
+}
+
+#[cfg(windows)]
+fn spawn_registered(cmd: &mut Command) -> io::Result<Spawned> {
+    let child = cmd.spawn()?;
+    let job = crate::job::Job::assign(&child)
+        .map_err(|err| {
+            log::warn!(
+                "Failed to assign the solution to a job object ({}). Its child processes will not \
+                 be killed with it",
+                err
+            )
+        })
+        .ok();
+    Ok(Spawned { child, job })
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{INTERRUPTED, RUNNING};
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    /// The process groups of the running solutions, or 0. The handler cannot lock a mutex.
+    pub(super) static GROUPS: [AtomicI32; 64] = [const { AtomicI32::new(0) }; 64];
+
+    pub(super) fn init() {
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+            unsafe {
+                let mut action = std::mem::zeroed::<libc::sigaction>();
+                action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                libc::sigemptyset(&mut action.sa_mask);
+                libc::sigaction(signal, &action, std::ptr::null_mut());
+            }
+        }
+    }
+
+    /// Only with the async-signal-safe functions
+    extern "C" fn handle(signal: libc::c_int) {
+        for group in &GROUPS {
+            let pid = group.load(Ordering::SeqCst);
+            if pid != 0 {
+                unsafe { libc::kill(-pid, libc::SIGKILL) };
+            }
+        }
+        if INTERRUPTED.swap(true, Ordering::SeqCst) || RUNNING.load(Ordering::SeqCst) == 0 {
+            unsafe { libc::_exit(128 + signal) };
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{INTERRUPTED, RUNNING};
+    use std::sync::atomic::Ordering;
+    use winapi::{
+        shared::minwindef::{BOOL, DWORD, FALSE, TRUE},
+        um::{consoleapi::SetConsoleCtrlHandler, wincon::CTRL_C_EVENT},
+    };
+
+    pub(super) fn init() {
+        unsafe { SetConsoleCtrlHandler(Some(handle), TRUE) };
+    }
+
+    /// Called in a thread of its own. The default handler exits the process.
+    unsafe extern "system" fn handle(ctrl_type: DWORD) -> BOOL {
+        if ctrl_type == CTRL_C_EVENT
+            && RUNNING.load(Ordering::SeqCst) > 0
+            && !INTERRUPTED.swap(true, Ordering::SeqCst)
+        {
+            TRUE
+        } else {
+            FALSE
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Warnings for the IO patterns too slow for large inputs and outputs: printing or reading a line
+//! at a time in loops, a frequent cause of TLE.
+
+use crate::atcoder;
+use log::warn;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+static LOOP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(for\b.*\bin\b|while\b|loop\b)|\.for_each\(").unwrap());
+// Bounds of the sizes like `N \leq 2 \times 10^5` and `|S| \le 10^{6}`, rather than the values
+static SIZE_BOUND: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?:\b[NMQKHWT]|\|[A-Z]\|)\s*\\leq?\s*(?:[\d.]+\s*\\times\s*)?10\s*\^\s*\{?\s*(\d+)",
+    )
+    .unwrap()
+});
+
+/// Inputs or outputs with `10^5` or more lines may be slow to process a line at a time.
+const LARGE_EXPONENT: u32 = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pattern {
+    Print,
+    ReadLine,
+}
+
+impl Pattern {
+    fn find(rest: &str) -> Option<Self> {
+        if rest.starts_with("println!") || rest.starts_with("print!") {
+            Some(Pattern::Print)
+        } else if rest.starts_with(".read_line(") {
+            Some(Pattern::ReadLine)
+        } else {
+            None
+        }
+    }
+
+    fn advice(&self) -> &'static str {
+        match self {
+            Pattern::Print => {
+                "`println!` in a loop locks and flushes the stdout for each line. Write to \
+                 `BufWriter::new(io::stdout().lock())` with `writeln!` instead."
+            }
+            Pattern::ReadLine => {
+                "`read_line` in a loop reads the input a line at a time. Read the whole input at \
+                 once with `read_to_string` or `proconio::input!` instead."
+            }
+        }
+    }
+}
+
+/// Warns the slow IO patterns in the solution at `src_path`, unless the constraints in
+/// `statement` (the HTML, if cached) are small enough.
+pub(crate) fn warn_slow_io(src_path: &Path, source: &str, statement: Option<&str>) {
+    if statement.is_some_and(|statement| !has_large_constraints(statement)) {
+        return;
+    }
+    // `proconio` replaces `println!` with buffered output.
+    let fastout = source.contains("#[fastout]");
+
+    let mut warned = vec![];
+    for (line_no, pattern) in in_loops(source) {
+        if (pattern == Pattern::Print && fastout) || warned.contains(&pattern) {
+            continue;
+        }
+        warn!("{}:{}: {}", src_path.display(), line_no, pattern.advice());
+        warned.push(pattern);
+    }
+}
+
+fn has_large_constraints(statement: &str) -> bool {
+    let constraints = atcoder::parse_statement(statement, false)
+        .ok()
+        .and_then(|statement| statement.constraints);
+    SIZE_BOUND
+        .captures_iter(constraints.as_deref().unwrap_or(statement))
+        .any(|caps| {
+            caps[1]
+                .parse::<u32>()
+                .is_ok_and(|exp| exp >= LARGE_EXPONENT)
+        })
+}
+
+/// The patterns in the loops with their line numbers, by a rough look at the braces
+fn in_loops(source: &str) -> Vec<(usize, Pattern)> {
+    let mut found = vec![];
+    // Whether each open brace starts a loop
+    let mut braces = Vec::<bool>::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default();
+        let mut loop_head = LOOP.is_match(line);
+        for (j, c) in line.char_indices() {
+            match c {
+                '{' => braces.push(std::mem::take(&mut loop_head)),
+                '}' => drop(braces.pop()),
+                _ => {}
+            }
+            if braces.contains(&true) && !line.contains(".lock()") {
+                if let Some(pattern) = Pattern::find(&line[j..]) {
+                    found.push((i + 1, pattern));
+                }
+            }
+        }
+    }
+    found
+}
@@ -0,0 +1,77 @@
+//! Stack size of the solutions run locally, raised to allow deep recursion as on the judge.
+
+use anyhow::{anyhow, Result};
+use bytesize::ByteSize;
+use std::process::Command;
+
+const DEFAULT_STACK_SIZE: ByteSize = ByteSize::mib(256);
+
+/// The soft limit to set in the solutions, `RLIM_INFINITY` for `unlimited`
+#[cfg(unix)]
+static STACK_SIZE: once_cell::sync::OnceCell<libc::rlim_t> = once_cell::sync::OnceCell::new();
+
+/// Reads `atcoder.stack_size` in the config, which [`apply`] sets in the solutions.
+pub(crate) fn init(config: Option<&str>) -> Result<()> {
+    let size = match config {
+        Some("unlimited") => None,
+        Some(size) => Some(
+            size.parse::<ByteSize>()
+                .map_err(|err| anyhow!("Invalid `atcoder.stack_size` `{}`: {}", size, err))?,
+        ),
+        None => Some(DEFAULT_STACK_SIZE),
+    };
+    #[cfg(unix)]
+    {
+        use std::convert::TryFrom as _;
+
+        let size = size.map_or(libc::RLIM_INFINITY, |size| {
+            libc::rlim_t::try_from(size.as_u64()).unwrap_or(libc::RLIM_INFINITY)
+        });
+        let _ = STACK_SIZE.set(size);
+    }
+    #[cfg(not(unix))]
+    let _ = size;
+    Ok(())
+}
+
+/// Raises the soft limit of the stack size of the solution run by `cmd`, in the child only so
+/// that this process and its other children (cargo, the generators and so on) keep their own.
+/// The limit is kept if it is already larger, and capped by the hard limit. Only for Unix since
+/// the stack size is fixed in the executable on Windows.
+pub(crate) fn apply(cmd: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    if let Some(&size) = STACK_SIZE.get() {
+        use std::os::unix::process::CommandExt as _;
+
+        // SAFETY: `raise` only makes the system calls, which are async-signal-safe.
+        unsafe {
+            cmd.pre_exec(move || {
+                raise(size);
+                Ok(())
+            });
+        }
+    }
+    cmd
+}
+
+/// Failures are ignored, running the solution with the limit as is. Nothing can be logged
+/// between `fork` and `exec`.
+#[cfg(unix)]
+fn raise(size: libc::rlim_t) {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `limit` is a valid `rlimit` to write to and read from.
+    unsafe {
+        if libc::getrlimit(libc::RLIMIT_STACK, &mut limit) != 0 {
+            return;
+        }
+        // `RLIM_INFINITY` is the largest value.
+        if limit.rlim_cur >= size {
+            return;
+        }
+        limit.rlim_cur = size.min(limit.rlim_max);
+        libc::setrlimit(libc::RLIMIT_STACK, &limit);
+    }
+}
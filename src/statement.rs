@@ -0,0 +1,585 @@
+//! Rendering problem statements as plain text for terminals.
+
+use console::Style;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Terminal graphics protocol to show images inline
+#[derive(Clone, Copy)]
+pub(crate) enum ImageProtocol {
+    Kitty,
+    Iterm2,
+}
+
+impl ImageProtocol {
+    /// The protocol supported by the terminal of stdout, detected from the environment variables
+    pub(crate) fn detect() -> Option<Self> {
+        if !console::Term::stdout().is_term() {
+            return None;
+        }
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || env::var("TERM").is_ok_and(|t| t == "xterm-kitty")
+        {
+            Some(ImageProtocol::Kitty)
+        } else if matches!(
+            env::var("TERM_PROGRAM").as_deref(),
+            Ok("iTerm.app") | Ok("WezTerm")
+        ) {
+            Some(ImageProtocol::Iterm2)
+        } else {
+            None
+        }
+    }
+
+    // Kitty can only show PNG files without converting them.
+    fn escape(self, path: &Path) -> Option<String> {
+        let data = fs::read(path).ok()?;
+        let base64 = data_encoding::BASE64.encode(&data);
+        match self {
+            ImageProtocol::Kitty => {
+                if path.extension()? != "png" {
+                    return None;
+                }
+                let chunks = base64.as_bytes().chunks(4096).collect::<Vec<_>>();
+                let mut escape = String::new();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = (i + 1 < chunks.len()) as u8;
+                    let control = if i == 0 {
+                        format!("a=T,f=100,m={}", more)
+                    } else {
+                        format!("m={}", more)
+                    };
+                    escape +=
+                        &format!("\x1b_G{};{}\x1b\\", control, String::from_utf8_lossy(chunk));
+                }
+                Some(escape)
+            }
+            ImageProtocol::Iterm2 => Some(format!(
+                "\x1b]1337;File=inline=1;size={}:{}\x07",
+                data.len(),
+                base64
+            )),
+        }
+    }
+}
+
+/// `src` of the images in the HTML of a problem statement
+pub(crate) fn image_sources(html: &str) -> Vec<String> {
+    let mut sources = Html::parse_fragment(html)
+        .select(&Selector::parse("img[src]").unwrap())
+        .filter_map(|img| img.value().attr("src"))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    sources.dedup();
+    sources
+}
+
+/// Renders the HTML of a problem statement as text.
+///
+/// The formulas in `<var>` are converted with [`latex_to_unicode`]. The images are shown with
+/// `protocol` if given, and their local paths in `images` (or their URLs) are printed.
+pub(crate) fn render(
+    html: &str,
+    images: &BTreeMap<String, PathBuf>,
+    protocol: Option<ImageProtocol>,
+) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut renderer = Renderer::new(images, protocol);
+    renderer.children(fragment.root_element());
+    renderer.out.trim().to_owned() + "\n"
+}
+
+struct Renderer<'a> {
+    out: String,
+    preformatted: bool,
+    images: &'a BTreeMap<String, PathBuf>,
+    protocol: Option<ImageProtocol>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(images: &'a BTreeMap<String, PathBuf>, protocol: Option<ImageProtocol>) -> Self {
+        Self {
+            out: String::new(),
+            preformatted: false,
+            images,
+            protocol,
+        }
+    }
+
+    fn children(&mut self, element: ElementRef<'_>) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.text(text),
+                Node::Element(_) => self.element(ElementRef::wrap(child).unwrap()),
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef<'_>) {
+        match element.value().name() {
+            "script" | "style" => {}
+            "var" => {
+                let formula = element.text().collect::<String>();
+                self.push(&latex_to_unicode(&formula));
+            }
+            "br" => self.out.push('\n'),
+            "img" => {
+                let src = element.value().attr("src").unwrap_or_default();
+                self.block(1);
+                match self.images.get(src) {
+                    Some(path) => {
+                        if let Some(escape) = self.protocol.and_then(|p| p.escape(path)) {
+                            self.out += &escape;
+                            self.out.push('\n');
+                        }
+                        self.out += &format!("[image: {}]", path.display());
+                    }
+                    None => self.out += &format!("[image: {}]", src),
+                }
+                self.block(1);
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block(2);
+                let title = element.text().collect::<String>();
+                let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+                self.out += &Style::new().bold().apply_to(title).to_string();
+                self.block(2);
+            }
+            "pre" => {
+                let mut inner = Renderer {
+                    preformatted: true,
+                    ..Renderer::new(self.images, self.protocol)
+                };
+                inner.children(element);
+                self.block(2);
+                for line in inner.out.trim_matches('\n').lines() {
+                    self.out += "    ";
+                    self.out += line.trim_end();
+                    self.out.push('\n');
+                }
+                self.block(2);
+            }
+            "li" => {
+                self.block(1);
+                self.out += "- ";
+                self.children(element);
+                self.block(1);
+            }
+            "tr" => {
+                self.block(1);
+                for (i, cell) in element
+                    .children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|e| matches!(e.value().name(), "td" | "th"))
+                    .enumerate()
+                {
+                    if i > 0 {
+                        self.out += " | ";
+                    }
+                    self.children(cell);
+                }
+                self.block(1);
+            }
+            "p" | "div" | "section" | "ul" | "ol" | "table" | "blockquote" | "hr" => {
+                self.block(2);
+                self.children(element);
+                self.block(2);
+            }
+            _ => self.children(element),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.preformatted {
+            self.out += text;
+            return;
+        }
+        // Runs of whitespace are collapsed as in browsers.
+        let mut collapsed = String::new();
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed += word;
+        }
+        if text.starts_with(char::is_whitespace) {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+        self.push(&collapsed);
+    }
+
+    fn push(&mut self, s: &str) {
+        let at_line_start = self.out.is_empty() || self.out.ends_with('\n');
+        if at_line_start && !self.preformatted {
+            self.out += s.trim_start_matches(' ');
+        } else if self.out.ends_with(' ') && s.starts_with(' ') {
+            self.out += &s[1..];
+        } else {
+            self.out += s;
+        }
+    }
+
+    // Ends the current line and leaves `newlines - 1` blank lines.
+    fn block(&mut self, newlines: usize) {
+        if self.out.is_empty() {
+            return;
+        }
+        while self.out.ends_with(' ') {
+            self.out.pop();
+        }
+        let trailing = self.out.len() - self.out.trim_end_matches('\n').len();
+        for _ in trailing..newlines {
+            self.out.push('\n');
+        }
+    }
+}
+
+/// Converts a LaTeX formula to readable text, e.g. `N \le 2 \times 10^5` to `N ≤ 2 × 10⁵`.
+///
+/// Unknown commands are left as they are.
+pub(crate) fn latex_to_unicode(formula: &str) -> String {
+    let chars = formula.chars().collect::<Vec<_>>();
+    let mut latex = Latex {
+        chars: &chars,
+        pos: 0,
+    };
+    let out = latex.sequence(false);
+    out.split(' ')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Latex<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl Latex<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    // Until the closing brace if in a group
+    fn sequence(&mut self, group: bool) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '}' if group => break,
+                '{' => out += &self.sequence(true),
+                '\\' => out += &self.command(),
+                '^' => {
+                    let arg = self.argument();
+                    out += &script(&arg, superscript, '^');
+                }
+                '_' => {
+                    let arg = self.argument();
+                    out += &script(&arg, subscript, '_');
+                }
+                '~' | '&' | '\n' | '\t' => out.push(' '),
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    fn argument(&mut self) -> String {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.sequence(true)
+            }
+            Some('\\') => {
+                self.pos += 1;
+                self.command()
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        }
+    }
+
+    fn command(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            let c = match self.peek() {
+                Some(c) => c,
+                None => return "\\".to_owned(),
+            };
+            self.pos += 1;
+            return match c {
+                ',' | ':' | ';' | ' ' | '\\' => " ".to_owned(),
+                '!' => "".to_owned(),
+                c => c.to_string(),
+            };
+        }
+        let name = self.chars[start..self.pos].iter().collect::<String>();
+
+        match &*name {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument();
+                let denominator = self.argument();
+                format!(
+                    "{}/{}",
+                    parenthesize(&numerator),
+                    parenthesize(&denominator)
+                )
+            }
+            "sqrt" => {
+                let mut index = String::new();
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    while let Some(c) = self.peek() {
+                        self.pos += 1;
+                        if c == ']' {
+                            break;
+                        }
+                        index.push(c);
+                    }
+                }
+                let radicand = self.argument();
+                format!(
+                    "{}√{}",
+                    script(&index, superscript, '^').trim_start_matches('^'),
+                    parenthesize(&radicand),
+                )
+            }
+            "binom" => {
+                let n = self.argument();
+                let k = self.argument();
+                format!("C({}, {})", n, k)
+            }
+            "overline" | "bar" => self
+                .argument()
+                .chars()
+                .flat_map(|c| vec![c, '\u{305}'])
+                .collect(),
+            "text" | "textrm" | "textbf" | "textit" | "texttt" | "mathrm" | "mathbf" | "mathit"
+            | "mathsf" | "mathtt" | "mathcal" | "mathbb" | "boldsymbol" | "operatorname" => {
+                self.argument()
+            }
+            "left" | "right" | "bigl" | "bigr" | "Bigl" | "Bigr" | "big" | "Big" => {
+                // `\left.` is an invisible delimiter.
+                if self.peek() == Some('.') {
+                    self.pos += 1;
+                }
+                String::new()
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => String::new(),
+            "quad" | "qquad" => " ".to_owned(),
+            "bmod" | "mod" => " mod ".to_owned(),
+            "pmod" => format!(" (mod {})", self.argument()),
+            "max" | "min" | "log" | "ln" | "lg" | "exp" | "gcd" | "lcm" | "sin" | "cos" | "tan"
+            | "lim" | "sup" | "inf" | "det" | "deg" | "arg" => name,
+            _ => match symbol(&name) {
+                Some(symbol) => symbol.to_owned(),
+                None => format!("\\{}", name),
+            },
+        }
+    }
+}
+
+fn parenthesize(s: &str) -> String {
+    if s.chars().all(|c| c.is_alphanumeric() || c == '.') {
+        s.to_owned()
+    } else {
+        format!("({})", s)
+    }
+}
+
+// Falls back to `^x` or `^(xy)` if some characters have no script forms.
+fn script(s: &str, f: fn(char) -> Option<char>, marker: char) -> String {
+    let s = s.trim();
+    if let Some(converted) = s.chars().map(f).collect::<Option<String>>() {
+        converted
+    } else if s.chars().count() == 1 {
+        format!("{}{}", marker, s)
+    } else {
+        format!("{}({})", marker, s)
+    }
+}
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'n' => 'ⁿ',
+        'i' => 'ⁱ',
+        '′' => '′',
+        _ => return None,
+    })
+}
+
+fn subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        ',' => ',',
+        _ => return None,
+    })
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "leqq" => "≦",
+        "geqq" => "≧",
+        "ne" | "neq" => "≠",
+        "lt" => "<",
+        "gt" => ">",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "times" => "×",
+        "cdot" => "⋅",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "ast" => "*",
+        "star" => "⋆",
+        "circ" => "∘",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "cdots" => "⋯",
+        "ldots" | "dots" => "…",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "sum" => "Σ",
+        "prod" => "Π",
+        "infty" => "∞",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "mid" | "vert" | "lvert" | "rvert" => "|",
+        "Vert" | "lVert" | "rVert" => "‖",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "lnot" | "neg" => "¬",
+        "forall" => "∀",
+        "exists" => "∃",
+        "prime" => "′",
+        "partial" => "∂",
+        "angle" => "∠",
+        "triangle" => "△",
+        "dagger" => "†",
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
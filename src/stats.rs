@@ -0,0 +1,261 @@
+//! `stats`: when each problem was created by `new`, first tested and accepted, recorded in the
+//! state directory to see where the time in contests goes.
+
+use crate::{
+    atcoder::{Submission, Verdict},
+    paths::state_dir,
+    table,
+};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Duration, FixedOffset, Local};
+use itertools::Itertools as _;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct StatsOpt {
+    /// Contest ID to show the times of the problems of (default: all the contests recorded)
+    contest_id: Option<String>,
+}
+
+/// Times of a problem in RFC 3339
+#[derive(Default, Serialize, Deserialize)]
+struct Times {
+    created: Option<String>,
+    tested: Option<String>,
+    accepted: Option<String>,
+}
+
+fn parse_time(time: Option<&str>) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(time?).ok()
+}
+
+impl Times {
+    /// From the first `test` to the first AC
+    fn solve_time(&self) -> Option<Duration> {
+        let time = parse_time(self.accepted.as_deref())? - parse_time(self.tested.as_deref())?;
+        Some(time).filter(|time| *time >= Duration::zero())
+    }
+}
+
+fn stats_dir() -> Result<PathBuf> {
+    Ok(state_dir()?.join("stats"))
+}
+
+/// Times by the problem ID in lowercase
+fn load(contest_id: &str) -> BTreeMap<String, Times> {
+    stats_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(contest_id).with_extension("toml")).ok())
+        .and_then(|s| toml::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+// Recording is best effort since the commands themselves have succeeded.
+fn update(contest_id: &str, f: impl FnOnce(&mut BTreeMap<String, Times>) -> bool) {
+    let mut times = load(contest_id);
+    if !f(&mut times) {
+        return;
+    }
+    let result = stats_dir().and_then(|dir| {
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(contest_id).with_extension("toml");
+        fs::write(&path, toml::to_string(&times)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    });
+    if let Err(err) = result {
+        warn!("failed to record the times of `{}`: {:#}", contest_id, err);
+    }
+}
+
+fn now() -> String {
+    Local::now().to_rfc3339()
+}
+
+pub(crate) fn record_created(contest_id: &str, problem_ids: &[String]) {
+    update(contest_id, |times| {
+        for problem_id in problem_ids {
+            let times = times.entry(problem_id.to_lowercase()).or_default();
+            times.created.get_or_insert_with(now);
+        }
+        true
+    });
+}
+
+pub(crate) fn record_tested(contest_id: &str, problem_id: &str) {
+    update(contest_id, |times| {
+        let times = times.entry(problem_id.to_lowercase()).or_default();
+        if times.tested.is_some() {
+            return false;
+        }
+        times.tested = Some(now());
+        true
+    });
+}
+
+/// Records the first AC of the problems among the submissions. Only the problems created or
+/// tested before are recorded.
+pub(crate) fn record_accepted(contest_id: &str, submissions: &[Submission]) {
+    let accepted = submissions
+        .iter()
+        .filter(|s| s.verdict == Verdict::Ac)
+        .filter_map(|s| {
+            let problem_id = s.task.rsplit('_').next()?.to_lowercase();
+            Some((problem_id, s.submitted_at()?))
+        })
+        .collect::<Vec<_>>();
+    if accepted.is_empty() {
+        return;
+    }
+    update(contest_id, |times| {
+        let mut updated = false;
+        for (problem_id, at) in accepted {
+            if let Some(times) = times.get_mut(&problem_id) {
+                if parse_time(times.accepted.as_deref()).is_none_or(|prev| at < prev) {
+                    times.accepted = Some(at.to_rfc3339());
+                    updated = true;
+                }
+            }
+        }
+        updated
+    });
+}
+
+pub fn stats(opt: StatsOpt) -> Result<()> {
+    match &opt.contest_id {
+        Some(contest_id) => contest_stats(contest_id),
+        None => history(),
+    }
+}
+
+fn format_duration(time: Duration) -> String {
+    let secs = time.num_seconds();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// Orders `a` < ... < `h` < `ex`
+fn problem_order(problem_id: &str) -> (usize, &str) {
+    (problem_id.len(), problem_id)
+}
+
+fn contest_stats(contest_id: &str) -> Result<()> {
+    let times = load(contest_id);
+    if times.is_empty() {
+        info!("No records for `{}`", contest_id);
+        return Ok(());
+    }
+    let format_time = |time: &Option<String>| {
+        parse_time(time.as_deref()).map_or_else(
+            || "-".to_owned(),
+            |time| {
+                time.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            },
+        )
+    };
+    let header = ["Problem", "Created", "First test", "AC", "Solve time"];
+    let rows = times
+        .iter()
+        .sorted_by_key(|(problem_id, _)| problem_order(problem_id))
+        .map(|(problem_id, times)| {
+            vec![
+                problem_id.to_uppercase(),
+                format_time(&times.created),
+                format_time(&times.tested),
+                format_time(&times.accepted),
+                times
+                    .solve_time()
+                    .map_or_else(|| "-".to_owned(), format_duration),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(&header.map(str::to_owned), &rows);
+    Ok(())
+}
+
+/// Solve times of all the contests by the problem, with the medians and the numbers solved
+fn history() -> Result<()> {
+    let dir = stats_dir()?;
+    let mut contests = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                let contest_id = path.file_stem()?.to_str()?.to_owned();
+                Some((contest_id.clone(), load(&contest_id)))
+            })
+            .collect::<Vec<_>>(),
+        Err(_) => vec![],
+    };
+    if contests.is_empty() {
+        info!("No records yet. The times are recorded by `new`, `test` and `status`.");
+        return Ok(());
+    }
+    contests.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let problem_ids = contests
+        .iter()
+        .flat_map(|(_, times)| times.keys())
+        .unique()
+        .sorted_by_key(|problem_id| problem_order(problem_id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut header = vec!["Contest".to_owned()];
+    header.extend(problem_ids.iter().map(|id| id.to_uppercase()));
+    let mut rows = contests
+        .iter()
+        .map(|(contest_id, times)| {
+            let mut row = vec![contest_id.clone()];
+            row.extend(problem_ids.iter().map(|problem_id| {
+                match times.get(problem_id) {
+                    Some(times) => times
+                        .solve_time()
+                        .map_or_else(|| "-".to_owned(), format_duration),
+                    None => "".to_owned(),
+                }
+            }));
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let solve_times = problem_ids
+        .iter()
+        .map(|problem_id| {
+            contests
+                .iter()
+                .filter_map(|(_, times)| times.get(problem_id)?.solve_time())
+                .sorted()
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut median = vec!["(median)".to_owned()];
+    median.extend(solve_times.iter().map(|times| {
+        times
+            .get(times.len() / 2)
+            .map_or_else(|| "-".to_owned(), |&time| format_duration(time))
+    }));
+    let mut solved = vec!["(solved)".to_owned()];
+    solved.extend(problem_ids.iter().map(|problem_id| {
+        let times = contests
+            .iter()
+            .filter_map(|(_, times)| times.get(problem_id))
+            .collect::<Vec<_>>();
+        let accepted = times.iter().filter(|t| t.accepted.is_some()).count();
+        format!("{}/{}", accepted, times.len())
+    }));
+    rows.push(median);
+    rows.push(solved);
+
+    print_table(&header, &rows);
+    Ok(())
+}
+
+pub(crate) fn print_table(header: &[String], rows: &[Vec<String>]) {
+    for line in table::lines(header, rows) {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,308 @@
+//! `brute` and `stress`: random testing of a solution against a brute-force one.
+
+use crate::{
+    build, cmp_output,
+    config::read_config,
+    execute, html, logging,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    print_lines,
+    project::{self, package_dir},
+    variant_bin, CaseStatus,
+};
+use anyhow::{bail, ensure, Context as _, Result};
+use cargo_metadata::Package;
+use console::Style;
+use itertools::Itertools as _;
+use log::info;
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+// Brute-force solutions are allowed to be slow.
+const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+const BRUTE_TEMPLATE: &str = r#"use std::io::{self, Read as _};
+
+// Brute-force solution of problem `{problem}`, checked against by `cargo atcoder stress`
+fn main() {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).unwrap();
+    let mut it = input.split_ascii_whitespace();
+    let n = it.next().unwrap().parse::<usize>().unwrap();
+    let a = (0..n)
+        .map(|_| it.next().unwrap().parse::<u64>().unwrap())
+        .collect::<Vec<_>>();
+    println!("{}", a.iter().sum::<u64>());
+}
+"#;
+
+const GENERATOR_TEMPLATE: &str = r#"use std::env;
+
+// Prints a random input of problem `{problem}` for the seed given as the argument, used by
+// `cargo atcoder stress`
+fn main() {
+    let seed = env::args().nth(1).and_then(|s| s.parse().ok()).unwrap_or(0);
+    let mut rng = XorShift(seed ^ 0x9e37_79b9_7f4a_7c15);
+    let n = rng.range(1, 10);
+    println!("{}", n);
+    let a = (0..n).map(|_| rng.range(1, 100).to_string()).collect::<Vec<_>>();
+    println!("{}", a.join(" "));
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `lo..=hi`
+    fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.next() % (hi - lo + 1)
+    }
+}
+"#;
+
+#[derive(StructOpt)]
+pub struct BruteOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Overwrite the existing files
+    #[structopt(short, long)]
+    force: bool,
+}
+
+/// Creates `src/bin/<problem-id>_brute.rs` and `src/bin/<problem-id>_gen.rs`, and sets them as
+/// the reference and the generator of `stress`.
+pub fn brute(opt: BruteOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let config = read_config()?;
+    let problem_id = &opt.problem_id;
+
+    let reference = format!("{}_brute", problem_id);
+    let generator = format!("{}_gen", problem_id);
+    let template = config
+        .project
+        .brute_template
+        .as_deref()
+        .unwrap_or(BRUTE_TEMPLATE);
+    let bin_dir = package_dir(package).join("src").join("bin");
+    for (bin, template) in [(&reference, template), (&generator, GENERATOR_TEMPLATE)] {
+        let path = bin_dir.join(bin).with_extension("rs");
+        if path.exists() && !opt.force {
+            info!("{} already exists", path.display());
+            continue;
+        }
+        fs::write(&path, template.replace("{problem}", problem_id))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        info!("Created {}", path.display());
+    }
+
+    let mut setting = project::problem_setting(package, problem_id);
+    setting.reference = Some(reference);
+    setting.generator = Some(generator);
+    project::save_problem_setting(package, problem_id, &setting)
+}
+
+#[derive(StructOpt)]
+pub struct StressOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Number of random inputs to try
+    #[structopt(short = "n", long, default_value = "100")]
+    iterations: u64,
+    /// Seed of the first input
+    #[structopt(long, default_value = "0")]
+    seed: u64,
+    /// Check `src/bin/<problem-id>_<variant>.rs` instead
+    #[structopt(long, value_name("NAME"))]
+    variant: Option<String>,
+    /// [cargo build] Use --release flag to compile
+    #[structopt(long)]
+    release: bool,
+    /// Keep trying the inputs after a counterexample, saving all of them
+    #[structopt(long)]
+    keep_going: bool,
+    /// Write an HTML report of the inputs to `stress/<problem-id>/report.html`
+    #[structopt(long)]
+    html: bool,
+}
+
+/// Compares the outputs of the solution and the reference on the inputs from the generator
+/// until they differ.
+pub fn stress(opt: StressOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let problem_id = &opt.problem_id;
+
+    let setting = project::problem_setting(package, problem_id);
+    let solution = variant_bin(package, problem_id, opt.variant.as_deref())?;
+    let reference = setting
+        .reference
+        .unwrap_or_else(|| format!("{}_brute", problem_id));
+    let generator = setting
+        .generator
+        .unwrap_or_else(|| format!("{}_gen", problem_id));
+    for bin in [&reference, &generator] {
+        package.find_bin(bin).with_context(|| {
+            format!(
+                "Run `cargo atcoder brute {}` to create the reference and the generator",
+                problem_id
+            )
+        })?;
+    }
+    let build = |bin: &str| {
+        build::build_bin(package, bin, opt.release)?
+            .with_context(|| format!("Failed to build `{}`", bin))
+    };
+    let (solution_path, reference_path, generator_path) =
+        (build(&solution)?, build(&reference)?, build(&generator)?);
+
+    let mut pb = logging::progress_bar(opt.iterations, "Stress");
+    let mut rows = vec![];
+    let mut counterexamples = vec![];
+    for seed in opt.seed..opt.seed + opt.iterations {
+        let mut cmd = Command::new(&generator_path);
+        cmd.arg(seed.to_string());
+        let (output, timed_out) = execute(cmd, b"", TIME_LIMIT)?;
+        ensure!(
+            !timed_out && output.status.success(),
+            "`{}` failed for seed {}:\n{}",
+            generator,
+            seed,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let input = output.stdout;
+
+        let (expected, timed_out) = execute(Command::new(&reference_path), &input, TIME_LIMIT)?;
+        ensure!(
+            !timed_out && expected.status.success(),
+            "`{}` failed for seed {}:\n{}",
+            reference,
+            seed,
+            String::from_utf8_lossy(&expected.stderr)
+        );
+        let start = Instant::now();
+        let (actual, timed_out) = execute(Command::new(&solution_path), &input, TIME_LIMIT)?;
+        let time = start.elapsed();
+        let expected = String::from_utf8_lossy(&expected.stdout);
+        let status = if timed_out {
+            CaseStatus::Tle
+        } else if !actual.status.success() {
+            CaseStatus::Re
+        } else if !cmp_output(&expected, &String::from_utf8_lossy(&actual.stdout)).0 {
+            CaseStatus::Wa
+        } else {
+            CaseStatus::Ac
+        };
+        let mut row = html::Row {
+            name: format!("seed {}", seed),
+            status: status.label().to_owned(),
+            time: Some(time),
+            link: None,
+            details: None,
+        };
+        if status != CaseStatus::Ac {
+            pb.finish_and_clear();
+            report(package, problem_id, seed, &input, &expected, &actual.stdout)?;
+            // A new bar under the counterexample
+            pb = logging::progress_bar(opt.iterations, "Stress");
+            pb.set_position(seed - opt.seed);
+            row.link = Some(format!("{}.in", seed));
+            row.details = Some(format!(
+                "input:\n{}\nexpected output:\n{}\nyour output:\n{}",
+                String::from_utf8_lossy(&input),
+                expected,
+                String::from_utf8_lossy(&actual.stdout),
+            ));
+            counterexamples.push(seed);
+        }
+        rows.push(row);
+        pb.inc(1);
+        if !counterexamples.is_empty() && !opt.keep_going {
+            break;
+        }
+    }
+    pb.finish_and_clear();
+
+    if opt.html {
+        let path = package_dir(package)
+            .join("stress")
+            .join(problem_id)
+            .join("report.html");
+        let title = format!(
+            "stress {} {} ({} inputs)",
+            package.name,
+            solution,
+            rows.len()
+        );
+        html::write(&path, &title, &rows)?;
+        info!("Report saved at {}", path.display());
+    }
+    match &*counterexamples {
+        [] => {}
+        [seed] => bail!("Found a counterexample for seed {}", seed),
+        seeds => bail!(
+            "Found {} counterexamples for seeds {}",
+            seeds.len(),
+            seeds.iter().join(", ")
+        ),
+    }
+    info!(
+        "`{}` agreed with `{}` on {} inputs",
+        solution, reference, opt.iterations
+    );
+    Ok(())
+}
+
+// Saved to be reused with `run --input`.
+fn report(
+    package: &Package,
+    problem_id: &str,
+    seed: u64,
+    input: &[u8],
+    expected: &str,
+    actual: &[u8],
+) -> Result<()> {
+    let dir = package_dir(package).join("stress").join(problem_id);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(seed.to_string()).with_extension("in");
+    fs::write(&path, input).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    println!(
+        "{} ({}):",
+        Style::new().cyan().apply_to("input"),
+        path.display()
+    );
+    print_lines(&String::from_utf8_lossy(input));
+    println!();
+    println!("{}:", Style::new().green().apply_to("expected output"));
+    print_lines(expected);
+    println!();
+    println!("{}:", Style::new().red().apply_to("your output"));
+    print_lines(&String::from_utf8_lossy(actual));
+    println!();
+    Ok(())
+}
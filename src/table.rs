@@ -0,0 +1,32 @@
+//! Tables of the commands, aligned in the terminal columns.
+
+use itertools::Itertools as _;
+
+/// Lines of `header` and `rows` with the cells separated by ` | `.
+///
+/// The cells are measured in the columns they take, so the styled ones and the ones with wide
+/// characters (e.g. the Japanese names) are aligned too.
+pub(crate) fn lines(header: &[String], rows: &[Vec<String>]) -> Vec<String> {
+    let widths = (0..header.len())
+        .map(|i| {
+            Some(header)
+                .into_iter()
+                .chain(rows.iter().map(Vec::as_slice))
+                .map(|row| console::measure_text_width(&row[i]))
+                .max()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    Some(header)
+        .into_iter()
+        .chain(rows.iter().map(Vec::as_slice))
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, &width)| console::pad_str(cell, width, console::Alignment::Left, None))
+                .join(" | ")
+                .trim_end()
+                .to_owned()
+        })
+        .collect()
+}
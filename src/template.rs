@@ -0,0 +1,154 @@
+//! `template`: keeps the dependencies of the template in the config in sync with the judge
+//! environment.
+
+use crate::{
+    config::{read_config_preserving, write_config_preserving},
+    http,
+};
+use anyhow::{Context as _, Result};
+use console::Style;
+use log::{info, warn};
+use std::fs;
+use structopt::StructOpt;
+use toml_edit::{Document, Item, Value};
+
+#[derive(StructOpt)]
+pub enum TemplateOpt {
+    /// Rewrite the dependency versions in the config to the ones of a new judge environment
+    Migrate(MigrateOpt),
+}
+
+#[derive(StructOpt)]
+pub struct MigrateOpt {
+    /// `Cargo.toml` of the new official environment (a path or a URL)
+    manifest: String,
+    /// Only show the changes without rewriting the config
+    #[structopt(long)]
+    dry_run: bool,
+}
+
+pub async fn template(opt: TemplateOpt) -> Result<()> {
+    match opt {
+        TemplateOpt::Migrate(opt) => migrate(opt).await,
+    }
+}
+
+async fn migrate(opt: MigrateOpt) -> Result<()> {
+    let official = if opt.manifest.starts_with("https://") || opt.manifest.starts_with("http://") {
+        reqwest::ClientBuilder::new()
+            .timeout(http::timeout())
+            .build()?
+            .get(&opt.manifest)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?
+    } else {
+        fs::read_to_string(&opt.manifest)
+            .with_context(|| format!("Failed to read {}", opt.manifest))?
+    };
+    let official = official
+        .parse::<Document>()
+        .with_context(|| format!("Failed to parse {}", opt.manifest))?;
+    let official_deps = official["dependencies"]
+        .as_table()
+        .with_context(|| format!("No `[dependencies]` in {}", opt.manifest))?;
+
+    let mut config = read_config_preserving()?;
+    let names = config["dependencies"]
+        .as_table()
+        .map(|deps| deps.iter().map(|(name, _)| name.to_owned()).collect())
+        .unwrap_or_else(Vec::new);
+
+    let mut updated = 0;
+    let mut removed = vec![];
+    for name in &names {
+        let item = &mut config["dependencies"][name.as_str()];
+        let new = match official_deps.get(name).and_then(version) {
+            Some(new) => new.to_owned(),
+            None => {
+                removed.push(name.as_str());
+                continue;
+            }
+        };
+        // Not the ones without a version (e.g. from git)
+        let old = match version(item) {
+            Some(old) if old != new => old.to_owned(),
+            _ => continue,
+        };
+        if !set_version(item, &new) {
+            continue;
+        }
+        println!(
+            "{}: {} -> {}",
+            name,
+            old,
+            Style::new().green().apply_to(&new)
+        );
+        updated += 1;
+    }
+
+    let added = official_deps
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| !names.iter().any(|n| n == name))
+        .collect::<Vec<_>>();
+    if !added.is_empty() {
+        info!(
+            "Also available in the new environment: {}",
+            added.join(", ")
+        );
+    }
+    if !removed.is_empty() {
+        warn!(
+            "Not available in the new environment (remove them from the config): {}",
+            removed.join(", ")
+        );
+    }
+
+    if updated == 0 {
+        info!("The dependency versions are up to date.");
+    } else if opt.dry_run {
+        info!("{} dependencies would be updated.", updated);
+    } else {
+        write_config_preserving(&config)?;
+        info!("Updated {} dependencies in the config.", updated);
+    }
+    Ok(())
+}
+
+// Either `foo = "1.0"`, `foo = { version = "1.0", ... }` or `[dependencies.foo]` with
+// `version = "1.0"`
+fn version(item: &Item) -> Option<&str> {
+    match item {
+        Item::Table(table) => table.get("version")?.as_str(),
+        Item::Value(Value::InlineTable(table)) => table.get("version")?.as_str(),
+        item => item.as_str(),
+    }
+}
+
+// Keeps the features and the formatting, returning whether the version is rewritten.
+fn set_version(item: &mut Item, version: &str) -> bool {
+    let value = match item {
+        Item::Table(table) => match table.get_mut("version").and_then(Item::as_value_mut) {
+            Some(value) => value,
+            None => return false,
+        },
+        Item::Value(Value::InlineTable(table)) => match table.get_mut("version") {
+            Some(value) => value,
+            None => return false,
+        },
+        Item::Value(value) => value,
+        _ => return false,
+    };
+    if value.as_str().is_none() {
+        return false;
+    }
+    let (prefix, suffix) = (
+        value.decor().prefix().to_owned(),
+        value.decor().suffix().to_owned(),
+    );
+    *value = toml_edit::decorated(version.into(), &prefix, &suffix);
+    true
+}
@@ -0,0 +1,323 @@
+//! `testcase`: rescues the sample cases by hand when they can't be scraped from the page, and
+//! generates the integration test running them with `cargo test`.
+
+use crate::{
+    atcoder::TestCase,
+    cache, ci,
+    config::read_config,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    project, selectors, timer,
+};
+use anyhow::{anyhow, ensure, Context as _, Result};
+use dialoguer::Select;
+use log::{info, warn};
+use scraper::{ElementRef, Html};
+use std::{env, fmt::Write as _, fs, path::PathBuf};
+use structopt::StructOpt;
+
+// Characters of the first line of a block shown in the picker
+const PREVIEW_WIDTH: usize = 40;
+
+#[derive(StructOpt)]
+pub enum TestcaseOpt {
+    /// Pick the sample cases of a problem from the `<pre>` blocks of a saved page
+    Import(ImportOpt),
+    /// Generate `tests/samples.rs` to run the samples with `cargo test`
+    Harness(HarnessOpt),
+}
+
+#[derive(StructOpt)]
+pub struct ImportOpt {
+    /// Problem ID (e.g. a, b, ...)
+    problem_id: String,
+    /// HTML of the problem page (e.g. the diagnostic file written when scraping failed)
+    #[structopt(long, value_name("FILE"))]
+    from_html: PathBuf,
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+#[derive(StructOpt)]
+pub struct HarnessOpt {
+    /// [cargo] Package of the contest
+    #[structopt(short, long, value_name("SPEC"))]
+    package: Option<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+}
+
+pub fn testcase(opt: TestcaseOpt) -> Result<()> {
+    match opt {
+        TestcaseOpt::Import(opt) => import(opt),
+        TestcaseOpt::Harness(opt) => harness(opt),
+    }
+}
+
+/// A `<pre>` block with the heading of the section containing it.
+struct Block {
+    label: String,
+    text: String,
+}
+
+impl Block {
+    fn preview(&self) -> String {
+        let mut lines = self.text.lines();
+        let first = lines.next().unwrap_or_default();
+        let mut preview = first.chars().take(PREVIEW_WIDTH).collect::<String>();
+        if preview.len() < first.len() || lines.next().is_some() {
+            preview += " ...";
+        }
+        preview
+    }
+}
+
+fn blocks(html: &str) -> Vec<Block> {
+    let doc = Html::parse_document(html);
+    let h3_sel = selectors::get("sample-heading");
+    doc.select(&selectors::get("sample"))
+        .map(|pre| {
+            let label = pre
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find_map(|e| e.select(&h3_sel).next())
+                .map(|h3| h3.text().collect::<String>().trim().to_owned())
+                .unwrap_or_else(|| "(no heading)".to_owned());
+            Block {
+                label,
+                text: pre.text().collect::<String>().trim().to_owned(),
+            }
+        })
+        .collect()
+}
+
+/// Saves the page whose samples could not be paired with the structure detected in it, and
+/// returns the error telling how to pick the samples from it.
+pub(crate) fn scrape_failure(
+    contest_id: &str,
+    problem_id: &str,
+    html: &str,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    let doc = Html::parse_document(html);
+    let h3_sel = selectors::get("sample-heading");
+    let pre_sel = selectors::get("sample");
+
+    let mut structure = String::new();
+    for h3 in doc.select(&h3_sel) {
+        let label = h3.text().collect::<String>();
+        let pre = h3
+            .parent()
+            .and_then(ElementRef::wrap)
+            .and_then(|p| p.select(&pre_sel).next());
+        let detail = match pre {
+            Some(pre) => format!(
+                "{} lines",
+                pre.text().collect::<String>().trim().lines().count()
+            ),
+            None => "no <pre>".to_owned(),
+        };
+        writeln!(structure, "  {}: {}", label.trim(), detail).unwrap();
+    }
+    let header = format!(
+        "<!--\n{}\n\nHeadings (h3) found and the <pre> blocks in their sections:\n{}\nPick the \
+         samples by `cargo atcoder testcase import {} --from-html <this file>`.\n-->\n",
+        err,
+        structure,
+        problem_id.to_lowercase(),
+    );
+
+    match cache::save_sample_diagnostic(contest_id, problem_id, &(header + html)) {
+        Ok(path) => anyhow!(
+            "{}\nThe page and the structure detected in it are written to {}. Pick the samples \
+             by `cargo atcoder testcase import {} --from-html {}`",
+            err,
+            path.display(),
+            problem_id.to_lowercase(),
+            path.display(),
+        ),
+        Err(save_err) => err.context(save_err),
+    }
+}
+
+fn import(opt: ImportOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+
+    let html = fs::read_to_string(&opt.from_html)
+        .with_context(|| format!("Failed to read {}", opt.from_html.display()))?;
+    let blocks = blocks(&html);
+    ensure!(
+        !blocks.is_empty(),
+        "No `<pre>` blocks in {}",
+        opt.from_html.display()
+    );
+
+    ensure!(
+        ci::interactive(),
+        "`testcase import` needs to ask which blocks to use"
+    );
+
+    let mut items = vec!["(done)".to_owned()];
+    items.extend(
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| format!("[{}] {}: {}", i + 1, b.label, b.preview())),
+    );
+
+    let mut cases = vec![];
+    // Samples usually come as input, output, input, output, ...
+    let mut next = blocks
+        .iter()
+        .position(|b| b.label.contains("入力例") || b.label.contains("Sample Input"))
+        .unwrap_or(0);
+    let _paused = timer::pause();
+    loop {
+        let n = cases.len() + 1;
+        let input = Select::new()
+            .with_prompt(format!("Input of sample {}", n))
+            .items(&items)
+            .default(if next < blocks.len() { next + 1 } else { 0 })
+            .interact()?;
+        if input == 0 {
+            break;
+        }
+        let output = Select::new()
+            .with_prompt(format!("Output of sample {}", n))
+            .items(&items[1..])
+            .default(input.min(blocks.len() - 1))
+            .interact()?;
+        cases.push(TestCase {
+            input: blocks[input - 1].text.clone(),
+            output: blocks[output].text.clone(),
+        });
+        next = output + 1;
+    }
+    ensure!(!cases.is_empty(), "No samples picked");
+
+    cache::save_samples(&package.name, &opt.problem_id, &cases)?;
+    // The sample files are read instead of the cache if any.
+    let dir = project::package_dir(package);
+    if project::has_sample_files(dir, &opt.problem_id) || read_config()?.project.sample_files {
+        project::save_sample_files(dir, &opt.problem_id, &cases)?;
+    }
+    info!("Saved {} sample cases of `{}`", cases.len(), opt.problem_id);
+    Ok(())
+}
+
+// Compares the outputs as `test` does, with the same tolerance for floats.
+const HARNESS: &str = r#"// Generated by `cargo atcoder testcase harness`. Runs the samples in `tests/<bin>/in_N.txt` and
+// `out_N.txt` on the bins with `cargo test --test samples`.
+
+use std::{
+    fs,
+    io::Write as _,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+const ERROR_THRESHOLD: f64 = 1e-6;
+
+fn matches(expected: &str, actual: &str) -> bool {
+    let expected = expected.split_whitespace().collect::<Vec<_>>();
+    let actual = actual.split_whitespace().collect::<Vec<_>>();
+    expected.len() == actual.len()
+        && expected.iter().zip(&actual).all(|(e, a)| {
+            let floats = (e.contains('.') || a.contains('.'))
+                .then(|| Some((e.parse::<f64>().ok()?, a.parse::<f64>().ok()?)))
+                .flatten();
+            e == a
+                || floats.map_or(false, |(e, a)| {
+                    (e - a).abs() <= ERROR_THRESHOLD || (e - a).abs() <= ERROR_THRESHOLD * e.abs()
+                })
+        })
+}
+
+fn run(bin: &str, exe: &str) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(bin);
+    let mut failed = vec![];
+    let mut n = 1;
+    while let Ok(input) = fs::read_to_string(dir.join(format!("in_{}.txt", n))) {
+        let expected = fs::read_to_string(dir.join(format!("out_{}.txt", n))).unwrap();
+        let mut child = Command::new(exe)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Fed from a thread so that a solution printing before reading all the input does not block
+        // on the full pipe of stdout. The solutions exiting before reading it all are not errors.
+        let mut stdin = child.stdin.take().unwrap();
+        let feeder = thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+        let output = child.wait_with_output().unwrap();
+        feeder.join().unwrap();
+        let actual = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            eprintln!("sample {}: RE ({})", n, output.status);
+            failed.push(n);
+        } else if !matches(&expected, &actual) {
+            eprintln!("sample {}: WA\nexpected:\n{}actual:\n{}", n, expected, actual);
+            failed.push(n);
+        }
+        n += 1;
+    }
+    assert!(n > 1, "no samples in {}", dir.display());
+    assert!(failed.is_empty(), "failed samples: {:?}", failed);
+}
+"#;
+
+fn harness(opt: HarnessOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let package = metadata.query_for_member(opt.package.as_deref())?;
+    let dir = project::package_dir(package);
+
+    let mut source = HARNESS.to_owned();
+    // Not the variants, generators and so on (e.g. `e_v2`)
+    let mut bins = package
+        .all_bins()
+        .into_iter()
+        .map(|t| t.name.clone())
+        .filter(|name| !name.contains('_'))
+        .collect::<Vec<_>>();
+    bins.sort();
+    for bin in bins {
+        if !project::has_sample_files(dir, &bin) {
+            match cache::load_samples(&package.name, &bin) {
+                Some(cases) => project::save_sample_files(dir, &bin, &cases)?,
+                None => {
+                    warn!(
+                        "No samples of `{}` are fetched yet. Run `cargo atcoder test {}` first.",
+                        bin, bin
+                    );
+                    continue;
+                }
+            }
+        }
+        write!(
+            source,
+            "\n#[test]\nfn {}() {{\n    run({:?}, env!(\"CARGO_BIN_EXE_{}\"));\n}}\n",
+            bin.replace('-', "_"),
+            bin,
+            bin,
+        )
+        .unwrap();
+    }
+
+    let path = dir.join("tests").join("samples.rs");
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))?;
+    info!(
+        "Generated {}. Run the samples by `cargo test --test samples`.",
+        path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! Remaining time of the contest and the penalty in the terminal title, shown while the commands
+//! run during the contest if `atcoder.timer` is set in the config.
+
+use crate::{
+    atcoder::{AtCoder, ContestInfo, Submission, Verdict},
+    config::Config,
+};
+use chrono::{DateTime, FixedOffset, Utc};
+use std::{
+    collections::BTreeMap,
+    io::{self, Write as _},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// The number of the prompts shown, during which the title is not drawn
+static PAUSED: AtomicUsize = AtomicUsize::new(0);
+
+/// Held while the title is drawn, for [`pause`] to wait for it
+static DRAWING: Mutex<()> = Mutex::new(());
+
+/// The penalty shown, or [`NO_PENALTY`] until it is fetched
+static PENALTY: AtomicUsize = AtomicUsize::new(NO_PENALTY);
+
+const NO_PENALTY: usize = usize::MAX;
+
+// The submissions are fetched less often than the title is drawn, not to overload AtCoder.
+const PENALTY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Starts updating the title every second until the process exits, with the penalty refetched
+/// every [`PENALTY_INTERVAL`]. Failures are ignored since the timer is only a help.
+pub(crate) async fn start(atc: &AtCoder, config: &Config, contest_id: &str, info: &ContestInfo) {
+    let (start, end) = match (info.start_time(), info.end_time()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return,
+    };
+    let now = Utc::now();
+    if !config.atcoder.timer
+        || now < start
+        || now > end
+        || !console::Term::stderr().is_term()
+        || STARTED.swap(true, Ordering::Relaxed)
+    {
+        return;
+    }
+
+    {
+        let atc = atc.clone();
+        let contest_id = contest_id.to_owned();
+        tokio::spawn(async move {
+            loop {
+                if let Some(submissions) = submissions(&atc, &contest_id).await {
+                    PENALTY.store(penalty(&submissions), Ordering::Relaxed);
+                }
+                tokio::time::sleep(PENALTY_INTERVAL).await;
+            }
+        });
+    }
+    // Saves the title to restore it in `stop`.
+    eprint!("\x1b[22;0t");
+    let contest_id = contest_id.to_owned();
+    thread::spawn(move || loop {
+        {
+            let _drawing = DRAWING.lock().unwrap_or_else(|err| err.into_inner());
+            if PAUSED.load(Ordering::SeqCst) == 0 {
+                let penalty = Some(PENALTY.load(Ordering::Relaxed)).filter(|&p| p != NO_PENALTY);
+                eprint!("\x1b]0;{}\x07", title(&contest_id, end, penalty));
+                let _ = io::stderr().flush();
+            }
+        }
+        thread::sleep(Duration::from_secs(1));
+    });
+}
+
+/// Stops drawing the title until dropped, not to write over a prompt
+pub(crate) struct Paused(());
+
+/// Pauses drawing the title while a prompt is shown, waiting for the one being drawn.
+pub(crate) fn pause() -> Paused {
+    PAUSED.fetch_add(1, Ordering::SeqCst);
+    drop(DRAWING.lock());
+    Paused(())
+}
+
+impl Drop for Paused {
+    fn drop(&mut self) {
+        PAUSED.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Restores the title saved in `start`.
+pub(crate) fn stop() {
+    if STARTED.load(Ordering::Relaxed) {
+        eprint!("\x1b[23;0t");
+    }
+}
+
+fn title(contest_id: &str, end: DateTime<FixedOffset>, penalty: Option<usize>) -> String {
+    let secs = (end.with_timezone(&Utc) - Utc::now()).num_seconds().max(0);
+    let mut title = format!(
+        "{} {}:{:02}:{:02} left",
+        contest_id,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    );
+    if let Some(penalty) = penalty {
+        title += &format!(", penalty {}", penalty);
+    }
+    title
+}
+
+async fn submissions(atc: &AtCoder, contest_id: &str) -> Option<Vec<Submission>> {
+    let mut submissions = vec![];
+    let mut page = 1;
+    loop {
+        let (mut page_submissions, num_pages) = atc.submissions(contest_id, page).await.ok()?;
+        submissions.append(&mut page_submissions);
+        if page >= num_pages {
+            return Some(submissions);
+        }
+        page += 1;
+    }
+}
+
+/// Rejected submissions before the first AC of the problems accepted, not counting CE
+fn penalty(submissions: &[Submission]) -> usize {
+    // Submission IDs increase with time.
+    let mut first_ac = BTreeMap::new();
+    for submission in submissions.iter().filter(|s| s.verdict == Verdict::Ac) {
+        let id = first_ac.entry(&submission.task).or_insert(submission.id);
+        *id = (*id).min(submission.id);
+    }
+    submissions
+        .iter()
+        .filter(|s| first_ac.get(&s.task).is_some_and(|&ac| s.id < ac))
+        .filter(|s| s.verdict.is_judged() && s.verdict != Verdict::Ce)
+        .count()
+}
@@ -0,0 +1,677 @@
+//! `verify`: runs the samples of every solution in an archive of solutions, e.g. on CI.
+//!
+//! The problem of a bin is given in `verify.toml` at the workspace root:
+//!
+//! ```toml
+//! [abc999] # package
+//! a = "https://atcoder.jp/contests/abc999/tasks/abc999_a"
+//! ```
+//!
+//! or by the first URL of a task in the comments of its source.
+//!
+//! With `--library`, the modules of a library are verified by the harness bins generated for the
+//! problems in `[library]`:
+//!
+//! ```toml
+//! [library.segtree]
+//! problem = "https://atcoder.jp/contests/practice2/tasks/practice2_j"
+//! module = "src/segtree.rs" # included as `mod segtree;`
+//! main = "verify/segtree.rs" # `main` solving the problem with `segtree`
+//! ```
+
+use crate::{
+    atcoder::{parse_test_cases, AtCoder, TestCase},
+    build, cache,
+    ci::Failure,
+    config::read_config,
+    html, junit, library_checker, logging,
+    metadata::{self, MetadataExt as _, PackageExt as _},
+    parse_duration,
+    project::{self, package_dir},
+    run_samples, session_file, testcase, CaseStatus, RunFlags, DEFAULT_TIME_LIMIT,
+};
+use anyhow::{anyhow, ensure, Context as _, Result};
+use cargo_metadata::{Package, Target};
+use console::Style;
+use itertools::Itertools as _;
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::BTreeMap, env, fmt::Write as _, fs, path::PathBuf, str::FromStr, time::Duration,
+};
+use structopt::StructOpt;
+
+static TASK_URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"https://atcoder\.jp/contests/([0-9A-Za-z_-]+)/tasks/([0-9A-Za-z_]+)").unwrap()
+});
+
+#[derive(StructOpt)]
+pub struct VerifyOpt {
+    /// [cargo] Package(s) to verify (all the members by default)
+    #[structopt(short, long, value_name("SPEC"), number_of_values(1))]
+    package: Vec<String>,
+    /// [cargo] Path to Cargo.toml
+    #[structopt(long, value_name("PATH"))]
+    manifest_path: Option<PathBuf>,
+    /// Format of the report [possible values: markdown, junit, json]
+    #[structopt(long, default_value("markdown"), value_name("FORMAT"))]
+    format: Format,
+    /// Verify only the M-th of N shards of the bins with problems (e.g. `1/4`), for CI jobs
+    /// run in parallel
+    #[structopt(long, value_name("M/N"))]
+    partition: Option<Partition>,
+    /// Write the report to the file instead of stdout (e.g. `$GITHUB_STEP_SUMMARY`)
+    #[structopt(short, long, value_name("PATH"))]
+    output: Option<PathBuf>,
+    /// Time limit of each sample (e.g. 2s)
+    #[structopt(long, value_name("DURATION"), parse(try_from_str = parse_duration))]
+    time_limit: Option<chrono::Duration>,
+    /// Verify the modules in `[library]` by the harness bins generated for them
+    #[structopt(long)]
+    library: bool,
+    /// [cargo build] Use --release flag to compile
+    #[structopt(long)]
+    release: bool,
+    /// Also write an HTML report of the samples to `verify.html` at the workspace root
+    #[structopt(long)]
+    html: bool,
+}
+
+#[derive(Deserialize, Default)]
+struct Mapping {
+    #[serde(default)]
+    library: BTreeMap<String, Library>,
+    /// URLs of the problems of the bins for each package
+    #[serde(flatten)]
+    bins: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Module of a library verified by a problem
+#[derive(Deserialize)]
+struct Library {
+    problem: String,
+    module: Option<String>,
+    main: String,
+}
+
+enum Format {
+    Markdown,
+    Junit,
+    /// Events of libtest's `--format json`, one per line
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "markdown" | "md" => Ok(Format::Markdown),
+            "junit" => Ok(Format::Junit),
+            "json" => Ok(Format::Json),
+            _ => Err(anyhow!(
+                "Unknown format: `{}` (expected `markdown`, `junit` or `json`)",
+                s
+            )),
+        }
+    }
+}
+
+/// Shard of the bins, counted from 1 as `cargo nextest --partition count:M/N`
+struct Partition {
+    shard: usize,
+    total: usize,
+}
+
+impl Partition {
+    fn contains(&self, index: usize) -> bool {
+        index % self.total == self.shard - 1
+    }
+}
+
+impl FromStr for Partition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (shard, total) = s
+            .strip_prefix("count:")
+            .unwrap_or(s)
+            .split_once('/')
+            .and_then(|(shard, total)| Some((shard.parse().ok()?, total.parse().ok()?)))
+            .with_context(|| format!("Invalid partition: `{}` (expected e.g. `1/4`)", s))?;
+        ensure!(
+            1 <= shard && shard <= total,
+            "Invalid partition: `{}` (the shard should be from 1 to {})",
+            s,
+            total
+        );
+        Ok(Partition { shard, total })
+    }
+}
+
+/// Problem solved by a bin
+enum Task {
+    /// Task of AtCoder, tested with the samples
+    AtCoder { contest_id: String, name: String },
+    /// Problem of Library Checker, tested with all the testcases
+    LibraryChecker { name: String },
+}
+
+impl Task {
+    fn from_url(url: &str) -> Option<Self> {
+        if let Some(caps) = TASK_URL.captures(url) {
+            return Some(Task::AtCoder {
+                contest_id: caps[1].to_owned(),
+                name: caps[2].to_owned(),
+            });
+        }
+        library_checker::problem_name(url).map(|name| Task::LibraryChecker { name })
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Task::AtCoder { name, .. } | Task::LibraryChecker { name } => name,
+        }
+    }
+
+    fn url(&self) -> String {
+        match self {
+            Task::AtCoder { contest_id, name } => {
+                format!("https://atcoder.jp/contests/{}/tasks/{}", contest_id, name)
+            }
+            Task::LibraryChecker { name } => library_checker::problem_url(name),
+        }
+    }
+}
+
+enum Outcome {
+    Skipped,
+    // Failed to get the samples
+    Error(anyhow::Error),
+    BuildFailed,
+    Ran(Vec<(usize, CaseStatus)>),
+}
+
+enum Testcases {
+    Samples(Vec<TestCase>),
+    // Generated in the clone of the problems, judged by the checker
+    LibraryChecker(library_checker::Problem),
+}
+
+struct Entry {
+    package: String,
+    bin: String,
+    task: Option<Task>,
+    outcome: Outcome,
+    /// Path to the notes on the problem from the workspace root, if any
+    note: Option<String>,
+    /// The samples run, with the times and the outputs of the failing ones
+    cases: Vec<junit::Case>,
+}
+
+pub async fn verify(opt: VerifyOpt) -> Result<()> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+    let mapping_path = metadata.workspace_root.join("verify.toml");
+    let mapping = match fs::read_to_string(&mapping_path) {
+        Ok(s) => toml::from_str::<Mapping>(&s)
+            .with_context(|| format!("Failed to parse {}", mapping_path))?,
+        Err(_) => Mapping::default(),
+    };
+
+    let (metadata, harnesses) = if opt.library {
+        ensure!(
+            !mapping.library.is_empty(),
+            "No `[library]` in {}",
+            mapping_path
+        );
+        let package = metadata.query_for_member(opt.package.first().map(String::as_str))?;
+        let harnesses = generate_harnesses(package, &mapping.library)?;
+        let package = package.name.clone();
+        // to find the generated bins
+        let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
+        (metadata, Some((package, harnesses)))
+    } else {
+        (metadata, None)
+    };
+    let scratch_bins = read_config()?.project.scratch_bins;
+    let targets = match &harnesses {
+        Some((name, harnesses)) => {
+            let package = metadata
+                .all_members()
+                .into_iter()
+                .find(|p| p.name == *name)
+                .expect("should be a member");
+            let bins = package
+                .all_bins()
+                .into_iter()
+                .filter(|bin| harnesses.contains(&bin.name))
+                .collect();
+            vec![(package, bins)]
+        }
+        None if opt.package.is_empty() => metadata
+            .all_members()
+            .into_iter()
+            .map(|p| (p, p.solution_bins(&scratch_bins)))
+            .collect(),
+        None => opt
+            .package
+            .iter()
+            .map(|spec| {
+                let package = metadata.query_for_member(Some(spec))?;
+                Ok((package, package.solution_bins(&scratch_bins)))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+    let time_limit = match opt.time_limit {
+        Some(time_limit) => time_limit.to_std()?,
+        None => DEFAULT_TIME_LIMIT,
+    };
+    let atc = AtCoder::new(&session_file()?)?;
+
+    let mut entries = vec![];
+    let mut index = 0;
+    for (package, bins) in targets {
+        for bin in bins {
+            let task = task_of(&mapping, package, bin)?;
+            if let (Some(partition), Some(_)) = (&opt.partition, &task) {
+                index += 1;
+                if !partition.contains(index - 1) {
+                    continue;
+                }
+            }
+            let mut suite = junit::Suite::new(&package.name, &bin.name);
+            let outcome = match &task {
+                None => Outcome::Skipped,
+                Some(task) => {
+                    run_task(
+                        &atc,
+                        package,
+                        bin,
+                        task,
+                        time_limit,
+                        opt.release,
+                        &mut suite,
+                    )
+                    .await?
+                }
+            };
+            let note = project::note_path(package_dir(package), &bin.name);
+            let note = note.exists().then(|| {
+                let note = note.strip_prefix(&metadata.workspace_root).unwrap_or(&note);
+                note.to_string_lossy().replace('\\', "/")
+            });
+            entries.push(Entry {
+                package: package.name.clone(),
+                bin: bin.name.clone(),
+                task,
+                outcome,
+                note,
+                cases: suite.cases,
+            });
+        }
+    }
+
+    let report = match opt.format {
+        Format::Markdown => markdown(&entries),
+        Format::Junit => junit(&entries),
+        Format::Json => json(&entries),
+    };
+    match &opt.output {
+        Some(path) => fs::write(path, report)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", report),
+    }
+    if opt.html {
+        let path = metadata.workspace_root.join("verify.html");
+        html::write(path.as_ref(), "verify", &html_rows(&entries))?;
+        info!("Report saved at {}", path);
+    }
+
+    let failed = |f: fn(&Outcome) -> bool| entries.iter().filter(|e| f(&e.outcome)).count();
+    let errors = failed(|o| matches!(o, Outcome::Error(_)));
+    let build_failures = failed(|o| matches!(o, Outcome::BuildFailed));
+    let test_failures = failed(|o| matches!(o, Outcome::Ran(r) if !all_passed(r)));
+    info!(
+        "{}: {} bins verified, {} failed, {} skipped",
+        if errors + build_failures + test_failures == 0 {
+            Style::new().green().apply_to("ok")
+        } else {
+            Style::new().red().apply_to("FAILED")
+        },
+        failed(|o| matches!(o, Outcome::Ran(r) if all_passed(r))),
+        errors + build_failures + test_failures,
+        failed(|o| matches!(o, Outcome::Skipped)),
+    );
+
+    if let Some(err) = entries.into_iter().find_map(|e| match e.outcome {
+        Outcome::Error(err) => Some(err),
+        _ => None,
+    }) {
+        return Err(err.context(format!("Failed to get the samples of {} bins", errors)));
+    }
+    if build_failures > 0 {
+        return Err(Failure::Build.into());
+    }
+    if test_failures > 0 {
+        return Err(Failure::Test.into());
+    }
+    Ok(())
+}
+
+/// Writes `src/bin/verify_<name>.rs` for each module of the library, returning the names of
+/// the bins.
+fn generate_harnesses(
+    package: &Package,
+    library: &BTreeMap<String, Library>,
+) -> Result<Vec<String>> {
+    let bin_dir = package_dir(package).join("src").join("bin");
+    fs::create_dir_all(&bin_dir)?;
+
+    let mut bins = vec![];
+    for (name, library) in library {
+        ensure!(
+            Task::from_url(&library.problem).is_some(),
+            "Not a URL of a task: `{}` for `{}`",
+            library.problem,
+            name
+        );
+        // The paths are relative to the package, which is two levels above the bin.
+        let mut source = format!(
+            "// Generated by `cargo atcoder verify --library` from `verify.toml`. Do not edit.\n\
+             // {}\n\n",
+            library.problem,
+        );
+        if let Some(module) = &library.module {
+            writeln!(source, "#[path = \"../../{}\"]\nmod {};\n", module, name).unwrap();
+        }
+        writeln!(source, "include!(\"../../{}\");", library.main).unwrap();
+
+        let bin = format!("verify_{}", name);
+        let path = bin_dir.join(&bin).with_extension("rs");
+        fs::write(&path, source).with_context(|| format!("Failed to write {}", path.display()))?;
+        bins.push(bin);
+    }
+    Ok(bins)
+}
+
+fn task_of(mapping: &Mapping, package: &Package, bin: &Target) -> Result<Option<Task>> {
+    if let Some(url) = mapping
+        .bins
+        .get(&package.name)
+        .and_then(|bins| bins.get(&bin.name))
+    {
+        return Task::from_url(url)
+            .with_context(|| format!("Not a URL of a task: `{}` for `{}`", url, bin.name))
+            .map(Some);
+    }
+    let source = fs::read_to_string(&bin.src_path)
+        .with_context(|| format!("Failed to read {}", bin.src_path))?;
+    Ok(source
+        .lines()
+        .filter(|line| line.trim_start().starts_with("//"))
+        .find_map(Task::from_url))
+}
+
+async fn run_task(
+    atc: &AtCoder,
+    package: &Package,
+    bin: &Target,
+    task: &Task,
+    time_limit: Duration,
+    release: bool,
+    report: &mut junit::Suite,
+) -> Result<Outcome> {
+    let testcases = match task {
+        Task::AtCoder { contest_id, name } => {
+            samples(atc, contest_id, name).await.map(Testcases::Samples)
+        }
+        Task::LibraryChecker { name } => {
+            library_checker::prepare(name).map(Testcases::LibraryChecker)
+        }
+    };
+    let testcases = match testcases {
+        Ok(testcases) => testcases,
+        Err(err) => {
+            warn!("failed to get the testcases of `{}`: {:#}", bin.name, err);
+            return Ok(Outcome::Error(err));
+        }
+    };
+
+    info!("==== {} {} ====", package.name, bin.name);
+    let results = match testcases {
+        Testcases::Samples(test_cases) => {
+            let test_cases = test_cases.into_iter().enumerate().collect::<Vec<_>>();
+            run_samples(
+                package,
+                &bin.name,
+                &test_cases,
+                time_limit,
+                RunFlags {
+                    release,
+                    verbose: logging::verbose(),
+                    ..RunFlags::default()
+                },
+                Some(report),
+            )?
+        }
+        Testcases::LibraryChecker(problem) => {
+            match build::build_bin(package, &bin.name, release)? {
+                Some(binary) => Some(problem.run(binary.as_ref())?),
+                None => None,
+            }
+        }
+    };
+    Ok(match results {
+        Some(results) => Outcome::Ran(results),
+        None => Outcome::BuildFailed,
+    })
+}
+
+/// Sample cases of the task, read from the cache if they have been fetched.
+async fn samples(atc: &AtCoder, contest_id: &str, name: &str) -> Result<Vec<TestCase>> {
+    if let Some(test_cases) = cache::load_samples(contest_id, name) {
+        return Ok(test_cases);
+    }
+    let url = Task::AtCoder {
+        contest_id: contest_id.to_owned(),
+        name: name.to_owned(),
+    }
+    .url();
+    let html = atc.problem_page(&url).await?;
+    let test_cases = parse_test_cases(&html)
+        .map_err(|err| testcase::scrape_failure(contest_id, name, &html, err))?;
+    cache::save_samples(contest_id, name, &test_cases)?;
+    Ok(test_cases)
+}
+
+fn all_passed(results: &[(usize, CaseStatus)]) -> bool {
+    results.iter().all(|&(_, status)| status == CaseStatus::Ac)
+}
+
+fn markdown(entries: &[Entry]) -> String {
+    let mut md = String::new();
+    writeln!(md, "| Package | Bin | Problem | Result |").unwrap();
+    writeln!(md, "|---|---|---|---|").unwrap();
+    for entry in entries {
+        let mut problem = match &entry.task {
+            Some(task) => format!("[{}]({})", task.name(), task.url()),
+            None => "-".to_owned(),
+        };
+        if let Some(note) = &entry.note {
+            write!(problem, " ([notes]({}))", note).unwrap();
+        }
+        let result = match &entry.outcome {
+            Outcome::Skipped => "skipped".to_owned(),
+            Outcome::Error(_) => "error".to_owned(),
+            Outcome::BuildFailed => "CE".to_owned(),
+            Outcome::Ran(results) => {
+                let passed = results
+                    .iter()
+                    .filter(|&&(_, status)| status == CaseStatus::Ac)
+                    .count();
+                if passed == results.len() {
+                    format!("AC ({}/{})", passed, results.len())
+                } else {
+                    format!(
+                        "{} ({}/{})",
+                        results.iter().map(|&(_, status)| status.label()).join(" "),
+                        passed,
+                        results.len(),
+                    )
+                }
+            }
+        };
+        writeln!(
+            md,
+            "| {} | {} | {} | {} |",
+            entry.package, entry.bin, problem, result
+        )
+        .unwrap();
+    }
+    md
+}
+
+fn html_rows(entries: &[Entry]) -> Vec<html::Row> {
+    let mut rows = vec![];
+    for entry in entries {
+        let name = format!("{}.{}", entry.package, entry.bin);
+        let link = entry.task.as_ref().map(Task::url);
+        let row = |name: String, status: &str, details: Option<String>| html::Row {
+            name,
+            status: status.to_owned(),
+            time: None,
+            link: link.clone(),
+            details,
+        };
+        match &entry.outcome {
+            Outcome::Skipped => {}
+            Outcome::Error(err) => rows.push(row(name, "error", Some(format!("{:#}", err)))),
+            Outcome::BuildFailed => rows.push(row(name, "CE", None)),
+            // Only the statuses for the testcases of Library Checker
+            Outcome::Ran(results) if entry.cases.is_empty() => {
+                for &(i, status) in results {
+                    rows.push(row(
+                        format!("{} case {}", name, i + 1),
+                        status.label(),
+                        None,
+                    ));
+                }
+            }
+            Outcome::Ran(_) => {
+                for case in &entry.cases {
+                    let status = case.failure.as_ref().map_or("AC", |f| &f.kind);
+                    let details = case
+                        .failure
+                        .as_ref()
+                        .map(|f| format!("{}\n\n{}", f.message, f.details));
+                    rows.push(html::Row {
+                        time: Some(case.time),
+                        ..row(format!("{} {}", name, case.name), status, details)
+                    });
+                }
+            }
+        }
+    }
+    rows
+}
+
+// A suite for each bin as `test --report` does
+fn junit(entries: &[Entry]) -> String {
+    let suites = entries
+        .iter()
+        .map(|entry| {
+            let cases = match &entry.outcome {
+                Outcome::Skipped => vec![junit::Case::skipped(
+                    entry.bin.clone(),
+                    "no problem is given",
+                )],
+                Outcome::Error(err) => {
+                    vec![junit::Case::error(
+                        "samples".to_owned(),
+                        format!("{:#}", err),
+                    )]
+                }
+                // Only the statuses for the testcases of Library Checker
+                Outcome::BuildFailed if entry.cases.is_empty() => {
+                    vec![junit::Case::failed(
+                        "build".to_owned(),
+                        "CE",
+                        "the build failed",
+                    )]
+                }
+                Outcome::Ran(results) if entry.cases.is_empty() => results
+                    .iter()
+                    .map(|&(i, status)| {
+                        let name = format!("case {}", i + 1);
+                        match status {
+                            CaseStatus::Ac => junit::Case::passed(name),
+                            status => junit::Case::failed(name, status.label(), status.label()),
+                        }
+                    })
+                    .collect(),
+                Outcome::BuildFailed | Outcome::Ran(_) => entry.cases.clone(),
+            };
+            junit::Suite {
+                cases,
+                ..junit::Suite::new(&entry.package, &entry.bin)
+            }
+        })
+        .collect::<Vec<_>>();
+    junit::to_xml(&suites)
+}
+
+fn json(entries: &[Entry]) -> String {
+    let mut events = vec![];
+    for entry in entries {
+        let name = |case: &str| format!("{}::{}::{}", entry.package, entry.bin, case);
+        match &entry.outcome {
+            Outcome::Skipped => events.push(json!({
+                "type": "test", "event": "ignored", "name": name("samples"),
+            })),
+            Outcome::Error(err) => events.push(json!({
+                "type": "test", "event": "failed", "name": name("samples"),
+                "stdout": format!("{:#}", err),
+            })),
+            Outcome::BuildFailed => events.push(json!({
+                "type": "test", "event": "failed", "name": name("build"),
+                "stdout": "build failed",
+            })),
+            Outcome::Ran(results) => {
+                for &(i, status) in results {
+                    let mut event = json!({
+                        "type": "test",
+                        "event": if status == CaseStatus::Ac { "ok" } else { "failed" },
+                        "name": name(&format!("sample {}", i + 1)),
+                    });
+                    if status != CaseStatus::Ac {
+                        event["stdout"] = status.label().into();
+                    }
+                    events.push(event);
+                }
+            }
+        }
+    }
+    let count = |event| events.iter().filter(|e| e["event"] == event).count();
+    let (passed, failed, ignored) = (count("ok"), count("failed"), count("ignored"));
+
+    let mut lines =
+        vec![json!({ "type": "suite", "event": "started", "test_count": events.len() })];
+    for event in &events {
+        let mut started = json!({ "type": "test", "event": "started" });
+        started["name"] = event["name"].clone();
+        lines.push(started);
+        lines.push(event.clone());
+    }
+    lines.push(json!({
+        "type": "suite",
+        "event": if failed == 0 { "ok" } else { "failed" },
+        "passed": passed,
+        "failed": failed,
+        "ignored": ignored,
+    }));
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
@@ -1,23 +1,44 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     env, fs,
     path::PathBuf,
+    process::Command,
     sync::{mpsc::channel, Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
 use cargo_metadata::{Package, Target};
+use console::Style;
 use futures::{select, FutureExt};
+use itertools::Itertools as _;
+use log::{info, warn};
 use sha2::Digest;
 use structopt::StructOpt;
 
 use crate::{
-    atcoder::AtCoder,
+    atcoder::{AtCoder, ContestInfo, StandingsRow, Verdict, DEFAULT_LANGUAGE},
+    config::{read_config, Config},
+    detect_rejudges,
+    i18n::tr,
     metadata::{self, MetadataExt, PackageExt},
-    session_file, test_samples,
+    project::{self, package_dir},
+    sample_cases, session_file, statement, statement_html, stats, test_samples, timer,
+    warn_rejudges, CaseStatus, DEFAULT_TIME_LIMIT,
 };
 
+// Lines of the statement of the next problem shown after AC
+const SUMMARY_LINES: usize = 12;
+
+// Interval of fetching the standings for the rivals
+const RIVALS_INTERVAL: Duration = Duration::from_secs(60);
+
+// Interval of fetching the submissions for the rejudges
+const REJUDGES_INTERVAL: Duration = Duration::from_secs(60);
+
+// Time to wait for the verdict of a submission before going on watching
+const VERDICT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 // use termion::raw::IntoRawMode;
 // use tui::backend::TermionBackend;
 // use tui::layout::{Constraint, Direction, Layout};
@@ -33,6 +54,9 @@ pub struct WatchOpt {
     /// [cargo] Path to Cargo.toml
     #[structopt(long, value_name("PATH"))]
     manifest_path: Option<PathBuf>,
+    /// Submit when the sample tests pass, and move on to the next problem after AC
+    #[structopt(long)]
+    submit: bool,
 }
 
 pub async fn watch(opt: WatchOpt) -> Result<()> {
@@ -55,6 +79,7 @@ pub async fn watch(opt: WatchOpt) -> Result<()> {
     let metadata = metadata::cargo_metadata(opt.manifest_path.as_deref(), &cwd)?;
     let package = metadata.query_for_member(opt.package.as_deref())?.clone();
     let atc = AtCoder::new(&session_file()?)?;
+    let config = read_config()?;
 
     let atc = Arc::new(atc);
 
@@ -64,9 +89,22 @@ pub async fn watch(opt: WatchOpt) -> Result<()> {
     //     tokio::spawn(async move { watch_submission_status(&atc, &contest_id).await })
     // };
 
+    // In the background until the end of `watch`, never ending it
+    {
+        let atc = atc.clone();
+        let contest_id = package.name.clone();
+        let config = config.clone();
+        tokio::spawn(async move { watch_rivals(&atc, &contest_id, &config).await });
+    }
+    {
+        let atc = atc.clone();
+        let contest_id = package.name.clone();
+        tokio::spawn(async move { watch_rejudges(&atc, &contest_id).await });
+    }
+
     let file_watcher_fut = {
         let atc = atc.clone();
-        tokio::spawn(async move { watch_filesystem(&package, &atc).await })
+        tokio::spawn(async move { watch_filesystem(&package, &atc, &config, opt.submit).await })
     };
 
     // let ui_fut = {
@@ -85,23 +123,32 @@ pub async fn watch(opt: WatchOpt) -> Result<()> {
 
     select! {
         // _ = submission_fut.fuse() => (),
-        _ = file_watcher_fut.fuse() => (),
+        res = file_watcher_fut.fuse() => res?,
         // _ = ui_fut.fuse() => (),
-    };
-
-    Ok(())
+    }
 }
 
-async fn watch_filesystem(package: &Package, atc: &AtCoder) -> Result<()> {
+async fn watch_filesystem(
+    package: &Package,
+    atc: &AtCoder,
+    config: &Config,
+    submit: bool,
+) -> Result<()> {
     use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 
-    let contest_info = atc.contest_info(&package.name).await?;
+    let contest_info = crate::contest_info_or_register(atc, project::contest_id(package)?).await?;
+    timer::start(atc, config, &package.name, &contest_info).await;
+    let mut solved = if submit {
+        solved_problems(atc, &package.name, &contest_info).await?
+    } else {
+        BTreeSet::new()
+    };
 
     let (tx, rx) = channel();
     let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_millis(150))?;
     let rx = Arc::new(Mutex::new(rx));
 
-    for Target { src_path, .. } in package.all_bins() {
+    for Target { src_path, .. } in package.solution_bins(&config.project.scratch_bins) {
         watcher.watch(src_path, RecursiveMode::NonRecursive)?;
     }
 
@@ -111,9 +158,7 @@ async fn watch_filesystem(package: &Package, atc: &AtCoder) -> Result<()> {
         let rx = rx.clone();
         let pb = tokio::task::spawn_blocking(move || -> Option<PathBuf> {
             if let DebouncedEvent::Write(pb) = rx.lock().unwrap().recv().unwrap() {
-                let pb = pb.canonicalize().ok()?;
-                let r = pb.strip_prefix(pb.parent()?).ok()?;
-                Some(r.to_owned())
+                pb.canonicalize().ok()
             } else {
                 None
             }
@@ -130,10 +175,15 @@ async fn watch_filesystem(package: &Package, atc: &AtCoder) -> Result<()> {
         let problem = if let Some(problem) = contest_info.problem(&problem_id) {
             problem
         } else {
-            eprintln!("Problem `{}` is not contained in this contest", &problem_id);
+            warn!("{}", tr!(ProblemNotInContest, problem_id));
             continue;
         };
 
+        if solved.contains(&problem.id) {
+            info!("Problem `{}` is already solved", problem.id);
+            continue;
+        }
+
         let source = fs::read(&pb).with_context(|| format!("Failed to read {}", pb.display()))?;
         let hash = sha2::Sha256::digest(&source);
 
@@ -143,15 +193,368 @@ async fn watch_filesystem(package: &Package, atc: &AtCoder) -> Result<()> {
 
         file_hash.insert(problem_id.clone(), hash);
 
-        let test_cases = atc.test_cases(&problem.url).await?;
+        let test_cases = sample_cases(atc, package, problem).await?;
         let test_cases = test_cases.into_iter().enumerate().collect::<Vec<_>>();
-        let test_passed = test_samples(package, &problem_id, &test_cases, false, false)?;
+        stats::record_tested(&package.name, &problem_id);
+        let test_passed = test_samples(
+            package,
+            &problem_id,
+            &test_cases,
+            problem.time_limit().unwrap_or(DEFAULT_TIME_LIMIT),
+            false,
+            false,
+        )?;
 
-        if !test_passed {
+        if !test_passed || !submit {
             continue;
         }
 
-        // atc.submit(&contest_id, &problem_id, &String::from_utf8_lossy(&source))
-        //     .await?;
+        let submission_id = match atc
+            .submit(
+                &package.name,
+                &problem_id,
+                &String::from_utf8_lossy(&source),
+                DEFAULT_LANGUAGE,
+            )
+            .await
+        {
+            Ok(submission_id) => submission_id,
+            Err(err) => {
+                // Submitted again when saved, even if unchanged
+                warn!("failed to submit: {:#}", err);
+                file_hash.remove(&problem_id);
+                continue;
+            }
+        };
+        let interval = Duration::from_millis(config.atcoder.update_interval);
+        let verdict = match wait_for_verdict(atc, &package.name, submission_id, interval).await {
+            Some(verdict) => verdict,
+            None => {
+                warn!(
+                    "Submission {} was not judged in {} minutes. Check it with `cargo atcoder status`",
+                    submission_id,
+                    VERDICT_TIMEOUT.as_secs() / 60,
+                );
+                continue;
+            }
+        };
+        println!("{}: {}", problem.id, verdict.style().apply_to(&verdict));
+        if verdict != Verdict::Ac {
+            continue;
+        }
+
+        solved.insert(problem.id.clone());
+        if let Err(err) = on_ac(config, &problem.id) {
+            warn!("{:#}", err);
+        }
+        let next = contest_info
+            .problems()
+            .iter()
+            .find(|p| !solved.contains(&p.id));
+        match next {
+            Some(next) => {
+                println!("Next: problem `{}`", next.id);
+                let html = statement_html(atc, &package.name, next, false).await?;
+                let text = statement::render(&html, &BTreeMap::new(), None);
+                for line in text.lines().take(SUMMARY_LINES) {
+                    println!("{}", line);
+                }
+                println!();
+            }
+            None => {
+                info!("All problems are solved.");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// `test --watch-files`: runs the samples with `run` once and again whenever the source of the
+/// bin or the library (the lib target and the path dependencies of the package) changes, with a
+/// line of the results after each run.
+pub(crate) fn test_on_change(
+    package: &Package,
+    bin: &Target,
+    mut run: impl FnMut() -> Result<Option<Vec<(usize, CaseStatus)>>>,
+) -> Result<()> {
+    use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+    let (tx, rx) = channel();
+    let mut watcher: RecommendedWatcher = Watcher::new(tx, Duration::from_millis(150))?;
+    watcher.watch(&bin.src_path, RecursiveMode::NonRecursive)?;
+    let library = library_dirs(package);
+    for dir in &library {
+        watcher.watch(dir, RecursiveMode::Recursive)?;
     }
+    let bins_dir = package_dir(package).join("src").join("bin");
+    let bin_path = bin.src_path.canonicalize()?;
+
+    let mut file_hash = BTreeMap::new();
+    loop {
+        println!("{}", results_line(&bin.name, &run()?));
+        info!(
+            "Watching {}{} (Ctrl-C to stop)",
+            bin.src_path,
+            if library.is_empty() {
+                "".to_owned()
+            } else {
+                format!(" and {} library directories", library.len())
+            },
+        );
+
+        // Until a source has changed, taking the events coming together at once
+        let mut changed = false;
+        while !changed {
+            let mut events = vec![rx.recv()?];
+            while let Ok(event) = rx.recv_timeout(Duration::from_millis(50)) {
+                events.push(event);
+            }
+            for event in events {
+                let path = match event {
+                    DebouncedEvent::Write(path)
+                    | DebouncedEvent::Create(path)
+                    | DebouncedEvent::Rename(_, path) => path,
+                    _ => continue,
+                };
+                let path = match path.canonicalize() {
+                    Ok(path) => path,
+                    Err(_) => continue,
+                };
+                // The other bins are in the library directory `src` too.
+                if path.extension() != Some("rs".as_ref())
+                    || (path != bin_path && path.starts_with(&bins_dir))
+                {
+                    continue;
+                }
+                let hash = match fs::read(&path) {
+                    Ok(source) => sha2::Sha256::digest(&source),
+                    Err(_) => continue,
+                };
+                if file_hash.insert(path, hash) != Some(hash) {
+                    changed = true;
+                }
+            }
+        }
+    }
+}
+
+/// The directories of the lib target and the path dependencies of the package
+fn library_dirs(package: &Package) -> Vec<PathBuf> {
+    let lib = package
+        .targets
+        .iter()
+        .filter(|target| target.kind.iter().any(|kind| kind.ends_with("lib")))
+        .filter_map(|target| target.src_path.parent())
+        .map(|dir| dir.as_std_path().to_owned());
+    let deps = package
+        .dependencies
+        .iter()
+        .filter_map(|dep| dep.path.as_ref())
+        .map(|path| path.as_std_path().to_owned());
+    lib.chain(deps).filter(|dir| dir.is_dir()).collect()
+}
+
+/// e.g. `21:05:23 a: 1/3 passed (2: WA, 3: TLE)`
+fn results_line(bin: &str, results: &Option<Vec<(usize, CaseStatus)>>) -> String {
+    let time = chrono::Local::now().format("%H:%M:%S");
+    let results = match results {
+        Some(results) => results,
+        None => return format!("{} {}: {}", time, bin, Style::new().red().apply_to("CE")),
+    };
+    let failed = results
+        .iter()
+        .filter(|(_, status)| *status != CaseStatus::Ac)
+        .collect::<Vec<_>>();
+    let passed = format!("{}/{} passed", results.len() - failed.len(), results.len());
+    if failed.is_empty() {
+        format!(
+            "{} {}: {}",
+            time,
+            bin,
+            Style::new().green().apply_to(passed)
+        )
+    } else {
+        format!(
+            "{} {}: {} ({})",
+            time,
+            bin,
+            Style::new().red().apply_to(passed),
+            failed
+                .iter()
+                .map(|(i, status)| format!(
+                    "{}: {}",
+                    i + 1,
+                    status.style().apply_to(status.label())
+                ))
+                .join(", "),
+        )
+    }
+}
+
+/// Notifies the problems the rivals in the config have solved and I have not, polling the
+/// standings forever. Errors, e.g. of the username, are only reported since they are fetched
+/// again.
+async fn watch_rivals(atc: &AtCoder, contest_id: &str, config: &Config) {
+    let rivals = &config.atcoder.rivals;
+    if rivals.is_empty() {
+        return;
+    }
+    let mut me = None;
+    let mut notified = BTreeSet::new();
+    loop {
+        if me.is_none() {
+            match atc.username().await {
+                Ok(username) => me = Some(username),
+                Err(err) => warn!("failed to fetch the username for the rivals: {:#}", err),
+            }
+        }
+        match atc.standings(contest_id).await {
+            Ok(standings) => {
+                let mine = me.as_ref().and_then(|me| standings.row(me.as_deref()?));
+                for rival in rivals {
+                    let row = match standings.row(rival) {
+                        Some(row) => row,
+                        None => continue,
+                    };
+                    for task in &standings.task_info {
+                        let solved = |row: &StandingsRow| {
+                            row.task_results
+                                .get(&task.task_screen_name)
+                                .is_some_and(|r| r.score > 0)
+                        };
+                        if !solved(row)
+                            || mine.is_some_and(solved)
+                            || !notified.insert((rival.clone(), task.task_screen_name.clone()))
+                        {
+                            continue;
+                        }
+                        if let Err(err) = on_rival_ac(config, rival, &task.assignment) {
+                            warn!("{:#}", err);
+                        }
+                    }
+                }
+            }
+            Err(err) => warn!("failed to fetch the standings for the rivals: {:#}", err),
+        }
+        tokio::time::sleep(RIVALS_INTERVAL).await;
+    }
+}
+
+/// Warns of the submissions rejudged since they were seen in `watch`, `status` or
+/// `submissions`, polling the submissions forever. Errors are only reported as in `watch_rivals`.
+async fn watch_rejudges(atc: &AtCoder, contest_id: &str) {
+    loop {
+        match atc.submissions(contest_id, 1).await {
+            Ok((submissions, _)) => match detect_rejudges(contest_id, &submissions) {
+                Ok(rejudged) => warn_rejudges(&rejudged),
+                Err(err) => warn!("{:#}", err),
+            },
+            Err(err) => warn!(
+                "failed to fetch the submissions for the rejudges: {:#}",
+                err
+            ),
+        }
+        tokio::time::sleep(REJUDGES_INTERVAL).await;
+    }
+}
+
+/// IDs of the problems with an accepted submission
+async fn solved_problems(
+    atc: &AtCoder,
+    contest_id: &str,
+    contest_info: &ContestInfo,
+) -> Result<BTreeSet<String>> {
+    let mut solved = BTreeSet::new();
+    let mut page = 1;
+    loop {
+        let (submissions, num_pages) = atc.submissions(contest_id, page).await?;
+        for submission in submissions.iter().filter(|s| s.verdict == Verdict::Ac) {
+            if let Some(p) = contest_info
+                .problems()
+                .iter()
+                .find(|p| submission.is_for(&p.id))
+            {
+                solved.insert(p.id.clone());
+            }
+        }
+        if page >= num_pages {
+            return Ok(solved);
+        }
+        page += 1;
+    }
+}
+
+/// Polls the verdict of the submission, which may not be on the first page at once, giving up
+/// after [`VERDICT_TIMEOUT`]. Failures to fetch are retried until then.
+async fn wait_for_verdict(
+    atc: &AtCoder,
+    contest_id: &str,
+    submission_id: u64,
+    interval: Duration,
+) -> Option<Verdict> {
+    let deadline = Instant::now() + VERDICT_TIMEOUT;
+    loop {
+        match atc.submissions(contest_id, 1).await {
+            Ok((submissions, _)) => {
+                if let Some(submission) = submissions.iter().find(|s| s.id == submission_id) {
+                    if submission.verdict.is_judged() {
+                        stats::record_accepted(contest_id, &submissions);
+                        return Some(submission.verdict.clone());
+                    }
+                }
+            }
+            Err(err) => warn!("failed to fetch the verdict: {:#}", err),
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Runs `watch.on_ac` in the config, or rings the bell.
+fn on_ac(config: &Config, problem_id: &str) -> Result<()> {
+    match config.watch.as_ref().and_then(|w| w.on_ac.as_ref()) {
+        Some(command) => run_hook(command, &[("CARGO_ATCODER_PROBLEM_ID", problem_id)]),
+        None => {
+            eprint!("\x07");
+            Ok(())
+        }
+    }
+}
+
+/// Runs `watch.on_rival_ac` in the config, or prints the problem.
+fn on_rival_ac(config: &Config, rival: &str, problem_id: &str) -> Result<()> {
+    match config.watch.as_ref().and_then(|w| w.on_rival_ac.as_ref()) {
+        Some(command) => run_hook(
+            command,
+            &[
+                ("CARGO_ATCODER_PROBLEM_ID", problem_id),
+                ("CARGO_ATCODER_RIVAL", rival),
+            ],
+        ),
+        None => {
+            info!("{} has solved problem `{}`", rival, problem_id);
+            Ok(())
+        }
+    }
+}
+
+fn run_hook(command: &str, envs: &[(&str, &str)]) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    let status = cmd
+        .arg(command)
+        .envs(envs.iter().copied())
+        .status()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+    ensure!(status.success(), "`{}` failed", command);
+    Ok(())
 }
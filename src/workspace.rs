@@ -0,0 +1,247 @@
+//! `workspace`: opens the panes for a contest in tmux or zellij, i.e. the editor, `test
+//! --watch-files` (or `test` once without the `watch` feature), `status` and a shell, all in the
+//! directory of the project.
+
+use crate::{
+    build::shell_quote,
+    cache::cache_dir,
+    config::read_config,
+    metadata::{self, MetadataExt as _},
+    project::package_dir,
+};
+use anyhow::{bail, ensure, Context as _, Result};
+use itertools::Itertools as _;
+use std::{
+    env, fs, iter,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct WorkspaceOpt {
+    /// Contest ID of the project, in the current directory or the workspace (default: the
+    /// package name of the current project)
+    contest_id: Option<String>,
+    /// Problem to edit and test first
+    #[structopt(long, default_value("a"))]
+    problem: String,
+    /// Terminal multiplexer [possible values: tmux, zellij] (default: `workspace.multiplexer` in
+    /// the config, or tmux)
+    #[structopt(long)]
+    multiplexer: Option<String>,
+    /// Print the commands instead of running them
+    #[structopt(long)]
+    dry_run: bool,
+}
+
+/// The shell commands of the panes besides the shell
+struct Panes {
+    editor: String,
+    test: String,
+    status: String,
+}
+
+pub async fn workspace(opt: WorkspaceOpt) -> Result<()> {
+    let config = read_config()?;
+    let settings = config.workspace.unwrap_or_default();
+    let (contest_id, dir) = project_dir(opt.contest_id.as_deref())?;
+
+    let src = Path::new("src")
+        .join("bin")
+        .join(&opt.problem)
+        .with_extension("rs");
+    ensure!(
+        dir.join(&src).is_file(),
+        "No source of problem `{}` in {}",
+        opt.problem,
+        dir.display(),
+    );
+    let editor = settings
+        .editor
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .unwrap_or_else(|| "vi".to_owned());
+    let test = format!("cargo atcoder test {}", shell_quote(&opt.problem));
+    let panes = Panes {
+        editor: format!("{} {}", editor, shell_quote(&src.to_string_lossy())),
+        // Without the `watch` feature, tested once and left in the shell to test again
+        test: if cfg!(feature = "watch") {
+            format!("{} --watch-files", test)
+        } else {
+            format!("{}; exec \"${{SHELL:-sh}}\"", test)
+        },
+        status: "cargo atcoder status".to_owned(),
+    };
+
+    let multiplexer = opt
+        .multiplexer
+        .or(settings.multiplexer)
+        .unwrap_or_else(|| "tmux".to_owned());
+    let commands = match multiplexer.as_str() {
+        "tmux" => {
+            let exists = !opt.dry_run
+                && Command::new("tmux")
+                    .args(["has-session", "-t", &format!("={}", contest_id)])
+                    .output()
+                    .is_ok_and(|output| output.status.success());
+            tmux_commands(&contest_id, &dir, &panes, exists)
+        }
+        "zellij" => {
+            let layout = cache_dir()?
+                .join("layouts")
+                .join(&contest_id)
+                .with_extension("kdl");
+            if !opt.dry_run {
+                fs::create_dir_all(layout.parent().unwrap())?;
+                fs::write(&layout, zellij_layout(&dir, &panes))
+                    .with_context(|| format!("Failed to write {}", layout.display()))?;
+            }
+            vec![vec![
+                "zellij".to_owned(),
+                "--session".to_owned(),
+                contest_id.clone(),
+                "--layout".to_owned(),
+                layout.display().to_string(),
+            ]]
+        }
+        multiplexer => bail!(
+            "Unknown multiplexer `{}`. Use `tmux` or `zellij`",
+            multiplexer
+        ),
+    };
+
+    for args in commands {
+        let line = args.iter().map(|arg| shell_quote(arg)).join(" ");
+        if opt.dry_run {
+            println!("{}", line);
+            continue;
+        }
+        let status = Command::new(&args[0])
+            .args(&args[1..])
+            .current_dir(&dir)
+            .status()
+            .with_context(|| format!("Failed to run `{}`", args[0]))?;
+        ensure!(status.success(), "`{}` failed", line);
+    }
+    Ok(())
+}
+
+/// The contest ID and the directory of the project, which is `./<contest-id>` or the member of
+/// the workspace
+fn project_dir(contest_id: Option<&str>) -> Result<(String, PathBuf)> {
+    let cwd = env::current_dir().with_context(|| "failed to get CWD")?;
+    if let Some(contest_id) = contest_id {
+        let dir = cwd.join(contest_id);
+        if dir.join("Cargo.toml").is_file() {
+            return Ok((contest_id.to_owned(), dir));
+        }
+    }
+    let metadata = metadata::cargo_metadata(None, &cwd).with_context(|| match contest_id {
+        Some(contest_id) => format!(
+            "No project of `{0}`. Create it with `cargo atcoder new {0}`",
+            contest_id
+        ),
+        None => "Not in a project. Give the contest ID".to_owned(),
+    })?;
+    let package = metadata.query_for_member(contest_id)?;
+    Ok((package.name.clone(), package_dir(package).to_owned()))
+}
+
+/// The editor and the shell on the left, and the tests and the submissions on the right, in a
+/// session named after the contest. The session is only attached if it exists.
+fn tmux_commands(session: &str, dir: &Path, panes: &Panes, exists: bool) -> Vec<Vec<String>> {
+    let tmux = |args: &[&str]| {
+        iter::once("tmux")
+            .chain(args.iter().copied())
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+    let dir = &dir.display().to_string();
+
+    // Each split goes to the new pane, and the panes are selected by the directions to work with
+    // any `base-index`.
+    let mut commands = vec![];
+    if !exists {
+        commands.push(tmux(&[
+            "new-session",
+            "-d",
+            "-s",
+            session,
+            "-c",
+            dir,
+            &panes.editor,
+        ]));
+        commands.push(tmux(&[
+            "split-window",
+            "-h",
+            "-t",
+            session,
+            "-c",
+            dir,
+            &panes.test,
+        ]));
+        commands.push(tmux(&[
+            "split-window",
+            "-v",
+            "-t",
+            session,
+            "-c",
+            dir,
+            &panes.status,
+        ]));
+        commands.push(tmux(&["select-pane", "-L", "-t", session]));
+        commands.push(tmux(&[
+            "split-window",
+            "-v",
+            "-l",
+            "30%",
+            "-t",
+            session,
+            "-c",
+            dir,
+        ]));
+        commands.push(tmux(&["select-pane", "-U", "-t", session]));
+    }
+    // Inside tmux, the client is switched to the session instead of nesting them.
+    if env::var_os("TMUX").is_some() {
+        commands.push(tmux(&["switch-client", "-t", session]));
+    } else {
+        commands.push(tmux(&["attach-session", "-t", session]));
+    }
+    commands
+}
+
+/// The same panes as the ones of tmux, as a layout of zellij
+fn zellij_layout(dir: &Path, panes: &Panes) -> String {
+    let pane = |command: &str| {
+        format!(
+            "pane command=\"sh\" {{ args \"-c\" {}; }}",
+            kdl_string(command)
+        )
+    };
+    format!(
+        r#"layout {{
+    cwd {}
+    pane split_direction="vertical" {{
+        pane split_direction="horizontal" {{
+            {}
+            pane size="30%"
+        }}
+        pane split_direction="horizontal" {{
+            {}
+            {}
+        }}
+    }}
+}}
+"#,
+        kdl_string(&dir.display().to_string()),
+        pane(&panes.editor),
+        pane(&panes.test),
+        pane(&panes.status),
+    )
+}
+
+fn kdl_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
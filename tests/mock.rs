@@ -0,0 +1,1005 @@
+//! The real subcommands against a local server serving the pages of AtCoder, through the same
+//! HTTP client, cookies and session file as against AtCoder.
+
+use predicates::prelude::PredicateBooleanExt as _;
+use std::{
+    fs,
+    io::{self, BufRead as _, BufReader, Read as _, Write as _},
+    net::{TcpListener, TcpStream},
+    str,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use tempdir::TempDir;
+
+#[test]
+fn login_and_info() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-login-and-info")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/login", LOGIN_PAGE),
+        Route::ok("POST", "/login", LOGIN_SUCCEEDED_PAGE)
+            .header("Set-Cookie", "REVEL_SESSION=mock-session; Path=/; HttpOnly"),
+        Route::ok("GET", "/", TOP_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+
+    let assert = cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "login"])
+        .env("ATCODER_USERNAME", "tourist")
+        .env("ATCODER_PASSWORD", "hunter2")
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "Login succeeded.\n",
+    );
+    let login = server.request("POST", "/login").expect("not logged in");
+    assert_eq!(
+        login.body,
+        "username=tourist&password=hunter2&csrf_token=mock-token",
+    );
+
+    // The session is kept for the next command.
+    let assert = cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "info"])
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "Logged in as tourist.\n",
+    );
+    let info = server.request("GET", "/").expect("not requested");
+    assert_eq!(info.cookie.as_deref(), Some("REVEL_SESSION=mock-session"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn csrf_rejection() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-csrf-rejection")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/login", LOGIN_PAGE),
+        Route::ok("POST", "/login", CSRF_REJECTED_PAGE),
+        Route::ok("GET", "/", TOP_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+
+    // Retried once with the token taken again
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "login"])
+        .env("ATCODER_USERNAME", "tourist")
+        .env("ATCODER_PASSWORD", "hunter2")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "The CSRF token was rejected. Retrying with a new one",
+        ));
+    assert_eq!(server.count("POST", "/login"), 2);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn new_and_submit() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-new-and-submit")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/", TOP_PAGE),
+        Route::ok("GET", "/contests/abc999/submit", SUBMIT_PAGE),
+        Route::redirect(
+            "POST",
+            "/contests/abc999/submit",
+            "/contests/abc999/submissions/me",
+        ),
+        // Before and after each submission
+        Route::ok(
+            "GET",
+            "/contests/abc999/submissions/me",
+            NO_SUBMISSIONS_PAGE,
+        ),
+        Route::ok("GET", "/contests/abc999/submissions/me", SUBMISSIONS_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", SUBMISSIONS_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", RESUBMITTED_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", "a"])
+        .current_dir(tempdir.path())
+        .assert()
+        .success();
+    assert!(tempdir
+        .path()
+        .join("abc999")
+        .join("src")
+        .join("bin")
+        .join("a.rs")
+        .exists());
+
+    let assert = cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "submit", "a", "--stdin", "--practice"])
+        .current_dir(tempdir.path().join("abc999"))
+        .write_stdin("fn main() {}\n")
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.contains("Submitted to problem `abc999_a`, using language `Rust (rustc 1.70.0)` (submission 1001)"),
+        "{}",
+        stdout,
+    );
+
+    let submit = server
+        .request("POST", "/contests/abc999/submit")
+        .expect("not submitted");
+    assert_eq!(submit.cookie.as_deref(), Some("REVEL_SESSION=mock-session"));
+    for field in [
+        "data.TaskScreenName=abc999_a",
+        "data.LanguageId=5054",
+        "sourceCode=fn+main%28%29+%7B%7D%0A",
+        "csrf_token=mock-token",
+    ] {
+        assert!(
+            submit.body.split('&').any(|f| f == field),
+            "{} not in {}",
+            field,
+            submit.body,
+        );
+    }
+
+    // The form is cached for the day, with the token taken from the top page.
+    let assert = cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "submit", "a", "--stdin", "--practice"])
+        .current_dir(tempdir.path().join("abc999"))
+        .write_stdin("fn main() { }\n")
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("(submission 1002)"), "{}", stdout);
+    assert_eq!(server.count("GET", "/contests/abc999/submit"), 1);
+    assert_eq!(server.count("POST", "/contests/abc999/submit"), 2);
+    let submit = server
+        .request("POST", "/contests/abc999/submit")
+        .expect("not submitted");
+    assert!(
+        submit.body.split('&').any(|f| f == "csrf_token=mock-token"),
+        "{}",
+        submit.body,
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[cfg(unix)]
+#[test]
+fn submit_file() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-submit-file")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/contests/abc999/tasks/abc999_a", SAMPLES_PAGE),
+        Route::ok("GET", "/", TOP_PAGE),
+        Route::ok("GET", "/contests/abc999/submit", LANGUAGES_SUBMIT_PAGE),
+        Route::redirect(
+            "POST",
+            "/contests/abc999/submit",
+            "/contests/abc999/submissions/me",
+        ),
+        Route::ok(
+            "GET",
+            "/contests/abc999/submissions/me",
+            NO_SUBMISSIONS_PAGE,
+        ),
+        Route::ok("GET", "/contests/abc999/submissions/me", SUBMISSIONS_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", SUBMISSIONS_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", RESUBMITTED_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", "a"])
+        .current_dir(tempdir.path())
+        .assert()
+        .success();
+    let dir = tempdir.path().join("abc999");
+    let submit = |args: &[&str]| {
+        let mut cmd = cargo_atcoder(&tempdir, &server);
+        cmd.args(["atcoder", "submit", "a", "--practice"])
+            .args(args)
+            .current_dir(&dir);
+        cmd
+    };
+    let language_id = || {
+        let body = server
+            .request("POST", "/contests/abc999/submit")
+            .expect("not submitted")
+            .body;
+        body.split('&')
+            .find_map(|f| f.strip_prefix("data.LanguageId="))
+            .map(ToOwned::to_owned)
+    };
+
+    // Not submitted without the command to test it
+    fs::write(dir.join("a.sh"), "read a b\necho $((a + b))\n")?;
+    submit(&["--file", "a.sh", "--language", "bash"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Give the command to run `"));
+    assert!(server.request("POST", "/contests/abc999/submit").is_none());
+
+    submit(&["--file", "a.sh", "--language", "bash", "--run", "sh a.sh"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("test sample 1 ... ok"));
+    assert_eq!(language_id().as_deref(), Some("5023"));
+
+    // Tested with the saved file and command too
+    fs::write(dir.join("a.sh"), "echo 0\n")?;
+    submit(&[])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("test sample 1 ... FAILED"));
+    assert_eq!(server.count("POST", "/contests/abc999/submit"), 1);
+
+    // The whole name over the first word
+    submit(&["--skip-test", "--language", "Python (PyPy 3.10-v7.3.12)"])
+        .assert()
+        .success();
+    assert_eq!(language_id().as_deref(), Some("5078"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn watch_submit() -> anyhow::Result<()> {
+    use std::process::{Command, Stdio};
+
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-watch-submit")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/contests/abc999/tasks/abc999_a", SAMPLES_PAGE),
+        Route::ok("GET", "/", TOP_PAGE),
+        Route::ok("GET", "/contests/abc999/submit", SUBMIT_PAGE),
+        Route::ok("POST", "/contests/abc999/submit", WATCH_SUBMITTED_PAGE),
+        // The old WA stays the newest for a while after the submission: for the solved problems,
+        // the rejudges, before submitting and for the verdict.
+        Route::ok("GET", "/contests/abc999/submissions/me", WA_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", WA_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", WA_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", WA_PAGE),
+        Route::ok("GET", "/contests/abc999/submissions/me", WATCH_AC_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", "a"])
+        .current_dir(tempdir.path())
+        .assert()
+        .success();
+    let dir = tempdir.path().join("abc999");
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("cargo-atcoder"))
+        .args(["atcoder", "watch", "--submit"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_ENDPOINT", &server.url)
+        .current_dir(&dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let read = |mut pipe: Box<dyn io::Read + Send>| {
+        thread::spawn(move || {
+            let mut output = String::new();
+            let _ = pipe.read_to_string(&mut output);
+            output
+        })
+    };
+    let stdout = read(Box::new(child.stdout.take().unwrap()));
+    let stderr = read(Box::new(child.stderr.take().unwrap()));
+
+    // Saved once the files are watched, after the solved problems are fetched
+    let (start, timeout) = (Instant::now(), Duration::from_secs(120));
+    while server.count("GET", "/contests/abc999/submissions/me") == 0 {
+        assert!(start.elapsed() < timeout, "not started");
+        thread::sleep(Duration::from_millis(100));
+    }
+    thread::sleep(Duration::from_secs(1));
+    fs::write(
+        dir.join("src").join("bin").join("a.rs"),
+        "use std::io::Read as _;\n\
+         fn main() {\n\
+         \x20   let mut s = String::new();\n\
+         \x20   std::io::stdin().read_to_string(&mut s).unwrap();\n\
+         \x20   println!(\"{}\", s.split_whitespace().map(|x| x.parse::<u64>().unwrap()).sum::<u64>());\n\
+         }\n",
+    )?;
+
+    // Ends when all the problems are solved.
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() > timeout {
+            child.kill()?;
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    let (stdout, stderr) = (stdout.join().unwrap(), stderr.join().unwrap());
+    assert!(status.success(), "{}\n{}", stdout, stderr);
+    assert!(stdout.contains("(submission 1002)"), "{}", stdout);
+    assert!(stdout.contains("A: AC"), "{}", stdout);
+    assert!(!stdout.contains("A: WA"), "{}", stdout);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn new_not_registered() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-new-not-registered")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/contests/abc999", UNREGISTERED_CONTEST_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+
+    // The problems are public, which cannot be submitted to without registering.
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "new", "abc999", "--skip-warmup"])
+        .current_dir(tempdir.path())
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "Not registered for `abc999`. Register on",
+        ));
+    assert!(server
+        .request("POST", "/contests/abc999/register")
+        .is_none());
+    // The top page fetched for the scores is reused.
+    assert_eq!(server.count("GET", "/contests/abc999"), 1);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn new_not_registered_unrated() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-new-not-registered-unrated")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/contests/abc999", UNRATED_CONTEST_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+
+    // Nothing to lose in the unrated contests
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "new", "abc999", "--skip-warmup"])
+        .current_dir(tempdir.path())
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Not registered").not());
+    assert_eq!(server.count("GET", "/contests/abc999"), 1);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn prefetch() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-prefetch")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/contests/abc999/tasks/abc999_a", PROBLEM_PAGE),
+        Route::ok("GET", "/", TOP_PAGE),
+        Route::ok("GET", "/contests/abc999/submit", SUBMIT_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+
+    // No editorials during the contest
+    let assert = cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "--prefetch"])
+        .current_dir(tempdir.path())
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.contains("Prefetched 1 statements, 0 editorials, and the submission form."),
+        "{}",
+        stdout,
+    );
+    let statements = tempdir.path().join("statements").join("abc999");
+    assert_eq!(
+        fs::read_to_string(statements.join("a.ja.html"))?.trim(),
+        "<p>和を求めよ。</p>",
+    );
+    assert_eq!(
+        fs::read_to_string(statements.join("a.en.html"))?.trim(),
+        "<p>Find the sum.</p>",
+    );
+    assert!(server.request("GET", "/contests/abc999/submit").is_some());
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn submission_source() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-submission-source")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/submissions/1001", SUBMISSION_PAGE),
+        Route::ok("GET", "/", TOP_PAGE),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    let project = tempdir.path().join("abc999");
+    fs::create_dir_all(project.join("src").join("bin"))?;
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"abc999\"\nversion = \"0.1.0\"\nedition = \"2018\"\n",
+    )?;
+    fs::write(
+        project.join("src").join("bin").join("a.rs"),
+        "fn main() {}\n",
+    )?;
+
+    // Cached for the second time
+    for _ in 0..2 {
+        cargo_atcoder(&tempdir, &server)
+            .args(["atcoder", "submissions", "--source", "1001"])
+            .current_dir(&project)
+            .assert()
+            .success()
+            .stdout("fn main() {\n    println!(\"{}\", 1 < 2);\n}\n");
+    }
+    assert_eq!(server.count("GET", "/contests/abc999/submissions/1001"), 1);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn timeout() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-timeout")?;
+    // Never answers, as a hung connection
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+
+    let start = Instant::now();
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "info", "--timeout", "1"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env(
+            "CARGO_ATCODER_TEST_ENDPOINT",
+            format!("http://{}", listener.local_addr()?),
+        )
+        .timeout(Duration::from_secs(60))
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("timed out after 1s"));
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // Not diagnosed through a proxy, which is the one hanging
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "info", "--timeout", "1"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_ENDPOINT", "http://atcoder.invalid")
+        .env("http_proxy", format!("http://{}", listener.local_addr()?))
+        .env_remove("no_proxy")
+        .env_remove("NO_PROXY")
+        .timeout(Duration::from_secs(60))
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains(
+            "skipped since the requests go through the proxy",
+        ));
+
+    drop(listener);
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn ip_version() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-ip-version")?;
+    let server = MockServer::start(vec![Route::ok("GET", "/", TOP_PAGE)])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "info", "--ipv4"])
+        .assert()
+        .success();
+    // The server is on 127.0.0.1 only.
+    cargo_atcoder(&tempdir, &server)
+        .args(["atcoder", "info", "--ipv6"])
+        .assert()
+        .code(3)
+        .stderr(predicates::str::contains("Failed to connect for GET"))
+        .stderr(predicates::str::contains("Diagnosis:"))
+        .stderr(predicates::str::contains(
+            "No IPv6 address to connect to with `--ipv6`",
+        ));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn conditional_get() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-mock-conditional-get")?;
+    let server = MockServer::start(vec![
+        Route::ok("GET", "/contests/abc999/tasks", TASKS_PAGE),
+        Route::ok("GET", "/contests/abc999/tasks/abc999_a", PROBLEM_PAGE)
+            .header("ETag", "\"mock-etag\""),
+        Route::ok("GET", "/", TOP_PAGE).header("ETag", "\"mock-etag\""),
+    ])?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(
+        tempdir.path().join("session.txt"),
+        "REVEL_SESSION=mock-session\n",
+    )?;
+    let project = tempdir.path().join("abc999");
+    fs::create_dir_all(project.join("src").join("bin"))?;
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"abc999\"\nversion = \"0.1.0\"\nedition = \"2018\"\n",
+    )?;
+    fs::write(
+        project.join("src").join("bin").join("a.rs"),
+        "fn main() {}\n",
+    )?;
+
+    // The statements in the languages are cached apart, but from the same page.
+    for (args, statement) in [(&["--en"][..], "Find the sum."), (&[], "和を求めよ。")] {
+        let assert = cargo_atcoder(&tempdir, &server)
+            .args(["atcoder", "statement", "a"])
+            .args(args)
+            .current_dir(&project)
+            .assert()
+            .success();
+        let stdout = str::from_utf8(&assert.get_output().stdout)?;
+        assert!(stdout.contains(statement), "{}", stdout);
+    }
+    // Without the session, and answered by 304 and the cache for the second time
+    let page = server
+        .request("GET", "/contests/abc999/tasks/abc999_a")
+        .expect("not requested");
+    assert_eq!(page.cookie, None);
+    assert_eq!(page.if_none_match.as_deref(), Some("\"mock-etag\""));
+
+    // The pages of the session are not cached.
+    for _ in 0..2 {
+        cargo_atcoder(&tempdir, &server)
+            .args(["atcoder", "info"])
+            .assert()
+            .success();
+    }
+    let info = server.request("GET", "/").expect("not requested");
+    assert_eq!(info.if_none_match, None);
+
+    tempdir.close().map_err(Into::into)
+}
+
+fn cargo_atcoder(tempdir: &TempDir, server: &MockServer) -> assert_cmd::Command {
+    let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder").unwrap();
+    cmd.env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_ENDPOINT", &server.url);
+    cmd
+}
+
+/// A response of the mock server
+struct Route {
+    method: &'static str,
+    path: &'static str,
+    status: u16,
+    headers: Vec<(&'static str, &'static str)>,
+    body: &'static str,
+}
+
+impl Route {
+    fn ok(method: &'static str, path: &'static str, body: &'static str) -> Self {
+        Self {
+            method,
+            path,
+            status: 200,
+            headers: vec![],
+            body,
+        }
+    }
+
+    fn redirect(method: &'static str, path: &'static str, location: &'static str) -> Self {
+        Self {
+            method,
+            path,
+            status: 302,
+            headers: vec![("Location", location)],
+            body: "",
+        }
+    }
+
+    fn header(mut self, name: &'static str, value: &'static str) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+}
+
+/// A request received by the mock server
+#[derive(Clone, Debug)]
+struct Request {
+    method: String,
+    path: String,
+    cookie: Option<String>,
+    if_none_match: Option<String>,
+    body: String,
+}
+
+/// An HTTP/1.1 server on a local port, answering the requests by the routes in a thread until
+/// the test process exits. The routes for the same request answer in order, and the last one is
+/// repeated. The requests without a route get 404, and the ones with the `ETag` of the route in
+/// `If-None-Match` get 304.
+struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    fn start(routes: Vec<Route>) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let requests = Arc::new(Mutex::new(vec![]));
+        {
+            let requests = requests.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let _ = respond(stream, &routes, &requests);
+                }
+            });
+        }
+        Ok(Self { url, requests })
+    }
+
+    /// The number of the requests to the path
+    fn count(&self, method: &str, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.method == method && request.path == path)
+            .count()
+    }
+
+    /// The last request to the path
+    fn request(&self, method: &str, path: &str) -> Option<Request> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|request| request.method == method && request.path == path)
+            .cloned()
+    }
+}
+
+// Records a request before answering it, and closes the connection.
+fn respond(stream: TcpStream, routes: &[Route], requests: &Mutex<Vec<Request>>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_owned(), path.to_owned()),
+        _ => return Err(io::ErrorKind::InvalidData.into()),
+    };
+
+    let (mut content_length, mut cookie, mut if_none_match) = (0, None, None);
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match &*name.to_lowercase() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "cookie" => cookie = Some(value.trim().to_owned()),
+                "if-none-match" => if_none_match = Some(value.trim().to_owned()),
+                _ => {}
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let mut requests = requests.lock().unwrap();
+    let same = |m: &str, p: &str| m == method && p.split('?').next() == path.split('?').next();
+    let candidates = routes
+        .iter()
+        .filter(|route| same(route.method, route.path))
+        .collect::<Vec<_>>();
+    let answered = requests
+        .iter()
+        .filter(|request| same(&request.method, &request.path))
+        .count();
+    let route = candidates
+        .get(answered)
+        .or_else(|| candidates.last())
+        .copied();
+    requests.push(Request {
+        method,
+        path,
+        cookie,
+        if_none_match: if_none_match.clone(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    });
+    drop(requests);
+
+    let (status, headers, content) = match route {
+        Some(route)
+            if route.headers.iter().any(|&(name, value)| {
+                name == "ETag" && Some(value) == if_none_match.as_deref()
+            }) =>
+        {
+            (304, &route.headers[..], "")
+        }
+        Some(route) => (route.status, &route.headers[..], route.body),
+        None => (404, &[][..], ""),
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\
+         Connection: close\r\n",
+        status,
+        content.len(),
+    );
+    for (name, value) in headers {
+        response += &format!("{}: {}\r\n", name, value);
+    }
+    response += "\r\n";
+    response += content;
+    (&stream).write_all(response.as_bytes())
+}
+
+static LOGIN_PAGE: &str =
+    r#"<form><input type="hidden" name="csrf_token" value="mock-token"></form>"#;
+
+static LOGIN_SUCCEEDED_PAGE: &str =
+    r#"<div class="alert alert-success" role="alert">ようこそ、tourist さん。</div>"#;
+
+static CSRF_REJECTED_PAGE: &str = r#"
+<div class="alert alert-danger" role="alert">不正なリクエストです。</div>
+<form><input type="hidden" name="csrf_token" value="mock-token"></form>
+"#;
+
+static TOP_PAGE: &str = r#"<script>var csrfToken = "mock-token"</script>
+<ul><li><a href="/users/tourist">tourist</a></li></ul>"#;
+
+static TASKS_PAGE: &str = r#"
+<small class="contest-duration">
+<a><time class="fixtime fixtime-full">2021-01-02 21:00:00+0900</time></a> ~
+<a><time class="fixtime fixtime-full">2021-01-02 22:40:00+0900</time></a>
+</small>
+<table><tbody>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_a">A</a></td>
+<td><a href="/contests/abc999/tasks/abc999_a">Sum</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"#;
+
+static UNREGISTERED_CONTEST_PAGE: &str = r#"
+<ul><li><a href="/users/tourist">tourist</a></li></ul>
+<form action="/contests/abc999/register" method="POST"><input type="hidden" name="csrf_token" value="mock-token"></form>
+"#;
+
+static UNRATED_CONTEST_PAGE: &str = r#"
+<ul><li><a href="/users/tourist">tourist</a></li></ul>
+<p>Rated対象: -</p>
+<form action="/contests/abc999/register" method="POST"><input type="hidden" name="csrf_token" value="mock-token"></form>
+"#;
+
+static PROBLEM_PAGE: &str = r#"
+<div id="task-statement">
+<span class="lang-ja">
+<p>和を求めよ。</p>
+</span>
+<span class="lang-en">
+<p>Find the sum.</p>
+</span>
+</div>
+"#;
+
+static SUBMIT_PAGE: &str = r#"
+<select name="data.TaskScreenName"><option value="abc999_a">A - Sum</option></select>
+<div id="select-lang-abc999_a"><select><option value="5054">Rust (rustc 1.70.0)</option></select></div>
+<input type="hidden" name="csrf_token" value="mock-token">
+"#;
+
+static SAMPLES_PAGE: &str = r#"
+<div id="task-statement">
+<span class="lang-ja">
+<div class="part"><section><h3>入力例 1</h3><pre>1 2
+</pre></section></div>
+<div class="part"><section><h3>出力例 1</h3><pre>3
+</pre></section></div>
+</span>
+</div>
+"#;
+
+// The languages of the same first word chosen by the whole name
+static LANGUAGES_SUBMIT_PAGE: &str = r#"
+<select name="data.TaskScreenName"><option value="abc999_a">A - Sum</option></select>
+<div id="select-lang-abc999_a"><select>
+<option value="5054">Rust (rustc 1.70.0)</option>
+<option value="5023">Bash (bash 5.2.2)</option>
+<option value="5055">Python (CPython 3.11.4)</option>
+<option value="5078">Python (PyPy 3.10-v7.3.12)</option>
+</select></div>
+<input type="hidden" name="csrf_token" value="mock-token">
+"#;
+
+static SUBMISSIONS_PAGE: &str = r#"
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:06:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>13 Byte</td>
+<td class="waiting-judge"><span class="label label-default">WJ</span></td>
+<td><a href="/contests/abc999/submissions/1001">Detail</a></td>
+</tr>
+</tbody></table>
+"#;
+
+static NO_SUBMISSIONS_PAGE: &str = "<table><tbody></tbody></table>";
+
+static RESUBMITTED_PAGE: &str = r#"
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:07:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>14 Byte</td>
+<td class="waiting-judge"><span class="label label-default">WJ</span></td>
+<td><a href="/contests/abc999/submissions/1002">Detail</a></td>
+</tr>
+<tr>
+<td><time>2021-08-01 21:06:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>13 Byte</td>
+<td class="waiting-judge"><span class="label label-default">WJ</span></td>
+<td><a href="/contests/abc999/submissions/1001">Detail</a></td>
+</tr>
+</tbody></table>
+"#;
+
+#[cfg(feature = "watch")]
+static WA_PAGE: &str = r#"
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:06:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>13 Byte</td>
+<td><span class="label label-warning">WA</span></td>
+<td>5 ms</td>
+<td>2100 KB</td>
+<td><a href="/contests/abc999/submissions/1001">Detail</a></td>
+</tr>
+</tbody></table>
+"#;
+
+#[cfg(feature = "watch")]
+static WATCH_SUBMITTED_PAGE: &str = r#"
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:07:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>200 Byte</td>
+<td class="waiting-judge"><span class="label label-default">WJ</span></td>
+<td><a href="/contests/abc999/submissions/1002">Detail</a></td>
+</tr>
+</tbody></table>
+"#;
+
+#[cfg(feature = "watch")]
+static WATCH_AC_PAGE: &str = r#"
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:07:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>100</td>
+<td>200 Byte</td>
+<td><span class="label label-success">AC</span></td>
+<td>4 ms</td>
+<td>2000 KB</td>
+<td><a href="/contests/abc999/submissions/1002">Detail</a></td>
+</tr>
+<tr>
+<td><time>2021-08-01 21:06:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>13 Byte</td>
+<td><span class="label label-warning">WA</span></td>
+<td>5 ms</td>
+<td>2100 KB</td>
+<td><a href="/contests/abc999/submissions/1001">Detail</a></td>
+</tr>
+</tbody></table>
+"#;
+
+static SUBMISSION_PAGE: &str = r#"
+<pre id="submission-code" class="prettyprint linenums">fn main() {
+    println!(&quot;{}&quot;, 1 &lt; 2);
+}
+</pre>
+"#;
+
+// No delays between the requests to the mock server
+static CONFIG: &str = r#"[atcoder]
+submit_via_binary = false
+use_cross = false
+binary_column = 80
+update_interval = 1000
+
+[http]
+interval = 0
+
+[profile]
+target = "x86_64-unknown-linux-musl"
+
+[profile.release]
+lto = true
+panic = 'abort'
+
+[dependencies]
+
+[project]
+template = ""
+"#;
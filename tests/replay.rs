@@ -0,0 +1,1802 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process, str,
+};
+use tempdir::TempDir;
+
+#[test]
+fn info() -> anyhow::Result<()> {
+    let fixture = Fixture::new("info", RECORDING)?;
+
+    let assert = fixture.cargo_atcoder(&["info"])?.assert().success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "Logged in as tourist.\n",
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn info_with_problems() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "info-with-problems",
+        &[
+            RECORDING,
+            r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_a">A</a></td>
+<td><a href="/contests/abc999/tasks/abc999_a">Sum</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_b">B</a></td>
+<td><a href="/contests/abc999/tasks/abc999_b">Product</a></td>
+<td>500 msec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999"
+status = 200
+body = """
+<div id="contest-statement"><span class="lang"><span class="lang-ja">
+<table><thead><tr><th>問題</th><th>点数</th></tr></thead>
+<tbody><tr><td>A</td><td>100</td></tr><tr><td>B</td><td>200</td></tr></tbody></table>
+</span></span></div>
+"""
+"#,
+        ]
+        .join("\n"),
+    )?;
+
+    let assert = fixture
+        .cargo_atcoder(&["info", "abc999"])?
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "Logged in as tourist.\n\n\
+         Problem | Name    | Time limit | Score\n\
+         A       | Sum     | 2 sec      | 100\n\
+         B       | Product | 500 msec   | 200\n",
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn info_with_partial_scores() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "info-with-partial-scores",
+        &[
+            RECORDING,
+            r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/future999/tasks"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><a href="/contests/future999/tasks/future999_a">A</a></td>
+<td><a href="/contests/future999/tasks/future999_a">Route</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/future999"
+status = 200
+body = """
+<a class="contest-title" href="/contests/future999">Future Programming Contest 999 (Marathon)</a>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/ahc999/tasks"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><a href="/contests/ahc999/tasks/ahc999_a">A</a></td>
+<td><a href="/contests/ahc999/tasks/ahc999_a">Warmup</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/ahc999"
+status = 200
+body = """
+<a class="contest-title" href="/contests/ahc999">AtCoder Heuristic Contest 999</a>
+<div id="contest-statement"><span class="lang"><span class="lang-ja">
+<table><thead><tr><th>問題</th><th>点数</th></tr></thead>
+<tbody><tr><td>A</td><td>100</td></tr></tbody></table>
+</span></span></div>
+"""
+"#,
+        ]
+        .join("\n"),
+    )?;
+
+    // The heuristic contest of another name, without a score table
+    let assert = fixture
+        .cargo_atcoder(&["info", "future999"])?
+        .assert()
+        .success();
+    assert!(str::from_utf8(&assert.get_output().stdout)?.contains("| 2 sec      | partial\n"));
+
+    // The table over the name
+    let assert = fixture
+        .cargo_atcoder(&["info", "ahc999"])?
+        .assert()
+        .success();
+    assert!(str::from_utf8(&assert.get_output().stdout)?.contains("| 2 sec      | 100\n"));
+
+    fixture.close()
+}
+
+#[test]
+fn selector_override() -> anyhow::Result<()> {
+    // The navigation bar in a markup the compiled-in selector does not know
+    let fixture = Fixture::new(
+        "selector-override",
+        &RECORDING.replace(
+            r#"'<ul><li><a href="/users/tourist">tourist</a></li></ul>'"#,
+            r#"'<nav><a class="user-name" href="/users/tourist">tourist</a></nav>'"#,
+        ),
+    )?;
+
+    let info = |selectors: &str| -> anyhow::Result<_> {
+        fixture.write_config(&format!("{}\n[selectors]\n{}\n", CONFIG, selectors))?;
+        Ok(fixture.cargo_atcoder(&["info"])?.assert())
+    };
+
+    let assert = info(r#"username = "nav a.user-name""#)?.success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "Logged in as tourist.\n",
+    );
+
+    let assert = info(r#"user-name = "nav a""#)?.failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("Unknown selector `selectors.user-name`"),
+        "{}",
+        stderr,
+    );
+    let assert = info(r#"username = "nav >> a""#)?.failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("Invalid selector `selectors.username`: `nav >> a`"),
+        "{}",
+        stderr,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn info_in_japanese() -> anyhow::Result<()> {
+    let fixture = Fixture::new("info-in-japanese", RECORDING)?;
+
+    let assert = fixture
+        .cargo_atcoder(&["info", "--lang", "ja"])?
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "tourist としてログインしています。\n",
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn login_from_env() -> anyhow::Result<()> {
+    let fixture = Fixture::new("login-from-env", LOGIN_RECORDING)?;
+
+    // No prompts without a terminal
+    let assert = fixture
+        .cargo_atcoder(&["login"])?
+        .env("ATCODER_USERNAME", "tourist")
+        .env("ATCODER_PASSWORD", "hunter2")
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "Login succeeded.\n",
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn concurrent_sessions() -> anyhow::Result<()> {
+    let fixture = Fixture::new("concurrent-sessions", RECORDING)?;
+    let session_file = fixture.path().join("session.txt");
+    fs::write(&session_file, "REVEL_SESSION=old; other=1\n")?;
+
+    // Each command changes the session cookie while the others read and write the session file.
+    let children = (0..16)
+        .map(|i| {
+            process::Command::new(assert_cmd::cargo::cargo_bin("cargo-atcoder"))
+                .args(["atcoder", "info", "--replay"])
+                .arg(fixture.path().join("recording.toml"))
+                .env("CARGO_ATCODER_TEST_CONFIG_DIR", fixture.path())
+                .env("CARGO_ATCODER_TEST_CACHE_DIR", fixture.path())
+                .env("ATCODER_SESSION", format!("new{}", i))
+                .stdout(process::Stdio::null())
+                .spawn()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for mut child in children {
+        assert!(child.wait()?.success());
+    }
+
+    let session = fs::read_to_string(&session_file)?;
+    let cookies = session
+        .trim_end()
+        .split("; ")
+        .map(|cookie| cookie.split_once('=').unwrap())
+        .collect::<HashMap<_, _>>();
+    assert_eq!(session.lines().count(), 1, "{:?}", session);
+    assert_eq!(cookies.len(), 2, "{:?}", session);
+    assert_eq!(cookies["other"], "1");
+    assert!((0..16).any(|i| cookies["REVEL_SESSION"] == format!("new{}", i)));
+    assert!(fs::read_dir(fixture.path())?.all(|entry| !entry
+        .unwrap()
+        .path()
+        .to_string_lossy()
+        .ends_with(".tmp")));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        assert_eq!(
+            fs::metadata(&session_file)?.permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    fixture.close()
+}
+
+#[test]
+fn missing_response() -> anyhow::Result<()> {
+    let fixture = Fixture::new("missing-response", "responses = []\n")?;
+
+    let assert = fixture.cargo_atcoder(&["info"])?.assert().failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("No recorded response for GET https://atcoder.jp/"),
+        "{}",
+        stderr,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn doctor_clock() -> anyhow::Result<()> {
+    let fixture = Fixture::new("doctor-clock", RECORDING)?;
+
+    let doctor = |date: &str| -> anyhow::Result<_> {
+        fixture.write_recording(&format!(
+            "{}\n[[responses]]\nmethod = \"HEAD\"\nurl = \"https://atcoder.jp/\"\nstatus = 200\nbody = \"{}\"\n",
+            RECORDING, date
+        ))?;
+        // The other checks depend on the environment.
+        let assert = fixture
+            .cargo_atcoder(&["doctor", "--skip-template"])?
+            .assert();
+        let stdout = str::from_utf8(&assert.get_output().stdout)?;
+        Ok(stdout
+            .lines()
+            .find(|line| line.contains(" clock: "))
+            .unwrap_or_default()
+            .to_owned())
+    };
+
+    let now = chrono::Utc::now();
+    assert!(doctor(&now.to_rfc2822())?.ends_with("clock: in sync with atcoder.jp"));
+    let skewed = doctor(&(now - chrono::Duration::hours(1)).to_rfc2822())?;
+    assert!(
+        skewed.contains("clock: +3600 seconds off") || skewed.contains("clock: +3601 seconds off"),
+        "{}",
+        skewed,
+    );
+    assert!(doctor("")?.ends_with("clock: atcoder.jp did not send the time"));
+
+    fixture.close()
+}
+
+#[test]
+fn new_before_contest() -> anyhow::Result<()> {
+    let fixture = Fixture::new("new-before-contest", BEFORE_CONTEST_RECORDING)?;
+
+    let assert = fixture
+        .cargo_atcoder(&["new", "abc999", "--skip-warmup"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("Assuming problems a, b, c"), "{}", stdout);
+
+    let mut bins = fs::read_dir(fixture.project().join("src").join("bin"))?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    bins.sort();
+    assert_eq!(
+        bins,
+        ["a.rs", "b.rs", "c.rs", "d.rs", "e.rs", "f.rs", "g.rs"]
+    );
+    let manifest = fs::read_to_string(fixture.project().join("Cargo.toml"))?;
+    assert!(manifest.contains("provisional-bins = true"), "{}", manifest);
+
+    fixture.close()
+}
+
+#[test]
+fn new_from_url_or_series() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "new-from-url-or-series",
+        &[ENDED_RECORDING, CONTESTS_RECORDING].join("\n"),
+    )?;
+
+    let new = |contest: &str| -> anyhow::Result<_> {
+        let assert = fixture
+            .cargo_atcoder(&["new", contest, "--skip-warmup"])?
+            .assert()
+            .success();
+        Ok(String::from_utf8(assert.get_output().stdout.clone())?)
+    };
+
+    new("https://atcoder.jp/contests/abc999/tasks/abc999_a")?;
+    let bin = fixture.project().join("src").join("bin").join("a.rs");
+    assert!(bin.is_file());
+
+    fs::remove_dir_all(fixture.project())?;
+    let stdout = new("ABC")?;
+    assert!(
+        stdout.contains("The latest contest of `abc` is `abc999`"),
+        "{}",
+        stdout
+    );
+    assert!(bin.is_file());
+
+    fixture.close()
+}
+
+#[test]
+fn new_nonexistent_contest() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "new-nonexistent-contest",
+        r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 404
+body = ""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999"
+status = 404
+body = ""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/"
+status = 200
+body = '<ul><li><a href="/users/tourist">tourist</a></li></ul>'
+"#,
+    )?;
+
+    let assert = fixture
+        .cargo_atcoder(&["new", "abc999", "--skip-warmup"])?
+        .assert()
+        .failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("Contest `abc999` does not exist"),
+        "{}",
+        stderr
+    );
+    assert!(!fixture.project().exists());
+
+    fixture.close()
+}
+
+#[test]
+fn restricted_contest() -> anyhow::Result<()> {
+    let recording = |top: &str| {
+        format!(
+            r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 404
+body = ""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999"
+status = 200
+body = '{}'
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/"
+status = 200
+body = '<ul><li><a href="/users/tourist">tourist</a></li></ul>'
+"#,
+            top
+        )
+    };
+
+    let fixture = Fixture::new(
+        "restricted-contest",
+        &recording(r#"<div class="alert alert-danger">このコンテストには参加登録できません</div>"#),
+    )?;
+    fixture
+        .cargo_atcoder(&["new", "abc999", "--skip-warmup"])?
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "You cannot take part in `abc999`: このコンテストには参加登録できません",
+        ));
+    assert!(!fixture.project().exists());
+
+    fixture.write_recording(&recording(
+        r#"<form method="POST" action="/contests/abc999/register"><input type="hidden" name="csrf_token" value="REDACTED"></form>"#,
+    ))?;
+    fixture
+        .cargo_atcoder(&["new", "abc999", "--skip-warmup", "-b", "a"])?
+        .assert()
+        .success();
+    // Not asked without a terminal
+    fixture
+        .cargo_atcoder(&["test", "a"])?
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Register for `abc999` on https://atcoder.jp/contests/abc999 to see the problems",
+        ));
+
+    fixture.close()
+}
+
+#[test]
+fn status() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("status", SUBMISSIONS_RECORDING)?;
+
+    let assert = fixture
+        .cargo_atcoder(&["status", "--once"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3, "{}", stdout);
+    assert!(
+        lines[1].contains("| A - Sum ") && lines[1].contains("| 3/7 WA "),
+        "{}",
+        stdout
+    );
+    assert!(lines[1].ends_with("| 1002"), "{}", stdout);
+    assert!(
+        lines[2].contains("| AC ") && lines[2].contains("| 5 ms"),
+        "{}",
+        stdout
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn result() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "result",
+        &format!("{}\n{}", SUBMISSIONS_RECORDING, SUBMISSION_RECORDING),
+    )?;
+
+    // The newest submission, being judged
+    let assert = fixture.cargo_atcoder(&["result"])?.assert().success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4, "{}", stdout);
+    assert_eq!(lines[0], "Submission 1002: 3/7 WA", "{}", stdout);
+    assert!(lines[2].starts_with("sample_01.txt | AC "), "{}", stdout);
+    assert!(
+        lines[3].starts_with("sample_02.txt | WA ") && lines[3].ends_with("| 5 ms | 2100 KB"),
+        "{}",
+        stdout
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn status_rejudged() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("status-rejudged", SUBMISSIONS_RECORDING)?;
+    let verdicts = fixture.path().join("verdicts").join("abc999.toml");
+    fs::create_dir_all(verdicts.parent().unwrap())?;
+    fs::write(&verdicts, "1001 = \"WA\"\n")?;
+
+    let assert = fixture
+        .cargo_atcoder(&["status", "--once"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert!(lines[2].contains("| AC (was WA) "), "{}", stdout);
+    assert!(stderr.contains("rejudged"), "{}", stderr);
+    assert_eq!(fs::read_to_string(&verdicts)?, "1001 = \"AC\"\n");
+
+    fixture.close()
+}
+
+#[test]
+fn status_with_variants() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "status-with-variants",
+        &format!("{}\n{}", SUBMISSIONS_RECORDING, STATEMENT_RECORDING),
+    )?;
+    fs::write(fixture.project().join("variants.toml"), "1001 = \"v2\"\n")?;
+
+    let assert = fixture
+        .cargo_atcoder(&["status", "--once"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert!(lines[0].ends_with("| Variant"), "{}", stdout);
+    assert!(lines[1].ends_with("| 1002 |"), "{}", stdout);
+    assert!(lines[2].ends_with("| 1001 | v2"), "{}", stdout);
+
+    let assert = fixture
+        .cargo_atcoder(&["test", "a", "--variant", "v3"])?
+        .assert()
+        .failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("Create `src/bin/a_v3.rs`"), "{}", stderr);
+
+    fixture.close()
+}
+
+#[test]
+fn submissions_with_verdict() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("submissions-with-verdict", SUBMISSIONS_RECORDING)?;
+
+    let assert = fixture
+        .cargo_atcoder(&["submissions", "--problem", "a", "--verdict", "AC"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{}", stdout);
+    assert!(lines[1].ends_with("| 1001"), "{}", stdout);
+
+    fixture.close()
+}
+
+#[test]
+fn statement() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("statement", STATEMENT_RECORDING)?;
+    // Images are not recorded, but the cached ones are used.
+    let statements = fixture.path().join("statements").join("abc999");
+    let image = statements.join("images").join("figure.png");
+    fs::create_dir_all(image.parent().unwrap())?;
+    fs::write(&image, b"")?;
+
+    let assert = fixture
+        .cargo_atcoder(&["statement", "a"])?
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        format!(
+            "問題文\n\n\
+             長さ N の数列 A₁, …, A_N の総和を 10⁹+7 で割った余りを求めてください。\n\n\
+             [image: {}]\n\n\
+             制約\n\n\
+             - 1 ≤ N ≤ 2 × 10⁵\n\
+             - Aᵢ/2 ≠ √(N+1)\n\n\
+             入力\n\n    \
+             N\n    \
+             A₁ A₂ … A_N\n",
+            image.display(),
+        ),
+    );
+    assert!(statements.join("a.ja.html").is_file());
+
+    fixture.close()
+}
+
+#[test]
+fn samples_in_sections() -> anyhow::Result<()> {
+    // The headings wrapped with the copy buttons, and the samples with the markup
+    let fixture = Fixture::new(
+        "samples-in-sections",
+        r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_a">A</a></td>
+<td><a href="/contests/abc999/tasks/abc999_a">Sum</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks/abc999_a"
+status = 200
+body = '''
+<div id="task-statement"><span class="lang">
+<span class="lang-ja">
+<div class="part"><section><h3>問題文</h3><p>和を求めてください。</p></section></div>
+<hr>
+<div class="part"><section>
+<div class="heading"><h3>入力例 1 <span class="btn btn-copy">Copy</span></h3></div>
+<div class="div-btn-copy"><span class="btn-copy btn-pre" data-target="pre-sample0">Copy</span></div>
+<pre id="pre-sample0">1 2
+</pre>
+</section></div>
+<div class="part"><section>
+<div class="heading"><h3>出力例 1 <span class="btn btn-copy">Copy</span></h3></div>
+<pre id="pre-sample1"><span>3</span>
+</pre>
+</section></div>
+</span>
+<span class="lang-en">
+<div class="part"><section><h3>Problem Statement</h3><p>Find the sum.</p></section></div>
+</span>
+</span></div>
+'''
+"#,
+    )?;
+
+    fixture
+        .cargo_atcoder(&["new", "abc999", "--skip-warmup"])?
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(fixture.path().join("samples").join("abc999").join("a.toml"))?,
+        "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n",
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn samples_not_scraped() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("samples-not-scraped", STATEMENT_RECORDING)?;
+
+    let assert = fixture.cargo_atcoder(&["test", "a"])?.assert().failure();
+    let diagnostic = fixture
+        .path()
+        .join("samples")
+        .join("abc999")
+        .join("a.diagnostic.html");
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains(&format!(
+            "`cargo atcoder testcase import a --from-html {}`",
+            diagnostic.display(),
+        )),
+        "{}",
+        stderr,
+    );
+    let diagnostic = fs::read_to_string(&diagnostic)?;
+    assert!(diagnostic.starts_with("<!--\nCould not scrape sample test cases (JA inputs: 0"));
+    assert!(diagnostic.contains("\n  問題文: no <pre>\n  制約: no <pre>\n  入力: 2 lines\n"));
+
+    // Nothing to pick from
+    fs::write(fixture.path().join("empty.html"), "<h3>入力例 1</h3>")?;
+    let assert = fixture
+        .cargo_atcoder(&["testcase", "import", "a", "--from-html"])?
+        .arg(fixture.path().join("empty.html"))
+        .assert()
+        .failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("No `<pre>` blocks in"), "{}", stderr);
+
+    fixture.close()
+}
+
+#[test]
+fn open_url() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("open-url", STATEMENT_RECORDING)?;
+
+    let assert = fixture
+        .cargo_atcoder(&["open", "a", "--submissions", "--url"])?
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "https://atcoder.jp/contests/abc999/submissions/me?f.Task=abc999_a\n",
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn submit_after_contest() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("submit-after-contest", ENDED_RECORDING)?;
+
+    let assert = fixture.cargo_atcoder(&["submit", "a"])?.assert().failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("The contest ended at 2021-01-02 22:40:00 +0900."),
+        "{}",
+        stderr,
+    );
+    assert!(stderr.contains("Pass `--practice`"), "{}", stderr);
+
+    fixture.close()
+}
+
+#[test]
+fn test_submit_after_contest() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "test-submit-after-contest",
+        &[ENDED_RECORDING, RECORDING, SUBMIT_RECORDING].join("\n"),
+    )?;
+    let samples = fixture.path().join("samples").join("abc999");
+    fs::create_dir_all(&samples)?;
+    fs::write(
+        samples.join("a.toml"),
+        "[[cases]]\ninput = \"1 2\\n\"\noutput = \"3\\n\"\n",
+    )?;
+    fs::write(
+        fixture.project().join("src").join("bin").join("a.rs"),
+        r#"fn main() {
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line).unwrap();
+    let sum = line
+        .split_whitespace()
+        .map(|x| x.parse::<i64>().unwrap())
+        .sum::<i64>();
+    eprintln!("sum = {}", sum);
+    println!("{}", sum);
+}
+"#,
+    )?;
+
+    // Not tested nor submitted
+    let assert = fixture
+        .cargo_atcoder(&["test", "a", "--submit"])?
+        .assert()
+        .failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("The contest ended at 2021-01-02 22:40:00 +0900."),
+        "{}",
+        stderr,
+    );
+    assert!(stderr.contains("Pass `--practice`"), "{}", stderr);
+
+    // The same source as `submit`
+    let assert = fixture
+        .cargo_atcoder(&["test", "a", "--submit", "--practice"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stdout.contains("Submitted to problem `abc999_a`"),
+        "{}",
+        stdout,
+    );
+    assert!(stderr.contains("`eprintln!` at line 8"), "{}", stderr,);
+
+    fixture.close()
+}
+
+#[test]
+fn submit_all_passing() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("submit-all-passing", ENDED_RECORDING)?;
+    let samples = fixture.path().join("samples").join("abc999");
+    fs::create_dir_all(&samples)?;
+    fs::write(
+        samples.join("a.toml"),
+        "[[cases]]\ninput = \"1 2\\n\"\noutput = \"3\\n\"\n",
+    )?;
+
+    // The empty template does not compile, so nothing is submitted.
+    let assert = fixture
+        .cargo_atcoder(&["submit", "--all-passing", "--practice"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("==== a ====\n"), "{}", stdout);
+    assert!(
+        console::strip_ansi_codes(stdout).contains("problem | submission\na       | CE\n"),
+        "{}",
+        stdout,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn submit_batch_error() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("submit-batch-error", ENDED_RECORDING)?;
+
+    // The submit form is not recorded, which fails after the samples but still shows the summary.
+    // The trailing number is the column, not a problem.
+    let assert = fixture
+        .cargo_atcoder(&["submit", "--all-passing", "80", "--skip-test", "--practice"])?
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("failed to submit `a`"));
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        console::strip_ansi_codes(stdout).contains("problem | submission\na       | error\n"),
+        "{}",
+        stdout,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn submit_stdin() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "submit-stdin",
+        &[ENDED_RECORDING, RECORDING, SUBMIT_RECORDING].join("\n"),
+    )?;
+    let submit = || -> anyhow::Result<_> {
+        let mut cmd = fixture.cargo_atcoder(&["submit", "a", "--stdin", "--practice"])?;
+        cmd.write_stdin("fn main() {}\n");
+        Ok(cmd)
+    };
+
+    // The empty template does not compile, but the source from stdin is submitted untested.
+    let assert = submit()?.assert().success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.contains(
+            "Submitted to problem `abc999_a`, using language `Rust (rustc 1.70.0)` (submission 1003)"
+        ),
+        "{}",
+        stdout,
+    );
+
+    // Back to the form with the banner, or without the new submission
+    let form = &SUBMIT_RECORDING[..SUBMIT_RECORDING
+        .find("\n[[responses]]\nmethod = \"POST\"")
+        .unwrap()];
+    for (banner, error) in [
+        (
+            r#"<div class="alert alert-danger">同じ内容の提出が既にあります</div>"#,
+            "The submission was rejected since the same code has already been submitted",
+        ),
+        (
+            r#"<div class="alert alert-warning">提出間隔が短すぎます。5 秒後に再度提出してください。</div>"#,
+            "Wait 5s before submitting again",
+        ),
+        // Only the submission before submitting
+        (
+            r#"<table><tbody><tr><td><time>2021-08-01 21:06:00+0900</time></td><td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td><td><a href="/users/tourist">tourist</a></td><td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td><td>0</td><td>100 Byte</td><td class="waiting-judge" colspan="3"><span class="label label-default">WJ</span></td><td><a href="/contests/abc999/submissions/1002">Detail</a></td></tr></tbody></table>"#,
+            "The submission is not found in the submissions after submitting",
+        ),
+    ] {
+        fixture.write_recording(
+            &[
+                ENDED_RECORDING,
+                RECORDING,
+                form,
+                &format!(
+                    "[[responses]]\nmethod = \"POST\"\nurl = \"https://atcoder.jp/contests/abc999/submit\"\nstatus = 200\nbody = '{}'\n",
+                    banner
+                ),
+            ]
+            .join("\n"),
+        )?;
+        submit()?
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(error));
+    }
+
+    fixture.close()
+}
+
+#[test]
+fn submit_variant_over_saved_file() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "submit-variant-over-saved-file",
+        &[ENDED_RECORDING, RECORDING, SUBMIT_RECORDING].join("\n"),
+    )?;
+    let dir = fixture.project();
+    fs::write(
+        dir.join("src").join("bin").join("a_v2.rs"),
+        "fn main() {}\n",
+    )?;
+    fs::write(dir.join("other.rs"), "fn main() {}\n")?;
+    let manifest = fs::read_to_string(dir.join("Cargo.toml"))?;
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "{}\n[package.metadata.cargo-atcoder.problems.a]\nfile = \"other.rs\"\n",
+            manifest
+        ),
+    )?;
+
+    let submit = |args: &[&str]| -> anyhow::Result<_> {
+        fixture
+            .cargo_atcoder(&["submit", "a", "--skip-test", "--practice"])?
+            .args(args)
+            .assert()
+            .success();
+        Ok(fs::read_to_string(dir.join("variants.toml"))?)
+    };
+    assert_eq!(submit(&[])?, "1003 = \"other.rs\"\n");
+    assert_eq!(submit(&["--variant", "v2"])?, "1003 = \"v2\"\n");
+
+    fixture.close()
+}
+
+#[test]
+fn practice_routed() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "practice-routed",
+        &[
+            PROBLEMS_RECORDING,
+            ENDED_RECORDING,
+            RECORDING,
+            SUBMIT_RECORDING,
+        ]
+        .join("\n"),
+    )?;
+    let dir = fixture.path().join("practice");
+
+    // `abc998_a` is solved and `abc999_b` is of 200 points.
+    fixture
+        .cargo_atcoder(&[
+            "practice",
+            "--points",
+            "100",
+            "--count",
+            "2",
+            "--exclude-solved",
+            "--user",
+            "tourist",
+            "--dir",
+            "practice",
+            "--skip-warmup",
+        ])?
+        .assert()
+        .success();
+    let bin_dir = dir.join("src").join("bin");
+    assert!(bin_dir.join("abc999_a.rs").exists());
+    assert!(!bin_dir.join("abc998_a.rs").exists());
+    assert!(!bin_dir.join("abc999_b.rs").exists());
+    let manifest = fs::read_to_string(dir.join("Cargo.toml"))?;
+    assert!(
+        manifest.contains(r#"task = "https://atcoder.jp/contests/abc999/tasks/abc999_a""#),
+        "{}",
+        manifest,
+    );
+    assert!(manifest.contains("practice = true"), "{}", manifest);
+
+    // Not looked up as a contest
+    fixture
+        .cargo_atcoder(&["test", "--all"])?
+        .current_dir(&dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "`practice` is a practice set of the tasks of several contests",
+        ));
+
+    // Submitted to the task in its contest
+    let assert = fixture
+        .cargo_atcoder(&["submit", "abc999_a", "--stdin"])?
+        .current_dir(&dir)
+        .write_stdin("fn main() {}\n")
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.contains("Submitted to problem `abc999_a`"),
+        "{}",
+        stdout,
+    );
+
+    // The submissions in the contest of the task, and none of the whole set
+    fixture.write_recording(SUBMISSIONS_RECORDING)?;
+    let assert = fixture
+        .cargo_atcoder(&["submissions", "--problem", "abc999_a"])?
+        .current_dir(&dir)
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("| 1001"), "{}", stdout);
+    fixture
+        .cargo_atcoder(&["status", "--once"])?
+        .current_dir(&dir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "`practice` is a practice set of the tasks of several contests",
+        ));
+
+    fixture.close()
+}
+
+#[test]
+fn daily_streak() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "daily-streak",
+        &[PROBLEMS_RECORDING, DIFFICULTIES_RECORDING].join("\n"),
+    )?;
+    let days = fixture.path().join("daily.toml");
+    // Solved yesterday
+    let yesterday = chrono::Local::now().naive_local().date() - chrono::Duration::days(1);
+    fs::write(
+        &days,
+        format!(
+            "[days]\n\"{}\" = \"abc998_a\"\n",
+            yesterday.format("%Y-%m-%d")
+        ),
+    )?;
+
+    // `abc999_b` is too difficult for the rating 1200.
+    for _ in 0..2 {
+        let assert = fixture
+            .cargo_atcoder(&[
+                "daily",
+                "--user",
+                "tourist",
+                "--dir",
+                "daily",
+                "--skip-warmup",
+            ])?
+            .assert()
+            .success();
+        let stdout = str::from_utf8(&assert.get_output().stdout)?;
+        assert!(stdout.contains("abc999_a: A. Sum"), "{}", stdout);
+        assert!(stdout.contains("Streak: 1 days"), "{}", stdout);
+    }
+    assert!(fixture
+        .path()
+        .join("daily")
+        .join("src")
+        .join("bin")
+        .join("abc999_a.rs")
+        .exists());
+
+    // Not overwritten, losing the streak
+    fs::write(&days, "[days\n")?;
+    fixture
+        .cargo_atcoder(&["daily", "--user", "tourist"])?
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Failed to parse"));
+    assert_eq!(fs::read_to_string(&days)?, "[days\n");
+
+    fixture.close()
+}
+
+#[test]
+fn mark_and_review() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project("mark-and-review", ENDED_RECORDING)?;
+    let schedule_file = fixture.path().join("review.toml");
+    let cargo_atcoder = |args: &[&str], dir: &str| -> anyhow::Result<String> {
+        let assert = fixture
+            .cargo_atcoder(args)?
+            .current_dir(fixture.path().join(dir))
+            .assert()
+            .success();
+        Ok(str::from_utf8(&assert.get_output().stdout)?.to_owned())
+    };
+
+    let tomorrow = chrono::Local::now().naive_local().date() + chrono::Duration::days(1);
+    let stdout = cargo_atcoder(&["mark", "a", "--assisted"], "abc999")?;
+    assert!(
+        stdout.contains(&format!(
+            "`abc999_a` will be reviewed on {}",
+            tomorrow.format("%Y-%m-%d")
+        )),
+        "{}",
+        stdout,
+    );
+    let stdout = cargo_atcoder(&["review"], "")?;
+    assert!(stdout.contains("No problems to review."), "{}", stdout);
+
+    // Due today
+    let schedule = fs::read_to_string(&schedule_file)?;
+    let today = chrono::Local::now().naive_local().date();
+    fs::write(
+        &schedule_file,
+        schedule.replace(
+            &tomorrow.format("%Y-%m-%d").to_string(),
+            &today.format("%Y-%m-%d").to_string(),
+        ),
+    )?;
+    cargo_atcoder(&["review", "--dir", "review", "--skip-warmup"], "")?;
+    assert!(fixture
+        .path()
+        .join("review")
+        .join("src")
+        .join("bin")
+        .join("abc999_a.rs")
+        .exists());
+
+    // Solved alone in the review project
+    cargo_atcoder(&["mark", "abc999_a"], "review")?;
+    let schedule = fs::read_to_string(&schedule_file)?;
+    assert!(schedule.contains("level = 1"), "{}", schedule);
+
+    // Not overwritten, losing the schedule
+    fs::write(&schedule_file, "[problems\n")?;
+    for args in [&["mark", "a", "--assisted"][..], &["review", "--list"]] {
+        fixture
+            .cargo_atcoder(args)?
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Failed to parse"));
+    }
+    assert_eq!(fs::read_to_string(&schedule_file)?, "[problems\n");
+
+    fixture.close()
+}
+
+#[test]
+fn editorial() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "editorial",
+        &[ENDED_RECORDING, EDITORIAL_RECORDING].join("\n"),
+    )?;
+    fixture.new_project("a,b")?;
+
+    let assert = fixture
+        .cargo_atcoder(&["editorial", "a"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert_eq!(
+        stdout.lines().take(3).collect::<Vec<_>>(),
+        [
+            "abc999_a",
+            "  official https://atcoder.jp/contests/abc999/editorial/1001 by writer",
+            "  user     https://example.com/abc999a by tourist",
+        ],
+    );
+    // The editorials of the other problems are not listed nor saved.
+    assert!(!stdout.contains("1002"), "{}", stdout);
+    let editorials = fixture.path().join("editorials").join("abc999");
+    assert!(fs::read_to_string(editorials.join("1001.html"))?.contains("Add A and B."));
+    assert!(!editorials.join("1002.html").exists());
+
+    fixture.close()
+}
+
+#[test]
+fn rating() -> anyhow::Result<()> {
+    let fixture = Fixture::new("rating", DIFFICULTIES_RECORDING)?;
+
+    let assert = fixture
+        .cargo_atcoder(&["rating", "tourist"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.starts_with("tourist: 1200 (highest 1200, 1 rated contests)\n"),
+        "{}",
+        stdout,
+    );
+    // The latest first, with the unrated ones
+    assert!(
+        stdout.ends_with(
+            "Date        Contest         Rank   Perf       Rating\n\
+             2021-01-02  abc999            50   2400            -\n\
+             2020-12-26  abc998           100   2000  1200 (+1200)\n"
+        ),
+        "{}",
+        stdout,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn submit_preprocessed() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "submit-preprocessed",
+        &[ENDED_RECORDING, RECORDING, SUBMIT_RECORDING].join("\n"),
+    )?;
+    fixture.write_config(&format!(
+        "{}\n[[preprocess]]\ntype = \"strip-comments\"\n\n[[preprocess]]\ntype = \"include\"\n",
+        CONFIG,
+    ))?;
+    fixture.new_project("a")?;
+
+    let submit = || -> anyhow::Result<_> {
+        let mut cmd = fixture.cargo_atcoder(&["submit", "a", "--stdin", "--practice"])?;
+        cmd.write_stdin("include!(\"src/lib.rs\"); // library\nfn main() {}\n");
+        Ok(cmd)
+    };
+
+    let assert = submit()?.assert().failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stderr.contains("lib.rs to include"), "{}", stderr);
+
+    fs::write(
+        fixture.project().join("src").join("lib.rs"),
+        "fn gcd() {}\n",
+    )?;
+    let assert = submit()?.assert().success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.contains("Submitted to problem `abc999_a`"),
+        "{}",
+        stdout,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn submit_strip_debug() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "submit-strip-debug",
+        &[ENDED_RECORDING, RECORDING, SUBMIT_RECORDING].join("\n"),
+    )?;
+
+    let source = r#"macro_rules! debug {
+    ($($t:tt)*) => { #[cfg(debug_assertions)] eprintln!($($t)*); }
+}
+
+fn main() {
+    let n = dbg!(1 + 2);
+    eprintln!("n = {}", n);
+    #[cfg(debug_assertions)]
+    eprintln!("gated");
+    println!("{}", n);
+}
+"#;
+    let submit = |args: &[&str]| -> anyhow::Result<_> {
+        let assert = fixture
+            .cargo_atcoder(&["submit", "a", "--stdin", "--practice"])?
+            .args(args)
+            .write_stdin(source)
+            .assert()
+            .success();
+        let output = assert.get_output();
+        Ok((
+            String::from_utf8(output.stdout.clone())?,
+            String::from_utf8(output.stderr.clone())?,
+        ))
+    };
+
+    let (_, stderr) = submit(&[])?;
+    assert!(
+        stderr.contains(
+            "Submitting the debug prints not gated by `cfg(debug_assertions)`: \
+             `dbg!` at line 6, `eprintln!` at line 7."
+        ),
+        "{}",
+        stderr,
+    );
+    let (stdout, _) = submit(&["--strip-debug"])?;
+    assert!(
+        stdout.contains("Removed the debug prints: `dbg!` at line 6, `eprintln!` at line 7"),
+        "{}",
+        stdout,
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn standings_frozen() -> anyhow::Result<()> {
+    let fixture = Fixture::with_project(
+        "standings-frozen",
+        &[RECORDING, SUBMISSIONS_RECORDING, STANDINGS_RECORDING].join("\n"),
+    )?;
+
+    let assert = fixture.cargo_atcoder(&["standings"])?.assert().success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 6, "{}", stdout);
+    assert_eq!(lines[0], "Standings of abc999 (frozen)");
+    assert_eq!(lines[1], "Rank | User    | Score         | A   | B");
+    assert_eq!(lines[2], "1    | alice   | 300 (0:50:00) | 100 | 200");
+    assert_eq!(lines[4], "3    | tourist | 100 (0:10:00) | 100 | ?");
+    assert_eq!(
+        lines[5],
+        "Pending: B. Rank: 1 (all accepted) to 3 (all rejected)"
+    );
+
+    fixture.close()
+}
+
+#[test]
+fn standings_rivals() -> anyhow::Result<()> {
+    let fixture = Fixture::new(
+        "standings-rivals",
+        &[RECORDING, SUBMISSIONS_RECORDING, STANDINGS_RECORDING].join("\n"),
+    )?;
+    fixture.write_config(
+        &CONFIG.replace("[atcoder]\n", "[atcoder]\nrivals = [\"bob\", \"carol\"]\n"),
+    )?;
+    fixture.new_project("a")?;
+
+    let assert = fixture
+        .cargo_atcoder(&["standings", "--rivals"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 6, "{}", stdout);
+    assert_eq!(lines[1], "carol is not in the standings");
+    assert_eq!(lines[2], "Rank | User    | Score         | A   | B");
+    assert_eq!(lines[3], "2    | bob     | 200 (0:20:00) |     | 200");
+    assert_eq!(lines[4], "3    | tourist | 100 (0:10:00) | 100 | ?");
+
+    fixture.close()
+}
+
+/// A temporary directory with [`CONFIG`] and a recording, in which `cargo atcoder` is run
+struct Fixture {
+    tempdir: TempDir,
+}
+
+impl Fixture {
+    fn new(name: &str, recording: &str) -> anyhow::Result<Self> {
+        let fixture = Self {
+            tempdir: TempDir::new(&format!("cargo-atcoder-test-replay-{}", name))?,
+        };
+        fixture.write_config(CONFIG)?;
+        fixture.write_recording(recording)?;
+        Ok(fixture)
+    }
+
+    /// Also with the project `abc999` of the problem `a`
+    fn with_project(name: &str, recording: &str) -> anyhow::Result<Self> {
+        let fixture = Self::new(name, recording)?;
+        fixture.new_project("a")?;
+        Ok(fixture)
+    }
+
+    /// Creates the project `abc999` of `problems` without the recording.
+    fn new_project(&self, problems: &str) -> anyhow::Result<()> {
+        assert_cmd::Command::cargo_bin("cargo-atcoder")?
+            .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", problems])
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", self.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", self.path())
+            .current_dir(self.path())
+            .assert()
+            .success();
+        Ok(())
+    }
+
+    fn path(&self) -> &Path {
+        self.tempdir.path()
+    }
+
+    fn project(&self) -> PathBuf {
+        self.path().join("abc999")
+    }
+
+    fn write_config(&self, config: &str) -> anyhow::Result<()> {
+        fs::write(self.path().join("cargo-atcoder.toml"), config).map_err(Into::into)
+    }
+
+    fn write_recording(&self, recording: &str) -> anyhow::Result<()> {
+        fs::write(self.path().join("recording.toml"), recording).map_err(Into::into)
+    }
+
+    /// `cargo atcoder <args>` replaying the recording, in the project if it exists
+    fn cargo_atcoder(&self, args: &[&str]) -> anyhow::Result<assert_cmd::Command> {
+        let dir = if self.project().exists() {
+            self.project()
+        } else {
+            self.path().to_owned()
+        };
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.args(["atcoder", "--replay"])
+            .arg(self.path().join("recording.toml"))
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", self.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", self.path())
+            .current_dir(dir);
+        Ok(cmd)
+    }
+
+    fn close(self) -> anyhow::Result<()> {
+        self.tempdir.close().map_err(Into::into)
+    }
+}
+
+static STANDINGS_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/standings/json"
+status = 200
+body = '''
+{
+  "TaskInfo": [
+    {"Assignment": "A", "TaskName": "Sum", "TaskScreenName": "abc999_a"},
+    {"Assignment": "B", "TaskName": "Product", "TaskScreenName": "abc999_b"}
+  ],
+  "StandingsData": [
+    {
+      "Rank": 1,
+      "UserScreenName": "alice",
+      "TaskResults": {
+        "abc999_a": {"Score": 10000, "Elapsed": 600000000000, "Pending": false},
+        "abc999_b": {"Score": 20000, "Elapsed": 3000000000000, "Pending": false}
+      },
+      "TotalResult": {"Score": 30000, "Elapsed": 3000000000000}
+    },
+    {
+      "Rank": 2,
+      "UserScreenName": "bob",
+      "TaskResults": {
+        "abc999_b": {"Score": 20000, "Elapsed": 1200000000000, "Pending": false}
+      },
+      "TotalResult": {"Score": 20000, "Elapsed": 1200000000000}
+    },
+    {
+      "Rank": 3,
+      "UserScreenName": "tourist",
+      "TaskResults": {
+        "abc999_a": {"Score": 10000, "Elapsed": 600000000000, "Pending": false},
+        "abc999_b": {"Score": 0, "Elapsed": 0, "Pending": true}
+      },
+      "TotalResult": {"Score": 10000, "Elapsed": 600000000000}
+    }
+  ]
+}
+'''
+"#;
+
+static BEFORE_CONTEST_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 404
+body = ""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/"
+status = 200
+body = '<ul><li><a href="/users/tourist">tourist</a></li></ul>'
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999"
+status = 200
+body = '<div id="contest-statement"><span class="lang"><span class="lang-ja"></span></span></div>'
+"#;
+
+static CONTESTS_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/"
+status = 200
+body = """
+<div id="contest-table-upcoming"><table><tbody>
+<tr>
+<td><a href="http://www.timeanddate.com/worldclock/fixedtime.html?iso=20210109T2100"><time>2021-01-09 21:00:00+0900</time></a></td>
+<td><a href="/contests/arc120">AtCoder Regular Contest 120</a></td>
+</tr>
+<tr>
+<td><a href="http://www.timeanddate.com/worldclock/fixedtime.html?iso=20210110T2100"><time>2021-01-10 21:00:00+0900</time></a></td>
+<td><a href="/contests/abc999">AtCoder Beginner Contest 999</a></td>
+</tr>
+</tbody></table></div>
+<div id="contest-table-recent"><table><tbody>
+<tr>
+<td><a href="http://www.timeanddate.com/worldclock/fixedtime.html?iso=20210102T2100"><time>2021-01-02 21:00:00+0900</time></a></td>
+<td><a href="/contests/abc998">AtCoder Beginner Contest 998</a></td>
+</tr>
+</tbody></table></div>
+"""
+"#;
+
+static PROBLEMS_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://kenkoooo.com/atcoder/resources/merged-problems.json"
+status = 200
+body = """
+[
+{"id": "abc998_a", "contest_id": "abc998", "problem_index": "A", "title": "A. Product", "point": 100.0},
+{"id": "abc999_a", "contest_id": "abc999", "problem_index": "A", "title": "A. Sum", "point": 100.0},
+{"id": "abc999_b", "contest_id": "abc999", "problem_index": "B", "title": "B. Difference", "point": 200.0}
+]
+"""
+
+[[responses]]
+method = "GET"
+url = "https://kenkoooo.com/atcoder/atcoder-api/v3/user/submissions?user=tourist&from_second=0"
+status = 200
+body = """
+[
+{"id": 1, "epoch_second": 1609588800, "problem_id": "abc998_a", "contest_id": "abc998", "user_id": "tourist", "language": "Rust", "point": 100.0, "length": 13, "result": "AC", "execution_time": 1}
+]
+"""
+"#;
+
+static DIFFICULTIES_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://kenkoooo.com/atcoder/resources/problem-models.json"
+status = 200
+body = """
+{
+"abc998_a": {"difficulty": 1250.0, "is_experimental": false},
+"abc999_a": {"difficulty": 1300.0, "is_experimental": false},
+"abc999_b": {"difficulty": 2000.0, "is_experimental": false}
+}
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/users/tourist/history/json"
+status = 200
+body = """
+[
+{"IsRated": true, "Place": 100, "OldRating": 0, "NewRating": 1200, "Performance": 2000, "InnerPerformance": 2000, "ContestScreenName": "abc998.contest.atcoder.jp", "ContestName": "AtCoder Beginner Contest 998", "ContestNameEn": "", "EndTime": "2020-12-26T22:40:00+09:00"},
+{"IsRated": false, "Place": 50, "OldRating": 1200, "NewRating": 1200, "Performance": 2400, "InnerPerformance": 2400, "ContestScreenName": "abc999.contest.atcoder.jp", "ContestName": "AtCoder Beginner Contest 999", "ContestNameEn": "", "EndTime": "2021-01-02T22:40:00+09:00"}
+]
+"""
+"#;
+
+static EDITORIAL_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/editorial"
+status = 200
+body = """
+<ul class="nav"><li><a href="/contests/abc999/tasks">Tasks</a></li></ul>
+<div id="main-container">
+<h3>A - Sum</h3>
+<h3><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></h3>
+<ul>
+<li><span class="label label-default">公式</span> <a href="/contests/abc999/editorial/1001">解説</a> by <a href="/users/writer" class="username"><span>writer</span></a></li>
+<li><a href="https://example.com/abc999a">解説</a> by <a href="/users/tourist" class="username"><span>tourist</span></a></li>
+</ul>
+<h3><a href="/contests/abc999/tasks/abc999_b">B - Difference</a></h3>
+<ul>
+<li><span class="label label-default">公式</span> <a href="/contests/abc999/editorial/1002">解説</a> by <a href="/users/writer" class="username"><span>writer</span></a></li>
+</ul>
+</div>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/editorial/1001"
+status = 200
+body = """
+<div id="main-container"><p>Add A and B.</p></div>
+"""
+"#;
+
+static ENDED_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 200
+body = """
+<small class="contest-duration">
+<a><time class="fixtime fixtime-full">2021-01-02 21:00:00+0900</time></a> ~
+<a><time class="fixtime fixtime-full">2021-01-02 22:40:00+0900</time></a>
+</small>
+<table><tbody>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_a">A</a></td>
+<td><a href="/contests/abc999/tasks/abc999_a">Sum</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+"#;
+
+static SUBMIT_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/submit"
+status = 200
+body = """
+<select name="data.TaskScreenName"><option value="abc999_a">A - Sum</option></select>
+<div id="select-lang-abc999_a"><select><option value="5054">Rust (rustc 1.70.0)</option></select></div>
+<input type="hidden" name="csrf_token" value="REDACTED">
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/submissions/me"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:06:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>100 Byte</td>
+<td class="waiting-judge" colspan="3"><span class="label label-default">WJ</span></td>
+<td><a href="/contests/abc999/submissions/1002">Detail</a></td>
+</tr>
+</tbody></table>
+"""
+
+[[responses]]
+method = "POST"
+url = "https://atcoder.jp/contests/abc999/submit"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:07:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=5054">Rust (rustc 1.70.0)</a></td>
+<td>0</td>
+<td>100 Byte</td>
+<td class="waiting-judge" colspan="3"><span class="label label-default">WJ</span></td>
+<td><a href="/contests/abc999/submissions/1003">Detail</a></td>
+</tr>
+</tbody></table>
+"""
+"#;
+
+static STATEMENT_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_a">A</a></td>
+<td><a href="/contests/abc999/tasks/abc999_a">Sum</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+
+[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks/abc999_a"
+status = 200
+body = '''
+<div id="task-statement"><span class="lang">
+<span class="lang-ja">
+<div class="part"><section>
+<h3>問題文</h3>
+<p>長さ <var>N</var> の数列 <var>A_1, \ldots, A_N</var> の総和を
+<var>10^9+7</var> で割った余りを求めてください。</p>
+<p><img src="https://img.atcoder.jp/abc999/figure.png"></p>
+</section></div>
+<div class="part"><section>
+<h3>制約</h3>
+<ul>
+<li><var>1 \leq N \leq 2 \times 10^{5}</var></li>
+<li><var>\frac{A_i}{2} \neq \sqrt{N+1}</var></li>
+</ul>
+</section></div>
+<div class="io-style"><div class="part"><section>
+<h3>入力</h3>
+<pre><var>N</var>
+<var>A_1</var> <var>A_2</var> <var>\ldots</var> <var>A_N</var>
+</pre>
+</section></div></div>
+</span>
+<span class="lang-en"><p>English</p></span>
+</span></div>
+'''
+"#;
+
+static SUBMISSIONS_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/submissions/me"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><time>2021-08-01 21:06:00+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=4050">Rust (1.42.0)</a></td>
+<td>0</td>
+<td>100 Byte</td>
+<td class="waiting-judge" colspan="3"><span class="label label-default">3/7 WA</span></td>
+<td><a href="/contests/abc999/submissions/1002">Detail</a></td>
+</tr>
+<tr>
+<td><time>2021-08-01 21:05:23+0900</time></td>
+<td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td>
+<td><a href="/users/tourist">tourist</a></td>
+<td><a href="/contests/abc999/submissions/me?f.Language=4050">Rust (1.42.0)</a></td>
+<td>100</td>
+<td>100 Byte</td>
+<td><span class="label label-success">AC</span></td>
+<td>5 ms</td>
+<td>2100 KB</td>
+<td><a href="/contests/abc999/submissions/1001">Detail</a></td>
+</tr>
+</tbody></table>
+"""
+"#;
+
+static SUBMISSION_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/submissions/1002"
+status = 200
+body = """
+<table class="table table-bordered"><tbody>
+<tr><th>Task</th><td><a href="/contests/abc999/tasks/abc999_a">A - Sum</a></td></tr>
+<tr><th>Status</th><td id="judge-status" class="waiting-judge"><span class="label label-default">3/7 WA</span></td></tr>
+</tbody></table>
+<table class="table table-bordered"><tbody>
+<tr><th>Score</th><td>0 / 0</td><td>0 / 100</td></tr>
+</tbody></table>
+<table class="table table-bordered"><thead>
+<tr><th>Case Name</th><th>Status</th><th>Exec Time</th><th>Memory</th></tr>
+</thead><tbody>
+<tr><td>sample_01.txt</td><td><span class="label label-success">AC</span></td><td>4 ms</td><td>2000 KB</td></tr>
+<tr><td>sample_02.txt</td><td><span class="label label-warning">WA</span></td><td>5 ms</td><td>2100 KB</td></tr>
+</tbody></table>
+"""
+"#;
+
+static LOGIN_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/login"
+status = 200
+body = '<form><input type="hidden" name="csrf_token" value="REDACTED"></form>'
+
+[[responses]]
+method = "POST"
+url = "https://atcoder.jp/login"
+status = 200
+body = '<div class="alert alert-success" role="alert">ようこそ、tourist さん。</div>'
+"#;
+
+static RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/"
+status = 200
+body = '<ul><li><a href="/users/tourist">tourist</a></li></ul>'
+"#;
+
+static CONFIG: &str = r#"[atcoder]
+submit_via_binary = false
+use_cross = false
+binary_column = 80
+update_interval = 1000
+
+[profile]
+target = "x86_64-unknown-linux-musl"
+
+[profile.release]
+lto = true
+panic = 'abort'
+
+[dependencies]
+
+[project]
+template = ""
+"#;
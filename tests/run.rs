@@ -0,0 +1,1895 @@
+use predicates::prelude::PredicateBooleanExt as _;
+use std::path::Path;
+use std::time::Duration;
+use std::{fs, str};
+use tempdir::TempDir;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[test]
+fn with_input_file() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-with-input-file")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    fs::write(tempdir.path().join("input.txt"), "1 2\n")?;
+
+    let assert = cargo_atcoder_run(tempdir.path())
+        .arg("--input")
+        .arg(tempdir.path().join("input.txt"))
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("     1 | 3\n"), "{}", stdout);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn with_stdin() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-with-stdin")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    let assert = cargo_atcoder_run(tempdir.path())
+        .arg("--stdin")
+        .write_stdin("40 2\n")
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("     1 | 42\n"), "{}", stdout);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn print() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-print")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "print", "--manifest-path"])
+        .arg(tempdir.path().join("abc999").join("Cargo.toml"))
+        .arg("a")
+        .arg("-o")
+        .arg(tempdir.path().join("a.ps"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env("CARGO_ATCODER_MEMBER", "alice")
+        .current_dir(tempdir.path())
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    let ps = fs::read_to_string(tempdir.path().join("a.ps"))?;
+    assert!(ps.starts_with("%!PS-Adobe-3.0\n"), "{}", ps);
+    assert!(ps.contains("(abc999 a \\(alice\\)    1/1) show"), "{}", ps);
+    assert!(ps.contains("B setfont 0 setgray (fn) show"), "{}", ps);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn templates() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-templates")?;
+
+    fs::write(
+        tempdir.path().join("cargo-atcoder.toml"),
+        format!(
+            "{}\n[project.templates]\n\"a-c\" = \"// easy\\n\"\nex = \"// ex\\n\"\n",
+            CONFIG
+        ),
+    )?;
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "new", "abc999", "--skip-warmup"])
+        .args(["-b", "a", "-b", "c", "-b", "d", "-b", "ex"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path())
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+
+    let bin_dir = tempdir.path().join("abc999").join("src").join("bin");
+    let read = |bin: &str| fs::read_to_string(bin_dir.join(bin).with_extension("rs"));
+    assert_eq!(read("a")?, "// easy\n");
+    assert_eq!(read("c")?, "// easy\n");
+    assert_eq!(read("ex")?, "// ex\n");
+    assert!(read("d")?.contains("fn main()"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn new_again() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-new-again")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    let bin_dir = tempdir.path().join("abc999").join("src").join("bin");
+    fs::write(bin_dir.join("a.rs"), "// edited\n")?;
+    let new = |args: &[&str]| -> anyhow::Result<String> {
+        let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+            .args([
+                "atcoder",
+                "new",
+                "abc999",
+                "--skip-warmup",
+                "-b",
+                "a",
+                "-b",
+                "b",
+            ])
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path())
+            .timeout(TIMEOUT)
+            .assert()
+            .success();
+        Ok(str::from_utf8(&assert.get_output().stdout)?.to_owned())
+    };
+
+    let stdout = new(&[])?;
+    assert!(
+        stdout.contains("Creating only what is missing."),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("a.rs, which has been edited"), "{}", stdout);
+    assert_eq!(fs::read_to_string(bin_dir.join("a.rs"))?, "// edited\n");
+    assert!(fs::read_to_string(bin_dir.join("b.rs"))?.contains("fn main()"));
+
+    new(&["--force"])?;
+    assert!(fs::read_to_string(bin_dir.join("a.rs"))?.contains("fn main()"));
+    assert_eq!(
+        fs::read_to_string(bin_dir.join("a.rs.orig"))?,
+        "// edited\n"
+    );
+
+    // The backup of the previous `--force` is kept.
+    fs::write(bin_dir.join("a.rs"), "// edited again\n")?;
+    new(&["--force"])?;
+    assert_eq!(
+        fs::read_to_string(bin_dir.join("a.rs.orig"))?,
+        "// edited\n"
+    );
+    assert_eq!(
+        fs::read_to_string(bin_dir.join("a.rs.orig.1"))?,
+        "// edited again\n"
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn new_dir() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-new-dir")?;
+
+    fs::write(
+        tempdir.path().join("cargo-atcoder.toml"),
+        CONFIG.replace(
+            "[project]\n",
+            "[project]\ndir = \"contests/{contest_id}\"\n",
+        ),
+    )?;
+    let new = |contest_id: &str, args: &[&str]| {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder").unwrap();
+        cmd.args(["atcoder", "new", contest_id, "--skip-warmup", "-b", "a"])
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path())
+            .timeout(TIMEOUT);
+        cmd
+    };
+
+    new("abc999", &[]).assert().success();
+    let manifest = fs::read_to_string(
+        tempdir
+            .path()
+            .join("contests")
+            .join("abc999")
+            .join("Cargo.toml"),
+    )?;
+    assert!(manifest.contains("name = \"abc999\""), "{}", manifest);
+
+    // The package is named after the contest in any directory.
+    new("abc998", &["--dir", "archive"]).assert().success();
+    let manifest = fs::read_to_string(tempdir.path().join("archive").join("Cargo.toml"))?;
+    assert!(manifest.contains("name = \"abc998\""), "{}", manifest);
+
+    new("abc997", &["--dir", "archive"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "already has the project of `abc998`, not `abc997`",
+        ));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn workspace_member() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-workspace-member")?;
+
+    fs::write(
+        tempdir.path().join("cargo-atcoder.toml"),
+        CONFIG.replace("[project]\n", "[project]\nworkspace = true\n"),
+    )?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let repo = tempdir.path().join("repo");
+    fs::create_dir(&repo)?;
+    fs::write(
+        repo.join("Cargo.toml"),
+        "[workspace]\nmembers = []\nresolver = \"2\"\n",
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", "a"])
+        .args(["--dir", "contests/abc999"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(&repo)
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    let root = fs::read_to_string(repo.join("Cargo.toml"))?;
+    assert!(root.contains("\"contests/abc999\""), "{}", root);
+    assert!(root.contains("[profile.local]"), "{}", root);
+    let package_dir = repo.join("contests").join("abc999");
+    let manifest = fs::read_to_string(package_dir.join("Cargo.toml"))?;
+    assert!(!manifest.contains("[profile"), "{}", manifest);
+
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n")?;
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        "fn main() {\n    println!(\"3\");\n}\n",
+    )?;
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "test", "a", "--contest", "abc999", "--release"])
+        .args(["--non-interactive", "--replay"])
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(&repo)
+        .timeout(TIMEOUT)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("profiles for the non root package").not());
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn workspace_not_member() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-workspace-not-member")?;
+
+    let repo = tempdir.path().join("repo");
+    fs::create_dir(&repo)?;
+    let root = "[workspace]\nmembers = []\nexclude = [\"old\"]\n";
+    fs::write(repo.join("Cargo.toml"), root)?;
+    let new = |config: &str, dir: &str| -> anyhow::Result<assert_cmd::assert::Assert> {
+        fs::write(tempdir.path().join("cargo-atcoder.toml"), config)?;
+        Ok(assert_cmd::Command::cargo_bin("cargo-atcoder")?
+            .args([
+                "atcoder",
+                "new",
+                "abc999",
+                "--skip-warmup",
+                "-b",
+                "a",
+                "--dir",
+                dir,
+            ])
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(&repo)
+            .timeout(TIMEOUT)
+            .assert()
+            .success())
+    };
+
+    // Not without the opt-in
+    new(CONFIG, "contests/abc999")?.stderr(predicates::str::contains(
+        "Set `project.workspace = true` in the config",
+    ));
+    assert_eq!(fs::read_to_string(repo.join("Cargo.toml"))?, root);
+
+    // Nor for the excluded ones, which are standalone
+    new(
+        &CONFIG.replace("[project]\n", "[project]\nworkspace = true\n"),
+        "old/abc999",
+    )?;
+    assert_eq!(fs::read_to_string(repo.join("Cargo.toml"))?, root);
+    let manifest = fs::read_to_string(repo.join("old").join("abc999").join("Cargo.toml"))?;
+    assert!(manifest.contains("[profile.release]"), "{}", manifest);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn stats() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-stats")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    let path = tempdir.path().join("stats").join("abc999.toml");
+    let times = fs::read_to_string(&path)?;
+    assert!(times.contains("[a]\ncreated = "), "{}", times);
+    fs::write(
+        &path,
+        format!(
+            "{}tested = \"2021-08-01T21:00:00+09:00\"\naccepted = \"2021-08-01T21:03:20+09:00\"\n\
+             [b]\ntested = \"2021-08-01T21:04:00+09:00\"\n",
+            times
+        ),
+    )?;
+
+    let stats = |args: &[&str]| -> anyhow::Result<String> {
+        let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+            .args(["atcoder", "stats"])
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .timeout(TIMEOUT)
+            .assert()
+            .success();
+        Ok(str::from_utf8(&assert.get_output().stdout)?.to_owned())
+    };
+    let history = stats(&[])?;
+    assert!(history.contains("abc999   | 0:03:20 | -\n"), "{}", history);
+    assert!(
+        history.contains("(solved) | 1/1     | 0/1\n"),
+        "{}",
+        history
+    );
+    let contest = stats(&["abc999"])?;
+    assert!(contest.contains("| 0:03:20\n"), "{}", contest);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn stress() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-stress")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    let cargo_atcoder = |args: &[&str]| -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.arg("atcoder")
+            .args(args)
+            .arg("--manifest-path")
+            .arg(tempdir.path().join("abc999").join("Cargo.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path())
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    cargo_atcoder(&["brute", "a"])?.assert().success();
+    let manifest = fs::read_to_string(tempdir.path().join("abc999").join("Cargo.toml"))?;
+    assert!(manifest.contains("reference = \"a_brute\""), "{}", manifest);
+
+    // The template also adds `N` to the sum.
+    let assert = cargo_atcoder(&["stress", "a", "-n", "3"])?
+        .assert()
+        .failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("Found a counterexample for seed 0"),
+        "{}",
+        stderr
+    );
+    assert!(tempdir
+        .path()
+        .join("abc999")
+        .join("stress")
+        .join("a")
+        .join("0.in")
+        .is_file());
+
+    let assert = cargo_atcoder(&["stress", "a", "-n", "3", "--keep-going", "--html"])?
+        .assert()
+        .failure();
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(
+        stderr.contains("Found 3 counterexamples for seeds 0, 1, 2"),
+        "{}",
+        stderr
+    );
+    let html = fs::read_to_string(
+        tempdir
+            .path()
+            .join("abc999")
+            .join("stress")
+            .join("a")
+            .join("report.html"),
+    )?;
+    assert!(html.contains(r#"<a href="2.in">2.in</a>"#), "{}", html);
+
+    let bin_dir = tempdir.path().join("abc999").join("src").join("bin");
+    fs::copy(bin_dir.join("a.rs"), bin_dir.join("a_brute.rs"))?;
+    let assert = cargo_atcoder(&["stress", "a", "-n", "3"])?
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(
+        stdout.contains("agreed with `a_brute` on 3 inputs"),
+        "{}",
+        stdout
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn complexity() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-complexity")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    let package_dir = tempdir.path().join("abc999");
+    fs::write(
+        package_dir.join("src").join("bin").join("a_gen.rs"),
+        r#"fn main() {
+    let n = std::env::args().nth(2).unwrap().parse::<usize>().unwrap();
+    println!("{}", vec!["1"; n].join(" "));
+}
+"#,
+    )?;
+    fs::write(
+        package_dir.join("gen.toml"),
+        "max = 1000000\nsizes = [1000, 2000, 4000, 8000]\n",
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "complexity", "a", "--time-limit", "2s"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("      8000 | "))
+        .stdout(predicates::str::contains("estimated: O("))
+        .stdout(predicates::str::contains("projected at n = 1000000: "));
+
+    // A generator ignoring the size
+    fs::write(
+        package_dir.join("src").join("bin").join("a_gen.rs"),
+        "fn main() {\n    println!(\"1 2 3\");\n}\n",
+    )?;
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "complexity", "a", "--time-limit", "2s"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "`a_gen` generated 6 bytes for size 2000, not more than 6 bytes for size 1000",
+        ));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn race() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-race")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n[[cases]]\ninput = \"3 4\"\noutput = \"7\"\n",
+    )?;
+    let package_dir = tempdir.path().join("abc999");
+    fs::write(
+        package_dir.join("src").join("bin").join("a_v2.rs"),
+        "fn main() {\n    println!(\"3\");\n}\n",
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "race", "a", "a_v2", "--runs", "1"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match(r"(?m)^case +\| a +\| a_v2$")?)
+        .stdout(predicates::str::is_match(r"(?m)^sample 2 \| .*\| .* WA$")?)
+        .stdout(predicates::str::contains("(total)"))
+        .stdout(predicates::str::contains("x as fast as"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn exit_codes() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-exit-codes")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+
+    let cargo_atcoder = |args: &[&str]| -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.arg("atcoder")
+            .args(args)
+            .arg("--non-interactive")
+            .arg("--replay")
+            .arg(tempdir.path().join("recording.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .env_remove("ATCODER_USERNAME")
+            .env_remove("ATCODER_PASSWORD")
+            .current_dir(tempdir.path().join("abc999"))
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n")?;
+    cargo_atcoder(&["test", "a"])?.assert().success();
+    // The project is found from the subdirectories too.
+    cargo_atcoder(&["test", "a"])?
+        .current_dir(tempdir.path().join("abc999").join("src").join("bin"))
+        .assert()
+        .success();
+    cargo_atcoder(&["test", "a"])?
+        .current_dir(tempdir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Run in the directory of a project created by `cargo atcoder new`",
+        ));
+
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"4\"\n")?;
+    cargo_atcoder(&["test", "a"])?.assert().code(1);
+    // Not 1, which is of the failing samples
+    cargo_atcoder(&["test", "a", "--no-such-flag"])?
+        .assert()
+        .code(4)
+        .stderr(predicates::str::contains("--no-such-flag"));
+    cargo_atcoder(&["test", "--help"])?.assert().success();
+
+    let source = tempdir
+        .path()
+        .join("abc999")
+        .join("src")
+        .join("bin")
+        .join("a.rs");
+    fs::write(&source, "fn main() { let }\n")?;
+    cargo_atcoder(&["test", "a"])?.assert().code(2);
+
+    cargo_atcoder(&["login"])?.assert().code(3);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn panic_backtrace() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-panic-backtrace")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n")?;
+    let package_dir = tempdir.path().join("abc999");
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        "fn main() {\n    let v: Vec<u32> = vec![];\n    println!(\"{}\", v[0]);\n}\n",
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "test", "a", "--non-interactive", "--replay"])
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env_remove("ATCODER_USERNAME")
+        .env_remove("ATCODER_PASSWORD")
+        .env_remove("RUST_BACKTRACE")
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "panicked: index out of bounds: the len is 0 but the index is 0",
+        ))
+        .stdout(predicates::str::contains("--> src/bin/a.rs:3:"))
+        .stdout(predicates::str::contains("println!(\"{}\", v[0]);"))
+        .stdout(predicates::str::contains("input:\n     1 | 1 2"));
+
+    // Overflows are checked in the optimized builds too.
+    fs::write(&samples, "[[cases]]\ninput = \"65536\"\noutput = \"0\"\n")?;
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        "fn main() {\n    let mut s = String::new();\n    std::io::stdin().read_line(&mut s).unwrap();\n    let n: u32 = s.trim().parse().unwrap();\n    println!(\"{}\", n * n);\n}\n",
+    )?;
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args([
+            "atcoder",
+            "test",
+            "a",
+            "--release",
+            "--non-interactive",
+            "--replay",
+        ])
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env_remove("ATCODER_USERNAME")
+        .env_remove("ATCODER_PASSWORD")
+        .env_remove("RUST_BACKTRACE")
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(
+            "panicked: attempt to multiply with overflow\nnote: the multiply below overflowed.",
+        ))
+        .stdout(predicates::str::contains("--> src/bin/a.rs:5:"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn stack_size() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-stack-size")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"300000\"\noutput = \"150000\"\n",
+    )?;
+    let package_dir = tempdir.path().join("abc999");
+    // Tens of megabytes of the stack, more than the usual default of 8 MiB
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        r#"fn f(n: u64) -> u64 {
+    let a = [n; 16];
+    if n == 0 {
+        0
+    } else {
+        f(n - 1) + a[(n % 16) as usize] % 2
+    }
+}
+
+fn main() {
+    let mut s = String::new();
+    std::io::stdin().read_line(&mut s).unwrap();
+    println!("{}", f(s.trim().parse().unwrap()));
+}
+"#,
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "test", "a", "--non-interactive", "--replay"])
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env_remove("ATCODER_USERNAME")
+        .env_remove("ATCODER_PASSWORD")
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn slow_io() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-slow-io")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"2\\n1\\n2\\n\"\noutput = \"1\\n2\\n\"\n",
+    )?;
+    let package_dir = tempdir.path().join("abc999");
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        r#"use std::io;
+
+fn main() {
+    let mut s = String::new();
+    io::stdin().read_line(&mut s).unwrap();
+    let n: usize = s.trim().parse().unwrap();
+    for _ in 0..n {
+        s.clear();
+        io::stdin().read_line(&mut s).unwrap();
+        println!("{}", s.trim());
+    }
+}
+"#,
+    )?;
+
+    let cargo_atcoder_test = || -> anyhow::Result<assert_cmd::assert::Assert> {
+        Ok(assert_cmd::Command::cargo_bin("cargo-atcoder")?
+            .args(["atcoder", "test", "a", "--non-interactive", "--replay"])
+            .arg(tempdir.path().join("recording.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .env_remove("ATCODER_USERNAME")
+            .env_remove("ATCODER_PASSWORD")
+            .current_dir(&package_dir)
+            .timeout(TIMEOUT)
+            .assert()
+            .success())
+    };
+
+    cargo_atcoder_test()?
+        .stderr(predicates::str::contains(
+            "src/bin/a.rs:9: `read_line` in a loop",
+        ))
+        .stderr(predicates::str::contains(
+            "src/bin/a.rs:10: `println!` in a loop",
+        ));
+
+    // No warnings for small inputs
+    let statement = tempdir
+        .path()
+        .join("statements")
+        .join("abc999")
+        .join("a.ja.html");
+    fs::create_dir_all(statement.parent().unwrap())?;
+    fs::write(
+        &statement,
+        "<section><h3>制約</h3><ul><li>1 \\leq N \\leq 100</li><li>1 \\leq A_i \\leq 10^9</li></ul></section>",
+    )?;
+    cargo_atcoder_test()?.stderr(predicates::str::contains("in a loop").not());
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn junit_report() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-junit-report")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n\n[[cases]]\ninput = \"1 2\"\noutput = \"4\"\n",
+    )?;
+    let report = tempdir.path().join("junit.xml");
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "test", "a", "--report"])
+        .arg(&report)
+        .arg("--non-interactive")
+        .arg("--replay")
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path().join("abc999"))
+        .timeout(TIMEOUT)
+        .assert()
+        .code(1);
+
+    let xml = fs::read_to_string(&report)?;
+    assert!(xml.contains("<testsuite name=\"abc999.a\" tests=\"2\" failures=\"1\""));
+    assert!(xml.contains("<testcase name=\"sample 1\" classname=\"abc999.a\""));
+    assert!(xml.contains("<failure type=\"WA\" message=\"wrong answer\">"));
+    assert!(xml.contains("-4\n+3\n"), "{}", xml);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn test_all() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-test-all")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(
+        tempdir.path().join("recording.toml"),
+        TASKS_RECORDING.replace(
+            "</tbody>",
+            "<tr>\n<td><a href=\"/contests/abc999/tasks/abc999_b\">B</a></td>\n\
+             <td><a href=\"/contests/abc999/tasks/abc999_b\">Slow</a></td>\n\
+             <td>1 sec</td>\n<td>1024 MB</td>\n</tr>\n</tbody>",
+        ),
+    )?;
+    let samples = tempdir.path().join("samples").join("abc999");
+    fs::create_dir_all(&samples)?;
+    for problem_id in ["a", "b"] {
+        fs::write(
+            samples.join(problem_id).with_extension("toml"),
+            "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n",
+        )?;
+    }
+    // Slower than the time limit
+    fs::write(
+        tempdir
+            .path()
+            .join("abc999")
+            .join("src")
+            .join("bin")
+            .join("b.rs"),
+        "fn main() {\n    std::thread::sleep(std::time::Duration::from_secs(2));\n    println!(\"3\");\n}\n",
+    )?;
+
+    let test_all = |args: &[&str]| -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.args(["atcoder", "test", "--all"])
+            .args(args)
+            .arg("--non-interactive")
+            .arg("--replay")
+            .arg(tempdir.path().join("recording.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path().join("abc999"))
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    // Waited for until it exits
+    let assert = test_all(&[])?.assert().success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("==== a ===="), "{}", stdout);
+    assert!(stdout.contains("==== b ===="), "{}", stdout);
+    assert!(!stdout.contains("TLE"), "{}", stdout);
+
+    // Killed at the time limit
+    let assert = test_all(&["--time-limit-margin", "1"])?.assert().code(1);
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("time limit exceeded"), "{}", stdout);
+    assert!(stdout.contains("TLE"), "{}", stdout);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn provisional_bins() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-provisional-bins")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    // As created before the contest, which turns out to have problem b too
+    let manifest = tempdir.path().join("abc999").join("Cargo.toml");
+    let mut s = fs::read_to_string(&manifest)?;
+    s += "\n[package.metadata.cargo-atcoder]\nprovisional-bins = true\n";
+    fs::write(&manifest, s)?;
+    fs::write(
+        tempdir.path().join("recording.toml"),
+        TASKS_RECORDING.replace(
+            "</tbody>",
+            "<tr>\n<td><a href=\"/contests/abc999/tasks/abc999_b\">B</a></td>\n\
+             <td><a href=\"/contests/abc999/tasks/abc999_b\">Sum</a></td>\n\
+             <td>2 sec</td>\n<td>1024 MB</td>\n</tr>\n</tbody>",
+        ),
+    )?;
+    let samples = tempdir.path().join("samples").join("abc999").join("b.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n")?;
+
+    // The bin created for b is tested in the same run.
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "test", "b", "--non-interactive", "--replay"])
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path().join("abc999"))
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    assert!(!fs::read_to_string(&manifest)?.contains("provisional-bins"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn fail_fast() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-fail-fast")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n\n\
+         [[cases]]\ninput = \"1 2\"\noutput = \"4\"\n\n\
+         [[cases]]\ninput = \"1 2\"\noutput = \"5\"\n",
+    )?;
+
+    let cargo_atcoder = |args: &[&str]| -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.arg("atcoder")
+            .args(args)
+            .arg("--non-interactive")
+            .arg("--replay")
+            .arg(tempdir.path().join("recording.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path().join("abc999"))
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    cargo_atcoder(&["test", "a"])?.assert().code(1);
+    // The failed ones first, stopping at the first of them
+    let assert = cargo_atcoder(&["test", "a", "--fail-fast"])?
+        .assert()
+        .code(1);
+    let stdout =
+        console::strip_ansi_codes(str::from_utf8(&assert.get_output().stdout)?).into_owned();
+    assert!(
+        stdout.contains("test sample 2 ... FAILED\n2 cases skipped after the first failure\n"),
+        "{}",
+        stdout,
+    );
+    assert!(!stdout.contains("test sample 1"), "{}", stdout);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn workspace() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-workspace")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    let workspace = |args: &[&str]| -> anyhow::Result<String> {
+        let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+            .args(["atcoder", "workspace", "abc999", "--dry-run"])
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .env("EDITOR", "vim")
+            .env_remove("VISUAL")
+            .env_remove("TMUX")
+            .current_dir(tempdir.path())
+            .timeout(TIMEOUT)
+            .assert()
+            .success();
+        Ok(str::from_utf8(&assert.get_output().stdout)?.to_owned())
+    };
+
+    let dir = tempdir.path().join("abc999").display().to_string();
+    let test = if cfg!(feature = "watch") {
+        "'cargo atcoder test a --watch-files'"
+    } else {
+        r#"'cargo atcoder test a; exec "${SHELL:-sh}"'"#
+    };
+    assert_eq!(
+        workspace(&[])?,
+        format!(
+            "tmux new-session -d -s abc999 -c {0} 'vim src/bin/a.rs'\n\
+             tmux split-window -h -t abc999 -c {0} {1}\n\
+             tmux split-window -v -t abc999 -c {0} 'cargo atcoder status'\n\
+             tmux select-pane -L -t abc999\n\
+             tmux split-window -v -l 30% -t abc999 -c {0}\n\
+             tmux select-pane -U -t abc999\n\
+             tmux attach-session -t abc999\n",
+            dir, test,
+        ),
+    );
+    let stdout = workspace(&["--multiplexer", "zellij"])?;
+    assert!(
+        stdout.starts_with("zellij --session abc999 --layout "),
+        "{}",
+        stdout
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn explain() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-explain")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    let package_dir = tempdir.path().join("abc999");
+
+    let cargo_atcoder = |args: &[&str]| -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.arg("atcoder")
+            .args(args)
+            .arg("--non-interactive")
+            .arg("--replay")
+            .arg(tempdir.path().join("recording.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .env_remove("ATCODER_USERNAME")
+            .env_remove("ATCODER_PASSWORD")
+            .current_dir(&package_dir)
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\\n0\"\n")?;
+    cargo_atcoder(&["test", "a"])?.assert().code(1);
+    cargo_atcoder(&["explain"])?
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("`a` sample 1: WA"))
+        .stdout(predicates::str::contains(
+            "The output ends before the expected one.",
+        ))
+        .stdout(predicates::str::contains("hints for WA:"));
+
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        "fn main() {\n    let v: Vec<u32> = vec![];\n    println!(\"{}\", v[0]);\n}\n",
+    )?;
+    cargo_atcoder(&["test", "a"])?.assert().code(1);
+    cargo_atcoder(&["explain"])?
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("`a` sample 1: RE"))
+        .stdout(predicates::str::contains("cause: index out of bounds"))
+        .stdout(predicates::str::contains("--> src/bin/a.rs:3:"));
+    // Only the hints for other verdicts
+    cargo_atcoder(&["explain", "tle"])?
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("sample 1").not())
+        .stdout(predicates::str::contains("hints for TLE:"));
+
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        "fn main() {\n    println!(\"3\\n0\");\n}\n",
+    )?;
+    cargo_atcoder(&["test", "a"])?.assert().success();
+    cargo_atcoder(&["explain"])?.assert().code(4);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn sample_files() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-sample-files")?;
+
+    assert_no_manifest(tempdir.path());
+    fs::write(
+        tempdir.path().join("cargo-atcoder.toml"),
+        CONFIG.replace("[project]\n", "[project]\nsample_files = true\n"),
+    )?;
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", "a"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path())
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"1 2\\n\"\noutput = \"3\\n\"\n",
+    )?;
+
+    let test = || -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.args(["atcoder", "test", "a", "--replay"])
+            .arg(tempdir.path().join("recording.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path().join("abc999"))
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    // Written from the cache on the first test, and read instead of it afterwards
+    test()?.assert().success();
+    let dir = tempdir.path().join("abc999").join("tests").join("a");
+    assert_eq!(fs::read_to_string(dir.join("in_1.txt"))?, "1 2\n");
+    assert_eq!(fs::read_to_string(dir.join("out_1.txt"))?, "3\n");
+
+    fs::write(dir.join("in_2.txt"), "40 2\n")?;
+    fs::write(dir.join("out_2.txt"), "41\n")?;
+    let assert = test()?.assert().code(1);
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("1 passed; 1 failed"), "{}", stdout);
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn harness() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-harness")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"1 2\\n\"\noutput = \"3\\n\"\n",
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "testcase", "harness", "--manifest-path"])
+        .arg(tempdir.path().join("abc999").join("Cargo.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    let tests_dir = tempdir.path().join("abc999").join("tests");
+    assert_eq!(
+        fs::read_to_string(tests_dir.join("a").join("in_1.txt"))?,
+        "1 2\n"
+    );
+
+    let cargo_test = || {
+        let mut cmd = assert_cmd::Command::new("cargo");
+        cmd.args(["test", "--test", "samples"])
+            .env_remove("CARGO_TARGET_DIR")
+            .current_dir(tempdir.path().join("abc999"))
+            .timeout(TIMEOUT);
+        cmd
+    };
+    cargo_test().assert().success();
+    fs::write(tests_dir.join("a").join("out_1.txt"), "4\n")?;
+    let assert = cargo_test().assert().failure();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("failed samples: [1]"), "{}", stdout);
+
+    // Echoing while reading, more than the pipes hold
+    fs::write(
+        tempdir
+            .path()
+            .join("abc999")
+            .join("src")
+            .join("bin")
+            .join("a.rs"),
+        "use std::io::BufRead as _;\n\
+         fn main() {\n    \
+             for line in std::io::stdin().lock().lines() {\n        \
+                 println!(\"{}\", line.unwrap());\n    \
+             }\n\
+         }\n",
+    )?;
+    let input = "1 2\n".repeat(1 << 18);
+    fs::write(tests_dir.join("a").join("in_1.txt"), &input)?;
+    fs::write(tests_dir.join("a").join("out_1.txt"), &input)?;
+    cargo_test().assert().success();
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn note() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-note")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+
+    // The editor is given the path, which is created before.
+    let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "note", "a"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .env_remove("VISUAL")
+        .env("EDITOR", "cat")
+        .current_dir(tempdir.path().join("abc999"))
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    assert_eq!(
+        str::from_utf8(&assert.get_output().stdout)?,
+        "# abc999 a\n\n"
+    );
+    let note = tempdir.path().join("abc999").join("notes").join("a.md");
+    fs::write(&note, "# abc999 a\n\nsort first\n")?;
+
+    // Kept
+    let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "note", "a", "--path"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path().join("abc999"))
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    let path = str::from_utf8(&assert.get_output().stdout)?.trim_end();
+    assert_eq!(fs::read_to_string(path)?, "# abc999 a\n\nsort first\n");
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn verify() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-verify")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    let source = tempdir
+        .path()
+        .join("abc999")
+        .join("src")
+        .join("bin")
+        .join("a.rs");
+    let template = fs::read_to_string(&source)?;
+    fs::write(
+        &source,
+        format!(
+            "// https://atcoder.jp/contests/abc001/tasks/abc001_a\n{}",
+            template
+        ),
+    )?;
+    // Scratchpads are not verified.
+    fs::copy(&source, source.with_file_name("scratch_a.rs"))?;
+    let samples = tempdir
+        .path()
+        .join("samples")
+        .join("abc001")
+        .join("abc001_a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(
+        &samples,
+        "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n\n\
+         [[cases]]\ninput = \"3 4\"\noutput = \"8\"\n",
+    )?;
+
+    let cargo_atcoder = |args: &[&str]| -> anyhow::Result<assert_cmd::Command> {
+        let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder")?;
+        cmd.arg("atcoder")
+            .args(args)
+            .arg("--manifest-path")
+            .arg(tempdir.path().join("abc999").join("Cargo.toml"))
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+            .current_dir(tempdir.path())
+            .timeout(TIMEOUT);
+        Ok(cmd)
+    };
+
+    cargo_atcoder(&["verify", "-o", "report.md"])?
+        .assert()
+        .code(1);
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("report.md"))?,
+        "| Package | Bin | Problem | Result |\n\
+         |---|---|---|---|\n\
+         | abc999 | a | [abc001_a](https://atcoder.jp/contests/abc001/tasks/abc001_a) | AC WA (1/2) |\n",
+    );
+
+    cargo_atcoder(&["verify", "--format", "junit", "-o", "report.xml"])?
+        .assert()
+        .code(1);
+    let xml = fs::read_to_string(tempdir.path().join("report.xml"))?;
+    assert!(
+        xml.contains(
+            r#"<testsuite name="abc999.a" tests="2" failures="1" errors="0" skipped="0" time=""#
+        ),
+        "{}",
+        xml
+    );
+    assert!(
+        xml.contains(r#"<failure type="WA" message="wrong answer">diff (-expected +actual):"#),
+        "{}",
+        xml
+    );
+
+    cargo_atcoder(&["verify", "--format", "json", "-o", "report.json"])?
+        .assert()
+        .code(1);
+    let json = fs::read_to_string(tempdir.path().join("report.json"))?;
+    assert!(
+        json.contains(
+            r#"{"event":"failed","name":"abc999::a::sample 2","stdout":"WA","type":"test"}"#
+        ),
+        "{}",
+        json
+    );
+    assert!(
+        json.ends_with(
+            r#"{"event":"failed","failed":1,"ignored":0,"passed":1,"type":"suite"}
+"#
+        ),
+        "{}",
+        json
+    );
+
+    cargo_atcoder(&["verify", "--html", "-o", "report.md"])?
+        .assert()
+        .code(1);
+    let html = fs::read_to_string(tempdir.path().join("abc999").join("verify.html"))?;
+    assert!(
+        html.contains(r##"<a href="#case-1">abc999.a sample 2</a>"##),
+        "{}",
+        html
+    );
+    assert!(html.contains("-8\n+7\n"), "{}", html);
+
+    // The notes are linked.
+    let note = tempdir.path().join("abc999").join("notes").join("a.md");
+    fs::create_dir_all(note.parent().unwrap())?;
+    fs::write(&note, "# abc999 a\n")?;
+    cargo_atcoder(&["verify", "-o", "report.md"])?
+        .assert()
+        .code(1);
+    let report = fs::read_to_string(tempdir.path().join("report.md"))?;
+    assert!(
+        report.contains(
+            "[abc001_a](https://atcoder.jp/contests/abc001/tasks/abc001_a) ([notes](notes/a.md))"
+        ),
+        "{}",
+        report
+    );
+
+    // The only bin is in the first shard.
+    cargo_atcoder(&["verify", "--partition", "2/2", "-o", "report.md"])?
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("report.md"))?,
+        "| Package | Bin | Problem | Result |\n|---|---|---|---|\n",
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn verify_library() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-verify-library")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    let package = tempdir.path().join("abc999");
+    fs::write(
+        package.join("src").join("sum.rs"),
+        "pub fn sum(a: u64, b: u64) -> u64 {\n    a + b\n}\n",
+    )?;
+    fs::create_dir(package.join("verify"))?;
+    fs::write(
+        package.join("verify").join("sum.rs"),
+        r#"use std::io::{self, Read as _};
+
+fn main() {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).unwrap();
+    let v = input
+        .split_ascii_whitespace()
+        .map(|s| s.parse().unwrap())
+        .collect::<Vec<_>>();
+    println!("{}", sum::sum(v[0], v[1]));
+}
+"#,
+    )?;
+    fs::write(
+        package.join("verify.toml"),
+        r#"[library.sum]
+problem = "https://atcoder.jp/contests/practice2/tasks/practice2_z"
+module = "src/sum.rs"
+main = "verify/sum.rs"
+"#,
+    )?;
+    let samples = tempdir
+        .path()
+        .join("samples")
+        .join("practice2")
+        .join("practice2_z.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n")?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args([
+            "atcoder",
+            "verify",
+            "--library",
+            "-o",
+            "report.md",
+            "--manifest-path",
+        ])
+        .arg(package.join("Cargo.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path())
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    assert!(package
+        .join("src")
+        .join("bin")
+        .join("verify_sum.rs")
+        .is_file());
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("report.md"))?,
+        "| Package | Bin | Problem | Result |\n\
+         |---|---|---|---|\n\
+         | abc999 | verify_sum | [practice2_z](https://atcoder.jp/contests/practice2/tasks/practice2_z) | AC (1/1) |\n",
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[cfg(unix)]
+#[test]
+fn verify_library_checker() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let tempdir = TempDir::new("cargo-atcoder-test-run-verify-library-checker")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    // A clone of `library-checker-problems` with the testcases already generated
+    let repo = tempdir.path().join("library-checker-problems");
+    let problem = repo.join("sample").join("aplusb");
+    fs::create_dir_all(problem.join("in"))?;
+    fs::create_dir_all(problem.join("out"))?;
+    fs::write(problem.join("info.toml"), "timelimit = 2.0\n")?;
+    for (i, (input, output)) in [("1 2\n", "3\n"), ("40 2\n", "42\n")].iter().enumerate() {
+        fs::write(problem.join("in").join(format!("example_0{}.in", i)), input)?;
+        fs::write(
+            problem.join("out").join(format!("example_0{}.out", i)),
+            output,
+        )?;
+    }
+    fs::write(problem.join("checker"), "#!/bin/sh\ncmp -s \"$2\" \"$3\"\n")?;
+    fs::set_permissions(problem.join("checker"), fs::Permissions::from_mode(0o755))?;
+    let config = fs::read_to_string(tempdir.path().join("cargo-atcoder.toml"))?;
+    fs::write(
+        tempdir.path().join("cargo-atcoder.toml"),
+        format!("{}\n[library-checker]\nrepo = {:?}\n", config, repo),
+    )?;
+
+    let source = tempdir
+        .path()
+        .join("abc999")
+        .join("src")
+        .join("bin")
+        .join("a.rs");
+    let template = fs::read_to_string(&source)?;
+    fs::write(
+        &source,
+        format!("// https://judge.yosupo.jp/problem/aplusb\n{}", template),
+    )?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "verify", "-o", "report.md", "--manifest-path"])
+        .arg(tempdir.path().join("abc999").join("Cargo.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path())
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("report.md"))?,
+        "| Package | Bin | Problem | Result |\n\
+         |---|---|---|---|\n\
+         | abc999 | a | [aplusb](https://judge.yosupo.jp/problem/aplusb) | AC (2/2) |\n",
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[cfg(unix)]
+#[test]
+fn ahc_run() -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let tempdir = TempDir::new("cargo-atcoder-test-run-ahc-run")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    let package_dir = tempdir.path().join("abc999");
+    // The official tools with the inputs bundled, whose scores are the outputs
+    let tools_dir = package_dir.join("tools");
+    fs::create_dir_all(tools_dir.join("in"))?;
+    for (seed, input) in ["-3", "2.5", "10"].iter().enumerate() {
+        fs::write(tools_dir.join("in").join(format!("000{}.txt", seed)), input)?;
+    }
+    let release_dir = tools_dir.join("target").join("release");
+    fs::create_dir_all(&release_dir)?;
+    fs::write(
+        release_dir.join("vis"),
+        "#!/bin/sh\necho \"Score = $(cat \"$2\")\"\n",
+    )?;
+    fs::set_permissions(release_dir.join("vis"), fs::Permissions::from_mode(0o755))?;
+    fs::write(
+        package_dir.join("src").join("bin").join("a.rs"),
+        r#"use std::io::{self, Read as _};
+
+fn main() {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).unwrap();
+    println!("{}", input.trim());
+}
+"#,
+    )?;
+
+    let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "ahc", "run", "--seeds", "3", "-j", "2"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(&package_dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("seed 0000:           -3 "), "{}", stdout);
+    assert!(stdout.contains("seed 0001:          2.5 "), "{}", stdout);
+    assert!(stdout.contains("seed 0002:           10 "), "{}", stdout);
+    assert!(stdout.contains("Total: 9.5, Average: 3.2\n"), "{}", stdout);
+    assert!(package_dir.join("ahc").join("a").join("best.rs").is_file());
+
+    tempdir.close().map_err(Into::into)
+}
+
+#[test]
+fn lib_error() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-lib-error")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(
+        tempdir.path().join("abc999").join("src").join("lib.rs"),
+        "pub fn broken() -> u32 { \"not a number\" }\n",
+    )?;
+
+    // The error of the lib is shown, not only "could not compile".
+    cargo_atcoder_run(tempdir.path())
+        .arg("--stdin")
+        .write_stdin("1 2\n")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("mismatched types"));
+
+    tempdir.close().map_err(Into::into)
+}
+
+fn assert_no_manifest(dir: &Path) {
+    if let Some(manifest_dir) = dir.ancestors().find(|p| p.join("Cargo.toml").exists()) {
+        panic!("found Cargo.toml at {}", manifest_dir.display());
+    }
+}
+
+fn cargo_atcoder_new(dir: &Path) -> anyhow::Result<()> {
+    fs::write(dir.join("cargo-atcoder.toml"), CONFIG)?;
+
+    assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "new", "abc999", "--skip-warmup", "-b", "a"])
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", dir)
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", dir)
+        .current_dir(dir)
+        .timeout(TIMEOUT)
+        .assert()
+        .success();
+    Ok(())
+}
+
+fn cargo_atcoder_run(dir: &Path) -> assert_cmd::Command {
+    let mut cmd = assert_cmd::Command::cargo_bin("cargo-atcoder").unwrap();
+    cmd.arg("atcoder")
+        .arg("run")
+        .arg("--manifest-path")
+        .arg(dir.join("abc999").join("Cargo.toml"))
+        .arg("a")
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", dir)
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", dir)
+        .current_dir(dir)
+        .timeout(TIMEOUT);
+    cmd
+}
+
+static TASKS_RECORDING: &str = r#"[[responses]]
+method = "GET"
+url = "https://atcoder.jp/contests/abc999/tasks"
+status = 200
+body = """
+<table><tbody>
+<tr>
+<td><a href="/contests/abc999/tasks/abc999_a">A</a></td>
+<td><a href="/contests/abc999/tasks/abc999_a">Sum</a></td>
+<td>2 sec</td>
+<td>1024 MB</td>
+</tr>
+</tbody></table>
+"""
+"#;
+
+// A configuration without external dependencies so that the tests can run offline.
+static CONFIG: &str = r#"[atcoder]
+submit_via_binary = false
+use_cross = false
+binary_column = 80
+update_interval = 1000
+
+[profile]
+target = "x86_64-unknown-linux-musl"
+
+[profile.release]
+lto = true
+panic = 'abort'
+
+[profile.local]
+inherits = "release"
+overflow-checks = true
+debug-assertions = true
+panic = 'unwind'
+
+[dependencies]
+
+[project]
+template = """
+use std::io::{self, Read as _};
+
+fn main() {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).unwrap();
+    let sum = input
+        .split_ascii_whitespace()
+        .map(|s| s.parse::<u64>().unwrap())
+        .sum::<u64>();
+    println!("{}", sum);
+}
+"""
+"#;
+
+#[test]
+fn reset() -> anyhow::Result<()> {
+    let config_dir = TempDir::new("cargo-atcoder-test-run-reset-config")?;
+    let cache_dir = TempDir::new("cargo-atcoder-test-run-reset-cache")?;
+
+    fs::write(config_dir.path().join("cargo-atcoder.toml"), "[atcoder\n")?;
+    fs::write(cache_dir.path().join("session.txt"), "")?;
+    fs::write(cache_dir.path().join("username.txt"), "user\n")?;
+    fs::create_dir(cache_dir.path().join("statements"))?;
+    fs::write(cache_dir.path().join("statements").join("a.html"), "")?;
+    // The records and the unknown files in the same directory in the tests
+    fs::write(cache_dir.path().join("daily.toml"), "")?;
+    fs::create_dir(cache_dir.path().join("stats"))?;
+    fs::write(cache_dir.path().join("notes.txt"), "")?;
+
+    let reset = |args: &[&str]| {
+        assert_cmd::Command::cargo_bin("cargo-atcoder")
+            .unwrap()
+            .args(["atcoder", "reset", "--non-interactive"])
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", config_dir.path())
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", cache_dir.path())
+            .timeout(TIMEOUT)
+            .assert()
+    };
+
+    let assert = reset(&["--cache"])
+        .failure()
+        .stderr(predicates::str::contains("--yes"));
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    assert!(stdout.contains("statements"), "{}", stdout);
+    for kept in ["session.txt", "daily.toml", "stats", "notes.txt"] {
+        assert!(!stdout.contains(kept), "{}", stdout);
+    }
+    assert!(cache_dir.path().join("statements").exists());
+
+    reset(&["--cache", "--yes"]).success();
+    assert!(!cache_dir.path().join("statements").exists());
+    for kept in ["session.txt", "daily.toml", "stats", "notes.txt"] {
+        assert!(cache_dir.path().join(kept).exists());
+    }
+
+    // Even with the broken config
+    reset(&["--session", "--config", "-y"])
+        .success()
+        .stdout(predicates::str::contains("Deleted 3 entries"));
+    assert!(!cache_dir.path().join("username.txt").exists());
+    assert!(!config_dir.path().join("cargo-atcoder.toml").exists());
+
+    reset(&["--all", "--yes"])
+        .success()
+        .stdout(predicates::str::contains("Nothing to reset"));
+    Ok(())
+}
+
+#[test]
+fn session_export_import() -> anyhow::Result<()> {
+    let from = TempDir::new("cargo-atcoder-test-run-session-from")?;
+    let to = TempDir::new("cargo-atcoder-test-run-session-to")?;
+
+    fs::write(
+        from.path().join("session.txt"),
+        "REVEL_SESSION=abc%00def; REVEL_FLASH=\n",
+    )?;
+    fs::write(from.path().join("username.txt"), "user\n")?;
+
+    let session = |dir: &Path, args: &[&str], passphrase: &str| {
+        assert_cmd::Command::cargo_bin("cargo-atcoder")
+            .unwrap()
+            .args(["atcoder", "session", "--non-interactive"])
+            .args(args)
+            .env("CARGO_ATCODER_TEST_CONFIG_DIR", dir)
+            .env("CARGO_ATCODER_TEST_CACHE_DIR", dir)
+            .env("ATCODER_SESSION_PASSPHRASE", passphrase)
+            .current_dir(dir)
+            .timeout(TIMEOUT)
+            .assert()
+    };
+
+    let plain = session(from.path(), &["export"], "").success();
+    let plain = str::from_utf8(&plain.get_output().stdout)?.to_owned();
+    assert!(plain.contains("abc%00def"), "{}", plain);
+    fs::write(to.path().join("plain.json"), &plain)?;
+    session(to.path(), &["import", "plain.json"], "")
+        .success()
+        .stdout(predicates::str::contains("Imported the session of `user`"));
+    assert_eq!(
+        fs::read_to_string(to.path().join("session.txt"))?,
+        "REVEL_FLASH=; REVEL_SESSION=abc%00def\n",
+    );
+    assert_eq!(
+        fs::read_to_string(to.path().join("username.txt"))?,
+        "user\n"
+    );
+
+    let encrypted = session(from.path(), &["export", "--passphrase"], "secret").success();
+    let encrypted = str::from_utf8(&encrypted.get_output().stdout)?.to_owned();
+    assert!(!encrypted.contains("abc%00def"), "{}", encrypted);
+    fs::remove_file(to.path().join("session.txt"))?;
+    fs::write(to.path().join("encrypted.json"), &encrypted)?;
+    session(to.path(), &["import", "encrypted.json"], "wrong")
+        .failure()
+        .stderr(predicates::str::contains("Wrong passphrase"));
+    assert!(!to.path().join("session.txt").exists());
+    session(to.path(), &["import", "encrypted.json"], "secret").success();
+    assert_eq!(
+        fs::read_to_string(to.path().join("session.txt"))?,
+        "REVEL_FLASH=; REVEL_SESSION=abc%00def\n",
+    );
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn state_dirs() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-state-dirs")?;
+    let xdg = |name: &str| tempdir.path().join(name);
+
+    // The places of the older versions
+    fs::create_dir_all(xdg("config"))?;
+    fs::write(xdg("config").join("cargo-atcoder.toml"), CONFIG)?;
+    fs::create_dir_all(xdg("cache").join("cargo-atcoder"))?;
+    fs::write(
+        xdg("cache").join("cargo-atcoder").join("session.txt"),
+        "REVEL_SESSION=abc\n",
+    )?;
+
+    let export = |args: &[&str]| {
+        assert_cmd::Command::cargo_bin("cargo-atcoder")
+            .unwrap()
+            .args(["atcoder", "session", "export"])
+            .args(args)
+            .env_remove("CARGO_ATCODER_TEST_CONFIG_DIR")
+            .env_remove("CARGO_ATCODER_TEST_CACHE_DIR")
+            .env_remove("CARGO_ATCODER_STATE_DIR")
+            .env("XDG_CONFIG_HOME", xdg("config"))
+            .env("XDG_CACHE_HOME", xdg("cache"))
+            .env("XDG_STATE_HOME", xdg("state"))
+            .current_dir(tempdir.path())
+            .timeout(TIMEOUT)
+            .assert()
+    };
+
+    export(&[])
+        .success()
+        .stdout(predicates::str::contains("abc"))
+        .stderr(predicates::str::contains("Moved"));
+    assert_eq!(
+        fs::read_to_string(
+            xdg("config")
+                .join("cargo-atcoder")
+                .join("cargo-atcoder.toml")
+        )?,
+        CONFIG,
+    );
+    assert!(!xdg("config").join("cargo-atcoder.toml").exists());
+    assert!(xdg("state")
+        .join("cargo-atcoder")
+        .join("session.txt")
+        .exists());
+    assert!(!xdg("cache")
+        .join("cargo-atcoder")
+        .join("session.txt")
+        .exists());
+
+    export(&["--state-dir", "portable"])
+        .failure()
+        .stderr(predicates::str::contains("Not logged in"));
+    assert!(xdg("portable").join("cargo-atcoder.toml").exists());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn interrupt() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-run-interrupt")?;
+
+    assert_no_manifest(tempdir.path());
+    cargo_atcoder_new(tempdir.path())?;
+    fs::write(tempdir.path().join("recording.toml"), TASKS_RECORDING)?;
+    let samples = tempdir.path().join("samples").join("abc999").join("a.toml");
+    fs::create_dir_all(samples.parent().unwrap())?;
+    fs::write(&samples, "[[cases]]\ninput = \"1 2\"\noutput = \"3\"\n")?;
+    // Hangs with a child of its own
+    fs::write(
+        tempdir
+            .path()
+            .join("abc999")
+            .join("src")
+            .join("bin")
+            .join("a.rs"),
+        r#"use std::{fs, process::Command, thread, time::Duration};
+
+fn main() {
+    let sleep = Command::new("sleep").arg("100").spawn().unwrap();
+    let pids = format!("{} {}", std::process::id(), sleep.id());
+    fs::write("pids.tmp", pids).unwrap();
+    fs::rename("pids.tmp", "pids").unwrap();
+    thread::sleep(Duration::from_secs(100));
+}
+"#,
+    )?;
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("cargo-atcoder"))
+        .args(["atcoder", "test", "a", "--non-interactive", "--replay"])
+        .arg(tempdir.path().join("recording.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .current_dir(tempdir.path().join("abc999"))
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    let pids = tempdir.path().join("abc999").join("pids");
+    let start = std::time::Instant::now();
+    while !pids.exists() {
+        assert!(start.elapsed() < TIMEOUT, "the solution did not start");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let status = std::process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()?;
+    assert!(status.success());
+
+    assert_eq!(child.wait()?.code(), Some(130));
+    // Dead, or a zombie of the sleep not reaped by the init of the container yet
+    for pid in fs::read_to_string(&pids)?.split(' ') {
+        let alive = fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map(|stat| !stat.rsplit(") ").next().unwrap().starts_with('Z'))
+            .unwrap_or(false);
+        assert!(!alive, "{} is still running", pid);
+    }
+    tempdir.close().map_err(Into::into)
+}
@@ -0,0 +1,90 @@
+use std::{fs, str};
+use tempdir::TempDir;
+
+#[test]
+fn migrate() -> anyhow::Result<()> {
+    let tempdir = TempDir::new("cargo-atcoder-test-template-migrate")?;
+
+    fs::write(tempdir.path().join("cargo-atcoder.toml"), CONFIG)?;
+    fs::write(tempdir.path().join("official.toml"), OFFICIAL_MANIFEST)?;
+
+    let assert = assert_cmd::Command::cargo_bin("cargo-atcoder")?
+        .args(["atcoder", "template", "migrate"])
+        .arg(tempdir.path().join("official.toml"))
+        .env("CARGO_ATCODER_TEST_CONFIG_DIR", tempdir.path())
+        .env("CARGO_ATCODER_TEST_CACHE_DIR", tempdir.path())
+        .assert()
+        .success();
+    let stdout = str::from_utf8(&assert.get_output().stdout)?;
+    let stderr = str::from_utf8(&assert.get_output().stderr)?;
+    assert!(stdout.contains("proconio: 0.4.5 -> =0.5.0"), "{}", stdout);
+    assert!(stdout.contains("num: 0.4.0 -> =0.4.3"), "{}", stdout);
+    assert!(!stdout.contains("ac-library-rs"), "{}", stdout);
+    assert!(
+        stdout.contains("Updated 3 dependencies in the config."),
+        "{}",
+        stdout
+    );
+    assert!(stderr.contains("text_io"), "{}", stderr);
+
+    let config = fs::read_to_string(tempdir.path().join("cargo-atcoder.toml"))?;
+    assert!(
+        config.contains("itertools = \"=0.13.0\" # comment\n"),
+        "{}",
+        config
+    );
+    assert!(
+        config.contains("proconio = { version = \"=0.5.0\", features = [\"derive\"] }\n"),
+        "{}",
+        config
+    );
+    assert!(config.contains("text_io = \"0.1.12\"\n"), "{}", config);
+    assert!(
+        config.contains("[dependencies.num]\nversion = \"=0.4.3\"\n"),
+        "{}",
+        config
+    );
+
+    tempdir.close().map_err(Into::into)
+}
+
+static OFFICIAL_MANIFEST: &str = r#"[package]
+name = "main"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+itertools = "=0.13.0"
+proconio = { version = "=0.5.0", features = ["derive"] }
+ac-library-rs = "=0.1.1"
+
+[dependencies.num]
+version = "=0.4.3"
+"#;
+
+static CONFIG: &str = r#"[atcoder]
+submit_via_binary = false
+use_cross = false
+binary_column = 80
+update_interval = 1000
+
+[profile]
+target = "x86_64-unknown-linux-musl"
+
+[profile.release]
+lto = true
+panic = 'abort'
+
+[dependencies]
+itertools = "0.11.0" # comment
+proconio = { version = "0.4.5", features = ["derive"] }
+text_io = "0.1.12"
+ac-library-rs = { git = "https://github.com/rust-lang-ja/ac-library-rs" }
+
+[dependencies.num]
+version = "0.4.0"
+default-features = false
+
+[project]
+template = ""
+"#;
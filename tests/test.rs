@@ -122,7 +122,7 @@ fn assert_no_manifest(dir: &Path) {
 
 fn cargo_atcoder_new(dir: &Path) -> anyhow::Result<()> {
     assert_cmd::Command::cargo_bin("cargo-atcoder")?
-        .args(&[
+        .args([
             "atcoder",
             "new",
             "language-test-202001",
@@ -175,7 +175,7 @@ fn run(
     return Ok(());
 
     fn predicate(f: fn(&str) -> bool) -> impl Predicate<[u8]> {
-        predicates::function::function(move |s| str::from_utf8(s).map_or(false, f))
+        predicates::function::function(move |s| str::from_utf8(s).is_ok_and(f))
     }
 }
 